@@ -0,0 +1,50 @@
+//! Parsing benchmarks against the full-size stations list and a week of tide predictions.
+//!
+//! Run with `cargo bench`, and with `cargo bench --features sonic-rs` to compare backends.
+
+use std::fs;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+fn parse_stations(c: &mut Criterion) {
+    let data = fs::read("./stations.json").expect("Failed to read stations reference file.");
+    let mut group = c.benchmark_group("stations");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("stations_from_reader", |b| {
+        b.iter(|| rjw_uktides::stations_from_reader(data.as_slice()).unwrap())
+    });
+    group.bench_function("stations_from_slice", |b| {
+        b.iter(|| rjw_uktides::stations_from_slice(&data).unwrap())
+    });
+    group.finish();
+}
+
+fn parse_tides(c: &mut Criterion) {
+    let data = fs::read("./reference/tides.json").expect("Failed to read tides reference file.");
+    let mut group = c.benchmark_group("tides");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("tides_from_reader", |b| {
+        b.iter(|| rjw_uktides::tides_from_reader(data.as_slice()).unwrap())
+    });
+    group.bench_function("tides_from_slice", |b| {
+        b.iter(|| rjw_uktides::tides_from_slice(&data).unwrap())
+    });
+    group.bench_function("tides_from_reader_streaming", |b| {
+        b.iter(|| rjw_uktides::tides_from_reader_streaming(data.as_slice(), |_| {}).unwrap())
+    });
+    let mut opts = rjw_uktides::ParseOptions::default();
+    opts.skip_continuous_heights = true;
+    group.bench_function("tides_from_reader_with/skip_continuous_heights", |b| {
+        b.iter(|| rjw_uktides::tides_from_reader_with(data.as_slice(), &opts).unwrap())
+    });
+    group.finish();
+}
+
+fn validate_tides(c: &mut Criterion) {
+    let data = fs::read("./reference/tides.json").expect("Failed to read tides reference file.");
+    let tides = rjw_uktides::tides_from_slice(&data).unwrap();
+    c.bench_function("validate", |b| b.iter(|| tides.validate()));
+}
+
+criterion_group!(benches, parse_stations, parse_tides, validate_tides);
+criterion_main!(benches);
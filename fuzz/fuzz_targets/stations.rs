@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = rjw_uktides::stations_from_reader(data);
+    let _ = rjw_uktides::stations_from_slice(data);
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(tides) = rjw_uktides::tides_from_reader(data) {
+        let _ = tides.validate();
+    }
+    let _ = rjw_uktides::tides_from_slice(data);
+    let _ = rjw_uktides::tides_from_reader_streaming(data, |_| {});
+    let mut opts = rjw_uktides::ParseOptions::default();
+    opts.skip_continuous_heights = true;
+    let _ = rjw_uktides::tides_from_reader_with(data, &opts);
+});
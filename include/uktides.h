@@ -0,0 +1,71 @@
+/*
+ * C API for rjw-uktides, built with the `capi` feature as a shared library by
+ *
+ *     cargo rustc --lib --release --no-default-features --features capi \
+ *         --crate-type cdylib
+ *
+ * Parsing functions return an opaque handle, or NULL on failure, in which case
+ * uktides_last_error() describes the failure. Handles must be released with the
+ * matching _free function. The accessor functions treat a NULL handle as empty,
+ * and write nothing to a NULL output pointer. Datetimes are Unix timestamps in
+ * seconds.
+ */
+
+#ifndef UKTIDES_H
+#define UKTIDES_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define UKTIDES_HIGH_WATER 0
+#define UKTIDES_LOW_WATER 1
+
+typedef struct UktidesTides UktidesTides;
+typedef struct UktidesStations UktidesStations;
+
+typedef struct {
+    int64_t timestamp;
+    double height;
+    int32_t event_type; /* UKTIDES_HIGH_WATER or UKTIDES_LOW_WATER */
+} UktidesEvent;
+
+typedef struct {
+    int64_t timestamp;
+    double height;
+} UktidesHeight;
+
+/* The strings are owned by the station list they were read from. */
+typedef struct {
+    const char *id;
+    const char *name;
+    const char *country;
+    double latitude;
+    double longitude;
+    bool continuous_heights_available;
+} UktidesStation;
+
+/* Valid until the next parsing call on the same thread. */
+const char *uktides_last_error(void);
+
+UktidesTides *uktides_tides_parse(const uint8_t *data, size_t len);
+size_t uktides_tides_event_count(const UktidesTides *tides);
+bool uktides_tides_event(const UktidesTides *tides, size_t index, UktidesEvent *out);
+size_t uktides_tides_height_count(const UktidesTides *tides);
+bool uktides_tides_height(const UktidesTides *tides, size_t index, UktidesHeight *out);
+void uktides_tides_free(UktidesTides *tides);
+
+UktidesStations *uktides_stations_parse(const uint8_t *data, size_t len);
+size_t uktides_stations_count(const UktidesStations *stations);
+bool uktides_stations_get(const UktidesStations *stations, size_t index, UktidesStation *out);
+void uktides_stations_free(UktidesStations *stations);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* UKTIDES_H */
@@ -0,0 +1,260 @@
+//! The UKHO's official Admiralty UK Tidal API.
+//!
+//! The [Admiralty UK Tidal API] is the supported, subscription-based counterpart to the
+//! EasyTide web service, with the same stations and predictions. Requests must carry a
+//! subscription key in the [`SUBSCRIPTION_KEY_HEADER`] header. The station list has the
+//! same format as the EasyTide one, so is parsed into the same [`Station`] type, and tidal
+//! events are parsed into the same [`TidalEvent`] type as EasyTide predictions.
+//!
+//! [Admiralty UK Tidal API]: https://admiraltyapi.portal.azure-api.net/
+//!
+//! # Examples
+//! ```
+//! use std::fs::File;
+//!
+//! use rjw_uktides::admiralty;
+//!
+//! let file = File::open("./reference/admiralty-events.json")
+//!     .expect("Failed to open events reference file.");
+//! let events = admiralty::tidal_events_from_reader(file)
+//!     .expect("Failed to read file as events data.");
+//! assert_eq!(events[0].event_type, rjw_uktides::TidalEventType::HighWater);
+//! ```
+
+use std::io::Read;
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Deserializer};
+
+use crate::trace::parse_json;
+use crate::{Error, Metres, Station, StationId, TidalEvent, TidalEventType};
+
+/// The Admiralty UK Tidal API, on the Discovery (free) tier.
+pub const ADMIRALTY_BASE_URL: &str = "https://admiraltyapi.azure-api.net/uktidalapi/api/V1";
+
+/// The request header carrying the API subscription key.
+pub const SUBSCRIPTION_KEY_HEADER: &str = "Ocp-Apim-Subscription-Key";
+
+/// The URL from which the list of all stations is fetched, for the service at `base_url`.
+///
+/// # Examples
+/// ```
+/// use rjw_uktides::admiralty::{stations_url, ADMIRALTY_BASE_URL};
+///
+/// assert_eq!(
+///     stations_url(ADMIRALTY_BASE_URL),
+///     "https://admiraltyapi.azure-api.net/uktidalapi/api/V1/Stations"
+/// );
+/// ```
+pub fn stations_url(base_url: &str) -> String {
+    format!("{base_url}/Stations")
+}
+
+/// The URL from which the high and low tides for the station are fetched, for the service
+/// at `base_url`.
+///
+/// The API returns a week of events by default; `duration_days` requests fewer or, on
+/// tiers that allow it, more.
+///
+/// # Examples
+/// ```
+/// use rjw_uktides::admiralty::{tidal_events_url, ADMIRALTY_BASE_URL};
+/// use rjw_uktides::StationId;
+///
+/// assert_eq!(
+///     tidal_events_url(ADMIRALTY_BASE_URL, &StationId("0053".to_owned()), Some(3)),
+///     "https://admiraltyapi.azure-api.net/uktidalapi/api/V1/Stations/0053/TidalEvents?duration=3"
+/// );
+/// ```
+pub fn tidal_events_url(base_url: &str, station: &StationId, duration_days: Option<u32>) -> String {
+    let url = format!("{base_url}/Stations/{}/TidalEvents", station.0);
+    match duration_days {
+        Some(days) => format!("{url}?duration={days}"),
+        None => url,
+    }
+}
+
+/// Attempt to parse the list of stations from the reader.
+///
+/// # Errors
+///
+/// This function will return an error in the same cases as
+/// [`stations_from_reader`](crate::stations_from_reader).
+pub fn stations_from_reader(rdr: impl Read) -> Result<Vec<Station>, Error> {
+    crate::stations_from_reader(rdr)
+}
+
+/// Attempt to parse the high and low tides from the reader.
+///
+/// The API reports whether the time and height are approximate as booleans; these are
+/// converted to `Some("true")` or `None` in the [`TidalEvent`] fields.
+///
+/// # Errors
+///
+/// This function will return an error in the same cases as
+/// [`tides_from_reader`](crate::tides_from_reader).
+pub fn tidal_events_from_reader(rdr: impl Read) -> Result<Vec<TidalEvent>, Error> {
+    let events: Vec<RawEvent> = parse_json(rdr, "tidal events")?;
+    Ok(events
+        .into_iter()
+        .map(|e| TidalEvent {
+            date: e
+                .date
+                .map_or_else(|| e.date_time.date_naive(), |d| d.date()),
+            date_time: e.date_time,
+            event_type: e.event_type,
+            height: Metres(e.height),
+            is_approximate_height: e.is_approximate_height.then(|| "true".to_owned()),
+            is_approximate_time: e.is_approximate_time.then(|| "true".to_owned()),
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RawEvent {
+    #[serde(deserialize_with = "deserialize_event_type")]
+    event_type: TidalEventType,
+    #[serde(deserialize_with = "deserialize_utc")]
+    date_time: DateTime<Utc>,
+    #[serde(default)]
+    is_approximate_time: bool,
+    height: f64,
+    #[serde(default)]
+    is_approximate_height: bool,
+    #[serde(default)]
+    date: Option<NaiveDateTime>,
+}
+
+fn deserialize_event_type<'de, D>(deserializer: D) -> Result<TidalEventType, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match String::deserialize(deserializer)?.as_str() {
+        "HighWater" => Ok(TidalEventType::HighWater),
+        "LowWater" => Ok(TidalEventType::LowWater),
+        other => Err(serde::de::Error::unknown_variant(
+            other,
+            &["HighWater", "LowWater"],
+        )),
+    }
+}
+
+/// Deserialize a UTC datetime, with or without a timezone specifier.
+fn deserialize_utc<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    match value.strip_suffix('Z') {
+        Some(naive) => naive.parse::<NaiveDateTime>(),
+        None => value.parse::<NaiveDateTime>(),
+    }
+    .map(|naive| Utc.from_utc_datetime(&naive))
+    .map_err(serde::de::Error::custom)
+}
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub use client::AdmiraltyClient;
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+mod client {
+    use std::error::Error;
+
+    use bytes::Bytes;
+
+    use super::{stations_url, tidal_events_url, ADMIRALTY_BASE_URL, SUBSCRIPTION_KEY_HEADER};
+    use crate::{Client, Station, StationId, TidalEvent};
+
+    /// Blocking client for the Admiralty UK Tidal API.
+    ///
+    /// Requests are sent with a [`Client`], so share its connection pool, and its cache,
+    /// retry policy and rate limit if it has them.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use rjw_uktides::admiralty::AdmiraltyClient;
+    /// use rjw_uktides::StationId;
+    ///
+    /// let client = AdmiraltyClient::new(std::env::var("ADMIRALTY_API_KEY").unwrap());
+    /// let events = client
+    ///     .fetch_tidal_events(&StationId("0053".to_owned()), None)
+    ///     .expect("Failed to fetch tidal events.");
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct AdmiraltyClient {
+        client: Client,
+        base_url: String,
+        subscription_key: String,
+    }
+
+    impl AdmiraltyClient {
+        /// Create a client for the public API using the given subscription key.
+        pub fn new(subscription_key: impl Into<String>) -> Self {
+            Self {
+                client: Client::new(),
+                base_url: ADMIRALTY_BASE_URL.to_owned(),
+                subscription_key: subscription_key.into(),
+            }
+        }
+
+        /// Send requests to `base_url` instead of the public API, such as for a different
+        /// subscription tier.
+        pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+            self.base_url = base_url.into();
+            self
+        }
+
+        /// Send requests with `client`, to use its cache, retry policy or rate limit.
+        ///
+        /// The client's base URL is not used.
+        pub fn with_client(mut self, client: Client) -> Self {
+            self.client = client;
+            self
+        }
+
+        /// The base URL to which requests are sent.
+        pub fn base_url(&self) -> &str {
+            &self.base_url
+        }
+
+        /// Fetch the list of all tidal stations.
+        ///
+        /// # Errors
+        ///
+        /// This function will return an error if the request fails, the server responds
+        /// with an error status (such as for an invalid subscription key), or the response
+        /// cannot be parsed as station data.
+        pub fn fetch_stations(&self) -> Result<Vec<Station>, Box<dyn Error>> {
+            let bytes = self.get(&stations_url(&self.base_url))?;
+            Ok(super::stations_from_reader(bytes.as_ref())?)
+        }
+
+        /// Fetch the high and low tides for the given station, for the API's default week
+        /// or `duration_days` days.
+        ///
+        /// # Errors
+        ///
+        /// This function will return an error if the request fails, the server responds
+        /// with an error status, or the response cannot be parsed as tidal events.
+        pub fn fetch_tidal_events(
+            &self,
+            station: &StationId,
+            duration_days: Option<u32>,
+        ) -> Result<Vec<TidalEvent>, Box<dyn Error>> {
+            let url = tidal_events_url(&self.base_url, station, duration_days);
+            let bytes = self.get(&url)?;
+            Ok(super::tidal_events_from_reader(bytes.as_ref())?)
+        }
+
+        fn get(&self, url: &str) -> Result<Bytes, Box<dyn Error>> {
+            let request = self
+                .client
+                .http()
+                .get(url)
+                .header(SUBSCRIPTION_KEY_HEADER, &self.subscription_key)
+                .build()?;
+            self.client.send(request).map_err(|e| e as Box<dyn Error>)
+        }
+    }
+}
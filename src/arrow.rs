@@ -0,0 +1,234 @@
+//! Arrow record batches of predictions, and writing them as Parquet files.
+//!
+//! Enabled by the `arrow` feature. [`events_batch`] and [`heights_batch`] lay out high and
+//! low tides and continuous heights as Arrow [`RecordBatch`]es, and [`write_parquet`]
+//! writes batches to a Snappy-compressed Parquet file that DuckDB, Arrow or pandas can
+//! query.
+//!
+//! # Examples
+//! ```
+//! use std::fs::File;
+//!
+//! use rjw_uktides::arrow;
+//! use rjw_uktides::StationId;
+//!
+//! let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+//!     .expect("Failed to read file as tides data.");
+//! let station = StationId("0053".to_owned());
+//! let batch = arrow::events_batch(&station, &tides.tidal_event_list);
+//! assert_eq!(batch.num_rows(), tides.tidal_event_list.len());
+//!
+//! let mut file = Vec::new();
+//! arrow::write_parquet(&mut file, &[batch]).unwrap();
+//! ```
+//!
+//! The file can then be queried with DuckDB, for example, as
+//! `SELECT * FROM 'events.parquet' WHERE type = 'high'`.
+
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, BooleanArray, Float64Array, StringArray, TimestampMillisecondArray};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+
+use crate::{StationId, TidalEvent, TidalHeightOccurrence};
+
+pub use arrow_array::RecordBatch;
+
+/// The time zone of the timestamp columns.
+const UTC: &str = "UTC";
+
+/// High and low tides at the station, with the columns `station`, `time`, `type`
+/// (`"high"` or `"low"`), `height` in metres, `approximate_time` and
+/// `approximate_height`.
+///
+/// Times are timestamps in UTC with millisecond precision, and no column is nullable.
+pub fn events_batch(station: &StationId, events: &[TidalEvent]) -> RecordBatch {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(vec![station.0.as_str(); events.len()])),
+        Arc::new(
+            TimestampMillisecondArray::from_iter_values(
+                events.iter().map(|e| e.date_time.timestamp_millis()),
+            )
+            .with_timezone(UTC),
+        ),
+        Arc::new(StringArray::from_iter_values(
+            events.iter().map(|e| e.event_type.name()),
+        )),
+        Arc::new(Float64Array::from_iter_values(
+            events.iter().map(|e| e.height.0),
+        )),
+        Arc::new(BooleanArray::from(
+            events
+                .iter()
+                .map(|e| e.is_approximate_time.is_some())
+                .collect::<Vec<_>>(),
+        )),
+        Arc::new(BooleanArray::from(
+            events
+                .iter()
+                .map(|e| e.is_approximate_height.is_some())
+                .collect::<Vec<_>>(),
+        )),
+    ];
+    let schema = Schema::new(vec![
+        Field::new("station", DataType::Utf8, false),
+        Field::new("time", timestamp(), false),
+        Field::new("type", DataType::Utf8, false),
+        Field::new("height", DataType::Float64, false),
+        Field::new("approximate_time", DataType::Boolean, false),
+        Field::new("approximate_height", DataType::Boolean, false),
+    ]);
+    RecordBatch::try_new(Arc::new(schema), columns).expect("Columns match the schema.")
+}
+
+/// Continuous heights at the station, with the columns `station`, `time` and `height`
+/// in metres.
+///
+/// Times are timestamps in UTC with millisecond precision, and no column is nullable.
+pub fn heights_batch(station: &StationId, heights: &[TidalHeightOccurrence]) -> RecordBatch {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(vec![station.0.as_str(); heights.len()])),
+        Arc::new(
+            TimestampMillisecondArray::from_iter_values(
+                heights.iter().map(|h| h.date_time.timestamp_millis()),
+            )
+            .with_timezone(UTC),
+        ),
+        Arc::new(Float64Array::from_iter_values(
+            heights.iter().map(|h| h.height.0),
+        )),
+    ];
+    let schema = Schema::new(vec![
+        Field::new("station", DataType::Utf8, false),
+        Field::new("time", timestamp(), false),
+        Field::new("height", DataType::Float64, false),
+    ]);
+    RecordBatch::try_new(Arc::new(schema), columns).expect("Columns match the schema.")
+}
+
+fn timestamp() -> DataType {
+    DataType::Timestamp(TimeUnit::Millisecond, Some(UTC.into()))
+}
+
+/// Write the batches to `writer` as a Snappy-compressed Parquet file, with a row group for
+/// each batch.
+///
+/// # Errors
+///
+/// Returns an error if writing fails, if there are no batches, or if they have different
+/// schemas.
+pub fn write_parquet<W: Write + Send>(
+    writer: W,
+    batches: &[RecordBatch],
+) -> Result<(), ParquetError> {
+    let Some(first) = batches.first() else {
+        return Err(ParquetError::General(
+            "there must be at least one batch to write".to_owned(),
+        ));
+    };
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut parquet = ArrowWriter::try_new(writer, first.schema(), Some(properties))?;
+    for batch in batches {
+        if batch.schema() != first.schema() {
+            return Err(ParquetError::General(
+                "the batches must have the same schema".to_owned(),
+            ));
+        }
+        parquet.write(batch)?;
+        parquet.flush()?;
+    }
+    parquet.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::*;
+    use crate::testing::{utc, TidalEventBuilder, TidePredictionsBuilder};
+
+    fn station() -> StationId {
+        StationId("0053".to_owned())
+    }
+
+    /// Write the batches to a Parquet file and read them back with the Parquet reader.
+    fn round_trip(batches: &[RecordBatch]) -> Vec<RecordBatch> {
+        let mut file = Vec::new();
+        write_parquet(&mut file, batches).unwrap();
+        ParquetRecordBatchReaderBuilder::try_new(Bytes::from(file))
+            .unwrap()
+            .build()
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn reads_back_events_and_heights() {
+        let tides = TidePredictionsBuilder::new()
+            .event(
+                TidalEventBuilder::high()
+                    .at("2023-04-03T06:00")
+                    .height(4.25),
+            )
+            .event(TidalEventBuilder::low().at("2023-04-03T12:15").height(0.5))
+            .height("2023-04-03T06:00", 4.25)
+            .height("2023-04-03T06:30", 4.0)
+            .build();
+
+        let events = events_batch(&station(), &tides.tidal_event_list);
+        let read = round_trip(std::slice::from_ref(&events));
+        assert_eq!(read, std::slice::from_ref(&events));
+        let times = read[0]
+            .column_by_name("time")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()
+            .unwrap();
+        assert_eq!(times.value(1), utc("2023-04-03T12:15").timestamp_millis());
+        assert_eq!(times.timezone(), Some(UTC));
+
+        let heights = heights_batch(&station(), &tides.tidal_height_occurrence_list);
+        assert_eq!(round_trip(std::slice::from_ref(&heights)), [heights]);
+    }
+
+    #[test]
+    fn writes_a_row_group_for_each_batch() {
+        let first = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::high().at("2023-04-03T06:00"))
+            .build();
+        let second = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::low().at("2023-04-03T12:15"))
+            .event(TidalEventBuilder::high().at("2023-04-03T18:30"))
+            .build();
+        let batches = [
+            events_batch(&station(), &first.tidal_event_list),
+            events_batch(&StationId("0001".to_owned()), &second.tidal_event_list),
+        ];
+        let mut file = Vec::new();
+        write_parquet(&mut file, &batches).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(file)).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+        let rows: usize = reader.build().unwrap().map(|b| b.unwrap().num_rows()).sum();
+        assert_eq!(rows, 3);
+    }
+
+    #[test]
+    fn rejects_no_batches_and_mixed_schemas() {
+        assert!(write_parquet(Vec::new(), &[]).is_err());
+        let batches = [
+            events_batch(&station(), &[]),
+            heights_batch(&station(), &[]),
+        ];
+        assert!(write_parquet(Vec::new(), &batches).is_err());
+    }
+}
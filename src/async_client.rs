@@ -0,0 +1,383 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures_core::Stream;
+use futures_util::stream::{self, StreamExt};
+
+use crate::rate_limit::RateLimiter;
+use crate::{
+    stations_from_reader, stations_url, tides_from_slice, tides_url, RateLimit, RetryPolicy,
+    Station, StationId, TidePredictions, DEFAULT_BASE_URL,
+};
+
+/// Asynchronous client for the EasyTide web service.
+///
+/// Enabled by the `async` feature. It sends requests with reqwest's asynchronous client,
+/// so must be used within a Tokio runtime. Failed requests are retried and the rate of
+/// requests may be limited as with [`Client`](crate::Client), but unlike `Client` it does
+/// not cache responses. It uses the same TLS features as `Client`.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "mock_server")]
+/// # {
+/// use futures_util::StreamExt;
+/// use rjw_uktides::mock_server::MockServer;
+/// use rjw_uktides::{AsyncClient, StationId};
+///
+/// let server = MockServer::start().expect("Failed to start mock server.");
+/// let client = AsyncClient::with_base_url(server.base_url());
+/// let runtime = tokio::runtime::Builder::new_current_thread()
+///     .enable_all()
+///     .build()
+///     .unwrap();
+///
+/// runtime.block_on(async {
+///     let stations: Vec<_> = client.stations_stream().collect().await;
+///     assert!(stations.iter().all(Result::is_ok));
+///
+///     let ids = [StationId("0053".to_owned()), StationId("nope".to_owned())];
+///     let mut results = client.tides_stream(ids, 2);
+///     let mut fetched = 0;
+///     while let Some((station, result)) = results.next().await {
+///         assert_eq!(result.is_ok(), station.0 == "0053");
+///         fetched += 1;
+///     }
+///     assert_eq!(fetched, 2);
+/// });
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AsyncClient {
+    base_url: String,
+    http: reqwest::Client,
+    retry: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl Default for AsyncClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsyncClient {
+    /// Create a client for the public EasyTide web service.
+    pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    /// Create a client that sends requests to `base_url` instead of the public EasyTide
+    /// web service, as with [`Client::with_base_url`](crate::Client::with_base_url).
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            http: reqwest::Client::new(),
+            retry: RetryPolicy::default(),
+            rate_limiter: None,
+        }
+    }
+
+    /// Retry failed requests according to `policy` instead of the default [`RetryPolicy`],
+    /// as with [`Client::with_retry`](crate::Client::with_retry).
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// The policy used to retry failed requests.
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// Limit the rate at which requests are sent, as with
+    /// [`Client::with_rate_limit`](crate::Client::with_rate_limit).
+    ///
+    /// The limit is shared by clones of the client, and applies to each attempt at a
+    /// request, including retries.
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(limit)));
+        self
+    }
+
+    /// The limit on the rate at which requests are sent, if any.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limiter.as_ref().map(|r| r.limit())
+    }
+
+    /// The base URL to which requests are sent.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Fetch the list of all tidal stations.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails, the server responds with
+    /// an error status, or the response cannot be parsed as station data.
+    pub async fn fetch_stations(&self) -> Result<Vec<Station>, Box<dyn Error + Send + Sync>> {
+        let bytes = self.get(stations_url(&self.base_url)).await?;
+        Ok(stations_from_reader(bytes.as_ref())?)
+    }
+
+    /// Fetch tide predictions for the given station.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails, the server responds with
+    /// an error status, or the response cannot be parsed as tide predictions.
+    pub async fn fetch_tides(
+        &self,
+        station: &StationId,
+    ) -> Result<TidePredictions, Box<dyn Error + Send + Sync>> {
+        let bytes = self.get(tides_url(&self.base_url, station)).await?;
+        Ok(tides_from_slice(&bytes)?)
+    }
+
+    /// A stream of the tidal stations, or of a single error if fetching the list fails.
+    ///
+    /// The service sends the list as a single response, so the stations follow one
+    /// another once it has been received.
+    pub fn stations_stream(
+        &self,
+    ) -> impl Stream<Item = Result<Station, Box<dyn Error + Send + Sync>>> + '_ {
+        stream::once(self.fetch_stations())
+            .map(|result| match result {
+                Ok(stations) => stations.into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            })
+            .flat_map(stream::iter)
+    }
+
+    /// A stream of the tide predictions for each of the given stations, in the order the
+    /// responses arrive, with up to `concurrency` requests sent at the same time.
+    ///
+    /// As with [`Client::fetch_tides_many`](crate::Client::fetch_tides_many), failures are
+    /// reported separately for each station, and stations listed more than once are only
+    /// fetched once. A `concurrency` of zero is treated as one. Requests are only sent as
+    /// the stream is polled, so a slow consumer slows the fetching.
+    pub fn tides_stream(
+        &self,
+        stations: impl IntoIterator<Item = StationId>,
+        concurrency: usize,
+    ) -> impl Stream<
+        Item = (
+            StationId,
+            Result<TidePredictions, Box<dyn Error + Send + Sync>>,
+        ),
+    > + '_ {
+        let mut seen = HashSet::new();
+        let unique: Vec<StationId> = stations
+            .into_iter()
+            .filter(|s| seen.insert(s.clone()))
+            .collect();
+        stream::iter(unique)
+            .map(move |station| async move {
+                let result = self.fetch_tides(&station).await;
+                (station, result)
+            })
+            .buffer_unordered(concurrency.max(1))
+    }
+
+    /// Fetch tide predictions for each of the given stations, with up to `concurrency`
+    /// requests sent at the same time, as with
+    /// [`Client::fetch_tides_many`](crate::Client::fetch_tides_many).
+    ///
+    /// Failures are reported separately for each station rather than failing the whole
+    /// batch. Stations listed more than once are only fetched once. A `concurrency` of zero
+    /// is treated as one. To handle each station's predictions as they arrive, use
+    /// [`tides_stream`](Self::tides_stream) instead.
+    pub async fn fetch_tides_many(
+        &self,
+        stations: &[StationId],
+        concurrency: usize,
+    ) -> HashMap<StationId, Result<TidePredictions, Box<dyn Error + Send + Sync>>> {
+        self.tides_stream(stations.iter().cloned(), concurrency)
+            .collect()
+            .await
+    }
+
+    async fn get(&self, url: String) -> Result<Bytes, Box<dyn Error + Send + Sync>> {
+        let response = self.execute(&url).await?.error_for_status()?;
+        #[cfg(feature = "compression")]
+        let encoding = response
+            .headers()
+            .get(reqwest::header::CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned);
+        let body = response.bytes().await?;
+        #[cfg(feature = "compression")]
+        if let Some(encoding) = encoding {
+            if let Some(decoded) = crate::compression::decode_content(&encoding, &body)? {
+                return Ok(decoded.into());
+            }
+        }
+        Ok(body)
+    }
+
+    /// Send a GET request for `url`, retrying it according to the retry policy.
+    async fn execute(&self, url: &str) -> reqwest::Result<reqwest::Response> {
+        let mut attempt = 1;
+        loop {
+            if let Some(limiter) = &self.rate_limiter {
+                limiter.acquire_async().await;
+            }
+            let request = self.http.get(url);
+            #[cfg(feature = "compression")]
+            let request = request.header(reqwest::header::ACCEPT_ENCODING, "gzip, deflate");
+            let result = request.send().await;
+            let Some(delay) = self.retry.retry_delay(attempt, &result) else {
+                return result;
+            };
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                url,
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                "retrying failed request"
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::mock_server::MockServer;
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn quick_retries() -> RetryPolicy {
+        RetryPolicy {
+            initial_backoff: Duration::from_millis(10),
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn retries_a_failed_request() {
+        let server = MockServer::start().unwrap();
+        let client = AsyncClient::with_base_url(server.base_url()).with_retry(quick_retries());
+        server.fail_next(1);
+        let stations = block_on(client.fetch_stations()).unwrap();
+        assert!(!stations.is_empty());
+        assert_eq!(server.request_count(), 2);
+    }
+
+    #[test]
+    fn gives_up_after_the_last_attempt() {
+        let server = MockServer::start().unwrap();
+        let client = AsyncClient::with_base_url(server.base_url()).with_retry(quick_retries());
+        server.fail_next(3);
+        assert!(block_on(client.fetch_stations()).is_err());
+        assert_eq!(server.request_count(), 3);
+
+        let client = client.with_retry(RetryPolicy::none());
+        server.fail_next(1);
+        assert!(block_on(client.fetch_stations()).is_err());
+        assert_eq!(server.request_count(), 4);
+    }
+
+    #[test]
+    fn does_not_retry_client_errors() {
+        let server = MockServer::start().unwrap();
+        let client = AsyncClient::with_base_url(server.base_url()).with_retry(quick_retries());
+        assert!(block_on(client.fetch_tides(&StationId("nope".to_owned()))).is_err());
+        assert_eq!(server.request_count(), 1);
+    }
+
+    #[test]
+    fn fetches_many_stations_once_each() {
+        let server = MockServer::start().unwrap();
+        let client = AsyncClient::with_base_url(server.base_url());
+        let sandown = StationId("0053".to_owned());
+        let unknown = StationId("nope".to_owned());
+        let stations = [sandown.clone(), unknown.clone(), sandown.clone()];
+        let results = block_on(client.fetch_tides_many(&stations, 4));
+        assert_eq!(results.len(), 2);
+        assert!(results[&sandown].is_ok());
+        assert!(results[&unknown].is_err());
+        assert_eq!(server.request_count(), 2);
+    }
+
+    #[test]
+    fn treats_zero_concurrency_as_one() {
+        let server = MockServer::start().unwrap();
+        let client = AsyncClient::with_base_url(server.base_url());
+        let stations = [StationId("0053".to_owned()), StationId("0014".to_owned())];
+        let results = block_on(client.fetch_tides_many(&stations, 0));
+        assert_eq!(results.len(), 2);
+        assert!(results.values().all(Result::is_ok));
+    }
+
+    #[test]
+    fn spaces_out_rate_limited_requests() {
+        let server = MockServer::start().unwrap();
+        let client =
+            AsyncClient::with_base_url(server.base_url()).with_rate_limit(RateLimit::new(1200, 1));
+        assert_eq!(client.rate_limit(), Some(RateLimit::new(1200, 1)));
+        let start = std::time::Instant::now();
+        block_on(async {
+            for _ in 0..3 {
+                client.fetch_stations().await.unwrap();
+            }
+        });
+        // One request immediately, then one every 50 ms.
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert_eq!(server.request_count(), 3);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn requests_and_decodes_compressed_responses() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut accept_encoding = String::new();
+            let mut line = String::new();
+            let mut reader = BufReader::new(&stream);
+            while reader.read_line(&mut line).unwrap() > 2 {
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("accept-encoding") {
+                        accept_encoding = value.trim().to_owned();
+                    }
+                }
+                line.clear();
+            }
+            let body = crate::testing::TIDES_GZIP_BYTES;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\
+                 Connection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(body).unwrap();
+            accept_encoding
+        });
+
+        let client = AsyncClient::with_base_url(base_url);
+        let tides = block_on(client.fetch_tides(&StationId("0053".to_owned()))).unwrap();
+        let expected = crate::testing::tide_predictions();
+        assert_eq!(tides.tidal_event_list, expected.tidal_event_list);
+        assert!(server.join().unwrap().contains("gzip"));
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{StationId, TidalEvent, TidalEventType, TidePredictions};
+
+/// Predictions for several stations, such as those along a stretch of coast, for answering
+/// questions about the tides across them.
+///
+/// Build one from the predictions for the stations of interest, for example those in one
+/// [`Region`](crate::Region), as the atlas treats all of its stations as a group.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// use chrono::Duration;
+/// use rjw_uktides::{StationId, TidalEventType, TideAtlas};
+///
+/// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+///     .expect("Failed to read file as tides data.");
+/// // Pretend a second station has the same tides twenty minutes later.
+/// let mut later = tides.clone();
+/// for event in &mut later.tidal_event_list {
+///     event.date_time += Duration::minutes(20);
+/// }
+/// let atlas = TideAtlas::new([
+///     (StationId("0053".to_owned()), tides.clone()),
+///     (StationId("0054".to_owned()), later),
+/// ]);
+///
+/// let high = tides.next_event(tides.tidal_event_list[0].date_time, TidalEventType::HighWater).unwrap();
+/// let next = atlas.next_high_tides(high.date_time - Duration::hours(1));
+/// assert_eq!(next[0].0 .0, "0053");
+/// assert_eq!(next[1].1.date_time, high.date_time + Duration::minutes(20));
+///
+/// let near = atlas.near_high_water(high.date_time, Duration::minutes(10));
+/// assert_eq!(near.len(), 1);
+///
+/// let spread = atlas.spread(TidalEventType::HighWater, high.date_time).unwrap();
+/// assert_eq!(spread.range(), Duration::minutes(20));
+/// assert_eq!(spread.latest().0 .0, "0054");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TideAtlas {
+    predictions: BTreeMap<StationId, TidePredictions>,
+}
+
+/// When the same high or low tide happens at each station in a [`TideAtlas`], as found
+/// by [`TideAtlas::spread`].
+#[derive(Debug, Clone)]
+pub struct EventSpread<'a> {
+    /// The tide at each station, earliest first.
+    pub events: Vec<(&'a StationId, &'a TidalEvent)>,
+}
+
+impl EventSpread<'_> {
+    /// The station where the tide happens first.
+    pub fn earliest(&self) -> (&StationId, &TidalEvent) {
+        self.events[0]
+    }
+
+    /// The station where the tide happens last.
+    pub fn latest(&self) -> (&StationId, &TidalEvent) {
+        self.events[self.events.len() - 1]
+    }
+
+    /// The time between the tide at the first station and at the last.
+    pub fn range(&self) -> Duration {
+        self.latest().1.date_time - self.earliest().1.date_time
+    }
+}
+
+impl TideAtlas {
+    /// Create an atlas of the predictions for each station. If a station is given more
+    /// than once, the last predictions for it are used.
+    pub fn new(predictions: impl IntoIterator<Item = (StationId, TidePredictions)>) -> Self {
+        Self {
+            predictions: predictions.into_iter().collect(),
+        }
+    }
+
+    /// Add or replace the predictions for a station.
+    pub fn insert(&mut self, station: StationId, predictions: TidePredictions) {
+        self.predictions.insert(station, predictions);
+    }
+
+    /// The stations in the atlas, in order of ID.
+    pub fn stations(&self) -> impl Iterator<Item = &StationId> {
+        self.predictions.keys()
+    }
+
+    /// The predictions for a station, if it is in the atlas.
+    pub fn get(&self, station: &StationId) -> Option<&TidePredictions> {
+        self.predictions.get(station)
+    }
+
+    /// The first high tide after `after` at each station, earliest first. Stations without
+    /// a high tide after then are left out.
+    pub fn next_high_tides(&self, after: DateTime<Utc>) -> Vec<(&StationId, &TidalEvent)> {
+        let mut next: Vec<_> = self
+            .predictions
+            .iter()
+            .filter_map(|(station, tides)| {
+                let event = tides.next_event(after, TidalEventType::HighWater)?;
+                Some((station, event))
+            })
+            .collect();
+        next.sort_by_key(|(_, event)| event.date_time);
+        next
+    }
+
+    /// The stations with a high tide within `within` of `at`, either side, with that high
+    /// tide, earliest first.
+    pub fn near_high_water(
+        &self,
+        at: DateTime<Utc>,
+        within: Duration,
+    ) -> Vec<(&StationId, &TidalEvent)> {
+        let mut near: Vec<_> = self
+            .predictions
+            .iter()
+            .filter_map(|(station, tides)| {
+                let event = nearest_event(tides, TidalEventType::HighWater, at)?;
+                (apart(event.date_time, at) <= within).then_some((station, event))
+            })
+            .collect();
+        near.sort_by_key(|(_, event)| event.date_time);
+        near
+    }
+
+    /// The high or low tide, as given by `event_type`, nearest to `at` at each station,
+    /// showing how the tide progresses along the coast. `None` if no station has a tide of
+    /// that type.
+    ///
+    /// Give a time close to the tide at one of the stations, so that the same tide is
+    /// picked at each station rather than the one before or after.
+    pub fn spread(&self, event_type: TidalEventType, at: DateTime<Utc>) -> Option<EventSpread<'_>> {
+        let mut events: Vec<_> = self
+            .predictions
+            .iter()
+            .filter_map(|(station, tides)| Some((station, nearest_event(tides, event_type, at)?)))
+            .collect();
+        if events.is_empty() {
+            return None;
+        }
+        events.sort_by_key(|(_, event)| event.date_time);
+        Some(EventSpread { events })
+    }
+}
+
+impl FromIterator<(StationId, TidePredictions)> for TideAtlas {
+    fn from_iter<T: IntoIterator<Item = (StationId, TidePredictions)>>(iter: T) -> Self {
+        Self::new(iter)
+    }
+}
+
+/// The tide of the given type closest in time to `at`, before or after.
+fn nearest_event(
+    tides: &TidePredictions,
+    event_type: TidalEventType,
+    at: DateTime<Utc>,
+) -> Option<&TidalEvent> {
+    tides
+        .tidal_event_list
+        .iter()
+        .filter(|e| e.event_type == event_type)
+        .min_by_key(|e| apart(e.date_time, at))
+}
+
+/// The time between `a` and `b`, whichever is first.
+pub(crate) fn apart(a: DateTime<Utc>, b: DateTime<Utc>) -> Duration {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
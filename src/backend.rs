@@ -0,0 +1,78 @@
+//! Selection of the JSON parser used to deserialize API responses.
+//!
+//! `serde_json` is used by default. Enabling the `sonic-rs` feature switches to the
+//! SIMD-accelerated `sonic-rs` parser, which is noticeably faster on the large stations
+//! payload (see `cargo bench` with and without the feature).
+//!
+//! With the `compression` feature enabled, the reader functions transparently decompress
+//! gzip or zlib-compressed data.
+
+use std::io::Read;
+
+use serde::de::{Deserialize, DeserializeOwned, DeserializeSeed};
+
+use crate::Error;
+
+/// Deserialize JSON from the reader using `serde_json`.
+#[cfg(not(feature = "sonic-rs"))]
+pub(crate) fn from_reader<T: DeserializeOwned>(rdr: impl Read) -> Result<T, Error> {
+    Ok(serde_json::from_reader(decompressing(rdr)?)?)
+}
+
+/// Deserialize JSON from the reader using `sonic-rs`.
+#[cfg(feature = "sonic-rs")]
+pub(crate) fn from_reader<T: DeserializeOwned>(rdr: impl Read) -> Result<T, Error> {
+    Ok(sonic_rs::from_reader(decompressing(rdr)?)?)
+}
+
+/// Deserialize JSON from the buffer using `serde_json`.
+#[cfg(not(feature = "sonic-rs"))]
+pub(crate) fn from_slice<'a, T: Deserialize<'a>>(data: &'a [u8]) -> Result<T, Error> {
+    Ok(serde_json::from_slice(data)?)
+}
+
+/// Deserialize JSON from the buffer using `sonic-rs`.
+#[cfg(feature = "sonic-rs")]
+pub(crate) fn from_slice<'a, T: Deserialize<'a>>(data: &'a [u8]) -> Result<T, Error> {
+    Ok(sonic_rs::from_slice(data)?)
+}
+
+/// Deserialize JSON from the reader with the given seed using `serde_json`.
+#[cfg(not(feature = "sonic-rs"))]
+pub(crate) fn from_reader_seed<T, V>(rdr: impl Read, seed: T) -> Result<V, Error>
+where
+    T: for<'de> DeserializeSeed<'de, Value = V>,
+{
+    let mut de = serde_json::Deserializer::from_reader(decompressing(rdr)?);
+    let value = seed.deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Deserialize JSON from the reader with the given seed using `sonic-rs`.
+#[cfg(feature = "sonic-rs")]
+pub(crate) fn from_reader_seed<T, V>(rdr: impl Read, seed: T) -> Result<V, Error>
+where
+    T: for<'de> DeserializeSeed<'de, Value = V>,
+{
+    let mut data = Vec::new();
+    decompressing(rdr)?
+        .read_to_end(&mut data)
+        .map_err(|e| Error::from(serde_json::Error::io(e)))?;
+    let mut de = sonic_rs::Deserializer::from_slice(&data);
+    let value = seed.deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Wrap the reader so that compressed data is decompressed.
+#[cfg(feature = "compression")]
+fn decompressing(rdr: impl Read) -> Result<impl Read, Error> {
+    crate::compression::reader(rdr).map_err(|e| Error::from(serde_json::Error::io(e)))
+}
+
+/// Pass the reader through unchanged, as decompression is not enabled.
+#[cfg(not(feature = "compression"))]
+fn decompressing(rdr: impl Read) -> Result<impl Read, Error> {
+    Ok(rdr)
+}
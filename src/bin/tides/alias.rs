@@ -0,0 +1,79 @@
+//! Names for stations, stored in the configuration file and accepted anywhere a station
+//! ID is expected.
+
+use std::error::Error;
+use std::io::Write;
+
+use clap::{Args, Subcommand};
+use toml_edit::{value, Item, Table};
+
+use rjw_uktides::StationId;
+
+use crate::config::{self, Config};
+use crate::error::CliError;
+
+/// Manage names that can be given in place of station IDs, such as `tides -s home`.
+#[derive(Args, Clone, Debug)]
+pub struct AliasArgs {
+    #[command(subcommand)]
+    command: AliasCommand,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum AliasCommand {
+    /// Add an alias for a station, replacing any existing alias with the same name.
+    Add {
+        /// The alias, such as "home".
+        name: String,
+        /// ID of the tidal station.
+        station: StationId,
+    },
+    /// Remove an alias.
+    Remove {
+        /// The alias to remove.
+        name: String,
+    },
+    /// List the aliases with their station IDs and names.
+    List,
+}
+
+pub fn alias(args: AliasArgs, config: &Config, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    match args.command {
+        AliasCommand::Add { name, station } => {
+            if crate::is_known_station(&StationId(name.clone()))? {
+                return Err(
+                    format!("{name} is a station ID and cannot be used as an alias.").into(),
+                );
+            }
+            if !crate::is_known_station(&station)? {
+                return Err(CliError::UnknownStation(station).into());
+            }
+            config::edit(|document| {
+                let aliases = document
+                    .entry("aliases")
+                    .or_insert_with(|| Item::Table(Table::new()));
+                aliases[name.as_str()] = value(station.0);
+            })?;
+        }
+        AliasCommand::Remove { name } => {
+            if !config.aliases.contains_key(&name) {
+                return Err(format!("There is no alias named {name}.").into());
+            }
+            config::edit(|document| {
+                if let Some(aliases) = document
+                    .get_mut("aliases")
+                    .and_then(Item::as_table_like_mut)
+                {
+                    aliases.remove(&name);
+                }
+            })?;
+        }
+        AliasCommand::List => {
+            for (name, station) in &config.aliases {
+                let station_name = crate::station_name(station)?;
+                writeln!(out, "{name}\t{station}\t{station_name}")?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use rjw_uktides::{Client, Station, StationCache, StationId, TidalEventType, TidePredictions};
+
+use crate::output::{Meta, TidesJson};
+use crate::serve::{read_request_path, write_response};
+use crate::units::Units;
+
+const JSON: &str = "application/json";
+
+/// How long the JSON API serves the station list before fetching it again.
+const STATIONS_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Read a single HTTP request from the stream and write the JSON API response, logging
+/// any errors.
+pub fn respond_api(stream: TcpStream, api: &JsonApi) {
+    let result = read_request_path(&stream).and_then(|path| {
+        let (status, content_type, body) = api.respond(&path);
+        write_response(stream, status, content_type, &body)
+    });
+    if let Err(e) = result {
+        eprintln!("Failed to respond to JSON API request: {e}");
+    }
+}
+
+/// The JSON API, with the station list and each station's predictions cached in memory
+/// and refetched when they become stale.
+pub struct JsonApi {
+    client: Client,
+    stations: StationCache,
+    refresh: Duration,
+    tides: Mutex<HashMap<StationId, StationTides>>,
+}
+
+impl JsonApi {
+    pub fn new(client: Client, refresh: Duration) -> Self {
+        Self {
+            stations: StationCache::new(client.clone(), STATIONS_MAX_AGE),
+            client,
+            refresh,
+            tides: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The response status, content type and body for the request path.
+    fn respond(&self, path: &str) -> (&'static str, &'static str, String) {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let result = match segments.as_slice() {
+            ["stations"] => self.stations.get_or_fetch().map(|stations| {
+                let stations: Vec<Value> = stations
+                    .iter()
+                    .map(|s| {
+                        json!({
+                            "id": s.id,
+                            "name": s.name,
+                            "country": s.country,
+                            "latitude": s.location.latitude.0,
+                            "longitude": s.location.longitude.0,
+                            "continuous_heights_available": s.continuous_heights_available,
+                        })
+                    })
+                    .collect();
+                (JSON, Value::from(stations).to_string())
+            }),
+            ["tides", id] => match self.tides(id) {
+                Ok(Some(StationTides {
+                    station,
+                    tides,
+                    fetched_at,
+                    ..
+                })) => {
+                    let meta = Meta::new(
+                        station.id.clone(),
+                        station.name.clone(),
+                        rjw_uktides::tides_url(self.client.base_url(), &station.id),
+                        Some(fetched_at),
+                        Units::Metric,
+                    );
+                    serde_json::to_string(&TidesJson::new(&station.id, &tides, &meta))
+                        .map(|body| (JSON, body))
+                        .map_err(Into::into)
+                }
+                Ok(None) => return not_found(),
+                Err(e) => Err(e),
+            },
+            ["next", id] => match self.tides(id) {
+                Ok(Some(StationTides { tides, .. })) => Ok((JSON, next_tides(&tides).to_string())),
+                Ok(None) => return not_found(),
+                Err(e) => Err(e),
+            },
+            ["ics", file] => match file.strip_suffix(".ics").map(|id| self.tides(id)) {
+                Some(Ok(Some(StationTides { station, tides, .. }))) => Ok((
+                    "text/calendar; charset=utf-8",
+                    tides.to_ics(&station.id, &station.name),
+                )),
+                Some(Ok(None)) | None => return not_found(),
+                Some(Err(e)) => Err(e),
+            },
+            _ => return not_found(),
+        };
+        match result {
+            Ok((content_type, body)) => ("200 OK", content_type, body),
+            Err(e) => (
+                "502 Bad Gateway",
+                JSON,
+                json!({ "error": e.to_string() }).to_string(),
+            ),
+        }
+    }
+
+    /// The station and its predictions, fetching the predictions if they are missing or
+    /// stale, or `None` if there is no such station.
+    fn tides(&self, id: &str) -> Result<Option<StationTides>, Box<dyn Error>> {
+        let id = StationId(id.to_owned());
+        let Some(station) = self
+            .stations
+            .get_or_fetch()?
+            .iter()
+            .find(|s| s.id == id)
+            .cloned()
+        else {
+            return Ok(None);
+        };
+        if let Some(cached) = self.tides.lock().unwrap().get(&id) {
+            if cached.fetched.elapsed() < self.refresh {
+                return Ok(Some(cached.clone()));
+            }
+        }
+        let fetched = StationTides {
+            station,
+            tides: Arc::new(self.client.fetch_tides(&id)?),
+            fetched: Instant::now(),
+            fetched_at: Utc::now(),
+        };
+        self.tides.lock().unwrap().insert(id, fetched.clone());
+        Ok(Some(fetched))
+    }
+}
+
+/// A station and its predictions, shared between requests, and when they were fetched.
+#[derive(Clone)]
+struct StationTides {
+    station: Station,
+    tides: Arc<TidePredictions>,
+    /// When the predictions were fetched, for judging whether they are stale.
+    fetched: Instant,
+    /// When the predictions were fetched, for the response's metadata.
+    fetched_at: DateTime<Utc>,
+}
+
+fn not_found() -> (&'static str, &'static str, String) {
+    (
+        "404 Not Found",
+        JSON,
+        json!({ "error": "Not Found" }).to_string(),
+    )
+}
+
+/// The times and heights of the next high and low tides, and the current height, as a
+/// flat JSON object.
+pub fn next_tides(tides: &TidePredictions) -> Value {
+    let now = Utc::now();
+    let high = tides.next_event(now, TidalEventType::HighWater);
+    let low = tides.next_event(now, TidalEventType::LowWater);
+    json!({
+        "next_high_time": high.map(|e| e.date_time.to_rfc3339()),
+        "next_high_height": high.map(|e| e.height.0),
+        "next_low_time": low.map(|e| e.date_time.to_rfc3339()),
+        "next_low_height": low.map(|e| e.height.0),
+        "height": tides.height_at(now).map(|h| h.0),
+    })
+}
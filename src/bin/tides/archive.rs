@@ -0,0 +1,79 @@
+//! Keeping a local history of predictions for a station.
+//!
+//! Each station has a directory in the archive containing every fetched response, named
+//! after the time it was fetched, so that later analysis can see how the predictions for a
+//! particular tide changed, and `merged.json`, all of the predictions combined with the
+//! most recent taking precedence. Both are in the EasyTide response format, so can be read
+//! with `tides --from-file`.
+
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use clap::Args;
+
+use rjw_uktides::StationId;
+
+use crate::fetch::Fetcher;
+
+/// The file in a station's archive directory holding all of its predictions combined.
+const MERGED_FILE: &str = "merged.json";
+
+/// Fetch a station's predictions and add them to a local archive.
+#[derive(Args, Clone, Debug)]
+pub struct ArchiveArgs {
+    /// ID of the tidal station to archive.
+    #[arg(short, long)]
+    station: StationId,
+
+    /// Directory containing the archive, which is created if necessary.
+    #[arg(long)]
+    dir: PathBuf,
+}
+
+pub fn archive(args: ArchiveArgs, fetcher: &Fetcher) -> Result<(), Box<dyn Error>> {
+    let tides = crate::fetch_tides(fetcher, &args.station)?;
+    let dir = args.dir.join(&args.station.0);
+    fs::create_dir_all(&dir).map_err(|e| format!("Could not create {}: {e}", dir.display()))?;
+
+    let fetched_at = Utc::now().format("%Y-%m-%dT%H%M%SZ");
+    write_atomically(
+        &dir.join(format!("{fetched_at}.json")),
+        &serde_json::to_vec(&tides)?,
+    )?;
+
+    let merged_path = dir.join(MERGED_FILE);
+    let fetched = tides.tidal_event_list.len();
+    let (merged, before) = match File::open(&merged_path) {
+        Ok(file) => {
+            let mut merged = rjw_uktides::tides_from_reader(BufReader::new(file))?;
+            let before = merged.tidal_event_list.len();
+            merged.merge(tides);
+            (merged, before)
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => (tides, 0),
+        Err(e) => return Err(format!("Could not open {}: {e}", merged_path.display()).into()),
+    };
+    write_atomically(&merged_path, &serde_json::to_vec(&merged)?)?;
+
+    println!(
+        "Archived {fetched} tides for {} ({} new, {} in total) in {}",
+        args.station,
+        merged.tidal_event_list.len() - before,
+        merged.tidal_event_list.len(),
+        dir.display()
+    );
+    Ok(())
+}
+
+/// Write `contents` to a temporary file beside `path` and rename it into place, so that the
+/// file at `path` is never left partially written.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), Box<dyn Error>> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    fs::write(&temp, contents).map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+    fs::rename(&temp, path).map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+    Ok(())
+}
@@ -0,0 +1,134 @@
+//! Charts of the height of the tide, drawn with Unicode block characters.
+
+use std::ops::Range;
+
+use chrono::{DateTime, Duration, Utc};
+
+use rjw_uktides::{TidalEventType, TidePredictions};
+
+use crate::units::Units;
+use crate::zone::Times;
+
+/// Width of the height labels to the left of the chart.
+const LABEL_WIDTH: usize = 8;
+/// Blocks of increasing height used for the top of each chart column.
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Marks the current time beneath the chart.
+const NOW_MARKER: char = '▲';
+
+/// Lines drawing the height of the tide over the `span` of time as a chart `rows` high
+/// and `width` characters wide, including its labels, which are in `units` and the time
+/// zone.
+///
+/// Beneath the chart, high and low tides are marked with H and L, and `now` with a
+/// triangle if it is within the chart. Where heights are not available, such as before
+/// the first prediction, the chart is left blank.
+pub fn height_chart(
+    tides: &TidePredictions,
+    span: Range<DateTime<Utc>>,
+    width: usize,
+    rows: usize,
+    now: DateTime<Utc>,
+    units: Units,
+    times: Times,
+) -> Vec<String> {
+    let Range { start, end } = span;
+    let columns = width.saturating_sub(LABEL_WIDTH + 1).max(1);
+    let span = (end - start).num_seconds().max(1);
+    let time_at = |col: usize| start + Duration::seconds(col as i64 * span / columns as i64);
+    let column_of = |time: DateTime<Utc>| {
+        let offset = (time - start).num_seconds();
+        (0..span)
+            .contains(&offset)
+            .then(|| (offset * columns as i64 / span) as usize)
+    };
+
+    let heights: Vec<Option<f64>> = (0..columns)
+        .map(|col| tides.height_at(time_at(col)).map(|h| h.0))
+        .collect();
+    let known = heights.iter().flatten().copied();
+    let min = known.clone().fold(f64::INFINITY, f64::min);
+    let max = known.fold(f64::NEG_INFINITY, f64::max);
+    if min > max {
+        return vec!["No heights available to chart.".to_owned()];
+    }
+    let range = (max - min).max(0.1);
+    // The height of each column in eighths of a row, or zero where it is missing.
+    let levels: Vec<usize> = heights
+        .iter()
+        .map(|h| {
+            h.map_or(0, |h| {
+                1 + ((h - min) / range * (rows * 8 - 1) as f64) as usize
+            })
+        })
+        .collect();
+
+    let mut lines = Vec::new();
+    for row in (0..rows).rev() {
+        let label = if row + 1 == rows {
+            format!("{max:.1} {} ", units.symbol())
+        } else if row == 0 {
+            format!("{min:.1} {} ", units.symbol())
+        } else {
+            String::new()
+        };
+        let bars: String = levels
+            .iter()
+            .map(|&level| match level.saturating_sub(row * 8) {
+                0 => ' ',
+                n => BLOCKS[n.min(8) - 1],
+            })
+            .collect();
+        lines.push(format!("{label:>LABEL_WIDTH$}│{}", bars.trim_end()));
+    }
+
+    let mut markers = vec![' '; columns];
+    for event in &tides.tidal_event_list {
+        if let Some(col) = column_of(event.date_time) {
+            markers[col] = match event.event_type {
+                TidalEventType::HighWater => 'H',
+                TidalEventType::LowWater => 'L',
+            };
+        }
+    }
+    if let Some(col) = column_of(now) {
+        markers[col] = NOW_MARKER;
+    }
+    let markers: String = markers.into_iter().collect();
+    lines.push(format!("{:LABEL_WIDTH$} {}", "", markers.trim_end()));
+    lines.push(axis(start, end, columns, times));
+    lines
+}
+
+/// Time labels beneath the chart, in the time zone: every six hours for a chart of a day
+/// or less, and at each midnight for a longer one.
+fn axis(start: DateTime<Utc>, end: DateTime<Utc>, columns: usize, times: Times) -> String {
+    let span = (end - start).num_seconds().max(1);
+    let (hours, format): (&[i64], _) = if end - start <= Duration::days(1) {
+        (&[0, 6, 12, 18], "%H:%M")
+    } else {
+        (&[0], "%a %-d")
+    };
+    let mut axis = " ".repeat(LABEL_WIDTH + 1);
+    let mut date = times.date(start);
+    while times.midnight(date) < end {
+        for hour in hours {
+            let tick = times.midnight(date) + Duration::hours(*hour);
+            if tick < start || tick >= end {
+                continue;
+            }
+            let col = ((tick - start).num_seconds() * columns as i64 / span) as usize;
+            let position = LABEL_WIDTH + 1 + col;
+            let len = axis.chars().count();
+            // Skip labels that would overlap the previous one.
+            if position >= len && (len == LABEL_WIDTH + 1 || position > len) {
+                axis.push_str(&" ".repeat(position - len));
+                axis.push_str(&times.format(tick, format));
+            }
+        }
+        date = date
+            .succ_opt()
+            .expect("dates in the predictions are not the last date");
+    }
+    axis
+}
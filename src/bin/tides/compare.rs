@@ -0,0 +1,119 @@
+//! The tides at two stations side by side, with how much later each tide is at the
+//! second station than at the first.
+
+use std::fmt::Write;
+
+use chrono::{Duration, NaiveDate};
+use clap::Args;
+
+use rjw_uktides::{StationId, TidalEvent, TidePredictions};
+
+use crate::locale;
+use crate::units::Units;
+use crate::zone::Times;
+
+/// The furthest apart the same tide can be at the two stations, a little under half the
+/// time between successive high tides, so that a tide is never paired with the next one.
+const MAX_OFFSET_MINUTES: i64 = 5 * 60;
+
+/// Compare the tides at two stations on a date.
+///
+/// Each high and low tide at the first station is shown beside the same tide at the
+/// second, with the time from one to the other, such as +0:25 when the tide is 25
+/// minutes later at the second station.
+#[derive(Args, Clone, Debug)]
+pub struct CompareArgs {
+    /// IDs of the two stations to compare; give this twice, or separate the IDs with a
+    /// comma.
+    #[arg(short, long = "station", value_delimiter = ',')]
+    pub stations: Vec<StationId>,
+
+    /// The date to compare, such as 2025-08-20, rather than today.
+    #[arg(long)]
+    pub date: Option<NaiveDate>,
+}
+
+/// The tides at the two stations on `date` as a table, each station given by its name
+/// and predictions.
+pub fn text(
+    first: (&str, &TidePredictions),
+    second: (&str, &TidePredictions),
+    date: NaiveDate,
+    units: Units,
+    times: Times,
+) -> String {
+    let phrases = locale::phrases();
+    let rows: Vec<_> = rjw_uktides::pair_tides(
+        &first.1.tidal_event_list,
+        &second.1.tidal_event_list,
+        Duration::minutes(MAX_OFFSET_MINUTES),
+    )
+    .into_iter()
+    .filter(|pair| {
+        [pair.0, pair.1]
+            .into_iter()
+            .flatten()
+            .any(|e| times.date(e.date_time) == date)
+    })
+    .collect();
+    let label = locale::label_width(&[phrases.high_tide, phrases.low_tide]);
+    // Each station's column is a time and a height, such as "04:12   4.2".
+    let slot = times.time_width() + 6;
+    let first_width = slot.max(first.0.chars().count());
+    let second_width = slot.max(second.0.chars().count());
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{}, {} ({}, {})",
+        [first.0, second.0].join(" / "),
+        times.format_date(date, "%A %-d %B %Y"),
+        times.tz(),
+        units.symbol()
+    );
+    if rows.is_empty() {
+        let _ = writeln!(out, "{}", phrases.no_predictions);
+        return out;
+    }
+    let _ = writeln!(
+        out,
+        "{:label$}  {:<first_width$}  {:<second_width$}  Offset",
+        "", first.0, second.0
+    );
+    for (a, b) in rows {
+        let event_type = a.or(b).map(|e| e.event_type).expect("a pair has a tide");
+        let _ = writeln!(
+            out,
+            "{:label$}  {:<first_width$}  {:<second_width$}  {}",
+            phrases.event(event_type),
+            slot_text(a, times),
+            slot_text(b, times),
+            a.zip(b)
+                .map_or_else(String::new, |(a, b)| offset(b.date_time - a.date_time)),
+        );
+    }
+    out.lines().fold(String::new(), |mut text, line| {
+        text.push_str(line.trim_end());
+        text.push('\n');
+        text
+    })
+}
+
+/// The time and height of the tide, or a dash if there is no corresponding tide.
+fn slot_text(event: Option<&TidalEvent>, times: Times) -> String {
+    match event {
+        Some(e) => format!(
+            "{:>w$} {:>5.1}",
+            times.format(e.date_time, "%H:%M"),
+            e.height.0,
+            w = times.time_width()
+        ),
+        None => "-".to_owned(),
+    }
+}
+
+/// The offset as hours and minutes with a sign, such as +0:25 or -1:05.
+fn offset(offset: Duration) -> String {
+    let minutes = offset.num_minutes();
+    let sign = if minutes < 0 { '-' } else { '+' };
+    format!("{sign}{}:{:02}", minutes.abs() / 60, minutes.abs() % 60)
+}
@@ -0,0 +1,103 @@
+//! Shell completion scripts, generated from the command-line definition with clap_complete.
+//!
+//! Station arguments complete to the IDs of the stations built into the binary, described
+//! by their names, and to the aliases in the config file when the script is generated.
+
+use std::error::Error;
+
+use clap::builder::{PossibleValue, PossibleValuesParser};
+use clap::{Args, Command};
+use clap_complete::Shell;
+
+use crate::config::Config;
+use crate::STATIONS_BAKED_BYTES;
+
+/// The IDs of the arguments that take station IDs.
+const STATION_ARGS: [&str; 2] = ["station", "stations"];
+
+/// Print a completion script for the shell.
+///
+/// For bash, add `source <(tides completions bash)` to ~/.bashrc. For zsh, save the
+/// output as _tides in a directory in $fpath. For fish, save the output as
+/// ~/.config/fish/completions/tides.fish. Generate the script again after adding aliases.
+#[derive(Args, Clone, Debug)]
+pub struct CompletionsArgs {
+    /// The shell to complete commands in.
+    #[arg(value_enum)]
+    shell: Shell,
+}
+
+pub fn completions(
+    args: CompletionsArgs,
+    command: Command,
+    config: &Config,
+    out: &mut impl std::io::Write,
+) -> Result<(), Box<dyn Error>> {
+    let mut stations: Vec<PossibleValue> = rjw_uktides::stations_from_reader(STATIONS_BAKED_BYTES)?
+        .into_iter()
+        .map(|s| PossibleValue::new(s.id.0).help(s.name))
+        .collect();
+    stations.extend(
+        config
+            .aliases
+            .iter()
+            .map(|(alias, id)| PossibleValue::new(alias).help(format!("alias for {id}"))),
+    );
+    let mut command = with_stations(command, &stations);
+    let name = command.get_name().to_owned();
+    // Generating panics on failing to write, so write the script once it is complete.
+    let mut script = Vec::new();
+    clap_complete::generate(args.shell, &mut command, name, &mut script);
+    out.write_all(&script)?;
+    Ok(())
+}
+
+/// The command with the station arguments of it and its subcommands taking `stations`,
+/// so that the completion scripts offer them.
+fn with_stations(mut command: Command, stations: &[PossibleValue]) -> Command {
+    for id in STATION_ARGS {
+        if command.get_arguments().any(|a| a.get_id() == id) {
+            command = command.mut_arg(id, |a| {
+                a.value_parser(PossibleValuesParser::new(stations.to_vec()))
+            });
+        }
+    }
+    let subcommands: Vec<_> = command
+        .get_subcommands()
+        .map(|s| s.get_name().to_owned())
+        .collect();
+    for name in subcommands {
+        command = command.mut_subcommand(name, |s| with_stations(s, stations));
+    }
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+    use crate::Cli;
+
+    fn script(shell: Shell) -> String {
+        let mut config = Config::default();
+        config
+            .aliases
+            .insert("home".to_owned(), rjw_uktides::StationId("0053".to_owned()));
+        let mut out = Vec::new();
+        completions(CompletionsArgs { shell }, Cli::command(), &config, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn completes_subcommands_and_stations() {
+        let bash = script(Shell::Bash);
+        assert!(bash.contains("completions"));
+        assert!(bash.contains("0053A"));
+        assert!(bash.contains("home"));
+
+        let fish = script(Shell::Fish);
+        assert!(fish.contains(r"0053\t'Sandown'"));
+        assert!(fish.contains(r"home\t'alias for 0053'"));
+    }
+}
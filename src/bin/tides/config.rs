@@ -0,0 +1,245 @@
+//! The configuration file, which sets defaults for command-line arguments.
+//!
+//! The file is `rjw-uktides/config.toml` in `$XDG_CONFIG_HOME`, or in `~/.config` if that
+//! is not set. For example:
+//!
+//! ```toml
+//! station = "home"
+//! format = "tsv"
+//! units = "imperial"
+//! timezone = "Europe/Paris"
+//! cache_dir = "/var/cache/tides"
+//!
+//! [aliases]
+//! home = "0053"
+//! ```
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use chrono_tz::Tz;
+use clap::{Command, ValueEnum};
+use serde::Deserialize;
+use toml_edit::DocumentMut;
+
+use rjw_uktides::StationId;
+
+use crate::station_arg;
+use crate::units::Units;
+use crate::Format;
+
+/// Settings read from the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Station used when none is given with `-s`.
+    pub station: Option<StationId>,
+    /// Output format used when none is given with `--format`, such as "json".
+    pub format: Option<String>,
+    /// Units for heights when none are given with `--units`, "metric" or "imperial".
+    pub units: Option<String>,
+    /// Time zone for times and dates when none is given with `--tz`, such as "UTC".
+    pub timezone: Option<Tz>,
+    /// Directory for cached responses, instead of `$XDG_CACHE_HOME/rjw-uktides`.
+    pub cache_dir: Option<PathBuf>,
+    /// Address of the service to send requests to instead of EasyTide, unless
+    /// `UKTIDES_BASE_URL` is set.
+    pub base_url: Option<String>,
+    /// Names that can be given in place of station IDs, managed with `tides alias`.
+    pub aliases: BTreeMap<String, StationId>,
+}
+
+/// The keys of the configuration file and what they set, for the manual page.
+pub const KEYS: &[(&str, &str)] = &[
+    (
+        "station",
+        "Station used when none is given with -s, as an ID or alias.",
+    ),
+    (
+        "format",
+        "Output format used when none is given with --format, such as \"json\".",
+    ),
+    (
+        "units",
+        "Units for heights when none are given with --units, \"metric\" or \"imperial\".",
+    ),
+    (
+        "timezone",
+        "Time zone for times and dates when none is given with --tz, such as \"UTC\".",
+    ),
+    (
+        "cache_dir",
+        "Directory for cached responses, instead of $XDG_CACHE_HOME/rjw-uktides.",
+    ),
+    (
+        "base_url",
+        "Address of the service to send requests to instead of EasyTide, such as \
+         \"http://localhost:8080\", unless UKTIDES_BASE_URL is set.",
+    ),
+    (
+        "aliases",
+        "A table of names that can be given in place of station IDs, managed with tides alias.",
+    ),
+];
+
+impl Config {
+    /// Read the configuration file, using the default configuration if there is none.
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        let Some(path) = path() else {
+            return Ok(Config::default());
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(format!("Could not read {}: {e}", path.display()).into()),
+        };
+        let config: Config = toml::from_str(&text)
+            .map_err(|e| format!("Invalid configuration file {}: {e}", path.display()))?;
+        if let Some(format) = &config.format {
+            Format::from_str(format, true)
+                .map_err(|_| format!("Invalid format {format:?} in {}", path.display()))?;
+        }
+        if let Some(units) = &config.units {
+            Units::from_str(units, true)
+                .map_err(|_| format!("Invalid units {units:?} in {}", path.display()))?;
+        }
+        if let Some(base_url) = &config.base_url {
+            check_base_url(base_url).map_err(|e| format!("{e} in {}", path.display()))?;
+        }
+        Ok(config)
+    }
+
+    /// Set the configured values as the defaults of the corresponding arguments, so that
+    /// arguments given on the command line take precedence, and accept aliases in place of
+    /// station IDs.
+    pub fn apply(&self, mut command: Command) -> Command {
+        let names: Vec<String> = command
+            .get_subcommands()
+            .map(|c| c.get_name().to_owned())
+            .collect();
+        command = self.apply_stations(command);
+        if let Some(station) = &self.station {
+            // The top-level station is not required when reading predictions from a file,
+            // so is not found by `default_station`.
+            command = command.mut_arg("stations", |a| a.default_value(station.0.clone()));
+        }
+        for name in names {
+            command = command.mut_subcommand(name, |c| self.apply_stations(c));
+        }
+        if let Some(format) = &self.format {
+            command = command.mut_arg("format", |a| a.default_value(format.to_lowercase()));
+        }
+        if let Some(units) = &self.units {
+            command = command.mut_arg("units", |a| a.default_value(units.to_lowercase()));
+        }
+        if let Some(tz) = self.timezone {
+            command = command.mut_arg("tz", |a| a.default_value(tz.name()));
+        }
+        command
+    }
+
+    /// Resolve aliases and check IDs in the command's station arguments, and set the
+    /// default station.
+    fn apply_stations(&self, mut command: Command) -> Command {
+        let ids: Vec<String> = command
+            .get_arguments()
+            .map(|a| a.get_id().to_string())
+            .filter(|id| id == "station" || id == "stations")
+            .collect();
+        for id in ids {
+            let aliases = self.aliases.clone();
+            command = command.mut_arg(id, |a| a.value_parser(station_arg::parser(aliases)));
+        }
+        match &self.station {
+            Some(station) => default_station(command, station),
+            None => command,
+        }
+    }
+}
+
+/// Edit the configuration file with `f`, creating it if it does not exist and preserving
+/// the formatting and comments of anything `f` leaves untouched.
+pub fn edit(f: impl FnOnce(&mut DocumentMut)) -> Result<(), Box<dyn Error>> {
+    let path = path().ok_or("Could not find the home directory for the configuration file.")?;
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Could not read {}: {e}", path.display()).into()),
+    };
+    let mut document: DocumentMut = text
+        .parse()
+        .map_err(|e| format!("Invalid configuration file {}: {e}", path.display()))?;
+    f(&mut document);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, document.to_string())
+        .map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+    Ok(())
+}
+
+/// Check that the base URL is an HTTP or HTTPS address.
+pub fn check_base_url(base_url: &str) -> Result<(), String> {
+    if base_url.starts_with("http://") || base_url.starts_with("https://") {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid base URL {base_url:?}, which should start with http:// or https://"
+        ))
+    }
+}
+
+/// Make the command's required `--station` argument, if it has one, default to `station`.
+fn default_station(command: Command, station: &StationId) -> Command {
+    let id = command
+        .get_arguments()
+        .find(|a| a.get_long() == Some("station") && a.is_required_set())
+        .map(|a| a.get_id().to_string());
+    match id {
+        Some(id) => command.mut_arg(id, |a| a.required(false).default_value(station.0.clone())),
+        None => command,
+    }
+}
+
+/// The location of the configuration file, if the home directory is known.
+pub fn path() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(dir.join("rjw-uktides").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, FromArgMatches};
+
+    use super::*;
+    use crate::Cli;
+
+    fn parse(config: &Config, args: &[&str]) -> Cli {
+        let matches = config
+            .apply(Cli::command())
+            .try_get_matches_from(args)
+            .unwrap();
+        Cli::from_arg_matches(&matches).unwrap()
+    }
+
+    #[test]
+    fn defaults_the_time_zone() {
+        let config: Config = toml::from_str("timezone = \"Europe/Paris\"").unwrap();
+        assert_eq!(config.timezone, Some(Tz::Europe__Paris));
+        assert_eq!(parse(&config, &["tides"]).tz, Tz::Europe__Paris);
+        // The command line takes precedence.
+        assert_eq!(parse(&config, &["tides", "--tz", "UTC"]).tz, Tz::UTC);
+        assert_eq!(parse(&Config::default(), &["tides"]).tz, Tz::Europe__London);
+    }
+
+    #[test]
+    fn rejects_unknown_time_zones() {
+        assert!(toml::from_str::<Config>("timezone = \"Mars/Olympus\"").is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! Error reports with miette, pointing to where in the data a parse error occurred.
+//!
+//! Enabled by the `miette` feature.
+
+use std::error::Error;
+use std::fmt::Display;
+
+use miette::{
+    Diagnostic, GraphicalReportHandler, LabeledSpan, MietteError, MietteSpanContents, SourceCode,
+    SourceSpan, SpanContents,
+};
+use rjw_uktides::ErrorKind;
+
+use crate::error::{CliError, ParseInput, EXCERPT_CONTEXT};
+
+impl Diagnostic for CliError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let code = match self {
+            CliError::UnknownStation(_) => "tides::unknown_station",
+            CliError::NoStationMatch { .. } => "tides::no_station_match",
+            CliError::InvalidStation { .. } => "tides::invalid_station",
+            CliError::Unparsable { error, .. } => return error.code(),
+        };
+        Some(Box::new(code))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match self {
+            CliError::UnknownStation(_) => Some(Box::new(
+                "use `tides list-stations` to find the ID of a station",
+            )),
+            CliError::NoStationMatch { suggestions, .. } if !suggestions.is_empty() => {
+                let names: Vec<_> = suggestions
+                    .iter()
+                    .map(|(id, name)| format!("{id} {name}"))
+                    .collect();
+                Some(Box::new(format!("did you mean {}?", names.join(", "))))
+            }
+            CliError::Unparsable { error, .. } => error.help(),
+            _ => None,
+        }
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        match self {
+            CliError::Unparsable { input, .. } => Some(input),
+            _ => None,
+        }
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let CliError::Unparsable { error, input, .. } = self else {
+            return None;
+        };
+        let label = match error.kind() {
+            ErrorKind::Io => return None,
+            ErrorKind::Syntax => "invalid JSON",
+            ErrorKind::Data => "unexpected data",
+        };
+        let span = error.span_in(&input.0)?;
+        Some(Box::new(std::iter::once(LabeledSpan::at(span, label))))
+    }
+}
+
+/// Only the line holding the span is read, shortened to the characters either side of
+/// the span, as responses are often a single long line.
+impl SourceCode for ParseInput {
+    fn read_span<'a>(
+        &'a self,
+        span: &SourceSpan,
+        _context_lines_before: usize,
+        _context_lines_after: usize,
+    ) -> Result<Box<dyn SpanContents<'a> + 'a>, MietteError> {
+        let input = &self.0;
+        let (start, end) = (span.offset(), span.offset() + span.len());
+        if end > input.len() {
+            return Err(MietteError::OutOfBounds);
+        }
+        let line_start = input[..start]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        let line_end = input[end..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(input.len(), |i| end + i);
+        let (from, to) = (
+            chars_before(input, line_start, start),
+            chars_after(input, end, line_end),
+        );
+        let line = input[..line_start].iter().filter(|&&b| b == b'\n').count();
+        let column = String::from_utf8_lossy(&input[line_start..from])
+            .chars()
+            .count();
+        Ok(Box::new(MietteSpanContents::new(
+            &input[from..to],
+            (from..to).into(),
+            line,
+            column,
+            1,
+        )))
+    }
+}
+
+/// The offset [`EXCERPT_CONTEXT`] characters before `offset`, or `limit` if it is nearer.
+fn chars_before(input: &[u8], limit: usize, offset: usize) -> usize {
+    let mut offset = offset;
+    let mut count = 0;
+    while offset > limit && count < EXCERPT_CONTEXT {
+        offset -= 1;
+        if !is_continuation(input[offset]) {
+            count += 1;
+        }
+    }
+    offset
+}
+
+/// The offset [`EXCERPT_CONTEXT`] characters after `offset`, or `limit` if it is nearer.
+fn chars_after(input: &[u8], offset: usize, limit: usize) -> usize {
+    let mut offset = offset;
+    let mut count = 0;
+    while offset < limit && count < EXCERPT_CONTEXT {
+        offset += 1;
+        while offset < limit && is_continuation(input[offset]) {
+            offset += 1;
+        }
+        count += 1;
+    }
+    offset
+}
+
+/// Whether the byte continues a multi-byte UTF-8 character.
+fn is_continuation(byte: u8) -> bool {
+    byte & 0xc0 == 0x80
+}
+
+/// The report for the first error in the chain that points to where in the data a parse
+/// error occurred, drawn for the terminal.
+pub fn render(err: &(dyn Error + 'static)) -> Option<String> {
+    let mut source = Some(err);
+    while let Some(e) = source {
+        if let Some(e @ CliError::Unparsable { error, input, .. }) = e.downcast_ref() {
+            error.span_in(&input.0)?;
+            let mut report = String::new();
+            GraphicalReportHandler::new()
+                .render_report(&mut report, e)
+                .ok()?;
+            return Some(report);
+        }
+        source = e.source();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unparsable(input: &[u8]) -> CliError {
+        CliError::parse("the test input", input.to_vec(), |input| {
+            rjw_uktides::tides_from_reader(input)
+        })
+        .unwrap_err()
+    }
+
+    #[test]
+    fn labels_the_offending_json() {
+        let err = unparsable(b"{\n  \"footerNote\" 1\n}");
+        let report = render(&err).unwrap();
+        assert!(report.contains(" 2 "), "{report}");
+        assert!(report.contains("\"footerNote\" 1"), "{report}");
+        assert!(report.contains("invalid JSON"), "{report}");
+        assert!(report.contains("rjw_uktides::syntax"), "{report}");
+    }
+
+    #[test]
+    fn shortens_long_lines_around_the_error() {
+        let padding = "x".repeat(200);
+        let input = format!("{{\"a\": \"{padding}\", \"footerNote\" 1, \"b\": \"{padding}\"}}");
+        let report = render(&unparsable(input.as_bytes())).unwrap();
+        assert!(report.contains("\"footerNote\" 1"), "{report}");
+        assert!(!report.contains(&padding), "{report}");
+    }
+}
@@ -0,0 +1,154 @@
+//! Diagnosing problems reaching or understanding the EasyTide web service.
+
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use serde_json::Value;
+
+use rjw_uktides::{Client, RetryPolicy, StationId};
+
+use crate::qa::{stations_drift, tides_drift};
+
+/// Responses slower than this are reported as a warning.
+const SLOW_RESPONSE: Duration = Duration::from_secs(3);
+
+/// Check that EasyTide can be reached and that its responses are understood.
+///
+/// Both endpoints are fetched directly, bypassing the response cache and without retrying,
+/// and their responses are checked strictly: any field added to or removed from the
+/// response is reported, as well as any failure to parse or validate it. Run this first
+/// when something is not working.
+#[derive(Args, Clone, Debug)]
+pub struct DoctorArgs {
+    /// ID of the tidal station whose predictions to check.
+    #[arg(short, long, default_value = "0001")]
+    station: StationId,
+}
+
+pub fn doctor(args: DoctorArgs, client: Client) -> Result<(), Box<dyn Error>> {
+    let client = client.with_retry(RetryPolicy::none());
+    let mut failures = 0;
+
+    println!("Station list");
+    let stations_url = rjw_uktides::stations_url(client.base_url());
+    match timed(&stations_url, || Ok(client.fetch_stations_raw()?.to_vec())) {
+        Some(body) => {
+            failures += check_schema(&body, stations_drift);
+            match rjw_uktides::stations_from_slice(&body) {
+                Ok(stations) => {
+                    pass(&format!("{} stations parsed", stations.len()));
+                    if !stations.iter().any(|s| s.id == args.station.0) {
+                        warn(&format!("station {} is not in the list", args.station));
+                    }
+                }
+                Err(e) => failures += fail("could not be parsed", &e),
+            }
+        }
+        None => failures += 1,
+    }
+
+    println!("Predictions for station {}", args.station);
+    let tides_url = rjw_uktides::tides_url(client.base_url(), &args.station);
+    match timed(&tides_url, || {
+        Ok(client.fetch_tides_raw(&args.station)?.to_vec())
+    }) {
+        Some(body) => {
+            failures += check_schema(&body, tides_drift);
+            match rjw_uktides::tides_from_slice(&body) {
+                Ok(tides) => {
+                    pass(&format!(
+                        "{} tidal events parsed",
+                        tides.tidal_event_list.len()
+                    ));
+                    let report = tides.validate();
+                    if report.anomalies.is_empty() {
+                        pass("predictions are consistent");
+                    }
+                    for anomaly in &report.anomalies {
+                        warn(&anomaly.to_string());
+                    }
+                }
+                Err(e) => failures += fail("could not be parsed", &e),
+            }
+        }
+        None => failures += 1,
+    }
+
+    match failures {
+        0 => {
+            println!("\nNo problems found.");
+            Ok(())
+        }
+        1 => Err("1 check failed.".into()),
+        n => Err(format!("{n} checks failed.").into()),
+    }
+}
+
+/// Fetch a response, reporting how long it took, or the failure and its likely cause.
+fn timed(url: &str, fetch: impl FnOnce() -> Result<Vec<u8>, Box<dyn Error>>) -> Option<Vec<u8>> {
+    let start = Instant::now();
+    let result = fetch();
+    let elapsed = start.elapsed();
+    match result {
+        Ok(body) if elapsed > SLOW_RESPONSE => {
+            warn(&format!(
+                "{url} responded slowly, in {} ms ({} bytes)",
+                elapsed.as_millis(),
+                body.len()
+            ));
+            Some(body)
+        }
+        Ok(body) => {
+            pass(&format!(
+                "{url} responded in {} ms ({} bytes)",
+                elapsed.as_millis(),
+                body.len()
+            ));
+            Some(body)
+        }
+        Err(e) => {
+            fail(&format!("{url} could not be fetched"), e.as_ref());
+            None
+        }
+    }
+}
+
+/// Report fields added to or removed from the response, returning the number of failures.
+fn check_schema(body: &[u8], drift: fn(&Value) -> Vec<String>) -> usize {
+    let json: Value = match serde_json::from_slice(body) {
+        Ok(json) => json,
+        Err(e) => return fail("response is not valid JSON", &e),
+    };
+    let changes = drift(&json);
+    if changes.is_empty() {
+        pass("response has the expected fields");
+        return 0;
+    }
+    for change in &changes {
+        println!("  FAIL  {change}");
+    }
+    println!(
+        "        EasyTide has changed its response format. Check for a newer version of \
+         rjw-uktides, or report the change if there is none."
+    );
+    1
+}
+
+fn pass(message: &str) {
+    println!("  ok    {message}");
+}
+
+fn warn(message: &str) {
+    println!("  warn  {message}");
+}
+
+/// Print the failure with a suggestion for fixing it, returning 1 for counting failures.
+fn fail(message: &str, err: &(dyn Error + 'static)) -> usize {
+    println!("  FAIL  {message}: {err}");
+    let (suggestion, _) = crate::error::describe(err);
+    if suggestion != err.to_string() {
+        println!("        {suggestion}");
+    }
+    1
+}
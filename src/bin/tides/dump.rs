@@ -0,0 +1,88 @@
+//! Fetching the predictions for every station into a directory, for a snapshot dataset.
+
+use std::error::Error;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use clap::Args;
+
+use rjw_uktides::RateLimit;
+
+use crate::archive::write_atomically;
+use crate::fetch::Fetcher;
+
+/// Fetch the predictions for every station into a directory.
+///
+/// Each station's predictions are written to <ID>.json, in the EasyTide response format,
+/// and the station list to stations.csv. Stations that already have a file are skipped,
+/// so an interrupted or partly failed dump can be resumed by running it again.
+#[derive(Args, Clone, Debug)]
+pub struct DumpAllArgs {
+    /// Directory to write the predictions to, which is created if necessary.
+    #[arg(long)]
+    dir: PathBuf,
+
+    /// Maximum rate of requests to EasyTide, such as "30/min" or "1/s".
+    #[arg(long, default_value = "30/min", value_parser = parse_rate)]
+    rate: RateLimit,
+
+    /// Fetch the predictions for every station, even those already in the directory.
+    #[arg(long)]
+    overwrite: bool,
+}
+
+pub fn dump_all(args: DumpAllArgs, fetcher: Fetcher) -> Result<(), Box<dyn Error>> {
+    let fetcher = fetcher.with_rate_limit(args.rate);
+    let mut stations = fetcher.stations()?;
+    stations.sort();
+    std::fs::create_dir_all(&args.dir)
+        .map_err(|e| format!("Could not create {}: {e}", args.dir.display()))?;
+    let mut csv = Vec::new();
+    rjw_uktides::write_stations_csv(BufWriter::new(&mut csv), &stations)?;
+    write_atomically(&args.dir.join("stations.csv"), &csv)?;
+
+    let total = stations.len();
+    let mut failed = 0;
+    for (n, station) in stations.iter().enumerate() {
+        let path = args.dir.join(format!("{}.json", station.id));
+        if !args.overwrite && path.exists() {
+            continue;
+        }
+        eprintln!("[{}/{total}] {} {}", n + 1, station.id, station.name);
+        let result = fetcher
+            .tides(&station.id)
+            .and_then(|tides| Ok(serde_json::to_vec(&tides)?))
+            .and_then(|json| write_atomically(&path, &json));
+        if let Err(e) = result {
+            eprintln!("Failed to dump {}: {e}", station.id);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(format!(
+            "Failed to dump {failed} of {total} stations. Run the command again to retry them."
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Parse a rate such as "30/min" or "1/s" into a rate limit without bursts.
+fn parse_rate(s: &str) -> Result<RateLimit, String> {
+    let (count, unit) = s
+        .split_once('/')
+        .ok_or_else(|| format!("expected a rate such as 30/min, not {s:?}"))?;
+    let count: u32 = count
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid number of requests {count:?}"))?;
+    let per_minute = match unit.trim() {
+        "s" | "sec" | "second" => count.saturating_mul(60),
+        "m" | "min" | "minute" => count,
+        unit => return Err(format!("unknown unit {unit:?}; use s or min")),
+    };
+    if per_minute == 0 {
+        return Err("the rate must be greater than zero".to_owned());
+    }
+    Ok(RateLimit::new(per_minute, 1))
+}
@@ -0,0 +1,77 @@
+//! Parsing durations given on the command line.
+
+use chrono::Duration;
+
+/// The most seconds chrono can represent in a duration, which is limited to `i64::MAX`
+/// milliseconds.
+const MAX_SECONDS: i64 = i64::MAX / 1000;
+
+/// Parse a duration such as "45m", "2h" or "1h30m", in days (d), hours (h), minutes (m)
+/// and seconds (s).
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("expected a duration such as 45m or 1h30m, not {s:?}");
+    let mut total = Duration::zero();
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let count: i64 = rest[..digits].parse().map_err(|_| invalid())?;
+        let unit_len = rest[digits..]
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len() - digits);
+        let unit_seconds = match &rest[digits..digits + unit_len] {
+            "d" => 24 * 60 * 60,
+            "h" => 60 * 60,
+            "m" | "min" => 60,
+            "s" => 1,
+            _ => return Err(invalid()),
+        };
+        // Checked, as chrono panics on durations it cannot represent.
+        let seconds = count
+            .checked_mul(unit_seconds)
+            .filter(|seconds| *seconds <= MAX_SECONDS)
+            .ok_or_else(invalid)?;
+        total = total
+            .checked_add(&Duration::seconds(seconds))
+            .ok_or_else(invalid)?;
+        rest = &rest[digits + unit_len..];
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_units_and_combinations() {
+        assert_eq!(parse_duration("45m"), Ok(Duration::minutes(45)));
+        assert_eq!(parse_duration("45min"), Ok(Duration::minutes(45)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::minutes(90)));
+        assert_eq!(parse_duration(" 2d "), Ok(Duration::days(2)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::seconds(90)));
+    }
+
+    #[test]
+    fn rejects_malformed_durations() {
+        for input in ["", "45", "m", "1x", "1h30", "-5m", "1.5h"] {
+            assert!(parse_duration(input).is_err(), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_durations_out_of_range_without_panicking() {
+        for input in [
+            "9999999999999h",
+            "9223372036854775807s",
+            "99999999999999999999m",
+            "106751991167d106751991167d",
+        ] {
+            assert!(parse_duration(input).is_err(), "{input:?}");
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::io;
+use std::ops::Range;
+use std::process::ExitCode;
+
+use rjw_uktides::{ErrorKind, StationId};
+
+/// Exit code for failures not covered by a more specific code.
+pub const EXIT_FAILURE: u8 = 1;
+/// Exit code when a server could not be reached or responded with an error status.
+pub const EXIT_NETWORK: u8 = 3;
+/// Exit code when a response could not be parsed.
+pub const EXIT_PARSE: u8 = 4;
+/// Exit code when the requested station does not exist or cannot be identified by name.
+pub const EXIT_UNKNOWN_STATION: u8 = 5;
+/// Exit code when `next --within` finds no tide within the given time.
+pub const EXIT_NO_TIDE: u8 = 6;
+
+/// Failures detected by the command-line program itself, rather than the library.
+#[derive(Debug)]
+pub enum CliError {
+    /// The station is not in the list of known stations.
+    UnknownStation(StationId),
+    /// No single station matches the name, with the closest matches as suggestions.
+    NoStationMatch {
+        query: String,
+        suggestions: Vec<(StationId, String)>,
+    },
+    /// A station argument is neither an ID nor an alias, with stations whose names are
+    /// close to it as suggestions.
+    InvalidStation {
+        value: String,
+        suggestions: Vec<(StationId, String)>,
+    },
+    /// Data that could not be parsed, described by `what`, kept so that the report can
+    /// show where in the data the error occurred.
+    Unparsable {
+        what: String,
+        error: rjw_uktides::Error,
+        input: ParseInput,
+    },
+}
+
+/// The data given to a parser, kept with any error so that the report can show where in
+/// the data the error occurred.
+#[derive(Debug)]
+pub struct ParseInput(pub Vec<u8>);
+
+impl CliError {
+    /// Parse `input`, described by `what` such as "the response from EasyTide", keeping
+    /// the input with any error.
+    pub fn parse<T>(
+        what: impl Into<String>,
+        input: Vec<u8>,
+        parse: impl FnOnce(&[u8]) -> Result<T, rjw_uktides::Error>,
+    ) -> Result<T, CliError> {
+        parse(&input).map_err(|error| CliError::Unparsable {
+            what: what.into(),
+            error,
+            input: ParseInput(input),
+        })
+    }
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::UnknownStation(id) => write!(f, "unknown station {id}"),
+            CliError::NoStationMatch { query, .. } => {
+                write!(f, "no single station matches {query:?}")
+            }
+            CliError::InvalidStation { value, suggestions } => {
+                write!(f, "{value} not found")?;
+                for (i, (id, name)) in suggestions.iter().enumerate() {
+                    let sep = match i {
+                        0 => "; did you mean",
+                        i if i + 1 == suggestions.len() => " or",
+                        _ => ",",
+                    };
+                    write!(f, "{sep} {id} {name}")?;
+                }
+                if suggestions.is_empty() {
+                    write!(
+                        f,
+                        "; IDs are a letter or digit then three digits, such as 0053, \
+                         optionally followed by a letter, such as 0628A"
+                    )
+                } else {
+                    write!(f, "?")
+                }
+            }
+            CliError::Unparsable { what, error, .. } => write!(f, "{what}: {error}"),
+        }
+    }
+}
+
+impl Error for CliError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CliError::Unparsable { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Print a user-friendly message for the error to stderr, followed by the chain of
+/// underlying errors if `verbose`, and return the exit code for the kind of failure.
+///
+/// Exit codes are 1 for general failures, 2 for invalid arguments (set by clap), 3 for
+/// network failures, 4 for responses that cannot be parsed, and 5 for unknown stations or
+/// station names that do not identify exactly one station. `next --within` exits with 6
+/// when there is no tide within the given time.
+pub fn report(err: &(dyn Error + 'static), verbose: bool) -> ExitCode {
+    // Output piped to a program that exits early, such as `head`, is not a failure.
+    if err
+        .downcast_ref::<io::Error>()
+        .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe)
+    {
+        return ExitCode::SUCCESS;
+    }
+    #[cfg(feature = "miette")]
+    if let Some(report) = crate::diagnostic::render(err) {
+        eprint!("{report}");
+        return ExitCode::from(EXIT_PARSE);
+    }
+    let (message, code) = describe(err);
+    eprintln!("Error: {message}");
+    if verbose {
+        eprintln!("\nCaused by:");
+        let mut source = Some(err);
+        while let Some(e) = source {
+            eprintln!("    {e}");
+            source = e.source();
+        }
+    } else {
+        eprintln!("Run with --verbose for details.");
+    }
+    ExitCode::from(code)
+}
+
+/// Print clap's message for an invalid command line, or help or version information, and
+/// return the exit code, which is 5 rather than clap's 2 for unknown stations.
+pub fn report_usage(err: &clap::Error) -> ExitCode {
+    let _ = err.print();
+    match err.source().and_then(|e| e.downcast_ref()) {
+        Some(CliError::InvalidStation { .. }) => ExitCode::from(EXIT_UNKNOWN_STATION),
+        _ if err.use_stderr() => ExitCode::from(2),
+        _ => ExitCode::SUCCESS,
+    }
+}
+
+/// The message and exit code for the first error in the chain that can be classified.
+pub fn describe(err: &(dyn Error + 'static)) -> (String, u8) {
+    let mut source = Some(err);
+    while let Some(e) = source {
+        if let Some(CliError::UnknownStation(id)) = e.downcast_ref() {
+            let message = format!(
+                "There is no station with ID {id}. \
+                 Use `tides list-stations` to find the ID of a station."
+            );
+            return (message, EXIT_UNKNOWN_STATION);
+        }
+        if let Some(CliError::NoStationMatch { query, suggestions }) = e.downcast_ref() {
+            let mut message = if suggestions.is_empty() {
+                format!("No station matches \"{query}\".")
+            } else {
+                format!("More than one station matches \"{query}\". Did you mean:")
+            };
+            for (id, name) in suggestions {
+                message.push_str(&format!("\n    {id}\t{name}"));
+            }
+            return (message, EXIT_UNKNOWN_STATION);
+        }
+        if let Some(e @ CliError::InvalidStation { .. }) = e.downcast_ref() {
+            return (e.to_string(), EXIT_UNKNOWN_STATION);
+        }
+        if let Some(CliError::Unparsable {
+            what,
+            error,
+            input: ParseInput(input),
+        }) = e.downcast_ref()
+        {
+            if let Some(span) = error.span_in(input) {
+                let message = format!(
+                    "Could not understand {what} (line {}, column {}):\n\n{}",
+                    error.line(),
+                    error.column(),
+                    excerpt(input, span)
+                );
+                return (message, EXIT_PARSE);
+            }
+        }
+        if let Some(e) = e.downcast_ref::<reqwest::Error>() {
+            let host = e
+                .url()
+                .and_then(|url| url.host_str())
+                .unwrap_or("the server");
+            let message = if let Some(status) = e.status() {
+                format!("{host} responded with an error ({status}). Try again later.")
+            } else if e.is_timeout() {
+                format!("The request to {host} timed out. Try again later.")
+            } else {
+                format!("Could not connect to {host}. Check your network connection.")
+            };
+            return (message, EXIT_NETWORK);
+        }
+        if let Some(e) = e.downcast_ref::<rjw_uktides::Error>() {
+            let message = match e.kind() {
+                ErrorKind::Io => "Failed to read the response from EasyTide.".to_owned(),
+                ErrorKind::Syntax | ErrorKind::Data => format!(
+                    "Could not understand the response from EasyTide \
+                     (line {}, column {}). The service may have changed.",
+                    e.line(),
+                    e.column()
+                ),
+            };
+            return (message, EXIT_PARSE);
+        }
+        source = e.source();
+    }
+    (err.to_string(), EXIT_FAILURE)
+}
+
+/// The most characters of the offending line to show either side of an error.
+pub const EXCERPT_CONTEXT: usize = 40;
+
+/// The line of `input` containing `span`, shortened around it if it is long, with a
+/// marker beneath the span, such as:
+///
+/// ```text
+///     12 | ..."height": "4.2", "dateTime"...
+///        |              ^ here
+/// ```
+fn excerpt(input: &[u8], span: Range<usize>) -> String {
+    let line_start = input[..span.start]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    let line_end = input[span.start..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(input.len(), |i| span.start + i);
+    let line_number = input[..line_start].iter().filter(|&&b| b == b'\n').count() + 1;
+    let before: Vec<char> = String::from_utf8_lossy(&input[line_start..span.start])
+        .chars()
+        .collect();
+    let after: Vec<char> = String::from_utf8_lossy(&input[span.start..line_end])
+        .chars()
+        .collect();
+    let skipped = before.len().saturating_sub(EXCERPT_CONTEXT);
+    let mut text = String::new();
+    if skipped > 0 {
+        text.push_str("...");
+    }
+    text.extend(&before[skipped..]);
+    // Where the marker goes, counting the ellipsis.
+    let column = text.chars().count();
+    text.extend(after.iter().take(EXCERPT_CONTEXT));
+    if after.len() > EXCERPT_CONTEXT {
+        text.push_str("...");
+    }
+    let gutter = line_number.to_string().len();
+    let text: String = text
+        .chars()
+        .map(|c| if c == '\t' { ' ' } else { c })
+        .collect();
+    format!(
+        "    {line_number} | {}\n    {:gutter$} | {:column$}^ here",
+        text.trim_end(),
+        "",
+        ""
+    )
+}
@@ -0,0 +1,279 @@
+//! Fetching data for the command-line program through an on-disk response cache, so that
+//! repeated invocations, such as from a status bar, do not refetch the same data.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::io;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, Utc};
+use clap::Args;
+
+use rjw_uktides::{
+    Cache, CachedResponse, Client, FileCache, RateLimit, RetryPolicy, Station, StationId,
+    TidePredictions,
+};
+
+use crate::config;
+use crate::error::CliError;
+
+/// The environment variable giving the address of the service to send requests to
+/// instead of EasyTide, overriding the configuration file.
+pub const BASE_URL_VAR: &str = "UKTIDES_BASE_URL";
+
+/// What a parsed response is, for error reports.
+const RESPONSE: &str = "the response from EasyTide";
+
+/// How long a cached station list is used before it is fetched again, in seconds.
+const STATIONS_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+/// How long cached tide predictions are used before they are fetched again, in seconds,
+/// unless a command sets its own time.
+const TIDES_TTL_SECS: i64 = 60 * 60;
+
+/// Options controlling the response cache.
+#[derive(Args, Clone, Debug)]
+pub struct CacheArgs {
+    /// Fetch fresh data even if the cached data has not expired, updating the cache.
+    #[arg(long, global = true, conflicts_with_all = ["no_cache", "offline"])]
+    refresh: bool,
+
+    /// Neither use nor update the response cache.
+    #[arg(long, global = true, conflicts_with = "offline")]
+    no_cache: bool,
+
+    /// Use only cached data, however old, without contacting EasyTide.
+    #[arg(long, global = true)]
+    offline: bool,
+}
+
+/// Options controlling requests to EasyTide, for slow connections and proxies.
+#[derive(Args, Clone, Debug)]
+pub struct NetworkArgs {
+    /// Give up on each attempt at a request after this many seconds.
+    #[arg(long, global = true, value_name = "SECS", default_value_t = 30)]
+    timeout: u64,
+
+    /// Retry a failed request up to this many times, waiting longer before each retry.
+    #[arg(long, global = true, value_name = "N", default_value_t = 2)]
+    retries: u32,
+
+    /// Send requests through this proxy, such as "http://proxy.example:3128".
+    ///
+    /// Otherwise the HTTP_PROXY, HTTPS_PROXY and NO_PROXY environment variables are
+    /// respected.
+    #[arg(long, global = true, value_name = "URL")]
+    proxy: Option<String>,
+}
+
+impl NetworkArgs {
+    /// A client for EasyTide with the timeout, retries and proxy, sending requests to the
+    /// address in `UKTIDES_BASE_URL`, or else the configured `base_url`, if either is set.
+    pub fn client(&self, base_url: Option<&str>) -> Result<Client, Box<dyn Error>> {
+        let mut retry = RetryPolicy::default();
+        retry.max_attempts = self.retries + 1;
+        let from_env = std::env::var(BASE_URL_VAR)
+            .ok()
+            .filter(|url| !url.is_empty());
+        let client = match from_env.as_deref().or(base_url) {
+            Some(base_url) => {
+                config::check_base_url(base_url)?;
+                Client::with_base_url(base_url.trim_end_matches('/'))
+            }
+            None => Client::new(),
+        };
+        let client = client
+            .with_timeout(std::time::Duration::from_secs(self.timeout))
+            .with_retry(retry);
+        match &self.proxy {
+            Some(proxy) => client.with_proxy(proxy),
+            None => Ok(client),
+        }
+    }
+}
+
+/// Fetches stations and predictions from EasyTide or the response cache.
+#[derive(Debug)]
+pub struct Fetcher {
+    client: Client,
+    /// The cache read directly, without contacting EasyTide, in offline mode.
+    offline: Option<FileCache>,
+    /// The response cache, if it is used.
+    cache: Option<FileCache>,
+    /// Ignore the cached responses, so that everything is fetched again.
+    refresh: bool,
+}
+
+impl Fetcher {
+    /// Create a fetcher sending requests with `client` and caching responses in `dir`, or
+    /// the default cache directory if `None`, according to the cache options.
+    pub fn new(client: Client, args: &CacheArgs, dir: Option<PathBuf>) -> Self {
+        let cache = dir
+            .or_else(default_dir)
+            .filter(|_| !args.no_cache)
+            .map(FileCache::new);
+        match cache {
+            Some(cache) if args.offline => Self {
+                client,
+                offline: Some(cache.clone()),
+                cache: Some(cache),
+                refresh: false,
+            },
+            Some(cache) => Self {
+                client,
+                offline: None,
+                cache: Some(cache),
+                refresh: args.refresh,
+            }
+            .with_tides_ttl(Duration::seconds(TIDES_TTL_SECS)),
+            None => Self {
+                client,
+                offline: None,
+                cache: None,
+                refresh: false,
+            },
+        }
+    }
+
+    /// Use cached predictions for `ttl` after they were fetched, rather than an hour.
+    pub fn with_tides_ttl(mut self, ttl: Duration) -> Self {
+        if let (Some(cache), None) = (&self.cache, &self.offline) {
+            self.client = self.client.clone().with_cache(TtlCache {
+                inner: cache.clone(),
+                stations_url: rjw_uktides::stations_url(self.client.base_url()),
+                tides_ttl: ttl,
+                refresh: self.refresh,
+            });
+        }
+        self
+    }
+
+    /// Limit the rate of requests to EasyTide; responses from the cache are not limited.
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.client = self.client.with_rate_limit(limit);
+        self
+    }
+
+    /// Fetch the list of all tidal stations.
+    pub fn stations(&self) -> Result<Vec<Station>, Box<dyn Error>> {
+        Ok(CliError::parse(RESPONSE, self.stations_raw()?, |input| {
+            rjw_uktides::stations_from_reader(input)
+        })?)
+    }
+
+    /// Fetch the unparsed EasyTide response listing all tidal stations.
+    pub fn stations_raw(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        match &self.offline {
+            Some(cache) => cached_body(cache, &rjw_uktides::stations_url(self.client.base_url())),
+            None => Ok(self.client.fetch_stations_raw()?.to_vec()),
+        }
+    }
+
+    /// Fetch the tide predictions for the station.
+    pub fn tides(&self, station: &StationId) -> Result<TidePredictions, Box<dyn Error>> {
+        Ok(CliError::parse(
+            RESPONSE,
+            self.tides_raw(station)?,
+            rjw_uktides::tides_from_slice,
+        )?)
+    }
+
+    /// Fetch the unparsed EasyTide response containing the station's tide predictions.
+    pub fn tides_raw(&self, station: &StationId) -> Result<Vec<u8>, Box<dyn Error>> {
+        match &self.offline {
+            Some(cache) => cached_body(cache, &self.tides_url(station)),
+            None => Ok(self.client.fetch_tides_raw(station)?.to_vec()),
+        }
+    }
+
+    /// The URL of the EasyTide response containing the station's tide predictions.
+    pub fn tides_url(&self, station: &StationId) -> String {
+        rjw_uktides::tides_url(self.client.base_url(), station)
+    }
+
+    /// When the station's predictions were fetched from EasyTide: when the cached response
+    /// was stored or last revalidated, or now if the cache is not used.
+    pub fn fetched_at(&self, station: &StationId) -> DateTime<Utc> {
+        self.cache
+            .as_ref()
+            .and_then(|cache| cache.get(&self.tides_url(station)).ok().flatten())
+            .map_or_else(Utc::now, |response| response.metadata.stored_at)
+    }
+
+    /// The predictions for the station in the cache, however old, if there are any.
+    pub fn cached_tides(&self, station: &StationId) -> Option<TidePredictions> {
+        let body = cached_body(self.cache.as_ref()?, &self.tides_url(station)).ok()?;
+        rjw_uktides::tides_from_slice(&body).ok()
+    }
+
+    /// Fetch the tide predictions for each of the stations concurrently.
+    pub fn tides_many(
+        &self,
+        stations: &[StationId],
+    ) -> HashMap<StationId, Result<TidePredictions, Box<dyn Error + Send + Sync>>> {
+        match &self.offline {
+            Some(_) => stations
+                .iter()
+                .map(|station| {
+                    let result = self.tides(station).map_err(|e| e.to_string().into());
+                    (station.clone(), result)
+                })
+                .collect(),
+            None => self.client.fetch_tides_many(stations, stations.len()),
+        }
+    }
+}
+
+/// The body of the cached response for the URL, for use in offline mode.
+fn cached_body(cache: &FileCache, url: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    match cache.get(url)? {
+        Some(response) => Ok(response.body.to_vec()),
+        None => Err(format!("{url} is not cached, so cannot be used with --offline.").into()),
+    }
+}
+
+/// `$XDG_CACHE_HOME/rjw-uktides`, falling back to `~/.cache/rjw-uktides`.
+fn default_dir() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(dir.join("rjw-uktides"))
+}
+
+/// A [`FileCache`] whose responses are fresh for a fixed time after they are stored,
+/// as EasyTide does not say how long its responses may be cached.
+#[derive(Debug)]
+struct TtlCache {
+    inner: FileCache,
+    /// The URL of the station list, which is cached for longer than predictions.
+    stations_url: String,
+    /// How long predictions are fresh.
+    tides_ttl: Duration,
+    /// Ignore the cached responses, so that everything is fetched again.
+    refresh: bool,
+}
+
+impl Cache for TtlCache {
+    fn get(&self, key: &str) -> io::Result<Option<CachedResponse>> {
+        if self.refresh {
+            return Ok(None);
+        }
+        let Some(mut response) = self.inner.get(key)? else {
+            return Ok(None);
+        };
+        let ttl = if key == self.stations_url {
+            Duration::seconds(STATIONS_TTL_SECS)
+        } else {
+            self.tides_ttl
+        };
+        let expires_at = response.metadata.stored_at + ttl;
+        if response.metadata.expires_at.is_none_or(|e| e < expires_at) {
+            response.metadata.expires_at = Some(expires_at);
+        }
+        Ok(Some(response))
+    }
+
+    fn put(&self, key: &str, response: &CachedResponse) -> io::Result<()> {
+        self.inner.put(key, response)
+    }
+}
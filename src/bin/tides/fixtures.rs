@@ -0,0 +1,50 @@
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Args;
+
+use rjw_uktides::{Client, StationId};
+
+/// Record live stations and tide predictions responses as dated fixture files.
+#[derive(Args, Clone, Debug)]
+pub struct RecordFixturesArgs {
+    /// ID of the tidal station whose predictions to record.
+    station: StationId,
+
+    /// Directory in which to write the fixture files, created if necessary.
+    dir: PathBuf,
+}
+
+/// Fetch live stations and tide predictions JSON and save it as dated fixture files.
+///
+/// Responses are checked to parse as stations and tide predictions, then normalised by
+/// pretty-printing with sorted keys so that successive recordings diff cleanly.
+pub fn record_fixtures(args: RecordFixturesArgs, client: Client) -> Result<(), Box<dyn Error>> {
+    let RecordFixturesArgs { station, dir } = args;
+    let station = &station;
+    let dir = &dir;
+    let date = chrono::Utc::now().date_naive();
+    fs::create_dir_all(dir)?;
+
+    let stations = client.fetch_stations_raw()?;
+    rjw_uktides::stations_from_slice(&stations)?;
+    let path = dir.join(format!("stations-{date}.json"));
+    fs::write(&path, normalise_json(&stations)?)?;
+    println!("{}", path.display());
+
+    let tides = client.fetch_tides_raw(station)?;
+    rjw_uktides::tides_from_slice(&tides)?;
+    let path = dir.join(format!("tides-{station}-{date}.json"));
+    fs::write(&path, normalise_json(&tides)?)?;
+    println!("{}", path.display());
+    Ok(())
+}
+
+/// Pretty-print JSON with object keys in sorted order.
+fn normalise_json(data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let value: serde_json::Value = serde_json::from_slice(data)?;
+    let mut normalised = serde_json::to_vec_pretty(&value)?;
+    normalised.push(b'\n');
+    Ok(normalised)
+}
@@ -0,0 +1,412 @@
+//! Day and month names, and the phrases of the human-readable output, in the language of
+//! the user's locale.
+//!
+//! The language is taken from the `LC_ALL`, `LC_TIME` (for names) or `LC_MESSAGES` (for
+//! phrases), or `LANG` environment variable, such as "cy" from `cy_GB.UTF-8`, falling back
+//! to English for languages without names or phrases here.
+
+use rjw_uktides::{LunarPhaseType, TidalEventType};
+
+/// The names used in dates and times.
+#[derive(Debug)]
+pub struct Names {
+    /// Days of the week, from Sunday.
+    pub days: [&'static str; 7],
+    pub short_days: [&'static str; 7],
+    /// Months, from January.
+    pub months: [&'static str; 12],
+    pub short_months: [&'static str; 12],
+    /// Marks morning and afternoon times on the 12-hour clock.
+    pub am: &'static str,
+    pub pm: &'static str,
+}
+
+const ENGLISH: Names = Names {
+    days: [
+        "Sunday",
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+    ],
+    short_days: ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"],
+    months: [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ],
+    short_months: [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ],
+    am: "am",
+    pm: "pm",
+};
+
+const WELSH: Names = Names {
+    days: [
+        "Dydd Sul",
+        "Dydd Llun",
+        "Dydd Mawrth",
+        "Dydd Mercher",
+        "Dydd Iau",
+        "Dydd Gwener",
+        "Dydd Sadwrn",
+    ],
+    short_days: ["Sul", "Llun", "Maw", "Mer", "Iau", "Gwe", "Sad"],
+    months: [
+        "Ionawr",
+        "Chwefror",
+        "Mawrth",
+        "Ebrill",
+        "Mai",
+        "Mehefin",
+        "Gorffennaf",
+        "Awst",
+        "Medi",
+        "Hydref",
+        "Tachwedd",
+        "Rhagfyr",
+    ],
+    short_months: [
+        "Ion", "Chwef", "Maw", "Ebr", "Mai", "Meh", "Gorff", "Awst", "Medi", "Hyd", "Tach", "Rhag",
+    ],
+    am: "yb",
+    pm: "yh",
+};
+
+const FRENCH: Names = Names {
+    days: [
+        "dimanche", "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi",
+    ],
+    short_days: ["dim.", "lun.", "mar.", "mer.", "jeu.", "ven.", "sam."],
+    months: [
+        "janvier",
+        "février",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+    ],
+    short_months: [
+        "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.",
+        "déc.",
+    ],
+    am: "AM",
+    pm: "PM",
+};
+
+const GERMAN: Names = Names {
+    days: [
+        "Sonntag",
+        "Montag",
+        "Dienstag",
+        "Mittwoch",
+        "Donnerstag",
+        "Freitag",
+        "Samstag",
+    ],
+    short_days: ["So", "Mo", "Di", "Mi", "Do", "Fr", "Sa"],
+    months: [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ],
+    short_months: [
+        "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+    ],
+    am: "AM",
+    pm: "PM",
+};
+
+const SPANISH: Names = Names {
+    days: [
+        "domingo",
+        "lunes",
+        "martes",
+        "miércoles",
+        "jueves",
+        "viernes",
+        "sábado",
+    ],
+    short_days: ["dom", "lun", "mar", "mié", "jue", "vie", "sáb"],
+    months: [
+        "enero",
+        "febrero",
+        "marzo",
+        "abril",
+        "mayo",
+        "junio",
+        "julio",
+        "agosto",
+        "septiembre",
+        "octubre",
+        "noviembre",
+        "diciembre",
+    ],
+    short_months: [
+        "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sep", "oct", "nov", "dic",
+    ],
+    am: "a. m.",
+    pm: "p. m.",
+};
+
+const DUTCH: Names = Names {
+    days: [
+        "zondag",
+        "maandag",
+        "dinsdag",
+        "woensdag",
+        "donderdag",
+        "vrijdag",
+        "zaterdag",
+    ],
+    short_days: ["zo", "ma", "di", "wo", "do", "vr", "za"],
+    months: [
+        "januari",
+        "februari",
+        "maart",
+        "april",
+        "mei",
+        "juni",
+        "juli",
+        "augustus",
+        "september",
+        "oktober",
+        "november",
+        "december",
+    ],
+    short_months: [
+        "jan", "feb", "mrt", "apr", "mei", "jun", "jul", "aug", "sep", "okt", "nov", "dec",
+    ],
+    am: "a.m.",
+    pm: "p.m.",
+};
+
+/// The names for the language of the user's locale.
+pub fn names() -> &'static Names {
+    match language("LC_TIME").as_str() {
+        "cy" => &WELSH,
+        "fr" => &FRENCH,
+        "de" => &GERMAN,
+        "es" => &SPANISH,
+        "nl" => &DUTCH,
+        _ => &ENGLISH,
+    }
+}
+
+/// The phrases used in the human-readable output, such as labels for tides and lunar
+/// phases.
+#[derive(Debug)]
+pub struct Phrases {
+    pub high_tide: &'static str,
+    pub low_tide: &'static str,
+    /// Following a percentage, such as "40% of the way to high tide".
+    pub way_to_high_tide: &'static str,
+    pub way_to_low_tide: &'static str,
+    /// The state of the tide after low and high tide.
+    pub flooding: &'static str,
+    pub ebbing: &'static str,
+    pub rising: &'static str,
+    pub falling: &'static str,
+    /// Lunar phases, in the order of [`LunarPhaseType`].
+    pub phases: [&'static str; 4],
+    pub springs: &'static str,
+    pub neaps: &'static str,
+    pub height: &'static str,
+    pub state: &'static str,
+    pub previous: &'static str,
+    pub next: &'static str,
+    pub not_available: &'static str,
+    pub no_predictions: &'static str,
+    pub no_upcoming_tides: &'static str,
+    /// Joining a tide to its time and to its station, the time to its date, and the start
+    /// and end of a range.
+    pub at: &'static str,
+    #[cfg(feature = "notify")]
+    pub at_station: &'static str,
+    pub on: &'static str,
+    pub to: &'static str,
+    /// Before a countdown to a future time, and after one for a past time.
+    pub within: &'static str,
+    pub ago: &'static str,
+    pub range: &'static str,
+    pub estimated: &'static str,
+    pub beyond_predictions: &'static str,
+    pub updated_at: &'static str,
+    pub refreshed_every: &'static str,
+    /// The names of the countries of stations, in the order of [`COUNTRIES`].
+    pub countries: [&'static str; 6],
+}
+
+/// The countries of stations as EasyTide names them.
+const COUNTRIES: [&str; 6] = [
+    "Channel Islands",
+    "England",
+    "Isle of Man",
+    "Northern Ireland",
+    "Scotland",
+    "Wales",
+];
+
+const ENGLISH_PHRASES: Phrases = Phrases {
+    high_tide: "High tide",
+    low_tide: "Low tide",
+    way_to_high_tide: "of the way to high tide",
+    way_to_low_tide: "of the way to low tide",
+    flooding: "Flooding (rising)",
+    ebbing: "Ebbing (falling)",
+    rising: "rising",
+    falling: "falling",
+    phases: ["New moon", "First quarter", "Full moon", "Last quarter"],
+    springs: "Springs",
+    neaps: "Neaps",
+    height: "Height:",
+    state: "State:",
+    previous: "Previous:",
+    next: "Next:",
+    not_available: "not available for this station",
+    no_predictions: "No predictions available.",
+    no_upcoming_tides: "no upcoming tides in the predictions",
+    at: "at",
+    #[cfg(feature = "notify")]
+    at_station: "at",
+    on: "on",
+    to: "to",
+    within: "in",
+    ago: "ago",
+    range: "range",
+    estimated: "(estimated)",
+    beyond_predictions: "(beyond the predictions)",
+    updated_at: "Updated at",
+    refreshed_every: "refreshed every",
+    countries: COUNTRIES,
+};
+
+const WELSH_PHRASES: Phrases = Phrases {
+    high_tide: "Penllanw",
+    low_tide: "Distyll",
+    way_to_high_tide: "o'r ffordd at benllanw",
+    way_to_low_tide: "o'r ffordd at ddistyll",
+    flooding: "Llanw (yn codi)",
+    ebbing: "Trai (yn gostwng)",
+    rising: "yn codi",
+    falling: "yn gostwng",
+    phases: [
+        "Lleuad newydd",
+        "Chwarter cyntaf",
+        "Lleuad lawn",
+        "Chwarter olaf",
+    ],
+    springs: "Llanw mawr",
+    neaps: "Llanw bach",
+    height: "Uchder:",
+    state: "Cyflwr:",
+    previous: "Blaenorol:",
+    next: "Nesaf:",
+    not_available: "ddim ar gael i'r orsaf hon",
+    no_predictions: "Dim rhagfynegiadau ar gael.",
+    no_upcoming_tides: "dim llanw i ddod yn y rhagfynegiadau",
+    at: "am",
+    #[cfg(feature = "notify")]
+    at_station: "yn",
+    on: "ar",
+    to: "i",
+    within: "ymhen",
+    ago: "yn ôl",
+    range: "amrediad",
+    estimated: "(amcangyfrif)",
+    beyond_predictions: "(y tu hwnt i'r rhagfynegiadau)",
+    updated_at: "Diweddarwyd am",
+    refreshed_every: "adnewyddir bob",
+    countries: [
+        "Ynysoedd y Sianel",
+        "Lloegr",
+        "Ynys Manaw",
+        "Gogledd Iwerddon",
+        "Yr Alban",
+        "Cymru",
+    ],
+};
+
+impl Phrases {
+    /// The name of the event type, such as "High tide".
+    pub fn event(&self, event_type: TidalEventType) -> &'static str {
+        match event_type {
+            TidalEventType::HighWater => self.high_tide,
+            TidalEventType::LowWater => self.low_tide,
+        }
+    }
+
+    /// The name of the lunar phase, such as "Full moon".
+    pub fn phase(&self, phase: LunarPhaseType) -> &'static str {
+        let index = match phase {
+            LunarPhaseType::NewMoon => 0,
+            LunarPhaseType::FirstQuarter => 1,
+            LunarPhaseType::FullMoon => 2,
+            LunarPhaseType::LastQuarter => 3,
+        };
+        self.phases[index]
+    }
+
+    /// The name of the country as EasyTide names it, or the name as given if it is not
+    /// one of the countries of stations.
+    pub fn country<'a>(&self, country: &'a str) -> &'a str {
+        COUNTRIES
+            .iter()
+            .position(|c| *c == country)
+            .map_or(country, |i| self.countries[i])
+    }
+}
+
+/// The width of the widest of the labels, for aligning the values that follow them.
+pub fn label_width(labels: &[&str]) -> usize {
+    labels.iter().map(|l| l.chars().count()).max().unwrap_or(0)
+}
+
+/// The phrases for the language of the user's locale.
+pub fn phrases() -> &'static Phrases {
+    match language("LC_MESSAGES").as_str() {
+        "cy" => &WELSH_PHRASES,
+        _ => &ENGLISH_PHRASES,
+    }
+}
+
+/// The language of the locale set by `LC_ALL`, the `category` variable, or `LANG`.
+fn language(category: &str) -> String {
+    let locale = ["LC_ALL", category, "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+    locale
+        .split(['_', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_owned()
+}
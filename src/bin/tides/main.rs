@@ -0,0 +1,1567 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufReader, IsTerminal, Read, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use chrono_tz::Tz;
+use rjw_uktides::{
+    Coordinates, DecimalDegrees, GridReference, LunarPhaseType, Station, StationChange, StationId,
+    StationsDiff, TidalEventType, TidePredictions,
+};
+
+use crate::config::Config;
+use crate::error::CliError;
+use crate::fetch::{CacheArgs, Fetcher, NetworkArgs};
+use crate::output::Field;
+use crate::sink::Sink;
+use crate::style::{Style, StyleArgs};
+use crate::template::Template;
+use crate::units::Units;
+use crate::zone::{Clock, Times};
+
+mod alias;
+mod api;
+mod archive;
+mod chart;
+mod compare;
+mod completions;
+mod config;
+#[cfg(feature = "miette")]
+mod diagnostic;
+mod doctor;
+mod dump;
+mod duration;
+mod error;
+mod fetch;
+mod fixtures;
+mod locale;
+mod man;
+mod metrics;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+#[cfg(feature = "notify")]
+mod notify;
+mod open;
+mod output;
+mod picker;
+mod plan;
+#[cfg(feature = "chart")]
+mod plot;
+#[cfg(feature = "postcode")]
+mod postcode;
+mod prompt;
+mod qa;
+mod schema;
+mod serve;
+mod sink;
+mod springs;
+mod station_arg;
+mod style;
+mod table;
+mod template;
+#[cfg(feature = "tui")]
+mod tui;
+mod units;
+mod watch;
+mod zone;
+
+const STATIONS_BAKED_BYTES: &[u8] = include_bytes!("../../../stations.json");
+
+fn main() -> ExitCode {
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => return error::report(e.as_ref(), false),
+    };
+    let matches = match config.apply(Cli::command()).try_get_matches() {
+        Ok(matches) => matches,
+        Err(e) => return error::report_usage(&e),
+    };
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if cli.command.is_none()
+        && cli.tides_args.stations.is_empty()
+        && cli.tides_args.from_file.is_none()
+        && cli.tides_args.from_dir.is_none()
+    {
+        if !(io::stdin().is_terminal() && io::stdout().is_terminal()) {
+            let e = Cli::command().error(
+                clap::error::ErrorKind::MissingRequiredArgument,
+                "a station is required; give one with --station, such as --station 0053",
+            );
+            return error::report_usage(&e);
+        }
+        match picker::pick_station() {
+            Ok(Some(station)) => {
+                eprintln!(
+                    "\nUsing station {} {}; give --station {} to skip this next time.",
+                    station.id, station.name, station.id
+                );
+                cli.tides_args.stations.push(station.id);
+            }
+            Ok(None) => return ExitCode::from(error::EXIT_FAILURE),
+            Err(e) => return error::report(e.as_ref(), cli.verbose),
+        }
+    }
+    let verbose = cli.verbose;
+    let mut out = Sink::new(cli.output.clone());
+    match run(cli, &config, &mut out).and_then(|code| out.finish().map(|()| code)) {
+        Ok(code) => code,
+        Err(e) => error::report(e.as_ref(), verbose),
+    }
+}
+
+fn run(cli: Cli, config: &Config, out: &mut Sink) -> Result<ExitCode, Box<dyn Error>> {
+    let Cli {
+        verbose,
+        output,
+        tides_args,
+        cache_args,
+        network_args,
+        style_args,
+        units,
+        tz,
+        clock,
+        command,
+    } = cli;
+    let times = Times::new(tz, clock);
+    if let (Some(_), Some(name)) = (&output, command.as_ref().and_then(Commands::without_output)) {
+        return Err(format!("--output cannot be used with tides {name}").into());
+    }
+    let style = Style::new(&style_args);
+    let client = network_args.client(config.base_url.as_deref())?;
+    let fetcher = Fetcher::new(client.clone(), &cache_args, config.cache_dir.clone());
+    match command {
+        None => {
+            let (from, to) = tides_args.date_range(times.today())?;
+            let mut tides = match &tides_args.from_file {
+                Some(path) => {
+                    let station = match tides_args.stations.as_slice() {
+                        [] => StationId("unknown".to_owned()),
+                        [station] => station.clone(),
+                        _ => {
+                            return Err("--from-file can only be used with a single station".into())
+                        }
+                    };
+                    let tides = CliError::parse(
+                        path.display().to_string(),
+                        read_input(path)?,
+                        rjw_uktides::tides_from_slice,
+                    )?;
+                    vec![(station, tides)]
+                }
+                None => match &tides_args.from_dir {
+                    Some(dir) => tides_from_dir(dir, &tides_args.stations)?,
+                    None => fetch_tides_all(&fetcher, &tides_args.stations)?,
+                },
+            };
+            for (_, tides) in &mut tides {
+                retain_dates(tides, from, to, times);
+                units.convert(tides);
+            }
+            // Label each tide with its station when there are several.
+            let multiple = tides.len() > 1;
+            let mut fields = tides_args.fields.clone();
+            if multiple {
+                fields.push(Field::Station);
+            }
+            if tides_args.chart {
+                let width = std::env::var("COLUMNS")
+                    .ok()
+                    .and_then(|c| c.parse().ok())
+                    .unwrap_or(CHART_WIDTH);
+                for (station, tides) in &tides {
+                    let heights = &tides.tidal_height_occurrence_list;
+                    let (Some(first), Some(last)) = (heights.first(), heights.last()) else {
+                        writeln!(out, "No heights available to chart for station {station}.")?;
+                        continue;
+                    };
+                    if multiple {
+                        writeln!(out, "{station}")?;
+                    }
+                    let chart = chart::height_chart(
+                        tides,
+                        first.date_time..last.date_time,
+                        width,
+                        CHART_ROWS,
+                        Utc::now(),
+                        units,
+                        times,
+                    );
+                    for line in chart {
+                        writeln!(out, "{line}")?;
+                    }
+                }
+                return Ok(ExitCode::SUCCESS);
+            }
+            if let Some(template) = &tides_args.template {
+                let now = Utc::now();
+                for (station, event) in output::merged_events(&tides) {
+                    writeln!(out, "{}", template.render(station, event, now, times))?;
+                }
+                return Ok(ExitCode::SUCCESS);
+            }
+            match tides_args.format() {
+                Format::Text if tides_args.group_by.is_some() => write!(
+                    out,
+                    "{}",
+                    output::grouped_by_day(&tides, style, units, times)
+                )?,
+                format if tides_args.group_by.is_some() => {
+                    let format = format.to_possible_value().expect("no skipped formats");
+                    return Err(format!(
+                        "--group-by cannot be used with --format {}",
+                        format.get_name()
+                    )
+                    .into());
+                }
+                Format::Text if !tides_args.fields.is_empty() => {
+                    output::write_rows(&mut *out, &tides, &fields, '\t', false, times.tz())?
+                }
+                Format::Text if multiple => {
+                    let fields = [Field::Station, Field::Time, Field::Type, Field::Height];
+                    output::write_rows(&mut *out, &tides, &fields, '\t', false, times.tz())?
+                }
+                Format::Text => {
+                    for (_, tides) in tides {
+                        for tide in tides.tidal_event_list {
+                            writeln!(
+                                out,
+                                "{},{}",
+                                output::rfc3339(tide.date_time.with_timezone(&times.tz())),
+                                style.event_type(tide.event_type, 0)
+                            )?;
+                        }
+                    }
+                }
+                format @ (Format::Json | Format::Jsonl | Format::Ics)
+                    if !tides_args.fields.is_empty() =>
+                {
+                    let format = format.to_possible_value().expect("no skipped formats");
+                    return Err(format!(
+                        "--fields cannot be used with --format {}",
+                        format.get_name()
+                    )
+                    .into());
+                }
+                Format::Json if multiple => {
+                    let metas = metas(&fetcher, &tides_args, &tides, units)?;
+                    let json: Vec<_> = tides
+                        .iter()
+                        .zip(&metas)
+                        .map(|((station, tides), meta)| {
+                            output::TidesJson::new(station, tides, meta)
+                        })
+                        .collect();
+                    writeln!(out, "{}", serde_json::to_string_pretty(&json)?)?;
+                }
+                Format::Json => {
+                    let metas = metas(&fetcher, &tides_args, &tides, units)?;
+                    for ((station, tides), meta) in tides.iter().zip(&metas) {
+                        let json = output::TidesJson::new(station, tides, meta);
+                        writeln!(out, "{}", serde_json::to_string_pretty(&json)?)?;
+                    }
+                }
+                Format::Jsonl => {
+                    let metas = metas(&fetcher, &tides_args, &tides, units)?;
+                    for line in output::EventLine::all(&tides, &metas) {
+                        writeln!(out, "{}", serde_json::to_string(&line)?)?;
+                    }
+                }
+                Format::Csv if !fields.is_empty() => {
+                    if fields == [Field::Station] {
+                        fields.extend(Field::DEFAULT);
+                    }
+                    output::write_rows(&mut *out, &tides, &fields, ',', true, Tz::UTC)?
+                }
+                Format::Csv => {
+                    for (_, tides) in &tides {
+                        rjw_uktides::write_tides_csv(&mut *out, tides)?
+                    }
+                }
+                Format::Tsv => {
+                    if tides_args.fields.is_empty() {
+                        fields.extend(Field::DEFAULT);
+                    }
+                    output::write_rows(&mut *out, &tides, &fields, '\t', true, Tz::UTC)?
+                }
+                Format::Ics if multiple => {
+                    return Err("--format ics can only be used with a single station".into());
+                }
+                Format::Ics => {
+                    for (station, mut upcoming) in tides {
+                        let now = Utc::now();
+                        upcoming.tidal_event_list.retain(|e| e.date_time >= now);
+                        let name = station_name(&station)?;
+                        write!(
+                            out,
+                            "{}",
+                            upcoming.to_ics_with_units(&station, &name, units.symbol())
+                        )?;
+                    }
+                }
+            }
+        }
+        Some(Commands::ListStations(args)) => {
+            let mut stations = if let Some(path) = &args.from_file {
+                CliError::parse(path.display().to_string(), read_input(path)?, |input| {
+                    rjw_uktides::stations_from_reader(input)
+                })?
+            } else if args.fetch || args.diff {
+                fetcher.stations()?
+            } else {
+                rjw_uktides::stations_from_reader(STATIONS_BAKED_BYTES)?
+            };
+            if args.diff {
+                let mut baked = rjw_uktides::stations_from_reader(STATIONS_BAKED_BYTES)?;
+                args.filter(&mut baked);
+                args.filter(&mut stations);
+                let diff = rjw_uktides::diff_stations(&baked, &stations);
+                display_stations_diff(&diff, out)?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            let point = args.lat.zip(args.lon).map(|(lat, lon)| Coordinates {
+                latitude: DecimalDegrees(lat),
+                longitude: DecimalDegrees(lon),
+            });
+            args.filter(&mut stations);
+            args.sort(&mut stations, point.as_ref());
+            let stations = args.paging.apply(stations, None);
+            match args.format {
+                StationsFormat::Text => display_stations(stations, point.as_ref(), out)?,
+                StationsFormat::Csv => rjw_uktides::write_stations_csv(&mut *out, &stations)?,
+                StationsFormat::Wkt => rjw_uktides::write_stations_wkt_csv(&mut *out, &stations)?,
+                #[cfg(feature = "store")]
+                StationsFormat::Sqlite => {
+                    let to_stdout = output.as_deref().is_none_or(|p| p == Path::new("-"));
+                    if to_stdout && io::stdout().is_terminal() {
+                        return Err("--format sqlite writes a database file; give its path \
+                                    with --output"
+                            .into());
+                    }
+                    rjw_uktides::store::write_stations_sqlite(&mut *out, &stations)?
+                }
+            }
+        }
+        Some(Commands::Today(args)) => {
+            let today = times.today();
+            print_days(&fetcher, &args, today..=today, style, units, times, out)?;
+        }
+        Some(Commands::Tomorrow(args)) => {
+            let tomorrow = times.today() + Days::new(1);
+            print_days(
+                &fetcher,
+                &args,
+                tomorrow..=tomorrow,
+                style,
+                units,
+                times,
+                out,
+            )?;
+        }
+        Some(Commands::Week(args)) => {
+            let today = times.today();
+            let last = today + Days::new(6);
+            print_days(&fetcher, &args, today..=last, style, units, times, out)?;
+        }
+        Some(Commands::Next(NextArgs {
+            station,
+            event_type,
+            within,
+        })) => {
+            let mut tides = fetch_tides(&fetcher, &station)?;
+            units.convert(&mut tides);
+            let now = Utc::now();
+            let next = NextType::event_types(event_type)
+                .iter()
+                .filter_map(|t| tides.next_event(now, *t))
+                .min_by_key(|e| e.date_time);
+            if let Some(within) = within {
+                let in_window = next.is_some_and(|e| e.date_time - now <= within);
+                if verbose && !in_window {
+                    writeln!(
+                        out,
+                        "No tide within {}.",
+                        template::countdown(now, now + within)
+                    )?;
+                }
+                if !verbose || !in_window {
+                    return Ok(if in_window {
+                        ExitCode::SUCCESS
+                    } else {
+                        ExitCode::from(error::EXIT_NO_TIDE)
+                    });
+                }
+            }
+            let next = next.ok_or("No upcoming tides in the predictions.")?;
+            let phrases = locale::phrases();
+            let time = if times.date(next.date_time) == times.today() {
+                times.format(next.date_time, "%H:%M %Z")
+            } else {
+                let pattern = format!("%H:%M %Z {} %a %-d %b", phrases.on);
+                times.format(next.date_time, &pattern)
+            };
+            writeln!(
+                out,
+                "{} {:.1} {} {} {time}, {} {}",
+                style.event_type(next.event_type, 0),
+                next.height.0,
+                units.symbol(),
+                phrases.at,
+                phrases.within,
+                template::countdown(now, next.date_time)
+            )?;
+        }
+        Some(Commands::Now(StationArgs { station })) => {
+            let mut tides = fetch_tides(&fetcher, &station)?;
+            units.convert(&mut tides);
+            let now = Utc::now();
+            let (before, after) = tides
+                .bracketing_events(now)
+                .ok_or("The current time is not covered by the predictions.")?;
+            let phrases = locale::phrases();
+            let (state, way_to) = match before.event_type {
+                TidalEventType::LowWater => (phrases.flooding, phrases.way_to_high_tide),
+                TidalEventType::HighWater => (phrases.ebbing, phrases.way_to_low_tide),
+            };
+            let width = 1 + locale::label_width(&[
+                phrases.height,
+                phrases.state,
+                phrases.previous,
+                phrases.next,
+            ]);
+            let span = (after.date_time - before.date_time).num_seconds() as f64;
+            let elapsed = (now - before.date_time).num_seconds() as f64;
+            writeln!(
+                out,
+                "{}, {}",
+                station_name(&station)?,
+                times.format(now, "%H:%M %Z")
+            )?;
+            let height = match tides.height_at(now) {
+                Some(height) => format!("{:.2} {}", height.0, units.symbol()),
+                None => phrases.not_available.to_owned(),
+            };
+            writeln!(out, "{:<width$}{height}", phrases.height)?;
+            writeln!(
+                out,
+                "{:<width$}{state}, {:.0}% {way_to}",
+                phrases.state,
+                elapsed / span * 100.0,
+            )?;
+            let since = format!(
+                "{} {}",
+                template::countdown(before.date_time, now),
+                phrases.ago
+            );
+            let until = format!(
+                "{} {}",
+                phrases.within,
+                template::countdown(now, after.date_time)
+            );
+            for (label, event, when) in [
+                (phrases.previous, before, since),
+                (phrases.next, after, until),
+            ] {
+                writeln!(
+                    out,
+                    "{label:<width$}{}  {:.2} {} {} {} ({when})",
+                    style.event_type(event.event_type, 9),
+                    event.height.0,
+                    units.symbol(),
+                    phrases.at,
+                    times.format(event.date_time, "%H:%M"),
+                )?;
+            }
+        }
+        Some(Commands::Moon(MoonArgs { station, glyph })) => {
+            let tides = fetch_tides(&fetcher, &station)?;
+            let today = times.today();
+            let mut phases: Vec<_> = tides
+                .lunar_phase_list
+                .iter()
+                .filter(|p| times.date(p.date_time) >= today)
+                .collect();
+            phases.sort_by_key(|p| p.date_time);
+            if phases.is_empty() {
+                writeln!(out, "No upcoming lunar phases in the predictions.")?;
+            }
+            for phase in phases {
+                let glyph = if glyph {
+                    format!("{}  ", moon_glyph(phase.lunar_phase_type))
+                } else {
+                    String::new()
+                };
+                writeln!(
+                    out,
+                    "{glyph}{}  {}",
+                    times.format(phase.date_time, "%a %-d %b %H:%M %Z"),
+                    style.phase(phase.lunar_phase_type)
+                )?;
+            }
+        }
+        Some(Commands::Springs(SpringsArgs { station, weeks })) => {
+            let mut tides = fetch_tides(&fetcher, &station)?;
+            units.convert(&mut tides);
+            let until = Utc::now() + chrono::Duration::weeks(weeks.into());
+            let periods = springs::periods(&tides, until, units, times);
+            if periods.is_empty() {
+                writeln!(
+                    out,
+                    "No lunar phases in the predictions to derive springs and neaps from."
+                )?;
+            }
+            for period in periods {
+                writeln!(out, "{period}")?;
+            }
+        }
+        Some(Commands::Nearest(args)) => {
+            let stations = if args.fetch {
+                fetcher.stations()?
+            } else {
+                rjw_uktides::stations_from_reader(STATIONS_BAKED_BYTES)?
+            };
+            let (lat, lon) = args.location()?;
+            let point = Coordinates {
+                latitude: DecimalDegrees(lat),
+                longitude: DecimalDegrees(lon),
+            };
+            for (station, distance) in rjw_uktides::nearest_stations(&stations, &point, args.count)
+            {
+                writeln!(
+                    out,
+                    "{}\t{}\t{}\t{distance:.1} km",
+                    station.id,
+                    station.name,
+                    locale::phrases().country(&station.country)
+                )?;
+            }
+        }
+        Some(Commands::Search(args)) => {
+            let stations = if args.fetch {
+                fetcher.stations()?
+            } else {
+                rjw_uktides::stations_from_reader(STATIONS_BAKED_BYTES)?
+            };
+            let point = args.lat.zip(args.lon).map(|(lat, lon)| Coordinates {
+                latitude: DecimalDegrees(lat),
+                longitude: DecimalDegrees(lon),
+            });
+            let matches = rjw_uktides::fuzzy_search_stations(&stations, &args.query);
+            if matches.is_empty() {
+                writeln!(out, "No stations match \"{}\".", args.query)?;
+            }
+            for station in args.paging.apply(matches, Some(SEARCH_LIMIT)) {
+                write!(
+                    out,
+                    "{}\t{}\t{}",
+                    station.id,
+                    station.name,
+                    locale::phrases().country(&station.country)
+                )?;
+                if let Some(point) = &point {
+                    write!(out, "\t{:.1} km", station.location.distance_km(point))?;
+                }
+                writeln!(out)?;
+            }
+        }
+        Some(Commands::Table(args)) => {
+            let mut tides = fetch_tides(&fetcher, &args.station)?;
+            units.convert(&mut tides);
+            let first = args.month.unwrap_or_else(|| {
+                let today = times.today();
+                today - Days::new(u64::from(today.day0()))
+            });
+            let name = station_name(&args.station)?;
+            let table = if args.html {
+                table::html(&name, &tides, first, units, times)
+            } else {
+                table::text(&name, &tides, first, units, times)
+            };
+            write!(out, "{table}")?;
+        }
+        Some(Commands::Compare(args)) => {
+            let [first, second] = args.stations.as_slice() else {
+                return Err("compare needs two stations, given with --station".into());
+            };
+            let mut first_tides = fetch_tides(&fetcher, first)?;
+            let mut second_tides = fetch_tides(&fetcher, second)?;
+            units.convert(&mut first_tides);
+            units.convert(&mut second_tides);
+            let date = args.date.unwrap_or_else(|| times.today());
+            let table = compare::text(
+                (&station_name(first)?, &first_tides),
+                (&station_name(second)?, &second_tides),
+                date,
+                units,
+                times,
+            );
+            write!(out, "{table}")?;
+        }
+        Some(Commands::Prompt(args)) => prompt::prompt(args, fetcher, units, times, out)?,
+        Some(Commands::Plan(args)) => {
+            let stations = rjw_uktides::stations_from_reader(STATIONS_BAKED_BYTES)?;
+            let station = stations
+                .into_iter()
+                .find(|s| s.id == args.station)
+                .ok_or_else(|| CliError::UnknownStation(args.station.clone()))?;
+            let mut tides = fetch_tides(&fetcher, &station.id)?;
+            units.convert(&mut tides);
+            write!(
+                out,
+                "{}",
+                plan::text(args.activity, &station, &tides, Utc::now(), units, times)
+            )?;
+        }
+        Some(Commands::Open(args)) => open::open(args, out)?,
+        Some(Commands::Alias(args)) => alias::alias(args, config, out)?,
+        Some(Commands::Archive(args)) => archive::archive(args, &fetcher)?,
+        Some(Commands::DumpAll(args)) => dump::dump_all(args, fetcher)?,
+        Some(Commands::Qa(args)) => qa::qa(args, &fetcher)?,
+        Some(Commands::Doctor(args)) => doctor::doctor(args, client)?,
+        Some(Commands::Watch(args)) => watch::watch(args, &fetcher, units, times)?,
+        Some(Commands::Id(args)) => {
+            let stations = rjw_uktides::stations_from_reader(STATIONS_BAKED_BYTES)?;
+            let Some(station) = rjw_uktides::find_station_by_name(&stations, &args.name) else {
+                let suggestions = rjw_uktides::fuzzy_search_stations(&stations, &args.name)
+                    .into_iter()
+                    .take(5)
+                    .map(|s| (s.id.clone(), s.name.clone()))
+                    .collect();
+                return Err(CliError::NoStationMatch {
+                    query: args.name,
+                    suggestions,
+                }
+                .into());
+            };
+            writeln!(out, "{}", station.id)?;
+        }
+        Some(Commands::RecordFixtures(args)) => fixtures::record_fixtures(args, client)?,
+        Some(Commands::Serve(args)) => serve::serve(args, client)?,
+        Some(Commands::Completions(args)) => {
+            completions::completions(args, Cli::command(), config, out)?
+        }
+        Some(Commands::Man(args)) => man::man(args, Cli::command(), out)?,
+        Some(Commands::Schema(args)) => schema::schema(args, out)?,
+        #[cfg(feature = "mqtt")]
+        Some(Commands::Publish(args)) => mqtt::publish(args, client)?,
+        #[cfg(feature = "notify")]
+        Some(Commands::Notify(args)) => notify::notify(args, &fetcher, units, times)?,
+        #[cfg(feature = "tui")]
+        Some(Commands::Tui(args)) => tui::tui(args, &fetcher, units, times)?,
+        #[cfg(feature = "chart")]
+        Some(Commands::Chart(args)) => plot::chart(args, &fetcher, units, times, out)?,
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Open the file for reading, or stdin if the path is "-".
+fn open_input(path: &Path) -> Result<Box<dyn Read>, Box<dyn Error>> {
+    if path == Path::new("-") {
+        return Ok(Box::new(io::stdin().lock()));
+    }
+    let file = File::open(path).map_err(|e| format!("Could not open {}: {e}", path.display()))?;
+    Ok(Box::new(BufReader::new(file)))
+}
+
+/// Read all of the file at the path, or stdin if it is "-".
+fn read_input(path: &Path) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut input = Vec::new();
+    open_input(path)?
+        .read_to_end(&mut input)
+        .map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+    Ok(input)
+}
+
+/// Read the predictions saved in the directory, as by `tides dump-all`, for the stations,
+/// or all of the stations with files if none are given, in order of station ID. Files that
+/// cannot be read are reported and skipped.
+fn tides_from_dir(
+    dir: &Path,
+    stations: &[StationId],
+) -> Result<Vec<(StationId, TidePredictions)>, Box<dyn Error>> {
+    let mut paths = BTreeMap::new();
+    if stations.is_empty() {
+        let entries =
+            fs::read_dir(dir).map_err(|e| format!("Could not read {}: {e}", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            let id = path
+                .extension()
+                .filter(|e| *e == "json")
+                .and(path.file_stem())
+                .and_then(|s| s.to_str())
+                .filter(|s| station_arg::is_id_shaped(s));
+            if let Some(id) = id {
+                paths.insert(StationId(id.to_owned()), path);
+            }
+        }
+    } else {
+        for station in stations {
+            let path = dir_file(dir, station);
+            if !path.exists() {
+                return Err(format!(
+                    "There are no predictions for {station} in {}",
+                    dir.display()
+                )
+                .into());
+            }
+            paths.insert(station.clone(), path);
+        }
+    }
+    let readers = paths
+        .iter()
+        .filter_map(|(station, path)| match File::open(path) {
+            Ok(file) => Some((station.clone(), BufReader::new(file))),
+            Err(e) => {
+                eprintln!("Skipping {}: {e}", path.display());
+                None
+            }
+        });
+    let mut tides: Vec<_> = rjw_uktides::tides_from_readers(readers)
+        .into_iter()
+        .filter_map(|(station, result)| match result {
+            Ok(predictions) => Some((station, predictions)),
+            Err(e) => {
+                eprintln!("Skipping {}: {e}", paths[&station].display());
+                None
+            }
+        })
+        .collect();
+    if tides.is_empty() {
+        return Err(format!("No predictions could be read from {}", dir.display()).into());
+    }
+    tides.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(tides)
+}
+
+/// The file in which `tides dump-all` saves the station's predictions.
+fn dir_file(dir: &Path, station: &StationId) -> PathBuf {
+    dir.join(format!("{station}.json"))
+}
+
+/// Fetch the predictions for the station, reporting an unknown station if the fetch
+/// fails for a station that is not in the list built into the binary.
+fn fetch_tides(fetcher: &Fetcher, station: &StationId) -> Result<TidePredictions, Box<dyn Error>> {
+    match fetcher.tides(station) {
+        Ok(tides) => Ok(tides),
+        Err(_) if !is_known_station(station)? => {
+            Err(CliError::UnknownStation(station.clone()).into())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Fetch the predictions for each of the stations concurrently, in the order given and
+/// without duplicates, reporting unknown stations as [`fetch_tides`] does.
+fn fetch_tides_all(
+    fetcher: &Fetcher,
+    stations: &[StationId],
+) -> Result<Vec<(StationId, TidePredictions)>, Box<dyn Error>> {
+    let mut results = fetcher.tides_many(stations);
+    let mut tides = Vec::with_capacity(results.len());
+    for station in stations {
+        let Some(result) = results.remove(station) else {
+            // A station given more than once, whose result has already been taken.
+            continue;
+        };
+        match result {
+            Ok(predictions) => tides.push((station.clone(), predictions)),
+            Err(_) if !is_known_station(station)? => {
+                return Err(CliError::UnknownStation(station.clone()).into())
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(tides)
+}
+
+/// Where and when each station's predictions were fetched, and the units of their
+/// heights, for the JSON outputs.
+fn metas(
+    fetcher: &Fetcher,
+    tides_args: &TidesArgs,
+    tides: &[(StationId, TidePredictions)],
+    units: Units,
+) -> Result<Vec<output::Meta>, Box<dyn Error>> {
+    tides
+        .iter()
+        .map(|(station, _)| {
+            let (source, fetched_at) = match (&tides_args.from_file, &tides_args.from_dir) {
+                (Some(path), _) => (path.display().to_string(), None),
+                (None, Some(dir)) => (dir_file(dir, station).display().to_string(), None),
+                (None, None) => (
+                    fetcher.tides_url(station),
+                    Some(fetcher.fetched_at(station)),
+                ),
+            };
+            let name = station_name(station)?;
+            Ok(output::Meta::new(
+                station.clone(),
+                name,
+                source,
+                fetched_at,
+                units,
+            ))
+        })
+        .collect()
+}
+
+/// Print the station's tides on the days, grouped by day.
+fn print_days(
+    fetcher: &Fetcher,
+    args: &DayArgs,
+    days: RangeInclusive<NaiveDate>,
+    style: Style,
+    units: Units,
+    times: Times,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let mut tides = fetch_tides(fetcher, &args.station)?;
+    units.convert(&mut tides);
+    // The periods are found before the other days are removed, as a period can follow a
+    // lunar phase from before the first day.
+    let periods = args.with_moon.then(|| {
+        let until = times.midnight(*days.end() + Days::new(1));
+        springs::periods(&tides, until, units, times)
+    });
+    retain_dates(&mut tides, Some(*days.start()), Some(*days.end()), times);
+    let name = station_name(&args.station)?;
+    write!(
+        out,
+        "{}",
+        output::daily_view(&name, &tides, days, style, units, times, periods.as_deref())
+    )?;
+    Ok(())
+}
+
+/// An ASCII picture of the moon in the phase, lit from the right as it waxes.
+fn moon_glyph(phase: LunarPhaseType) -> &'static str {
+    match phase {
+        LunarPhaseType::NewMoon => "(  )",
+        LunarPhaseType::FirstQuarter => "( #)",
+        LunarPhaseType::FullMoon => "(##)",
+        LunarPhaseType::LastQuarter => "(# )",
+    }
+}
+
+/// Whether the station is in the list of stations built into the binary.
+fn is_known_station(station: &StationId) -> Result<bool, Box<dyn Error>> {
+    let stations = rjw_uktides::stations_from_reader(STATIONS_BAKED_BYTES)?;
+    Ok(stations.iter().any(|s| &s.id == station))
+}
+
+/// Remove the events, heights and lunar phases outside the dates `from` to `to`
+/// inclusive, in the time zone. Either end may be open.
+fn retain_dates(
+    tides: &mut TidePredictions,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    times: Times,
+) {
+    let within = |date_time: DateTime<Utc>| {
+        let date = times.date(date_time);
+        from.is_none_or(|from| date >= from) && to.is_none_or(|to| date <= to)
+    };
+    tides.tidal_event_list.retain(|e| within(e.date_time));
+    tides
+        .tidal_height_occurrence_list
+        .retain(|h| within(h.date_time));
+    tides.lunar_phase_list.retain(|p| within(p.date_time));
+}
+
+/// The name of the station from the list built into the binary, or its ID if it is not
+/// in the list.
+fn station_name(station: &StationId) -> Result<String, Box<dyn Error>> {
+    let stations = rjw_uktides::stations_from_reader(STATIONS_BAKED_BYTES)?;
+    Ok(stations
+        .into_iter()
+        .find(|s| &s.id == station)
+        .map_or_else(|| station.0.clone(), |s| s.name))
+}
+
+/// Print the stations added, removed (with "+" and "-") and changed ("~") since the list
+/// built into the binary, with the changes to each.
+fn display_stations_diff(diff: &StationsDiff, out: &mut impl Write) -> io::Result<()> {
+    if diff.is_empty() {
+        return writeln!(out, "No changes to the stations built into this program.");
+    }
+    for (mark, stations) in [('+', &diff.added), ('-', &diff.removed)] {
+        for s in stations {
+            writeln!(out, "{mark} {}\t{}\t{}", s.id, s.name, s.country)?;
+        }
+    }
+    for StationChange { old, new, fields } in &diff.changed {
+        let changes: Vec<_> = fields
+            .iter()
+            .map(|field| match *field {
+                "name" => format!("name {} -> {}", old.name, new.name),
+                "country" => format!("country {} -> {}", old.country, new.country),
+                "location" => format!(
+                    "location {:.4}, {:.4} -> {:.4}, {:.4}",
+                    old.location.latitude.0,
+                    old.location.longitude.0,
+                    new.location.latitude.0,
+                    new.location.longitude.0
+                ),
+                "continuous_heights_available" => format!(
+                    "continuous heights {} -> {}",
+                    yes_no(old.continuous_heights_available),
+                    yes_no(new.continuous_heights_available)
+                ),
+                other => format!("{other} changed"),
+            })
+            .collect();
+        writeln!(out, "~ {}\t{}\t{}", new.id, new.name, changes.join("; "))?;
+    }
+    Ok(())
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+fn display_stations(
+    s: Vec<Station>,
+    point: Option<&Coordinates>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    for Station {
+        id, name, location, ..
+    } in s
+    {
+        match point {
+            Some(point) => writeln!(
+                out,
+                "{}\t{}\t{:.1} km",
+                id,
+                name,
+                location.distance_km(point)
+            )?,
+            None => writeln!(out, "{}\t{}", id, name)?,
+        }
+    }
+    Ok(())
+}
+
+/// Fetch high and low tide times from the UK Hydrographic Office.
+///
+/// Data shown is that currently available from the web service used by
+/// the official EasyTide website.
+///
+/// Labels and messages in the human-readable output are in Welsh where the locale, from
+/// LC_ALL, LC_MESSAGES or LANG, is Welsh, and otherwise English.
+#[derive(Parser, Debug)]
+#[command(
+    name = "tides",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+struct Cli {
+    /// Show the underlying causes of any error.
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Write the output to this file instead of stdout, creating its directory if
+    /// necessary.
+    ///
+    /// The file is replaced only once the command has succeeded, and never left partly
+    /// written, so it can be served while a scheduled job regenerates it.
+    #[arg(short, long, global = true, value_name = "PATH")]
+    output: Option<PathBuf>,
+
+    #[command(flatten)]
+    cache_args: CacheArgs,
+
+    #[command(flatten)]
+    network_args: NetworkArgs,
+
+    #[command(flatten)]
+    style_args: StyleArgs,
+
+    /// Units for heights, in every output format.
+    #[arg(long, global = true, value_enum, default_value_t = Units::Metric)]
+    units: Units,
+
+    /// Time zone for times and dates, such as "UTC" or "Europe/Paris", including the
+    /// dates given with --date, --from and --to.
+    ///
+    /// Times in JSON, JSON Lines, CSV, TSV and iCalendar output are always in UTC.
+    #[arg(
+        long,
+        global = true,
+        value_name = "ZONE",
+        default_value = "Europe/London"
+    )]
+    tz: Tz,
+
+    /// Show times on the 12-hour clock, such as "5:42 pm", or the 24-hour clock.
+    ///
+    /// Day and month names are in the language of the locale, from LC_ALL, LC_TIME or
+    /// LANG, where it is Welsh, French, German, Spanish or Dutch, and otherwise English.
+    #[arg(long, global = true, value_enum, default_value_t = Clock::TwentyFour)]
+    clock: Clock,
+
+    #[command(flatten)]
+    tides_args: TidesArgs,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum Commands {
+    #[command(visible_alias = "list")]
+    ListStations(StationsArgs),
+    /// Show today's tides for a station.
+    Today(DayArgs),
+    /// Show tomorrow's tides for a station.
+    Tomorrow(DayArgs),
+    /// Show the tides for the next seven days for a station, grouped by day.
+    Week(DayArgs),
+    Next(NextArgs),
+    /// Show the current height and state of the tide for a station.
+    Now(StationArgs),
+    Prompt(prompt::PromptArgs),
+    Moon(MoonArgs),
+    Springs(SpringsArgs),
+    Table(table::TableArgs),
+    Compare(compare::CompareArgs),
+    Plan(plan::PlanArgs),
+    Nearest(NearestArgs),
+    Search(SearchArgs),
+    Id(IdArgs),
+    Open(open::OpenArgs),
+    Alias(alias::AliasArgs),
+    Archive(archive::ArchiveArgs),
+    DumpAll(dump::DumpAllArgs),
+    Qa(qa::QaArgs),
+    Doctor(doctor::DoctorArgs),
+    Watch(watch::WatchArgs),
+    RecordFixtures(fixtures::RecordFixturesArgs),
+    Serve(serve::ServeArgs),
+    Completions(completions::CompletionsArgs),
+    Man(man::ManArgs),
+    Schema(schema::SchemaArgs),
+    #[cfg(feature = "mqtt")]
+    Publish(mqtt::PublishArgs),
+    #[cfg(feature = "notify")]
+    Notify(notify::NotifyArgs),
+    #[cfg(feature = "tui")]
+    Tui(tui::TuiArgs),
+    #[cfg(feature = "chart")]
+    Chart(plot::ChartArgs),
+}
+
+impl Commands {
+    /// The name of the subcommand if it cannot write its output to a file with --output,
+    /// as it writes its own files, runs until it is stopped, or reports on its progress.
+    fn without_output(&self) -> Option<&'static str> {
+        match self {
+            Commands::Archive(_) => Some("archive"),
+            Commands::DumpAll(_) => Some("dump-all"),
+            Commands::Qa(_) => Some("qa"),
+            Commands::Doctor(_) => Some("doctor"),
+            Commands::Watch(_) => Some("watch"),
+            Commands::RecordFixtures(_) => Some("record-fixtures"),
+            Commands::Serve(_) => Some("serve"),
+            #[cfg(feature = "mqtt")]
+            Commands::Publish(_) => Some("publish"),
+            #[cfg(feature = "notify")]
+            Commands::Notify(_) => Some("notify"),
+            #[cfg(feature = "tui")]
+            Commands::Tui(_) => Some("tui"),
+            _ => None,
+        }
+    }
+}
+
+/// List all UK tidal stations supported by the UKHO.
+#[derive(Args, Clone, Debug)]
+struct StationsArgs {
+    /// Fetch the current list of tidal stations from the UKHO web service.
+    ///
+    /// If this argument is omitted, stations data built into the binary will be used.
+    #[arg(short, long)]
+    fetch: bool,
+
+    /// Read the stations from a saved EasyTide response, or from stdin if this is "-".
+    #[arg(long, value_name = "PATH", conflicts_with = "fetch")]
+    from_file: Option<PathBuf>,
+
+    /// Compare the current list of stations, or the one given with --from-file, with the
+    /// list built into the binary, printing the stations added ("+"), removed ("-") and
+    /// changed ("~").
+    ///
+    /// The filters apply to both lists.
+    #[arg(long, conflicts_with_all = ["format", "sort", "lat"])]
+    diff: bool,
+
+    /// How to print the stations.
+    #[arg(long, value_enum, default_value_t = StationsFormat::Text)]
+    format: StationsFormat,
+
+    /// Only list stations in this country, such as "Wales" or "Channel Islands".
+    #[arg(long)]
+    country: Option<String>,
+
+    /// Only list stations that provide continuous height measurements.
+    #[arg(long)]
+    continuous_heights_only: bool,
+
+    /// Only list stations whose names contain this text, ignoring case.
+    #[arg(long)]
+    name_contains: Option<String>,
+
+    /// How to order the stations.
+    #[arg(long, value_enum, default_value_t = StationsSort::Id)]
+    sort: StationsSort,
+
+    #[command(flatten)]
+    paging: Paging,
+
+    /// Latitude of a reference point, in decimal degrees, to show each station's distance.
+    #[arg(
+        long,
+        allow_negative_numbers = true,
+        requires = "lon",
+        required_if_eq("sort", "distance")
+    )]
+    lat: Option<f64>,
+
+    /// Longitude of a reference point, in decimal degrees (negative west of Greenwich).
+    #[arg(long, allow_negative_numbers = true, requires = "lat")]
+    lon: Option<f64>,
+}
+
+impl StationsArgs {
+    /// Remove the stations that do not match the filters.
+    fn filter(&self, stations: &mut Vec<Station>) {
+        let name_contains = self.name_contains.as_ref().map(|n| n.to_lowercase());
+        stations.retain(|s| {
+            self.country
+                .as_ref()
+                .is_none_or(|c| s.country.eq_ignore_ascii_case(c))
+                && (!self.continuous_heights_only || s.continuous_heights_available)
+                && name_contains
+                    .as_ref()
+                    .is_none_or(|n| s.name.to_lowercase().contains(n))
+        });
+    }
+
+    /// Order the stations, measuring distances from `point` when sorting by distance.
+    fn sort(&self, stations: &mut [Station], point: Option<&Coordinates>) {
+        match (self.sort, point) {
+            (StationsSort::Id, _) => stations.sort(),
+            (StationsSort::Name, _) => stations.sort_by_key(|s| s.name.to_lowercase()),
+            (StationsSort::Country, _) => {
+                stations.sort_by_key(|s| (s.country.clone(), s.name.to_lowercase()))
+            }
+            (StationsSort::Distance, Some(point)) => stations.sort_by(|a, b| {
+                a.location
+                    .distance_km(point)
+                    .total_cmp(&b.location.distance_km(point))
+            }),
+            // Clap requires a reference point to sort by distance.
+            (StationsSort::Distance, None) => stations.sort(),
+        }
+    }
+}
+
+/// Output formats for the list of stations.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum StationsFormat {
+    /// The ID and name of each station, separated by a tab.
+    Text,
+    /// CSV with a header row.
+    Csv,
+    /// CSV with a header row and the location of each station as WKT in a "wkt" column,
+    /// such as "POINT(-1.15 50.65)", for GIS software.
+    Wkt,
+    /// An SQLite database with a "stations" table, with columns id, name, country, lat,
+    /// lon and continuous_heights (1 or 0).
+    #[cfg(feature = "store")]
+    Sqlite,
+}
+
+/// Number of matches listed by the search subcommand when no limit is given.
+const SEARCH_LIMIT: usize = 10;
+
+/// Width of `--chart` when the terminal width is not given by `$COLUMNS`.
+const CHART_WIDTH: usize = 80;
+/// Height of `--chart` in rows, not including its labels.
+const CHART_ROWS: usize = 8;
+
+/// Options to list a page of results rather than all of them.
+#[derive(Args, Clone, Debug)]
+struct Paging {
+    /// Maximum number of stations to list.
+    #[arg(short = 'n', long, visible_alias = "count")]
+    limit: Option<usize>,
+
+    /// Number of stations to skip before listing.
+    #[arg(long, default_value_t = 0)]
+    offset: usize,
+}
+
+impl Paging {
+    /// The page of `items`, listing up to `default_limit` if no limit was given.
+    fn apply<T>(&self, items: Vec<T>, default_limit: Option<usize>) -> Vec<T> {
+        let limit = self.limit.or(default_limit).unwrap_or(usize::MAX);
+        items.into_iter().skip(self.offset).take(limit).collect()
+    }
+}
+
+/// Orderings for the list of stations.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum StationsSort {
+    Id,
+    Name,
+    /// By country, then by name within each country.
+    Country,
+    /// Nearest first to the point given by --lat and --lon.
+    Distance,
+}
+
+/// Arguments for subcommands that take only a station.
+#[derive(Args, Clone, Debug)]
+struct StationArgs {
+    /// ID of the desired tidal station.
+    #[arg(short, long)]
+    station: StationId,
+}
+
+/// Arguments for subcommands that show the tides grouped by day.
+#[derive(Args, Clone, Debug)]
+struct DayArgs {
+    /// ID of the desired tidal station.
+    #[arg(short, long)]
+    station: StationId,
+
+    /// List the lunar phases among the tides, and tag the days of spring and neap tides.
+    #[arg(long)]
+    with_moon: bool,
+}
+
+/// List the upcoming lunar phases in a station's predictions.
+#[derive(Args, Clone, Debug)]
+struct MoonArgs {
+    /// ID of the desired tidal station.
+    #[arg(short, long)]
+    station: StationId,
+
+    /// Show an ASCII picture of each phase.
+    #[arg(long)]
+    glyph: bool,
+}
+
+/// List upcoming spring and neap tide periods for a station, with their tidal ranges.
+///
+/// Springs, the largest tides, follow new and full moons by a day or two, and neaps, the
+/// smallest, follow the quarter moons. Periods after the end of the predictions are
+/// estimated from the length of the lunar cycle, so have no ranges.
+#[derive(Args, Clone, Debug)]
+struct SpringsArgs {
+    /// ID of the desired tidal station.
+    #[arg(short, long)]
+    station: StationId,
+
+    /// Number of weeks ahead to list.
+    #[arg(long, default_value_t = 4)]
+    weeks: u32,
+}
+
+/// List the stations nearest to a location, with their distances.
+#[derive(Args, Clone, Debug)]
+struct NearestArgs {
+    /// Latitude of the location, in decimal degrees.
+    #[arg(long, allow_negative_numbers = true, requires = "lon")]
+    #[cfg_attr(
+        feature = "postcode",
+        arg(required_unless_present_any = ["postcode", "gridref"])
+    )]
+    #[cfg_attr(not(feature = "postcode"), arg(required_unless_present = "gridref"))]
+    lat: Option<f64>,
+
+    /// Longitude of the location, in decimal degrees (negative west of Greenwich).
+    #[arg(long, allow_negative_numbers = true, requires = "lat")]
+    lon: Option<f64>,
+
+    /// UK postcode of the location, looked up with postcodes.io.
+    #[cfg(feature = "postcode")]
+    #[arg(long, conflicts_with_all = ["lat", "lon"])]
+    postcode: Option<String>,
+
+    /// Ordnance Survey National Grid reference of the location, such as "SZ 59 84".
+    #[arg(long, conflicts_with_all = ["lat", "lon"])]
+    #[cfg_attr(feature = "postcode", arg(conflicts_with = "postcode"))]
+    gridref: Option<GridReference>,
+
+    /// Number of stations to list.
+    #[arg(short = 'n', long, default_value_t = 5)]
+    count: usize,
+
+    /// Search the current list of tidal stations from the UKHO web service, rather than
+    /// the one built into the binary.
+    #[arg(short, long)]
+    fetch: bool,
+}
+
+/// Search for stations by name, listing the best matches first.
+///
+/// Matching is forgiving of case, punctuation and small misspellings, so "st helier"
+/// finds "ST. HELIER".
+#[derive(Args, Clone, Debug)]
+struct SearchArgs {
+    /// All or part of the station name.
+    query: String,
+
+    /// Latitude of a reference point, in decimal degrees, to show each station's distance.
+    #[arg(long, allow_negative_numbers = true, requires = "lon")]
+    lat: Option<f64>,
+
+    /// Longitude of a reference point, in decimal degrees (negative west of Greenwich).
+    #[arg(long, allow_negative_numbers = true, requires = "lat")]
+    lon: Option<f64>,
+
+    #[command(flatten)]
+    paging: Paging,
+
+    /// Search the current list of tidal stations from the UKHO web service, rather than
+    /// the one built into the binary.
+    #[arg(short, long)]
+    fetch: bool,
+}
+
+/// Print the ID of the station with the given name, for use in other commands.
+///
+/// Exits with an error listing the closest matches if the name does not identify exactly
+/// one station, so it can be used safely in command substitution:
+/// `tides -s $(tides id ramsgate)`.
+#[derive(Args, Clone, Debug)]
+struct IdArgs {
+    /// All or part of the station name.
+    name: String,
+}
+
+impl NearestArgs {
+    /// The latitude and longitude of the location, looking up the postcode or converting
+    /// the grid reference if given.
+    fn location(&self) -> Result<(f64, f64), Box<dyn Error>> {
+        if let Some(gridref) = &self.gridref {
+            let coordinates = gridref.to_coordinates();
+            return Ok((coordinates.latitude.0, coordinates.longitude.0));
+        }
+        #[cfg(feature = "postcode")]
+        if let Some(postcode) = &self.postcode {
+            return postcode::lookup(postcode);
+        }
+        // Clap requires both coordinates when there is no postcode or grid reference.
+        self.lat
+            .zip(self.lon)
+            .ok_or_else(|| "No location given.".into())
+    }
+}
+
+/// Show the next high or low tide for a station and the time until it.
+#[derive(Args, Clone, Debug)]
+struct NextArgs {
+    /// ID of the desired tidal station.
+    #[arg(short, long)]
+    station: StationId,
+
+    /// Show the next tide of this type, rather than whichever is next.
+    #[arg(long = "type", value_enum)]
+    event_type: Option<NextType>,
+
+    /// Exit successfully if the tide is within this time, such as "30m", and with status 6
+    /// otherwise, printing nothing unless --verbose is given.
+    #[arg(long, value_parser = duration::parse_duration)]
+    within: Option<chrono::Duration>,
+}
+
+/// The type of tide shown by the next subcommand.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum NextType {
+    High,
+    Low,
+}
+
+impl NextType {
+    /// The event types matching the chosen type of tide, or both if none is chosen.
+    fn event_types(next_type: Option<NextType>) -> &'static [TidalEventType] {
+        match next_type {
+            Some(NextType::High) => &[TidalEventType::HighWater],
+            Some(NextType::Low) => &[TidalEventType::LowWater],
+            None => &[TidalEventType::HighWater, TidalEventType::LowWater],
+        }
+    }
+}
+
+/// Display tide information for one or more stations.
+#[derive(Args, Clone, Debug)]
+struct TidesArgs {
+    /// ID of the desired tidal station.
+    ///
+    /// Give this more than once, or separate IDs with commas, to show the tides at several
+    /// stations in one table, labelled with the station ID. Without a station here or in
+    /// the configuration file, a station is chosen interactively when run in a terminal.
+    #[arg(short, long = "station", value_delimiter = ',')]
+    stations: Vec<StationId>,
+
+    /// Read the predictions from a saved EasyTide response, or from stdin if this is "-",
+    /// instead of fetching them.
+    ///
+    /// The station given with --station, if any, is used to label the predictions.
+    #[arg(long, value_name = "PATH")]
+    from_file: Option<PathBuf>,
+
+    /// Read the predictions saved as <ID>.json files in a directory, as written by `tides
+    /// dump-all`, instead of fetching them.
+    ///
+    /// With --station, only those stations' files are read. Files that cannot be read are
+    /// reported and skipped.
+    #[arg(long, value_name = "DIR", conflicts_with = "from_file")]
+    from_dir: Option<PathBuf>,
+
+    /// Show only the tides on this date, such as 2025-08-20.
+    #[arg(long, conflicts_with_all = ["from", "to", "days"])]
+    date: Option<NaiveDate>,
+
+    /// Show only the tides on or after this date.
+    #[arg(long)]
+    from: Option<NaiveDate>,
+
+    /// Show only the tides on or before this date.
+    #[arg(long)]
+    to: Option<NaiveDate>,
+
+    /// Show only the tides on this many days, starting today or on the --from date.
+    #[arg(long, conflicts_with = "to", value_parser = clap::value_parser!(u64).range(1..))]
+    days: Option<u64>,
+
+    /// How to print the predictions.
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Print the predictions as JSON; shorthand for --format json.
+    ///
+    /// The output is an object with the station ID as "station"; "events", the high and
+    /// low tides, each with "time", "type" ("high" or "low"), "height", and booleans
+    /// "approximate_time" and "approximate_height"; "heights", the half-hourly heights,
+    /// each with "time" and "height"; "lunar_phases", each with "time" and "phase"
+    /// ("new_moon", "first_quarter", "full_moon" or "last_quarter"); and "note", the note
+    /// attached to the predictions; and "meta", describing where and when the predictions
+    /// were fetched, with "fetched_at" (null for --from-file), "source" (the URL or file),
+    /// "station", "station_name" and "version", the version of this program. Times are
+    /// RFC 3339 in UTC and heights are in metres above chart datum, or feet with --units
+    /// imperial. `tides schema` prints the JSON Schema of the output.
+    #[arg(long, conflicts_with = "format")]
+    json: bool,
+
+    /// Print each high and low tide as a JSON object on its own line; shorthand for
+    /// --format jsonl.
+    ///
+    /// Each object has the same fields as the "events" in the --json output, along with
+    /// the station ID as "station" and the "meta" object from the --json output.
+    #[arg(long, conflicts_with_all = ["format", "json"])]
+    jsonl: bool,
+
+    /// Print each high and low tide using a template, such as
+    /// "{type} {height:.1} m at {time:%H:%M}, {countdown}".
+    ///
+    /// The placeholders are {time}, which takes a strftime format such as {time:%H:%M};
+    /// {type}; {height}, which takes a number of decimal places such as {height:.1};
+    /// {station}; and {countdown}, the time until the tide, such as "in 2 h 10 m", or
+    /// since it, such as "2 h 10 m ago". Times are in the --tz time zone. Write literal
+    /// braces as {{ and }}.
+    #[arg(long, value_parser = Template::parse, conflicts_with_all = ["format", "json", "jsonl"])]
+    template: Option<Template>,
+
+    /// Columns to print with --format text, csv or tsv, separated by commas.
+    ///
+    /// Columns are always printed in the order station, time, type, height,
+    /// approximate_time, approximate_height, whatever order they are given in. Plain text
+    /// output is tab-separated without a header row.
+    #[arg(long, value_enum, value_delimiter = ',', conflicts_with_all = ["json", "jsonl", "template"])]
+    fields: Vec<Field>,
+
+    /// Draw a chart of the height of the tide over the predictions, marking high (H) and
+    /// low (L) tides and the current time, instead of listing the tides.
+    #[arg(long, conflicts_with_all = ["format", "json", "jsonl", "template", "fields"])]
+    chart: bool,
+
+    /// Print the tides under a heading for each day, with the weekday and any lunar
+    /// phase, rather than the full date and time on each line.
+    #[arg(long, value_enum, conflicts_with_all = ["json", "jsonl", "template", "fields", "chart"])]
+    group_by: Option<GroupBy>,
+}
+
+/// The first and last dates to show, either of which may be open.
+type DateRange = (Option<NaiveDate>, Option<NaiveDate>);
+
+impl TidesArgs {
+    /// The first and last dates to show, either of which may be open, given today's date.
+    ///
+    /// Fails if --days runs past the last date that can be represented.
+    fn date_range(&self, today: NaiveDate) -> Result<DateRange, Box<dyn Error>> {
+        if let Some(date) = self.date {
+            return Ok((Some(date), Some(date)));
+        }
+        match self.days {
+            Some(days) => {
+                let from = self.from.unwrap_or(today);
+                let to = from
+                    .checked_add_days(Days::new(days - 1))
+                    .ok_or_else(|| format!("--days {days} is too many days after {from}."))?;
+                Ok((Some(from), Some(to)))
+            }
+            None => Ok((self.from, self.to)),
+        }
+    }
+
+    /// The output format, taking the shorthand flags into account.
+    fn format(&self) -> Format {
+        if self.json {
+            Format::Json
+        } else if self.jsonl {
+            Format::Jsonl
+        } else {
+            self.format
+        }
+    }
+}
+
+/// Ways of grouping the tides in plain text output.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum GroupBy {
+    /// A heading for each day, with the time of each tide beneath it.
+    Day,
+}
+
+/// Output formats for tide predictions.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Format {
+    /// The time and type of each high and low tide.
+    Text,
+    /// A JSON document; see --json.
+    Json,
+    /// A JSON object per high and low tide; see --jsonl.
+    Jsonl,
+    /// CSV with a header row, with columns time, type, height, approximate_time and
+    /// approximate_height.
+    Csv,
+    /// Tab-separated values with a header row, with the same columns as CSV.
+    Tsv,
+    /// An iCalendar file of the upcoming high and low tides, for importing into a
+    /// calendar.
+    Ics,
+}
@@ -0,0 +1,228 @@
+//! The manual page, generated in roff format from the command-line definition.
+
+use std::error::Error;
+use std::fmt::Write;
+
+use clap::builder::StyledStr;
+use clap::{Arg, Args, Command};
+
+use crate::config;
+use crate::error::{EXIT_FAILURE, EXIT_NETWORK, EXIT_NO_TIDE, EXIT_PARSE, EXIT_UNKNOWN_STATION};
+use crate::fetch::BASE_URL_VAR;
+
+/// Print the manual page, in roff format.
+///
+/// For example, install it with `tides man > /usr/share/man/man1/tides.1`, or read it
+/// with `tides man | man -l -`.
+#[derive(Args, Clone, Debug)]
+pub struct ManArgs {}
+
+pub fn man(
+    _args: ManArgs,
+    mut command: Command,
+    out: &mut impl std::io::Write,
+) -> Result<(), Box<dyn Error>> {
+    // Building propagates global options, such as --verbose, to the subcommands.
+    command.build();
+    write!(out, "{}", page(&command)?)?;
+    Ok(())
+}
+
+fn page(command: &Command) -> Result<String, std::fmt::Error> {
+    let name = command.get_name();
+    let mut s = String::new();
+    writeln!(
+        s,
+        ".TH {} 1 \"\" \"{name} {}\" \"User Commands\"",
+        name.to_uppercase(),
+        env!("CARGO_PKG_VERSION")
+    )?;
+
+    writeln!(s, ".SH NAME")?;
+    let about = text(command.get_about());
+    writeln!(s, "{name} \\- {}", escape(about.trim_end_matches('.')))?;
+
+    writeln!(s, ".SH SYNOPSIS")?;
+    writeln!(s, ".B {name}\n[\\fIOPTIONS\\fR]\n.br")?;
+    writeln!(s, ".B {name}\n\\fICOMMAND\\fR [\\fIOPTIONS\\fR]")?;
+
+    writeln!(s, ".SH DESCRIPTION")?;
+    paragraphs(&mut s, &text(command.get_long_about()))?;
+
+    writeln!(s, ".SH OPTIONS")?;
+    for arg in command.get_arguments().filter(|a| !a.is_hide_set()) {
+        option(&mut s, arg)?;
+    }
+
+    writeln!(s, ".SH COMMANDS")?;
+    for subcommand in visible_subcommands(command) {
+        section(&mut s, subcommand, name)?;
+    }
+
+    writeln!(s, ".SH CONFIGURATION")?;
+    paragraphs(
+        &mut s,
+        "Defaults for command-line arguments can be set in a TOML configuration file. \
+         Arguments given on the command line take precedence. The keys are:",
+    )?;
+    for (key, description) in config::KEYS {
+        writeln!(s, ".TP\n\\fB{}\\fR\n{}", escape(key), escape(description))?;
+    }
+
+    writeln!(s, ".SH ENVIRONMENT")?;
+    writeln!(s, ".TP\n\\fB{BASE_URL_VAR}\\fR")?;
+    writeln!(
+        s,
+        "Address of the service to send requests to instead of EasyTide, such as a mirror \
+         or a mock server, overriding \\fBbase_url\\fR in the configuration file."
+    )?;
+
+    writeln!(s, ".SH FILES")?;
+    writeln!(s, ".TP\n\\fI$XDG_CONFIG_HOME/rjw\\-uktides/config.toml\\fR")?;
+    writeln!(
+        s,
+        "The configuration file, in \\fI~/.config\\fR if \\fBXDG_CONFIG_HOME\\fR is not set."
+    )?;
+    writeln!(s, ".TP\n\\fI$XDG_CACHE_HOME/rjw\\-uktides\\fR")?;
+    writeln!(
+        s,
+        "Cached responses, in \\fI~/.cache\\fR if \\fBXDG_CACHE_HOME\\fR is not set, \
+         unless \\fBcache_dir\\fR is configured."
+    )?;
+
+    writeln!(s, ".SH EXIT STATUS")?;
+    let statuses = [
+        (0, "Success."),
+        (EXIT_FAILURE, "Failure not covered by another status."),
+        (2, "The command-line arguments are invalid."),
+        (
+            EXIT_NETWORK,
+            "A server could not be reached or responded with an error.",
+        ),
+        (EXIT_PARSE, "A response could not be understood."),
+        (
+            EXIT_UNKNOWN_STATION,
+            "The station does not exist or cannot be identified by name.",
+        ),
+        (
+            EXIT_NO_TIDE,
+            "No tide is due within the time given to next --within.",
+        ),
+    ];
+    for (code, description) in statuses {
+        writeln!(s, ".TP\n\\fB{code}\\fR\n{}", escape(description))?;
+    }
+    Ok(s)
+}
+
+/// Describe the subcommand and its options, followed by its own subcommands.
+fn section(s: &mut String, command: &Command, parent: &str) -> Result<(), std::fmt::Error> {
+    let path = format!("{parent} {}", command.get_name());
+    writeln!(s, ".SS \"{}\"", escape(&path))?;
+    let about = command.get_long_about().or(command.get_about());
+    paragraphs(s, &text(about))?;
+    // Global options and help are described once, at the top of the page.
+    let arguments = command
+        .get_arguments()
+        .filter(|a| !a.is_hide_set() && !a.is_global_set() && a.get_id() != "help");
+    for arg in arguments {
+        option(s, arg)?;
+    }
+    for subcommand in visible_subcommands(command) {
+        section(s, subcommand, &path)?;
+    }
+    Ok(())
+}
+
+fn option(s: &mut String, arg: &Arg) -> Result<(), std::fmt::Error> {
+    let value_name = arg
+        .get_value_names()
+        .and_then(|names| names.first())
+        .map_or_else(|| arg.get_id().as_str().to_uppercase(), |n| n.to_string());
+    let takes_value = arg.get_num_args().is_some_and(|n| n.takes_values());
+    let mut tag: Vec<String> = arg
+        .get_short_and_visible_aliases()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|c| format!("\\fB\\-{c}\\fR"))
+        .collect();
+    tag.extend(
+        arg.get_long_and_visible_aliases()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|l| format!("\\fB\\-\\-{}\\fR", escape(l))),
+    );
+    let value = format!("\\fI{}\\fR", escape(&value_name));
+    let tag = match (tag.is_empty(), takes_value) {
+        (true, _) => value,
+        (false, true) => format!("{} {value}", tag.join(", ")),
+        (false, false) => tag.join(", "),
+    };
+    writeln!(s, ".TP\n{tag}")?;
+
+    let help = text(arg.get_long_help().or(arg.get_help()));
+    for (i, paragraph) in help.split("\n\n").enumerate() {
+        if i > 0 {
+            writeln!(s, ".IP")?;
+        }
+        writeln!(s, "{}", escape(paragraph.trim()))?;
+    }
+    let defaults: Vec<_> = arg
+        .get_default_values()
+        .iter()
+        .map(|v| v.to_string_lossy())
+        .collect();
+    if takes_value && !defaults.is_empty() && !arg.is_hide_default_value_set() {
+        writeln!(s, ".IP\nDefault: {}.", escape(&defaults.join(", ")))?;
+    }
+    let values: Vec<_> = arg
+        .get_possible_values()
+        .into_iter()
+        .filter(|v| !v.is_hide_set())
+        .collect();
+    if takes_value && !values.is_empty() {
+        writeln!(s, ".IP\nPossible values:\n.RS")?;
+        for v in values {
+            writeln!(s, ".TP\n\\fB{}\\fR", escape(v.get_name()))?;
+            if let Some(help) = v.get_help() {
+                writeln!(s, "{}", escape(&help.to_string()))?;
+            }
+        }
+        writeln!(s, ".RE")?;
+    }
+    Ok(())
+}
+
+fn visible_subcommands(command: &Command) -> impl Iterator<Item = &Command> {
+    command
+        .get_subcommands()
+        .filter(|c| !c.is_hide_set() && c.get_name() != "help")
+}
+
+fn text(text: Option<&StyledStr>) -> String {
+    text.map(ToString::to_string).unwrap_or_default()
+}
+
+/// Write the text as roff paragraphs, which are separated by blank lines.
+fn paragraphs(s: &mut String, text: &str) -> Result<(), std::fmt::Error> {
+    for paragraph in text.split("\n\n").filter(|p| !p.trim().is_empty()) {
+        writeln!(s, ".PP\n{}", escape(paragraph.trim()))?;
+    }
+    Ok(())
+}
+
+/// Escape the text for roff, so that backslashes and hyphens are printed as written and
+/// lines are not taken as requests.
+fn escape(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{line}")
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io;
+use std::net::TcpStream;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+
+use rjw_uktides::{Client, StationId, TidalEventType, TidePredictions};
+
+use crate::serve::{read_request_path, write_response};
+
+/// Read a single HTTP request from the stream and write the metrics in response.
+pub fn respond_metrics(stream: TcpStream, metrics: &StationMetrics) -> io::Result<()> {
+    let (status, body) = match read_request_path(&stream)?.as_str() {
+        "/metrics" => ("200 OK", metrics.render()),
+        _ => ("404 Not Found", "Not Found".to_owned()),
+    };
+    write_response(stream, status, "text/plain; version=0.0.4", &body)
+}
+
+/// Tide predictions for each served station, refetched by [`StationMetrics::refresh`]
+/// when they become stale, along with counts of the fetches made.
+pub struct StationMetrics {
+    client: Client,
+    stations: Vec<StationId>,
+    refresh: Duration,
+    state: RwLock<MetricsState>,
+}
+
+/// The predictions and counts, which are locked only to read or update them, never while
+/// fetching.
+#[derive(Default)]
+struct MetricsState {
+    predictions: HashMap<StationId, (Instant, TidePredictions)>,
+    fetches: HashMap<StationId, u64>,
+    errors: HashMap<StationId, u64>,
+}
+
+impl StationMetrics {
+    pub fn new(client: Client, stations: Vec<StationId>, refresh: Duration) -> Self {
+        Self {
+            client,
+            stations,
+            refresh,
+            state: RwLock::default(),
+        }
+    }
+
+    /// Refetch stale predictions, keeping the previous predictions if the fetch fails.
+    ///
+    /// The metrics can be rendered while this is fetching, from the predictions already
+    /// fetched.
+    pub fn refresh(&self) {
+        let stale: Vec<StationId> = {
+            let state = self.state.read().expect("Metrics lock poisoned.");
+            self.stations
+                .iter()
+                .filter(|station| {
+                    state
+                        .predictions
+                        .get(*station)
+                        .is_none_or(|(fetched, _)| fetched.elapsed() >= self.refresh)
+                })
+                .cloned()
+                .collect()
+        };
+        for station in stale {
+            let result = self.client.fetch_tides(&station);
+            let mut state = self.state.write().expect("Metrics lock poisoned.");
+            *state.fetches.entry(station.clone()).or_default() += 1;
+            match result {
+                Ok(tides) => {
+                    state.predictions.insert(station, (Instant::now(), tides));
+                }
+                Err(e) => {
+                    eprintln!("Failed to fetch tides for station {station}: {e}");
+                    *state.errors.entry(station).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    /// Render the metrics in the Prometheus text format.
+    fn render(&self) -> String {
+        let state = self.state.read().expect("Metrics lock poisoned.");
+        let now = Utc::now();
+        let mut out = String::new();
+        let event_types = [
+            (TidalEventType::HighWater, "high"),
+            (TidalEventType::LowWater, "low"),
+        ];
+
+        metric_header(
+            &mut out,
+            "uktides_seconds_until_next_tide",
+            "gauge",
+            "Seconds until the next high or low tide.",
+        );
+        for (station, (_, tides)) in self.served(&state) {
+            for (event_type, label) in event_types {
+                if let Some(event) = tides.next_event(now, event_type) {
+                    let seconds = (event.date_time - now).num_seconds();
+                    let _ = writeln!(
+                        out,
+                        "uktides_seconds_until_next_tide{{station=\"{}\",type=\"{label}\"}} {seconds}",
+                        escape_label(&station.0)
+                    );
+                }
+            }
+        }
+
+        metric_header(
+            &mut out,
+            "uktides_next_tide_height_metres",
+            "gauge",
+            "Predicted height of the next high or low tide, in metres above chart datum.",
+        );
+        for (station, (_, tides)) in self.served(&state) {
+            for (event_type, label) in event_types {
+                if let Some(event) = tides.next_event(now, event_type) {
+                    let _ = writeln!(
+                        out,
+                        "uktides_next_tide_height_metres{{station=\"{}\",type=\"{label}\"}} {}",
+                        escape_label(&station.0),
+                        event.height.0
+                    );
+                }
+            }
+        }
+
+        metric_header(
+            &mut out,
+            "uktides_height_metres",
+            "gauge",
+            "Current tide height interpolated from the predictions, in metres above chart datum.",
+        );
+        for (station, (_, tides)) in self.served(&state) {
+            if let Some(height) = tides.height_at(now) {
+                let _ = writeln!(
+                    out,
+                    "uktides_height_metres{{station=\"{}\"}} {}",
+                    escape_label(&station.0),
+                    height.0
+                );
+            }
+        }
+
+        for (name, help, counts) in [
+            (
+                "uktides_fetches_total",
+                "Number of attempts to fetch tide predictions.",
+                &state.fetches,
+            ),
+            (
+                "uktides_fetch_errors_total",
+                "Number of failed attempts to fetch tide predictions.",
+                &state.errors,
+            ),
+        ] {
+            metric_header(&mut out, name, "counter", help);
+            for station in &self.stations {
+                let count = counts.get(station).copied().unwrap_or_default();
+                let _ = writeln!(
+                    out,
+                    "{name}{{station=\"{}\"}} {count}",
+                    escape_label(&station.0)
+                );
+            }
+        }
+        out
+    }
+
+    /// The stations for which predictions have been fetched, in the order given.
+    fn served<'a>(
+        &'a self,
+        state: &'a MetricsState,
+    ) -> impl Iterator<Item = (&'a StationId, &'a (Instant, TidePredictions))> {
+        self.stations
+            .iter()
+            .filter_map(|s| state.predictions.get(s).map(|p| (s, p)))
+    }
+}
+
+fn metric_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} {kind}");
+}
+
+/// Escape a Prometheus label value.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,209 @@
+//! Publishing the next tides to an MQTT broker with rumqttc.
+//!
+//! Messages are retained and published with QoS 1, each connection waiting for the broker
+//! to acknowledge them before disconnecting.
+
+use std::error::Error;
+use std::thread;
+use std::time::Duration;
+
+use clap::Args;
+use rumqttc::{Event, MqttOptions, Outgoing, Packet, QoS, Transport};
+use serde_json::json;
+
+use rjw_uktides::{Client, StationId};
+
+use crate::api::next_tides;
+use crate::STATIONS_BAKED_BYTES;
+
+/// Periodically publish the next tides for the given stations to an MQTT broker,
+/// with Home Assistant discovery messages.
+#[derive(Args, Clone, Debug)]
+pub struct PublishArgs {
+    /// ID of a tidal station to publish; may be given more than once.
+    #[arg(short, long = "station", required = true)]
+    stations: Vec<StationId>,
+
+    /// Address of the MQTT broker, as a host name and optional port. The port defaults to
+    /// 1883, or 8883 with --tls.
+    #[arg(long, default_value = "localhost")]
+    broker: String,
+
+    /// Connect to the broker with TLS, verified against the platform's root certificates.
+    #[arg(long)]
+    tls: bool,
+
+    /// Client identifier sent to the broker.
+    #[arg(long, default_value = "uktides")]
+    client_id: String,
+
+    /// Username with which to connect to the broker.
+    #[arg(long, requires = "password")]
+    username: Option<String>,
+
+    /// Password with which to connect to the broker.
+    #[arg(long, requires = "username")]
+    password: Option<String>,
+
+    /// Prefix of the topics to which station state is published.
+    #[arg(long, default_value = "uktides")]
+    topic_prefix: String,
+
+    /// Prefix of the Home Assistant discovery topics.
+    #[arg(long, default_value = "homeassistant")]
+    discovery_prefix: String,
+
+    /// Minutes between publishing.
+    #[arg(long, default_value_t = 15)]
+    interval_minutes: u64,
+}
+
+/// The sensors announced for each station: the key in the state JSON, the sensor name,
+/// and whether it is a timestamp (otherwise a height in metres).
+const SENSORS: [(&str, &str, bool); 5] = [
+    ("next_high_time", "Next high tide", true),
+    ("next_high_height", "Next high tide height", false),
+    ("next_low_time", "Next low tide", true),
+    ("next_low_height", "Next low tide height", false),
+    ("height", "Tide height", false),
+];
+
+/// Publish the stations' discovery and state messages every interval until the process
+/// is killed.
+pub fn publish(args: PublishArgs, client: Client) -> Result<(), Box<dyn Error>> {
+    let names = rjw_uktides::stations_from_reader(STATIONS_BAKED_BYTES)?;
+    let mut announced = false;
+    loop {
+        let mut messages = Vec::new();
+        if !announced {
+            for station in &args.stations {
+                let name = names
+                    .iter()
+                    .find(|s| &s.id == station)
+                    .map_or_else(|| station.0.clone(), |s| s.name.clone());
+                messages.extend(discovery_messages(&args, station, &name));
+            }
+        }
+        for station in &args.stations {
+            match client.fetch_tides(station) {
+                Ok(tides) => messages.push((
+                    state_topic(&args.topic_prefix, station),
+                    next_tides(&tides).to_string(),
+                )),
+                Err(e) => eprintln!("Failed to fetch tides for station {station}: {e}"),
+            }
+        }
+        match send(&args, &messages) {
+            Ok(()) => announced = true,
+            Err(e) => eprintln!("Failed to publish to {}: {e}", args.broker),
+        }
+        thread::sleep(Duration::from_secs(args.interval_minutes * 60));
+    }
+}
+
+fn state_topic(prefix: &str, station: &StationId) -> String {
+    format!("{prefix}/{station}/state")
+}
+
+/// Home Assistant discovery messages for each of the station's sensors.
+fn discovery_messages(
+    args: &PublishArgs,
+    station: &StationId,
+    name: &str,
+) -> Vec<(String, String)> {
+    SENSORS
+        .iter()
+        .map(|(key, sensor_name, is_time)| {
+            let unique_id = format!("uktides_{station}_{key}");
+            let mut config = json!({
+                "name": sensor_name,
+                "unique_id": unique_id,
+                "state_topic": state_topic(&args.topic_prefix, station),
+                "value_template": format!("{{{{ value_json.{key} }}}}"),
+                "device": {
+                    "identifiers": [format!("uktides_{station}")],
+                    "name": format!("{name} tides"),
+                    "manufacturer": "UK Hydrographic Office",
+                    "configuration_url": station.easytide_url(),
+                },
+            });
+            if *is_time {
+                config["device_class"] = json!("timestamp");
+            } else {
+                config["device_class"] = json!("distance");
+                config["unit_of_measurement"] = json!("m");
+            }
+            let topic = format!("{}/sensor/{unique_id}/config", args.discovery_prefix);
+            (topic, config.to_string())
+        })
+        .collect()
+}
+
+/// How long to wait for the broker to respond before giving up.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connect to the broker, publish each retained message, and disconnect once the broker
+/// has acknowledged them all.
+fn send(args: &PublishArgs, messages: &[(String, String)]) -> Result<(), Box<dyn Error>> {
+    let (host, port) = broker_address(&args.broker, args.tls)?;
+    let mut options = MqttOptions::new(&args.client_id, host, port);
+    options.set_keep_alive(Duration::from_secs(60));
+    if let (Some(username), Some(password)) = (&args.username, &args.password) {
+        options.set_credentials(username, password);
+    }
+    if args.tls {
+        options.set_transport(Transport::tls_with_default_config());
+    }
+
+    // Room for every message and the disconnect, so that queueing them does not block.
+    let (client, mut connection) = rumqttc::Client::new(options, messages.len() + 1);
+    for (topic, message) in messages {
+        client.publish(topic, QoS::AtLeastOnce, true, message.as_bytes())?;
+    }
+    let mut unacknowledged = messages.len();
+    if unacknowledged == 0 {
+        client.disconnect()?;
+    }
+    loop {
+        let event = connection
+            .recv_timeout(RESPONSE_TIMEOUT)
+            .map_err(|_| "timed out waiting for the broker")??;
+        match event {
+            Event::Incoming(Packet::PubAck(_)) => {
+                unacknowledged -= 1;
+                if unacknowledged == 0 {
+                    client.disconnect()?;
+                }
+            }
+            Event::Outgoing(Outgoing::Disconnect) => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+/// The host and port of the broker address, with the default MQTT port if none is given.
+fn broker_address(broker: &str, tls: bool) -> Result<(&str, u16), String> {
+    match broker.rsplit_once(':') {
+        Some((host, port)) => port
+            .parse()
+            .map(|port| (host, port))
+            .map_err(|_| format!("Invalid port {port:?} in broker address {broker:?}")),
+        None => Ok((broker, if tls { 8883 } else { 1883 })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_the_broker_port() {
+        assert_eq!(broker_address("localhost", false), Ok(("localhost", 1883)));
+        assert_eq!(broker_address("localhost", true), Ok(("localhost", 8883)));
+        assert_eq!(
+            broker_address("mqtt.example.com:1884", true),
+            Ok(("mqtt.example.com", 1884))
+        );
+        assert!(broker_address("localhost:mqtt", false).is_err());
+    }
+}
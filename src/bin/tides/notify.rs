@@ -0,0 +1,104 @@
+//! Desktop notifications shortly before tides, sent with notify-rust through the
+//! platform's notification service.
+
+use std::error::Error;
+use std::thread;
+
+use chrono::{DateTime, Duration, Utc};
+use clap::Args;
+use notify_rust::Notification;
+
+use rjw_uktides::{StationId, TidalEvent};
+
+use crate::duration::parse_duration;
+use crate::fetch::Fetcher;
+use crate::locale;
+use crate::units::Units;
+use crate::zone::Times;
+use crate::NextType;
+
+/// Wait until shortly before the next tide and show a desktop notification.
+#[derive(Args, Clone, Debug)]
+pub struct NotifyArgs {
+    /// ID of the tidal station.
+    #[arg(short, long)]
+    station: StationId,
+
+    /// How long before the tide to notify, such as "45m" or "1h30m".
+    #[arg(long, default_value = "30m", value_parser = parse_duration)]
+    before: Duration,
+
+    /// Notify only for tides of this type, rather than both.
+    #[arg(long = "type", value_enum)]
+    event_type: Option<NextType>,
+
+    /// Keep running, notifying before every matching tide, rather than exiting after one.
+    #[arg(long)]
+    repeat: bool,
+}
+
+pub fn notify(
+    args: NotifyArgs,
+    fetcher: &Fetcher,
+    units: Units,
+    times: Times,
+) -> Result<(), Box<dyn Error>> {
+    let name = crate::station_name(&args.station)?;
+    let event_types = NextType::event_types(args.event_type);
+    let mut notified: Option<DateTime<Utc>> = None;
+    loop {
+        // Fetched afresh each time, as the wait may outlast the predictions.
+        let mut tides = crate::fetch_tides(fetcher, &args.station)?;
+        units.convert(&mut tides);
+        let after = notified.map_or_else(Utc::now, |t| t.max(Utc::now()));
+        let event = event_types
+            .iter()
+            .filter_map(|t| tides.next_event(after, *t))
+            .min_by_key(|e| e.date_time)
+            .ok_or("No upcoming tides in the predictions.")?;
+        if let Ok(wait) = (event.date_time - args.before - Utc::now()).to_std() {
+            thread::sleep(wait);
+        }
+        send(&name, event, units, times)?;
+        notified = Some(event.date_time);
+        if !args.repeat {
+            return Ok(());
+        }
+    }
+}
+
+/// Show a notification for the upcoming event.
+fn send(
+    station_name: &str,
+    event: &TidalEvent,
+    units: Units,
+    times: Times,
+) -> Result<(), Box<dyn Error>> {
+    let phrases = locale::phrases();
+    let summary = format!(
+        "{} {} {station_name}",
+        phrases.event(event.event_type),
+        phrases.at_station
+    );
+    let body = format!(
+        "{:.1} {} {} {}, {} {}",
+        event.height.0,
+        units.symbol(),
+        phrases.at,
+        times.format(event.date_time, "%H:%M %Z"),
+        phrases.within,
+        crate::template::countdown(Utc::now(), event.date_time)
+    );
+    show(&summary, &body)
+}
+
+/// Show a desktop notification with the summary as its title.
+pub fn show(summary: &str, body: &str) -> Result<(), Box<dyn Error>> {
+    Notification::new()
+        .appname("tides")
+        .summary(summary)
+        .body(body)
+        .show()
+        .map_err(|e| format!("Could not show a notification: {e}"))?;
+    Ok(())
+}
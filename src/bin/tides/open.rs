@@ -0,0 +1,67 @@
+//! Opening a station's EasyTide page, or its location on a map, in the web browser.
+
+use std::error::Error;
+use std::io::Write;
+use std::process::Command;
+
+use clap::Args;
+
+use rjw_uktides::{Station, StationId};
+
+use crate::error::CliError;
+
+/// Open the EasyTide page for a station in the web browser, to check the predictions
+/// against the official website.
+#[derive(Args, Clone, Debug)]
+pub struct OpenArgs {
+    /// ID of the desired tidal station.
+    #[arg(short, long)]
+    pub station: StationId,
+
+    /// Open the station's location on OpenStreetMap instead.
+    #[arg(long)]
+    pub map: bool,
+
+    /// Print the address rather than opening it.
+    #[arg(long)]
+    pub print: bool,
+}
+
+/// Open, or print, the page for the station.
+pub fn open(args: OpenArgs, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let stations = rjw_uktides::stations_from_reader(crate::STATIONS_BAKED_BYTES)?;
+    let station = stations
+        .iter()
+        .find(|s| s.id == args.station)
+        .ok_or_else(|| CliError::UnknownStation(args.station.clone()))?;
+    let url = if args.map {
+        map_url(station)
+    } else {
+        station.easytide_url()
+    };
+    if args.print {
+        writeln!(out, "{url}")?;
+        return Ok(());
+    }
+    let (program, status) = if cfg!(target_os = "macos") {
+        ("open", Command::new("open").arg(&url).status())
+    } else if cfg!(windows) {
+        (
+            "start",
+            Command::new("cmd").args(["/C", "start", "", &url]).status(),
+        )
+    } else {
+        ("xdg-open", Command::new("xdg-open").arg(&url).status())
+    };
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("{program} failed ({status}) to open {url}").into()),
+        Err(e) => Err(format!("Could not run {program} to open {url}: {e}").into()),
+    }
+}
+
+/// The station's location on OpenStreetMap.
+fn map_url(station: &Station) -> String {
+    let (lat, lon) = (station.location.latitude.0, station.location.longitude.0);
+    format!("https://www.openstreetmap.org/?mlat={lat}&mlon={lon}#map=13/{lat}/{lon}")
+}
@@ -0,0 +1,408 @@
+//! Machine-readable output of tide predictions.
+//!
+//! The output has its own documented structure, rather than the EasyTide one, so that
+//! scripts do not depend on the quirks of the upstream response.
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, TimeZone, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use rjw_uktides::{StationId, TidalEvent, TidePredictions};
+
+use crate::locale;
+use crate::springs::Period;
+use crate::style::Style;
+use crate::units::Units;
+use crate::zone::Times;
+
+/// Tide predictions for a station, printed by `tides --json`.
+#[derive(Debug, Serialize)]
+pub struct TidesJson<'a> {
+    /// The station ID.
+    station: &'a StationId,
+    /// High and low tides, in chronological order.
+    events: Vec<EventJson>,
+    /// Half-hourly heights, if the station provides them.
+    heights: Vec<HeightJson>,
+    /// Lunar phases during the predictions.
+    lunar_phases: Vec<LunarPhaseJson>,
+    /// The note EasyTide attaches to the predictions.
+    note: &'a str,
+    /// Where and when the predictions were fetched.
+    meta: &'a Meta,
+}
+
+/// Where and when predictions were fetched, so that scripts can judge how fresh they are,
+/// and the units of their heights.
+#[derive(Debug, Serialize)]
+pub struct Meta {
+    /// RFC 3339 time in UTC at which the predictions were fetched from EasyTide, or
+    /// `null` if they were read from a file.
+    fetched_at: Option<String>,
+    /// The URL or file from which the predictions were read.
+    source: String,
+    /// The station ID.
+    station: StationId,
+    /// The station name.
+    station_name: String,
+    /// `"m"` or `"ft"`, the units of the heights.
+    units: &'static str,
+    /// The version of this program.
+    version: &'static str,
+}
+
+impl Meta {
+    pub fn new(
+        station: StationId,
+        station_name: String,
+        source: String,
+        fetched_at: Option<DateTime<Utc>>,
+        units: Units,
+    ) -> Self {
+        Self {
+            fetched_at: fetched_at.map(rfc3339),
+            source,
+            station,
+            station_name,
+            units: units.symbol(),
+            version: env!("CARGO_PKG_VERSION"),
+        }
+    }
+}
+
+/// A high or low tide.
+#[derive(Debug, Serialize)]
+struct EventJson {
+    /// RFC 3339 time in UTC, such as `2023-04-03T04:07:00Z`.
+    time: String,
+    /// `"high"` or `"low"`.
+    #[serde(rename = "type")]
+    event_type: &'static str,
+    /// Metres above chart datum.
+    height: f64,
+    /// Whether the time is approximate.
+    approximate_time: bool,
+    /// Whether the height is approximate.
+    approximate_height: bool,
+}
+
+/// A predicted height at a particular time.
+#[derive(Debug, Serialize)]
+struct HeightJson {
+    /// RFC 3339 time in UTC.
+    time: String,
+    /// Metres above chart datum.
+    height: f64,
+}
+
+/// A lunar phase.
+#[derive(Debug, Serialize)]
+struct LunarPhaseJson {
+    /// RFC 3339 time in UTC.
+    time: String,
+    /// `"new_moon"`, `"first_quarter"`, `"full_moon"` or `"last_quarter"`.
+    phase: &'static str,
+}
+
+impl<'a> TidesJson<'a> {
+    pub fn new(station: &'a StationId, tides: &'a TidePredictions, meta: &'a Meta) -> Self {
+        Self {
+            station,
+            events: events(tides),
+            heights: tides
+                .tidal_height_occurrence_list
+                .iter()
+                .map(|h| HeightJson {
+                    time: rfc3339(h.date_time),
+                    height: h.height.0,
+                })
+                .collect(),
+            lunar_phases: tides
+                .lunar_phase_list
+                .iter()
+                .map(|p| LunarPhaseJson {
+                    time: rfc3339(p.date_time),
+                    phase: p.lunar_phase_type.name(),
+                })
+                .collect(),
+            note: tides.footer_note.as_str(),
+            meta,
+        }
+    }
+}
+
+/// A single high or low tide, printed one per line by `tides --jsonl`.
+#[derive(Debug, Serialize)]
+pub struct EventLine<'a> {
+    /// The station ID.
+    station: &'a StationId,
+    #[serde(flatten)]
+    event: EventJson,
+    /// Where and when the predictions were fetched.
+    meta: &'a Meta,
+}
+
+impl<'a> EventLine<'a> {
+    /// One line for each of the high and low tides at all of the stations, in
+    /// chronological order, with the `metas` for their stations.
+    pub fn all(tides: &'a [(StationId, TidePredictions)], metas: &'a [Meta]) -> Vec<Self> {
+        merged_events(tides)
+            .into_iter()
+            .filter_map(|(station, e)| {
+                let meta = metas.iter().find(|m| &m.station == station)?;
+                Some(Self {
+                    station,
+                    event: event_json(e),
+                    meta,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The high and low tides at all of the stations, in chronological order.
+pub fn merged_events(tides: &[(StationId, TidePredictions)]) -> Vec<(&StationId, &TidalEvent)> {
+    let mut events: Vec<_> = tides
+        .iter()
+        .flat_map(|(station, tides)| tides.tidal_event_list.iter().map(move |e| (station, e)))
+        .collect();
+    events.sort_by(|(a_station, a), (b_station, b)| a.cmp(b).then(a_station.cmp(b_station)));
+    events
+}
+
+/// The high and low tides in chronological order.
+fn events(tides: &TidePredictions) -> Vec<EventJson> {
+    let mut events: Vec<_> = tides.tidal_event_list.iter().collect();
+    events.sort();
+    events.into_iter().map(event_json).collect()
+}
+
+fn event_json(e: &TidalEvent) -> EventJson {
+    EventJson {
+        time: rfc3339(e.date_time),
+        event_type: e.event_type.name(),
+        height: e.height.0,
+        approximate_time: e.is_approximate_time.is_some(),
+        approximate_height: e.is_approximate_height.is_some(),
+    }
+}
+
+pub fn rfc3339<Tz: TimeZone>(date_time: DateTime<Tz>) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    date_time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// A column of the delimited and plain-text output, selected with `--fields`.
+///
+/// Columns always appear in the order of the variants here, whatever order they are
+/// given in, so that scripts can rely on the position of each column.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Field {
+    /// The station ID.
+    Station,
+    /// RFC 3339 time, in UTC except in plain text, which follows --tz.
+    Time,
+    /// "high" or "low".
+    Type,
+    /// Height above chart datum, in metres or in feet with --units imperial.
+    Height,
+    /// Whether the time is approximate.
+    ApproximateTime,
+    /// Whether the height is approximate.
+    ApproximateHeight,
+}
+
+impl Field {
+    /// The columns written when none are selected.
+    pub const DEFAULT: [Field; 5] = [
+        Field::Time,
+        Field::Type,
+        Field::Height,
+        Field::ApproximateTime,
+        Field::ApproximateHeight,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Field::Station => "station",
+            Field::Time => "time",
+            Field::Type => "type",
+            Field::Height => "height",
+            Field::ApproximateTime => "approximate_time",
+            Field::ApproximateHeight => "approximate_height",
+        }
+    }
+
+    fn value(self, station: &StationId, event: &TidalEvent, tz: chrono_tz::Tz) -> String {
+        match self {
+            Field::Station => station.0.clone(),
+            Field::Time => rfc3339(event.date_time.with_timezone(&tz)),
+            Field::Type => event.event_type.name().to_owned(),
+            Field::Height => event.height.0.to_string(),
+            Field::ApproximateTime => event.is_approximate_time.is_some().to_string(),
+            Field::ApproximateHeight => event.is_approximate_height.is_some().to_string(),
+        }
+    }
+}
+
+/// Write the selected columns of each high and low tide at the stations in chronological
+/// order, separated by `delimiter`, with a header row of column names if `header`, and
+/// times in the time zone.
+pub fn write_rows(
+    mut wtr: impl Write,
+    tides: &[(StationId, TidePredictions)],
+    fields: &[Field],
+    delimiter: char,
+    header: bool,
+    tz: chrono_tz::Tz,
+) -> io::Result<()> {
+    let mut fields = fields.to_vec();
+    fields.sort();
+    fields.dedup();
+    let quote = |value: String| {
+        if delimiter == ',' && value.contains([',', '"', '\n']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value
+        }
+    };
+    let join = |values: Vec<String>| values.join(&delimiter.to_string());
+    if header {
+        writeln!(
+            wtr,
+            "{}",
+            join(fields.iter().map(|f| f.name().to_owned()).collect())
+        )?;
+    }
+    for (station, event) in merged_events(tides) {
+        let values = fields.iter().map(|f| quote(f.value(station, event, tz)));
+        writeln!(wtr, "{}", join(values.collect()))?;
+    }
+    Ok(())
+}
+
+/// The tides on the `days` as a heading for each day followed by its high and low tides,
+/// with dates and times in the time zone.
+///
+/// With spring and neap `periods`, the lunar phases are listed among the tides and the
+/// headings of days in a period are tagged "springs" or "neaps".
+pub fn daily_view(
+    name: &str,
+    tides: &TidePredictions,
+    days: RangeInclusive<NaiveDate>,
+    style: Style,
+    units: Units,
+    times: Times,
+    periods: Option<&[Period]>,
+) -> String {
+    let mut events: Vec<_> = tides.tidal_event_list.iter().collect();
+    events.sort();
+    let width = times.time_width();
+    let mut out = String::new();
+    for date in days.start().iter_days().take_while(|d| d <= days.end()) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        let _ = write!(
+            out,
+            "{name}, {} ({})",
+            times.format_date(date, "%A %-d %B %Y"),
+            times.tz()
+        );
+        if let Some(period) = periods.into_iter().flatten().find(|p| p.contains(date)) {
+            let _ = write!(out, ", {}", period.tag());
+        }
+        out.push('\n');
+
+        let mut rows: Vec<(DateTime<Utc>, String)> = events
+            .iter()
+            .filter(|e| times.date(e.date_time) == date)
+            .map(|event| {
+                let row = format!(
+                    "  {:>width$}  {}  {:.2} {}",
+                    times.format(event.date_time, "%H:%M"),
+                    style.event_type(event.event_type, 9),
+                    event.height.0,
+                    units.symbol(),
+                );
+                (event.date_time, row)
+            })
+            .collect();
+        if rows.is_empty() {
+            let _ = writeln!(out, "  {}", locale::phrases().no_predictions);
+            continue;
+        }
+        if periods.is_some() {
+            let phases = tides
+                .lunar_phase_list
+                .iter()
+                .filter(|p| times.date(p.date_time) == date)
+                .map(|phase| {
+                    let row = format!(
+                        "  {:>width$}  {}",
+                        times.format(phase.date_time, "%H:%M"),
+                        style.phase(phase.lunar_phase_type),
+                    );
+                    (phase.date_time, row)
+                });
+            rows.extend(phases);
+            rows.sort_by_key(|(date_time, _)| *date_time);
+        }
+        for (_, row) in rows {
+            let _ = writeln!(out, "{row}");
+        }
+    }
+    out
+}
+
+/// The tides at the stations under a heading for each day with tides, giving the weekday
+/// and any lunar phase, with dates and times in the time zone. Each tide is labelled with
+/// its station when there are several.
+pub fn grouped_by_day(
+    tides: &[(StationId, TidePredictions)],
+    style: Style,
+    units: Units,
+    times: Times,
+) -> String {
+    let multiple = tides.len() > 1;
+    let phases = tides
+        .first()
+        .map(|(_, tides)| tides.lunar_phase_list.as_slice())
+        .unwrap_or_default();
+    let width = times.time_width();
+    let mut out = String::new();
+    let mut current = None;
+    for (station, event) in merged_events(tides) {
+        let date = times.date(event.date_time);
+        if current != Some(date) {
+            if current.is_some() {
+                out.push('\n');
+            }
+            current = Some(date);
+            out.push_str(&times.format_date(date, "%A %-d %B %Y"));
+            for phase in phases.iter().filter(|p| times.date(p.date_time) == date) {
+                let _ = write!(out, ", {}", style.phase(phase.lunar_phase_type));
+            }
+            out.push('\n');
+        }
+        out.push_str("  ");
+        if multiple {
+            let _ = write!(out, "{station}  ");
+        }
+        let _ = writeln!(
+            out,
+            "{:>width$}  {}  {:.2} {}",
+            times.format(event.date_time, "%H:%M"),
+            style.event_type(event.event_type, 9),
+            event.height.0,
+            units.symbol(),
+        );
+    }
+    out
+}
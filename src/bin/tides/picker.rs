@@ -0,0 +1,23 @@
+//! Choosing a station interactively when none is given on the command line or in the
+//! configuration file.
+
+use std::error::Error;
+
+use fuzzy_finder::item::Item;
+use fuzzy_finder::FuzzyFinder;
+use rjw_uktides::Station;
+
+/// The number of matching stations shown at once.
+const LINES_TO_SHOW: i8 = 10;
+
+/// Let the user choose one of the built-in stations by typing part of its name or ID, or
+/// `None` if they cancel with escape, ctrl-c or ctrl-d.
+pub fn pick_station() -> Result<Option<Station>, Box<dyn Error>> {
+    let stations = rjw_uktides::stations_from_reader(crate::STATIONS_BAKED_BYTES)?;
+    let items = stations
+        .into_iter()
+        .map(|s| Item::new(format!("{} {}, {}", s.id, s.name, s.country), s))
+        .collect();
+    eprintln!("No station given; type to search, then press enter to choose one.");
+    Ok(FuzzyFinder::find(items, LINES_TO_SHOW)?)
+}
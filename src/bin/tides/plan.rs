@@ -0,0 +1,129 @@
+//! Suggested times for activities that depend on the state of the tide, in daylight.
+
+use std::fmt::Write;
+
+use chrono::{DateTime, Duration, Utc};
+use clap::{Args, ValueEnum};
+
+use rjw_uktides::{Station, StationId, TidalEventType, TidePredictions};
+
+use crate::locale;
+use crate::units::Units;
+use crate::zone::Times;
+
+/// Suggest times over the coming days for an activity, when the tide suits it and it is
+/// daylight.
+#[derive(Args, Clone, Debug)]
+pub struct PlanArgs {
+    /// ID of the desired tidal station.
+    #[arg(short, long)]
+    pub station: StationId,
+
+    /// The activity to plan for.
+    #[arg(long, value_enum)]
+    pub activity: Activity,
+}
+
+/// Activities with the state of the tide that suits them.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Activity {
+    /// Within an hour and a half of low water, when the most shore is uncovered.
+    Rockpooling,
+    /// Within two hours of high water, when there is the most water at a slipway.
+    Launch,
+    /// Within an hour of high water, when the water is slack and close to the shore.
+    Swim,
+}
+
+impl Activity {
+    /// The tide the activity is planned around, and how long before and after it.
+    fn tide(self) -> (TidalEventType, Duration) {
+        match self {
+            Activity::Rockpooling => (TidalEventType::LowWater, Duration::minutes(90)),
+            Activity::Launch => (TidalEventType::HighWater, Duration::hours(2)),
+            Activity::Swim => (TidalEventType::HighWater, Duration::hours(1)),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Activity::Rockpooling => "Rockpooling",
+            Activity::Launch => "Launching",
+            Activity::Swim => "Swimming",
+        }
+    }
+}
+
+/// The times around the activity's tides that are in daylight at the station and have
+/// not passed by `now`, under a heading for each day, with the tide in each.
+pub fn text(
+    activity: Activity,
+    station: &Station,
+    tides: &TidePredictions,
+    now: DateTime<Utc>,
+    units: Units,
+    times: Times,
+) -> String {
+    let (event_type, within) = activity.tide();
+    let windows: Vec<_> = tides
+        .windows_around(event_type, within)
+        .into_iter()
+        .flat_map(|window| {
+            // Daylight on the UTC dates the window spans, which include the local dates.
+            window
+                .start
+                .date_naive()
+                .iter_days()
+                .take_while(move |d| *d <= window.end.date_naive())
+                .filter_map(|date| station.location.daylight(date))
+                .filter_map(move |daylight| window.intersection(&daylight))
+        })
+        .filter(|window| window.end > now)
+        .collect();
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{} at {} ({}, {})",
+        activity.name(),
+        station.name,
+        times.tz(),
+        units.symbol()
+    );
+    if windows.is_empty() {
+        let _ = writeln!(out, "No suitable times in daylight in the predictions.");
+        return out;
+    }
+    let mut current = None;
+    for window in windows {
+        let date = times.date(window.start);
+        if current != Some(date) {
+            current = Some(date);
+            let _ = writeln!(out, "\n{}", times.format_date(date, "%A %-d %B %Y"));
+        }
+        let tides: Vec<_> = tides
+            .tidal_event_list
+            .iter()
+            .filter(|e| e.event_type == event_type && window.contains(e.date_time))
+            .map(|e| {
+                format!(
+                    "{} {} {:.1}",
+                    locale::phrases().event(event_type),
+                    times.format(e.date_time, "%H:%M"),
+                    e.height.0
+                )
+            })
+            .collect();
+        let _ = write!(
+            out,
+            "  {}-{}",
+            times.format(window.start, "%H:%M"),
+            times.format(window.end, "%H:%M")
+        );
+        if tides.is_empty() {
+            let _ = writeln!(out);
+        } else {
+            let _ = writeln!(out, "  {}", tides.join(", "));
+        }
+    }
+    out
+}
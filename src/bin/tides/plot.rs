@@ -0,0 +1,162 @@
+//! Drawing tide charts as SVG images with plotters.
+
+use std::error::Error;
+use std::io::Write;
+
+use chrono::{DateTime, Duration, Utc};
+use clap::Args;
+use plotters::prelude::*;
+
+use rjw_uktides::{StationId, TidalEventType};
+
+use crate::fetch::Fetcher;
+use crate::sink::Sink;
+use crate::units::Units;
+use crate::zone::Times;
+
+const FONT: &str = "sans-serif";
+
+/// Draw a chart of the tide as an SVG image.
+///
+/// The chart shows the height of the tide through the predictions, with each high and low
+/// tide labelled with its time and height, and a line at each midnight, in the time zone
+/// chosen with --tz.
+///
+/// The image is written to stdout, or to the SVG file given with --output.
+#[derive(Args, Clone, Debug)]
+pub struct ChartArgs {
+    /// ID of the tidal station to chart.
+    #[arg(short, long)]
+    station: StationId,
+
+    /// Chart only this many days, starting today.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    days: Option<u64>,
+
+    /// Width of the image in pixels.
+    #[arg(long, default_value_t = 1200)]
+    width: u32,
+
+    /// Height of the image in pixels.
+    #[arg(long, default_value_t = 500)]
+    height: u32,
+}
+
+pub fn chart(
+    args: ChartArgs,
+    fetcher: &Fetcher,
+    units: Units,
+    times: Times,
+    out: &mut Sink,
+) -> Result<(), Box<dyn Error>> {
+    let is_svg = out.path().is_none_or(|path| {
+        path.extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"))
+    });
+    if !is_svg {
+        return Err("Charts can only be written as SVG; give a path ending in .svg.".into());
+    }
+
+    let mut tides = crate::fetch_tides(fetcher, &args.station)?;
+    units.convert(&mut tides);
+    if let Some(days) = args.days {
+        let today = times.today();
+        let to = today + chrono::Days::new(days - 1);
+        crate::retain_dates(&mut tides, Some(today), Some(to), times);
+    }
+    let heights = &tides.tidal_height_occurrence_list;
+    let (Some(first), Some(last)) = (heights.first(), heights.last()) else {
+        return Err(format!(
+            "No heights available to chart for station {}.",
+            args.station
+        )
+        .into());
+    };
+    let start = first.date_time;
+    let hours = |t: DateTime<Utc>| (t - start).num_minutes() as f64 / 60.0;
+    let span = hours(last.date_time).max(1.0);
+    let min = heights
+        .iter()
+        .map(|h| h.height.0)
+        .fold(f64::INFINITY, f64::min);
+    let max = heights
+        .iter()
+        .map(|h| h.height.0)
+        .fold(f64::NEG_INFINITY, f64::max);
+    // Leave room above the curve for the labels of high tides.
+    let (y_min, y_max) = ((min - 0.5).min(0.0), max + 1.0);
+    let name = crate::station_name(&args.station)?;
+
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, (args.width, args.height)).into_drawing_area();
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption(format!("Tides at {name}"), (FONT, 22))
+            .margin(15)
+            .x_label_area_size(40)
+            .y_label_area_size(50)
+            .build_cartesian_2d(0.0..span, y_min..y_max)?;
+        chart
+            .configure_mesh()
+            .disable_x_mesh()
+            .x_labels((span / 6.0).ceil() as usize + 1)
+            .x_label_formatter(&|h| {
+                let time = start + Duration::minutes((h * 60.0).round() as i64);
+                times.format(time, "%a %H:%M")
+            })
+            .x_desc(format!("Time ({})", times.tz()))
+            .y_desc(format!("Height above chart datum ({})", units.symbol()))
+            .draw()?;
+
+        let mut date = times.date(start).succ_opt();
+        while let Some(m) = date
+            .map(|d| times.midnight(d))
+            .filter(|m| *m < last.date_time)
+        {
+            let x = hours(m);
+            chart.draw_series(LineSeries::new(
+                [(x, y_min), (x, y_max)],
+                BLACK.mix(0.3).stroke_width(1),
+            ))?;
+            date = date.and_then(|d| d.succ_opt());
+        }
+
+        chart.draw_series(LineSeries::new(
+            heights.iter().map(|h| (hours(h.date_time), h.height.0)),
+            BLUE.stroke_width(2),
+        ))?;
+
+        let events: Vec<_> = tides
+            .tidal_event_list
+            .iter()
+            .filter(|e| e.date_time >= start && e.date_time <= last.date_time)
+            .collect();
+        chart.draw_series(
+            events
+                .iter()
+                .map(|e| Circle::new((hours(e.date_time), e.height.0), 3, BLUE.filled())),
+        )?;
+        chart.draw_series(events.iter().map(|e| {
+            // High tides are labelled above the curve, low tides below it.
+            let offset = match e.event_type {
+                TidalEventType::HighWater => 0.35,
+                TidalEventType::LowWater => -0.15,
+            };
+            Text::new(
+                format!(
+                    "{} {:.1} {}",
+                    times.format(e.date_time, "%H:%M"),
+                    e.height.0,
+                    units.symbol()
+                ),
+                (hours(e.date_time) - span / 60.0, e.height.0 + offset),
+                (FONT, 11),
+            )
+        }))?;
+        root.present()?;
+    }
+
+    out.write_all(svg.as_bytes())?;
+    Ok(())
+}
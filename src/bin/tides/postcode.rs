@@ -0,0 +1,39 @@
+use std::error::Error;
+
+use serde::Deserialize;
+
+/// The postcodes.io API, which geocodes UK postcodes without an API key.
+const POSTCODES_IO_URL: &str = "https://api.postcodes.io/postcodes";
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    result: Option<Location>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Location {
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+}
+
+/// The latitude and longitude of the postcode.
+///
+/// # Errors
+///
+/// This function will return an error if the request fails, or postcodes.io does not
+/// know the postcode or its location.
+pub fn lookup(postcode: &str) -> Result<(f64, f64), Box<dyn Error>> {
+    let mut url = reqwest::Url::parse(POSTCODES_IO_URL)?;
+    url.path_segments_mut()
+        .map_err(|()| "invalid postcodes.io URL")?
+        .push(postcode.trim());
+    let response = reqwest::blocking::get(url)?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(format!("postcode {postcode} was not found").into());
+    }
+    let response: Response = response.error_for_status()?.json()?;
+    response
+        .result
+        .and_then(|l| l.latitude.zip(l.longitude))
+        .ok_or_else(|| format!("postcode {postcode} has no known location").into())
+}
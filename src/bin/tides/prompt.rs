@@ -0,0 +1,136 @@
+//! The next tide as a short segment for a shell prompt or status bar.
+
+use std::error::Error;
+use std::io::Write;
+
+use chrono::{DateTime, Duration, Utc};
+use clap::{Args, ValueEnum};
+use serde_json::json;
+
+use rjw_uktides::{StationId, TidalEvent, TidalEventType, TidePredictions};
+
+use crate::fetch::Fetcher;
+use crate::locale;
+use crate::units::Units;
+use crate::zone::Times;
+
+/// How long cached predictions are used for the prompt before they are fetched again, in
+/// seconds, unless --interval is longer. The predictions run for a week, so there is no
+/// need to fetch them often.
+const PROMPT_TTL_SECS: i64 = 6 * 60 * 60;
+
+/// The 256-colour backgrounds of powerline segments for the next tide's type.
+const HIGH_BACKGROUND: u8 = 25;
+const LOW_BACKGROUND: u8 = 30;
+
+/// Print the next tide for a station as one short line, such as "▲3.4m 17:42 (2h10)", for
+/// a shell prompt, such as a starship custom module, or a status bar, such as i3blocks.
+///
+/// ▲ is a high tide and ▼ a low tide. Predictions are cached for several hours, so the
+/// command can be run every time the prompt is shown without contacting EasyTide, and
+/// cached predictions are used however old they are if EasyTide cannot be reached.
+#[derive(Args, Clone, Debug)]
+pub struct PromptArgs {
+    /// ID of the desired tidal station.
+    #[arg(short, long)]
+    pub station: StationId,
+
+    /// How often the status bar runs this, such as "12h"; predictions are cached for this
+    /// long if it is more than six hours, so that each run does not fetch them again.
+    #[arg(long, value_parser = crate::duration::parse_duration)]
+    pub interval: Option<Duration>,
+
+    /// How to print the segment.
+    #[arg(long, value_enum, default_value_t = PromptFormat::Plain)]
+    pub format: PromptFormat,
+}
+
+/// Formats for the prompt segment.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum PromptFormat {
+    /// The segment as plain text.
+    Plain,
+    /// A coloured powerline segment, with ANSI escape codes and a powerline arrow, which
+    /// needs a powerline or Nerd Font.
+    Powerline,
+    /// A JSON object with "text", "tooltip" and "class" ("high" or "low") for a waybar
+    /// custom module with `return-type` set to "json".
+    Waybar,
+}
+
+pub fn prompt(
+    args: PromptArgs,
+    fetcher: Fetcher,
+    units: Units,
+    times: Times,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let ttl = Duration::seconds(PROMPT_TTL_SECS).max(args.interval.unwrap_or_else(Duration::zero));
+    let fetcher = fetcher.with_tides_ttl(ttl);
+    let mut tides = match fetcher.tides(&args.station) {
+        Ok(tides) => tides,
+        Err(e) => fetcher.cached_tides(&args.station).ok_or(e)?,
+    };
+    units.convert(&mut tides);
+    let now = Utc::now();
+    let next = next_tide(&tides, now).ok_or("No upcoming tides in the predictions.")?;
+    let text = segment(next, now, units, times);
+    match args.format {
+        PromptFormat::Plain => writeln!(out, "{text}")?,
+        PromptFormat::Powerline => {
+            let background = match next.event_type {
+                TidalEventType::HighWater => HIGH_BACKGROUND,
+                TidalEventType::LowWater => LOW_BACKGROUND,
+            };
+            writeln!(
+                out,
+                "\x1b[38;5;15;48;5;{background}m {text} \x1b[0;38;5;{background}m\u{e0b0}\x1b[0m"
+            )?
+        }
+        PromptFormat::Waybar => {
+            let tooltip = format!(
+                "{} at {} at {}, {:.2} {}",
+                locale::phrases().event(next.event_type),
+                crate::station_name(&args.station)?,
+                times.format(next.date_time, "%H:%M"),
+                next.height.0,
+                units.symbol()
+            );
+            let class = match next.event_type {
+                TidalEventType::HighWater => "high",
+                TidalEventType::LowWater => "low",
+            };
+            let json = json!({ "text": text, "tooltip": tooltip, "class": class });
+            writeln!(out, "{json}")?
+        }
+    }
+    Ok(())
+}
+
+/// The next high or low tide after `now`.
+fn next_tide(tides: &TidePredictions, now: DateTime<Utc>) -> Option<&TidalEvent> {
+    [TidalEventType::HighWater, TidalEventType::LowWater]
+        .into_iter()
+        .filter_map(|t| tides.next_event(now, t))
+        .min_by_key(|e| e.date_time)
+}
+
+/// The tide as "▲3.4m 17:42 (2h10)", or "(45m)" when it is less than an hour away.
+fn segment(event: &TidalEvent, now: DateTime<Utc>, units: Units, times: Times) -> String {
+    let symbol = match event.event_type {
+        TidalEventType::HighWater => '▲',
+        TidalEventType::LowWater => '▼',
+    };
+    let minutes = (event.date_time - now).num_minutes().max(0);
+    let countdown = if minutes < 60 {
+        format!("{minutes}m")
+    } else {
+        format!("{}h{:02}", minutes / 60, minutes % 60)
+    };
+    format!(
+        "{symbol}{:.1}{} {} ({countdown})",
+        event.height.0,
+        units.symbol(),
+        times.format(event.date_time, "%H:%M")
+    )
+}
@@ -0,0 +1,215 @@
+//! Checking the EasyTide data for anomalies and for changes to the shape of its responses.
+
+use std::collections::BTreeSet;
+use std::error::Error;
+
+use clap::Args;
+use serde_json::Value;
+
+use rjw_uktides::Station;
+
+use crate::fetch::Fetcher;
+
+/// Range of latitudes, in decimal degrees, covering the UK, the Channel Islands and the
+/// Isle of Man, including outlying stations such as Rockall.
+const UK_LATITUDES: (f64, f64) = (48.0, 62.0);
+/// Range of longitudes, in decimal degrees, covering the same area as [`UK_LATITUDES`].
+const UK_LONGITUDES: (f64, f64) = (-15.0, 3.0);
+
+const STATIONS_FIELDS: &[&str] = &["type", "features"];
+const FEATURE_FIELDS: &[&str] = &["type", "geometry", "properties"];
+const GEOMETRY_FIELDS: &[&str] = &["type", "coordinates"];
+const PROPERTIES_FIELDS: &[&str] = &["Id", "Name", "Country", "ContinuousHeightsAvailable"];
+const TIDES_FIELDS: &[&str] = &[
+    "tidalEventList",
+    "tidalHeightOccurrenceList",
+    "lunarPhaseList",
+    "footerNote",
+];
+const EVENT_FIELDS: &[&str] = &[
+    "eventType",
+    "dateTime",
+    "isApproximateTime",
+    "height",
+    "isApproximateHeight",
+    "filtered",
+    "date",
+];
+const HEIGHT_FIELDS: &[&str] = &["dateTime", "height"];
+const LUNAR_PHASE_FIELDS: &[&str] = &["lunarPhaseType", "dateTime"];
+
+/// Check the station list and a sample of predictions for anomalies.
+///
+/// Reports stations without continuous heights, stations whose coordinates are outside the
+/// UK, predictions that fail to parse or validate, and fields that have been added to or
+/// removed from the EasyTide responses. Exits with an error if any problems are found.
+#[derive(Args, Clone, Debug)]
+pub struct QaArgs {
+    /// Number of stations, spread across the station list, whose predictions to check.
+    #[arg(long, default_value_t = 5)]
+    sample: usize,
+}
+
+pub fn qa(args: QaArgs, fetcher: &Fetcher) -> Result<(), Box<dyn Error>> {
+    let mut problems = 0;
+
+    let raw = fetcher.stations_raw()?;
+    let json: Value = serde_json::from_slice(&raw)?;
+    problems += report_drift("station list", &stations_drift(&json));
+    let stations = match rjw_uktides::stations_from_reader(raw.as_slice()) {
+        Ok(stations) => stations,
+        Err(e) => {
+            println!("The station list could not be parsed: {e}");
+            return Err("1 problem found.".into());
+        }
+    };
+    println!("{} stations listed.", stations.len());
+
+    let without_heights: Vec<_> = stations
+        .iter()
+        .filter(|s| !s.continuous_heights_available)
+        .collect();
+    if !without_heights.is_empty() {
+        println!(
+            "{} stations have no continuous heights:",
+            without_heights.len()
+        );
+        print_stations(&without_heights);
+    }
+
+    let outside: Vec<_> = stations.iter().filter(|s| !in_uk(s)).collect();
+    if !outside.is_empty() {
+        problems += outside.len();
+        println!(
+            "{} stations have coordinates outside the UK:",
+            outside.len()
+        );
+        print_stations(&outside);
+    }
+
+    let step = (stations.len() / args.sample.max(1)).max(1);
+    for station in stations.iter().step_by(step).take(args.sample) {
+        println!("Predictions for {} {}:", station.id, station.name);
+        let raw = match fetcher.tides_raw(&station.id) {
+            Ok(raw) => raw,
+            Err(e) => {
+                problems += 1;
+                println!("  Failed to fetch: {e}");
+                continue;
+            }
+        };
+        match serde_json::from_slice(&raw) {
+            Ok(json) => problems += report_drift("  predictions", &tides_drift(&json)),
+            Err(e) => {
+                problems += 1;
+                println!("  Not valid JSON: {e}");
+                continue;
+            }
+        }
+        match rjw_uktides::tides_from_reader(raw.as_slice()) {
+            Ok(tides) => {
+                let report = tides.validate();
+                problems += report.anomalies.len();
+                for line in report.to_string().lines() {
+                    println!("  {line}");
+                }
+            }
+            Err(e) => {
+                problems += 1;
+                println!("  Failed to parse: {e}");
+            }
+        }
+    }
+
+    match problems {
+        0 => Ok(()),
+        1 => Err("1 problem found.".into()),
+        n => Err(format!("{n} problems found.").into()),
+    }
+}
+
+fn in_uk(station: &Station) -> bool {
+    let (lat, lon) = (station.location.latitude.0, station.location.longitude.0);
+    (UK_LATITUDES.0..=UK_LATITUDES.1).contains(&lat)
+        && (UK_LONGITUDES.0..=UK_LONGITUDES.1).contains(&lon)
+}
+
+fn print_stations(stations: &[&Station]) {
+    for station in stations {
+        println!(
+            "  {}\t{}\t{}, {}",
+            station.id, station.name, station.location.latitude.0, station.location.longitude.0
+        );
+    }
+}
+
+/// Print the changes to the fields of a response, returning the number printed.
+fn report_drift(what: &str, drift: &[String]) -> usize {
+    for change in drift {
+        println!("{what}: {change}");
+    }
+    drift.len()
+}
+
+/// Fields added to or removed from the station list response.
+pub fn stations_drift(json: &Value) -> Vec<String> {
+    let mut drift = field_drift("response", json, STATIONS_FIELDS);
+    for feature in json["features"].as_array().into_iter().flatten() {
+        drift.extend(field_drift("feature", feature, FEATURE_FIELDS));
+        drift.extend(field_drift(
+            "geometry",
+            &feature["geometry"],
+            GEOMETRY_FIELDS,
+        ));
+        drift.extend(field_drift(
+            "properties",
+            &feature["properties"],
+            PROPERTIES_FIELDS,
+        ));
+    }
+    dedup(drift)
+}
+
+/// Fields added to or removed from a tide predictions response.
+pub fn tides_drift(json: &Value) -> Vec<String> {
+    let mut drift = field_drift("response", json, TIDES_FIELDS);
+    let lists = [
+        ("tidal event", "tidalEventList", EVENT_FIELDS),
+        ("height", "tidalHeightOccurrenceList", HEIGHT_FIELDS),
+        ("lunar phase", "lunarPhaseList", LUNAR_PHASE_FIELDS),
+    ];
+    for (what, key, fields) in lists {
+        for item in json[key].as_array().into_iter().flatten() {
+            drift.extend(field_drift(what, item, fields));
+        }
+    }
+    dedup(drift)
+}
+
+/// Describe the fields of `object` that are not `expected`, and the expected fields it lacks.
+fn field_drift(what: &str, object: &Value, expected: &[&str]) -> Vec<String> {
+    let Some(object) = object.as_object() else {
+        return vec![format!("{what} is not an object")];
+    };
+    let mut drift: Vec<_> = object
+        .keys()
+        .filter(|k| !expected.contains(&k.as_str()))
+        .map(|k| format!("unexpected field {k:?} in {what}"))
+        .collect();
+    drift.extend(
+        expected
+            .iter()
+            .filter(|k| !object.contains_key(**k))
+            .map(|k| format!("missing field {k:?} in {what}")),
+    );
+    drift
+}
+
+/// Remove repeated descriptions, as every item in a list usually changes in the same way.
+fn dedup(drift: Vec<String>) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    drift
+        .into_iter()
+        .filter(|d| seen.insert(d.clone()))
+        .collect()
+}
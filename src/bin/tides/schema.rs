@@ -0,0 +1,222 @@
+//! JSON Schemas describing the machine-readable output of `tides --json` and `--jsonl`.
+//!
+//! These are written by hand, so they must be kept in step with the structures in the
+//! output module.
+
+use std::error::Error;
+use std::io::Write;
+
+use clap::{Args, ValueEnum};
+use serde_json::{json, Value};
+
+/// The JSON Schema dialect the schemas are written in.
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Print the JSON Schema of an output format, for validating the output or generating
+/// types to read it.
+#[derive(Args, Clone, Debug)]
+pub struct SchemaArgs {
+    /// The output to describe.
+    #[arg(long, value_enum, default_value_t = SchemaFormat::Json)]
+    pub format: SchemaFormat,
+}
+
+/// The outputs with a schema.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum SchemaFormat {
+    /// The document printed by --json, an object or, for several stations, an array of
+    /// objects.
+    Json,
+    /// Each line printed by --jsonl.
+    Jsonl,
+    /// The "meta" object included in both, describing where and when the predictions
+    /// were fetched.
+    Envelope,
+}
+
+pub fn schema(args: SchemaArgs, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    writeln!(
+        out,
+        "{}",
+        serde_json::to_string_pretty(&document(args.format))?
+    )?;
+    Ok(())
+}
+
+/// The schema for the format, with its definitions.
+fn document(format: SchemaFormat) -> Value {
+    let (title, mut schema) = match format {
+        SchemaFormat::Json => (
+            "tides --json",
+            json!({
+                "anyOf": [
+                    { "$ref": "#/$defs/predictions" },
+                    { "type": "array", "items": { "$ref": "#/$defs/predictions" } },
+                ],
+                "$defs": {
+                    "predictions": predictions(),
+                    "event": event(),
+                    "height": height(),
+                    "lunarPhase": lunar_phase(),
+                    "meta": meta(),
+                },
+            }),
+        ),
+        SchemaFormat::Jsonl => {
+            let mut line = event();
+            line["description"] = json!("A high or low tide at a station.");
+            line["required"]
+                .as_array_mut()
+                .expect("required is an array")
+                .extend([json!("station"), json!("meta")]);
+            let properties = line["properties"]
+                .as_object_mut()
+                .expect("properties is an object");
+            properties.insert("station".to_owned(), station());
+            properties.insert("meta".to_owned(), json!({ "$ref": "#/$defs/meta" }));
+            line["$defs"] = json!({ "meta": meta() });
+            ("tides --jsonl", line)
+        }
+        SchemaFormat::Envelope => ("tides output metadata", meta()),
+    };
+    let object = schema.as_object_mut().expect("schemas are objects");
+    object.insert("$schema".to_owned(), json!(DIALECT));
+    object.insert("title".to_owned(), json!(title));
+    schema
+}
+
+/// The predictions for a station, printed by --json.
+fn predictions() -> Value {
+    json!({
+        "type": "object",
+        "description": "Tide predictions for a station.",
+        "required": ["station", "events", "heights", "lunar_phases", "note", "meta"],
+        "additionalProperties": false,
+        "properties": {
+            "station": station(),
+            "events": {
+                "description": "High and low tides, in chronological order.",
+                "type": "array",
+                "items": { "$ref": "#/$defs/event" },
+            },
+            "heights": {
+                "description": "Half-hourly heights, if the station provides them.",
+                "type": "array",
+                "items": { "$ref": "#/$defs/height" },
+            },
+            "lunar_phases": {
+                "description": "Lunar phases during the predictions.",
+                "type": "array",
+                "items": { "$ref": "#/$defs/lunarPhase" },
+            },
+            "note": {
+                "description": "The note EasyTide attaches to the predictions.",
+                "type": "string",
+            },
+            "meta": { "$ref": "#/$defs/meta" },
+        },
+    })
+}
+
+/// A high or low tide.
+fn event() -> Value {
+    json!({
+        "type": "object",
+        "description": "A high or low tide.",
+        "required": ["time", "type", "height", "approximate_time", "approximate_height"],
+        "properties": {
+            "time": time(),
+            "type": {
+                "description": "Whether this is a high or low tide.",
+                "enum": ["high", "low"],
+            },
+            "height": height_value(),
+            "approximate_time": {
+                "description": "Whether the time is approximate.",
+                "type": "boolean",
+            },
+            "approximate_height": {
+                "description": "Whether the height is approximate.",
+                "type": "boolean",
+            },
+        },
+    })
+}
+
+/// A predicted height at a particular time.
+fn height() -> Value {
+    json!({
+        "type": "object",
+        "description": "A predicted height at a particular time.",
+        "required": ["time", "height"],
+        "additionalProperties": false,
+        "properties": { "time": time(), "height": height_value() },
+    })
+}
+
+/// A lunar phase.
+fn lunar_phase() -> Value {
+    json!({
+        "type": "object",
+        "description": "A lunar phase.",
+        "required": ["time", "phase"],
+        "additionalProperties": false,
+        "properties": {
+            "time": time(),
+            "phase": { "enum": ["new_moon", "first_quarter", "full_moon", "last_quarter"] },
+        },
+    })
+}
+
+/// Where and when predictions were fetched, and the units of their heights.
+fn meta() -> Value {
+    json!({
+        "type": "object",
+        "description": "Where and when the predictions were fetched, and the units of \
+                        their heights.",
+        "required": ["fetched_at", "source", "station", "station_name", "units", "version"],
+        "additionalProperties": false,
+        "properties": {
+            "fetched_at": {
+                "description": "When the predictions were fetched from EasyTide, or null \
+                                if they were read from a file.",
+                "type": ["string", "null"],
+                "format": "date-time",
+            },
+            "source": {
+                "description": "The URL or file from which the predictions were read.",
+                "type": "string",
+            },
+            "station": station(),
+            "station_name": { "description": "The station name.", "type": "string" },
+            "units": {
+                "description": "The units of the heights: metres, or feet with --units \
+                                imperial.",
+                "enum": ["m", "ft"],
+            },
+            "version": {
+                "description": "The version of the program that printed the output.",
+                "type": "string",
+            },
+        },
+    })
+}
+
+fn station() -> Value {
+    json!({ "description": "The station ID, such as 0053.", "type": "string" })
+}
+
+fn time() -> Value {
+    json!({
+        "description": "RFC 3339 time in UTC, such as 2023-04-03T04:07:00Z.",
+        "type": "string",
+        "format": "date-time",
+    })
+}
+
+fn height_value() -> Value {
+    json!({
+        "description": "Height above chart datum, in metres, or feet with --units imperial.",
+        "type": "number",
+    })
+}
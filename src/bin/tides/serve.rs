@@ -0,0 +1,189 @@
+use std::error::Error;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use clap::Args;
+
+use rjw_uktides::{Client, RateLimit, StationId};
+
+use crate::api::{respond_api, JsonApi};
+use crate::metrics::{respond_metrics, StationMetrics};
+
+/// The number of connections each listener handles at once. Further connections wait to
+/// be accepted until one finishes.
+const WORKERS: usize = 8;
+
+/// The most of a request that is read, for the request line and headers together.
+const MAX_REQUEST_BYTES: u64 = 8192;
+
+/// How often the metrics' predictions are checked for staleness and refetched, which is
+/// also how soon a failed fetch is retried.
+const METRICS_POLL: Duration = Duration::from_secs(60);
+
+/// How long to wait for a client to send its request or to accept the response.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serve Prometheus metrics or a JSON API for tide data to other programs.
+#[derive(Args, Clone, Debug)]
+pub struct ServeArgs {
+    /// ID of a tidal station for which to expose metrics; may be given more than once.
+    #[arg(short, long = "station")]
+    stations: Vec<StationId>,
+
+    /// Address on which to expose Prometheus metrics, such as 127.0.0.1:9184.
+    #[arg(long, value_name = "ADDR", required_unless_present = "http")]
+    metrics: Option<SocketAddr>,
+
+    /// Address on which to serve the JSON API, such as 127.0.0.1:8080, or 0.0.0.0:8080 to
+    /// serve other machines on the network.
+    ///
+    /// The API serves /stations, /tides/<ID> and /next/<ID>, and an iCalendar feed of
+    /// each station's tides at /ics/<ID>.ics to which calendar clients can subscribe.
+    /// /tides/<ID> returns the same JSON as `tides --format json`, described by
+    /// `tides schema`.
+    #[arg(long, value_name = "ADDR")]
+    http: Option<SocketAddr>,
+
+    /// Minutes after which tide predictions are fetched again.
+    #[arg(long, default_value_t = 60)]
+    refresh_minutes: u64,
+
+    /// Maximum number of requests per minute the JSON API sends to EasyTide.
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..))]
+    rate_limit: u32,
+}
+
+/// Serve metrics or the JSON API, or both, until the process is killed.
+pub fn serve(args: ServeArgs, client: Client) -> Result<(), Box<dyn Error>> {
+    let refresh = Duration::from_secs(args.refresh_minutes * 60);
+    let metrics = args.metrics.map(TcpListener::bind).transpose()?;
+    let http = args.http.map(TcpListener::bind).transpose()?;
+    let station_metrics = StationMetrics::new(client.clone(), args.stations.clone(), refresh);
+    thread::scope(|scope| {
+        if let Some(listener) = metrics {
+            let metrics = &station_metrics;
+            // Fetching happens on its own thread, so a slow response from EasyTide does not
+            // hold up requests for the metrics.
+            scope.spawn(move || loop {
+                metrics.refresh();
+                thread::sleep(METRICS_POLL);
+            });
+            scope.spawn(move || {
+                serve_connections(listener, "metrics", |stream| {
+                    respond_metrics(stream, metrics)
+                })
+            });
+        }
+        if let Some(listener) = http {
+            let client = client.with_rate_limit(RateLimit::new(args.rate_limit, 1));
+            let api = JsonApi::new(client, refresh);
+            scope.spawn(move || {
+                serve_connections(listener, "JSON API", |stream| {
+                    respond_api(stream, &api);
+                    Ok(())
+                })
+            });
+        }
+    });
+    Ok(())
+}
+
+/// Handle connections to the listener on a pool of [`WORKERS`] threads, so a slow or idle
+/// client holds up only its own worker, and only until [`CONNECTION_TIMEOUT`], logging
+/// any errors.
+fn serve_connections(
+    listener: TcpListener,
+    name: &str,
+    handle: impl Fn(TcpStream) -> io::Result<()> + Sync,
+) {
+    match listener.local_addr() {
+        Ok(addr) => eprintln!("Serving {name} on http://{addr}/"),
+        Err(e) => eprintln!("Failed to get address for {name}: {e}"),
+    }
+    let (sender, receiver) = mpsc::sync_channel::<TcpStream>(WORKERS);
+    let receiver = Mutex::new(receiver);
+    thread::scope(|scope| {
+        for _ in 0..WORKERS {
+            scope.spawn(|| loop {
+                // The lock is released before the connection is handled.
+                let stream = receiver
+                    .lock()
+                    .expect("Connection queue lock poisoned.")
+                    .recv();
+                let Ok(stream) = stream else {
+                    return;
+                };
+                if let Err(e) = handle(stream) {
+                    eprintln!("Failed to respond to {name} request: {e}");
+                }
+            });
+        }
+        for stream in listener.incoming() {
+            let stream = stream.and_then(|stream| {
+                stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+                stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+                Ok(stream)
+            });
+            match stream {
+                Ok(stream) => {
+                    if sender.send(stream).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => eprintln!("Failed to accept {name} connection: {e}"),
+            }
+        }
+        drop(sender);
+    });
+}
+
+/// Read a single HTTP request from the stream, returning the request path and ignoring
+/// any headers or body.
+///
+/// Fails if the request line and headers are longer than [`MAX_REQUEST_BYTES`] or end
+/// before a blank line.
+pub fn read_request_path(stream: &TcpStream) -> io::Result<String> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_BYTES));
+    let mut read_line = |line: &mut String| {
+        line.clear();
+        reader.read_line(line)?;
+        if line.ends_with('\n') {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Request too long or incomplete.",
+            ))
+        }
+    };
+    let mut request_line = String::new();
+    read_line(&mut request_line)?;
+    let mut header = String::new();
+    loop {
+        read_line(&mut header)?;
+        if header.trim_end().is_empty() {
+            break;
+        }
+    }
+    let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+    let path = target.split_once('?').map_or(target, |(path, _)| path);
+    Ok(path.to_owned())
+}
+
+pub fn write_response(
+    mut stream: TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
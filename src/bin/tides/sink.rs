@@ -0,0 +1,68 @@
+//! Where the output of a command is written: stdout, or the file given with --output.
+
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::archive::write_atomically;
+
+/// The destination of a command's output.
+///
+/// Output to a file is held until the command has succeeded, then written atomically, so
+/// that the file is left as it was if the command fails, and never partially written.
+#[derive(Debug)]
+pub enum Sink {
+    Stdout,
+    File { path: PathBuf, buffer: Vec<u8> },
+}
+
+impl Sink {
+    /// Write to the file at `path`, or stdout if `None` or "-".
+    pub fn new(path: Option<PathBuf>) -> Self {
+        match path {
+            Some(path) if path != Path::new("-") => Sink::File {
+                path,
+                buffer: Vec::new(),
+            },
+            _ => Sink::Stdout,
+        }
+    }
+
+    /// The file being written, if not stdout.
+    #[cfg(feature = "chart")]
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Sink::Stdout => None,
+            Sink::File { path, .. } => Some(path),
+        }
+    }
+
+    /// Write the output to the file, creating its parent directories if necessary.
+    pub fn finish(self) -> Result<(), Box<dyn Error>> {
+        let Sink::File { path, buffer } = self else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Could not create {}: {e}", dir.display()))?;
+        }
+        write_atomically(&path, &buffer)
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Sink::Stdout => io::stdout().lock().write(buf),
+            Sink::File { buffer, .. } => buffer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Sink::Stdout => io::stdout().flush(),
+            Sink::File { .. } => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use rjw_uktides::{LunarPhaseType, TidePredictions};
+
+use crate::locale;
+use crate::units::Units;
+use crate::zone::Times;
+
+/// A quarter of the mean synodic month, the time in seconds between successive lunar
+/// phases.
+const QUARTER_LUNATION_SECS: i64 = 637_860;
+
+/// A spring or neap period, derived from a lunar phase.
+#[derive(Debug)]
+pub struct Period {
+    kind: Kind,
+    /// The first and last days of the period.
+    from: NaiveDate,
+    to: NaiveDate,
+    /// The smallest and largest rise or fall between successive tides during the period,
+    /// if it is covered by the predictions.
+    range: Option<(f64, f64)>,
+    /// Whether the phase was projected from the last one in the predictions.
+    estimated: bool,
+    /// The units the range is in.
+    units: Units,
+    times: Times,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Springs,
+    Neaps,
+}
+
+impl Period {
+    /// Whether the date is one of the days of the period.
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        (self.from..=self.to).contains(&date)
+    }
+
+    /// "springs" or "neaps", to tag the days of the period.
+    pub fn tag(&self) -> String {
+        self.kind.name().to_lowercase()
+    }
+}
+
+impl Kind {
+    /// "Springs" or "Neaps".
+    fn name(self) -> &'static str {
+        let phrases = locale::phrases();
+        match self {
+            Kind::Springs => phrases.springs,
+            Kind::Neaps => phrases.neaps,
+        }
+    }
+}
+
+impl Display for Period {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let phrases = locale::phrases();
+        let width = locale::label_width(&[Kind::Springs.name(), Kind::Neaps.name()]);
+        let to = phrases.to;
+        let dates = format!(
+            "{} {to} {}",
+            self.times.format_date(self.from, "%a %-d %b"),
+            self.times.format_date(self.to, "%a %-d %b")
+        );
+        write!(f, "{:<width$}  {dates:<24}  ", self.kind.name())?;
+        match self.range {
+            Some((min, max)) => write!(
+                f,
+                "{} {min:.1} {to} {max:.1} {}",
+                phrases.range,
+                self.units.symbol()
+            ),
+            None if self.estimated => write!(f, "{}", phrases.estimated),
+            None => write!(f, "{}", phrases.beyond_predictions),
+        }
+    }
+}
+
+/// The spring and neap periods following each lunar phase in the predictions, and those
+/// projected to follow the last phase, up to `until`, with the ranges in the units the
+/// predictions have been converted to and the dates in the time zone.
+///
+/// Each period covers the second to fourth days after its phase, when the tides are
+/// largest after new and full moons and smallest after quarter moons.
+pub fn periods(
+    tides: &TidePredictions,
+    until: DateTime<Utc>,
+    units: Units,
+    times: Times,
+) -> Vec<Period> {
+    let mut phases: Vec<(DateTime<Utc>, Kind, bool)> = tides
+        .lunar_phase_list
+        .iter()
+        .map(|p| {
+            let kind = match p.lunar_phase_type {
+                LunarPhaseType::NewMoon | LunarPhaseType::FullMoon => Kind::Springs,
+                LunarPhaseType::FirstQuarter | LunarPhaseType::LastQuarter => Kind::Neaps,
+            };
+            (p.date_time, kind, false)
+        })
+        .collect();
+    phases.sort_by_key(|(date_time, ..)| *date_time);
+    if let Some(&(mut date_time, mut kind, _)) = phases.last() {
+        loop {
+            date_time += Duration::seconds(QUARTER_LUNATION_SECS);
+            kind = match kind {
+                Kind::Springs => Kind::Neaps,
+                Kind::Neaps => Kind::Springs,
+            };
+            if date_time > until {
+                break;
+            }
+            phases.push((date_time, kind, true));
+        }
+    }
+
+    let mut events: Vec<_> = tides.tidal_event_list.iter().collect();
+    events.sort();
+    let today = times.today();
+    phases
+        .into_iter()
+        .map(|(date_time, kind, estimated)| {
+            let from = times.date(date_time) + chrono::Days::new(1);
+            let to = from + chrono::Days::new(2);
+            let within: Vec<_> = events
+                .iter()
+                .filter(|e| (from..=to).contains(&times.date(e.date_time)))
+                .collect();
+            let ranges = within
+                .windows(2)
+                .map(|w| (w[1].height.0 - w[0].height.0).abs());
+            let range = ranges.fold(None, |acc: Option<(f64, f64)>, r| match acc {
+                Some((min, max)) => Some((min.min(r), max.max(r))),
+                None => Some((r, r)),
+            });
+            Period {
+                kind,
+                from,
+                to,
+                range,
+                estimated,
+                units,
+                times,
+            }
+        })
+        .filter(|p| p.to >= today)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Europe::London;
+
+    use super::*;
+    use crate::zone::Clock;
+
+    fn period(from: NaiveDate, range: Option<(f64, f64)>) -> Period {
+        Period {
+            kind: Kind::Springs,
+            from,
+            to: from + chrono::Days::new(2),
+            range,
+            estimated: range.is_none(),
+            units: Units::Metric,
+            times: Times::new(London, Clock::TwentyFour),
+        }
+    }
+
+    #[test]
+    fn separates_the_dates_from_what_follows() {
+        let date = |day| NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+        // 18 to 20 October is two-digit dates, which fill the dates column exactly.
+        for (from, range) in [
+            (date(18), Some((3.2, 4.1))),
+            (date(25), None),
+            (date(1), None),
+        ] {
+            let period = period(from, range);
+            let last_date = period.times.format_date(period.to, "%a %-d %b");
+            let line = period.to_string();
+            let after_dates = &line[line.find(&last_date).unwrap() + last_date.len()..];
+            assert!(after_dates.starts_with("  "), "{line:?}");
+            assert!(!after_dates.trim().is_empty(), "{line:?}");
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Parsing station IDs given on the command line, checked against the stations built into
+//! the binary so that a mistyped ID is reported, with suggestions, before anything is
+//! fetched.
+
+use std::collections::BTreeMap;
+
+use rjw_uktides::{Station, StationId};
+
+use crate::error::CliError;
+
+/// The most stations suggested for an ID or name that matches none.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// A clap value parser for station arguments, which resolves `aliases`, then accepts the
+/// ID of a built-in station in either case.
+///
+/// Other values shaped like an ID are accepted with a warning, since they may be stations
+/// added upstream since the built-in list was made, or served from another base URL.
+pub fn parser(
+    aliases: BTreeMap<String, StationId>,
+) -> impl Fn(&str) -> Result<StationId, CliError> + Clone + Send + Sync + 'static {
+    move |value: &str| {
+        if let Some(station) = aliases.get(value) {
+            return Ok(station.clone());
+        }
+        let stations = rjw_uktides::stations_from_reader(crate::STATIONS_BAKED_BYTES)
+            .expect("the built-in stations are valid");
+        let id = parse(&stations, value)?;
+        if !stations.iter().any(|s| s.id == id) {
+            eprintln!("Warning: {}", unlisted(&stations, value));
+        }
+        Ok(id)
+    }
+}
+
+/// The ID of the station in `stations` matching `value`, or `value` itself if it has the
+/// form of an ID, or otherwise an error suggesting stations with similar names.
+fn parse(stations: &[Station], value: &str) -> Result<StationId, CliError> {
+    if let Some(station) = stations.iter().find(|s| s.id.0.eq_ignore_ascii_case(value)) {
+        return Ok(station.id.clone());
+    }
+    if is_id_shaped(value) {
+        return Ok(StationId(value.to_owned()));
+    }
+    Err(CliError::InvalidStation {
+        value: value.to_owned(),
+        suggestions: suggestions(stations, value)
+            .into_iter()
+            .map(|s| (s.id.clone(), s.name.clone()))
+            .collect(),
+    })
+}
+
+/// The warning for an ID that is not in `stations`, suggesting any it may be a slip for.
+fn unlisted(stations: &[Station], value: &str) -> String {
+    let suggestions = suggestions(stations, value);
+    if suggestions.is_empty() {
+        return format!("{value} is not a built-in station; fetching it anyway");
+    }
+    let mut warning = format!("{value} is not a built-in station; did you mean");
+    for (i, station) in suggestions.iter().enumerate() {
+        let sep = if i == 0 { "" } else { " or" };
+        warning.push_str(&format!("{sep} {} {}", station.id, station.name));
+    }
+    warning.push_str("? Fetching it anyway");
+    warning
+}
+
+/// Stations with IDs one slip away from `value` or, if `value` is a name rather than an
+/// ID, with similar names.
+fn suggestions<'a>(stations: &'a [Station], value: &str) -> Vec<&'a Station> {
+    let is_name = !is_id_shaped(value) && value.bytes().any(|b| b.is_ascii_alphabetic());
+    if is_name {
+        return rjw_uktides::fuzzy_search_stations(stations, value)
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .collect();
+    }
+    let value = value.to_ascii_uppercase();
+    let slips: Vec<_> = stations
+        .iter()
+        .filter_map(|s| Some((slip(&value, &s.id.0)?, s)))
+        .collect();
+    let likeliest = slips.iter().map(|(slip, _)| *slip).min();
+    slips
+        .into_iter()
+        .filter(|(slip, _)| Some(*slip) == likeliest)
+        .map(|(_, s)| s)
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+/// Whether the value has the form of a station ID: a letter or digit, then three digits,
+/// then optionally a letter, such as "0053", "0628A" or "T047A".
+pub fn is_id_shaped(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    (4..=5).contains(&bytes.len())
+        && bytes[0].is_ascii_alphanumeric()
+        && bytes[1..4].iter().all(u8::is_ascii_digit)
+        && bytes[4..].iter().all(u8::is_ascii_alphabetic)
+}
+
+/// A slip in typing an ID, in order from the likeliest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Slip {
+    /// Two adjacent characters swapped, such as "0503" for "0053".
+    Swap,
+    /// A character added, left out, or mistyped.
+    Edit,
+}
+
+/// The single slip that turns `typed` into `id`, if there is one.
+fn slip(typed: &str, id: &str) -> Option<Slip> {
+    let (a, b) = (typed.as_bytes(), id.as_bytes());
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    match (a.len(), b.len()) {
+        (0, 0) => None,
+        (0, 1) | (1, 0) => Some(Slip::Edit),
+        (x, y) if x == y && x >= 2 && a[0] == b[1] && a[1] == b[0] && a[2..] == b[2..] => {
+            Some(Slip::Swap)
+        }
+        (x, y) if x == y => (a[1..] == b[1..]).then_some(Slip::Edit),
+        (x, y) if x == y + 1 => (a[1..] == *b).then_some(Slip::Edit),
+        (x, y) if x + 1 == y => (*a == b[1..]).then_some(Slip::Edit),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stations() -> Vec<Station> {
+        rjw_uktides::stations_from_reader(crate::STATIONS_BAKED_BYTES).unwrap()
+    }
+
+    fn suggested(err: CliError) -> Vec<String> {
+        match err {
+            CliError::InvalidStation { suggestions, .. } => {
+                suggestions.into_iter().map(|(id, _)| id.0).collect()
+            }
+            e => panic!("unexpected error {e:?}"),
+        }
+    }
+
+    #[test]
+    fn accepts_built_in_ids_in_either_case() {
+        let stations = stations();
+        assert_eq!(parse(&stations, "0053").unwrap().0, "0053");
+        assert_eq!(parse(&stations, "0053a").unwrap().0, "0053A");
+    }
+
+    #[test]
+    fn accepts_unlisted_ids_with_a_warning() {
+        let stations = stations();
+        // Not in the built-in list, but may be a new station upstream.
+        assert_eq!(parse(&stations, "9999").unwrap().0, "9999");
+        assert_eq!(
+            unlisted(&stations, "9999"),
+            "9999 is not a built-in station; fetching it anyway"
+        );
+        let warning = unlisted(&stations, "0503");
+        assert!(warning.contains("did you mean 0053 Sandown"), "{warning}");
+        assert!(warning.ends_with("? Fetching it anyway"), "{warning}");
+    }
+
+    #[test]
+    fn rejects_names_suggesting_similar_stations() {
+        let stations = stations();
+        let suggestions = suggested(parse(&stations, "sandown").unwrap_err());
+        assert!(suggestions.contains(&"0053".to_owned()), "{suggestions:?}");
+        assert!(suggestions.len() <= MAX_SUGGESTIONS);
+        assert!(suggested(parse(&stations, "!!").unwrap_err()).is_empty());
+    }
+
+    #[test]
+    fn recognises_id_shaped_values() {
+        for id in ["0053", "0628A", "T047A", "t047a"] {
+            assert!(is_id_shaped(id), "{id}");
+        }
+        for value in ["", "053", "000053", "00S3", "0053AB", "0053-", "Sandown"] {
+            assert!(!is_id_shaped(value), "{value}");
+        }
+    }
+
+    #[test]
+    fn finds_single_slips() {
+        assert_eq!(slip("0503", "0053"), Some(Slip::Swap));
+        assert_eq!(slip("0035", "0053"), Some(Slip::Swap));
+        assert_eq!(slip("053", "0053"), Some(Slip::Edit));
+        assert_eq!(slip("00053", "0053"), Some(Slip::Edit));
+        assert_eq!(slip("0054", "0053"), Some(Slip::Edit));
+        assert_eq!(slip("0053A", "0053"), Some(Slip::Edit));
+        assert_eq!(slip("0053", "0053"), None);
+        assert_eq!(slip("0350", "0053"), None);
+        assert_eq!(slip("1153", "0053"), None);
+        assert!(Slip::Swap < Slip::Edit);
+    }
+}
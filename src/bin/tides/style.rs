@@ -0,0 +1,100 @@
+//! Colour and symbols in the human-readable output, to tell highs, lows and lunar phases
+//! apart at a glance.
+
+use std::io::{self, IsTerminal};
+
+use clap::{Args, ValueEnum};
+
+use rjw_uktides::{LunarPhaseType, TidalEventType};
+
+use crate::locale;
+
+const HIGH_COLOR: &str = "\x1b[1;34m";
+const LOW_COLOR: &str = "\x1b[36m";
+const PHASE_COLOR: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// Options controlling colour and symbols in the output.
+#[derive(Args, Clone, Debug)]
+pub struct StyleArgs {
+    /// When to colour the output. "auto" colours it when printing to a terminal, unless
+    /// the NO_COLOR environment variable is set.
+    #[arg(long, global = true, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Mark high and low tides with ▲ and ▼, and lunar phases with pictures of the moon.
+    #[arg(long, global = true)]
+    fancy: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+/// How to style the output, as chosen by the options.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Style {
+    color: bool,
+    fancy: bool,
+}
+
+impl Style {
+    pub fn new(args: &StyleArgs) -> Self {
+        let color = match args.color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                io::stdout().is_terminal()
+                    && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+            }
+        };
+        Style {
+            color,
+            fancy: args.fancy,
+        }
+    }
+
+    /// The name of the event type, padded to `width` characters and styled.
+    pub fn event_type(&self, event_type: TidalEventType, width: usize) -> String {
+        let (symbol, color) = match event_type {
+            TidalEventType::HighWater => ('▲', HIGH_COLOR),
+            TidalEventType::LowWater => ('▼', LOW_COLOR),
+        };
+        let name = format!("{:<width$}", locale::phrases().event(event_type));
+        let name = if self.fancy {
+            format!("{symbol} {name}")
+        } else {
+            name
+        };
+        self.paint(name, color)
+    }
+
+    /// The name of the lunar phase, styled.
+    pub fn phase(&self, phase: LunarPhaseType) -> String {
+        let text = locale::phrases().phase(phase);
+        let name = if self.fancy {
+            let picture = match phase {
+                LunarPhaseType::NewMoon => '🌑',
+                LunarPhaseType::FirstQuarter => '🌓',
+                LunarPhaseType::FullMoon => '🌕',
+                LunarPhaseType::LastQuarter => '🌗',
+            };
+            format!("{picture} {text}")
+        } else {
+            text.to_owned()
+        };
+        self.paint(name, PHASE_COLOR)
+    }
+
+    fn paint(&self, text: String, color: &str) -> String {
+        if self.color {
+            format!("{color}{}{RESET}", text.trim_end())
+                + &" ".repeat(text.len() - text.trim_end().len())
+        } else {
+            text
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! A month of tides laid out like a printed tide table, with a row for each day and
+//! columns for the morning and afternoon high and low waters.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, Timelike, Utc};
+use clap::Args;
+
+use rjw_uktides::{StationId, TidalEvent, TidalEventType, TidePredictions};
+
+use crate::locale;
+use crate::units::Units;
+use crate::zone::Times;
+
+/// Print a month of tides for a station as a printable tide table.
+///
+/// Each day has the time and height of the morning and afternoon high and low waters.
+/// EasyTide only predicts about a week ahead, so days outside the predictions are left
+/// blank.
+#[derive(Args, Clone, Debug)]
+pub struct TableArgs {
+    /// ID of the desired tidal station.
+    #[arg(short, long)]
+    pub station: StationId,
+
+    /// The month to show, such as 2025-09, rather than the current month.
+    #[arg(long, value_parser = parse_month)]
+    pub month: Option<NaiveDate>,
+
+    /// Write a standalone HTML page rather than plain text.
+    #[arg(long)]
+    pub html: bool,
+}
+
+/// The first day of the month given as YYYY-MM.
+fn parse_month(s: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("{s}-01"), "%Y-%m-%d")
+        .map_err(|_| format!("expected a month such as 2025-09, not {s:?}"))
+}
+
+/// A day's row of the table.
+struct Row {
+    date: NaiveDate,
+    /// The morning and afternoon high waters, then the morning and afternoon low waters.
+    slots: [Option<(DateTime<Utc>, f64)>; 4],
+}
+
+/// The rows for each day of the month beginning on `first`.
+fn rows(tides: &TidePredictions, first: NaiveDate, times: Times) -> Vec<Row> {
+    let days: BTreeMap<_, _> = tides
+        .days_in(times.tz())
+        .map(|day| (day.date, day.events))
+        .collect();
+    let next_month = first + Months::new(1);
+    first
+        .iter_days()
+        .take_while(|d| *d < next_month)
+        .map(|date| Row {
+            date,
+            slots: slots(days.get(&date).copied().unwrap_or_default(), times),
+        })
+        .collect()
+}
+
+/// Place each event in the morning or afternoon column for its type, or in the other
+/// one if two fall in the same half of the day.
+fn slots(events: &[TidalEvent], times: Times) -> [Option<(DateTime<Utc>, f64)>; 4] {
+    let mut slots = [None; 4];
+    for event in events {
+        let base = match event.event_type {
+            TidalEventType::HighWater => 0,
+            TidalEventType::LowWater => 2,
+        };
+        let pm = event.date_time.with_timezone(&times.tz()).hour() >= 12;
+        let preferred = base + usize::from(pm);
+        let index = if slots[preferred].is_none() {
+            preferred
+        } else {
+            base + usize::from(!pm)
+        };
+        slots[index].get_or_insert((event.date_time, event.height.0));
+    }
+    slots
+}
+
+/// The time of a tide in the table, always on the 24-hour clock as the columns show
+/// whether it is morning or afternoon.
+fn time(date_time: DateTime<Utc>, times: Times) -> String {
+    times.format_localized(date_time, "%H:%M")
+}
+
+/// The table as plain text, for printing in a monospaced font.
+pub fn text(
+    name: &str,
+    tides: &TidePredictions,
+    first: NaiveDate,
+    units: Units,
+    times: Times,
+) -> String {
+    let phrases = locale::phrases();
+    // Each slot is a time and a height, such as "04:12  4.2".
+    const SLOT: usize = 11;
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{name}, {} ({}, {})",
+        times.format_date(first, "%B %Y"),
+        times.tz(),
+        units.symbol()
+    );
+    let _ = writeln!(
+        out,
+        "{:8}  {:^w$}  {:^w$}",
+        "",
+        phrases.high_tide,
+        phrases.low_tide,
+        w = SLOT * 2 + 2
+    );
+    let _ = writeln!(
+        out,
+        "{:8}  {:^SLOT$}  {:^SLOT$}  {:^SLOT$}  {:^SLOT$}",
+        "", "AM", "PM", "AM", "PM"
+    );
+    for row in rows(tides, first, times) {
+        let _ = write!(
+            out,
+            "{:>2}  {:<4}",
+            row.date.day(),
+            times.format_date(row.date, "%a")
+        );
+        for slot in row.slots {
+            match slot {
+                Some((date_time, height)) => {
+                    let _ = write!(out, "  {:>5} {height:>5.1}", time(date_time, times));
+                }
+                None => {
+                    let _ = write!(out, "  {:^SLOT$}", "-");
+                }
+            }
+        }
+        out.push('\n');
+    }
+    out.lines().fold(String::new(), |mut text, line| {
+        text.push_str(line.trim_end());
+        text.push('\n');
+        text
+    })
+}
+
+/// The table as a standalone HTML page, styled for printing.
+pub fn html(
+    name: &str,
+    tides: &TidePredictions,
+    first: NaiveDate,
+    units: Units,
+    times: Times,
+) -> String {
+    let phrases = locale::phrases();
+    let title = format!("{name}, {}", times.format_date(first, "%B %Y"));
+    let mut out = String::new();
+    let _ = writeln!(out, "<!DOCTYPE html>");
+    let _ = writeln!(out, "<html>\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<title>{}</title>", escape(&title));
+    let _ = writeln!(
+        out,
+        "<style>\n\
+         body {{ font-family: sans-serif; }}\n\
+         table {{ border-collapse: collapse; }}\n\
+         th, td {{ border: 1px solid #888; padding: 0.1em 0.5em; text-align: right; }}\n\
+         </style>\n</head>\n<body>"
+    );
+    let _ = writeln!(out, "<h1>{}</h1>", escape(&title));
+    let _ = writeln!(
+        out,
+        "<p>{}, {}</p>",
+        escape(&times.tz().to_string()),
+        escape(units.symbol())
+    );
+    let _ = writeln!(out, "<table>\n<thead>");
+    let _ = writeln!(
+        out,
+        "<tr><th colspan=\"2\" rowspan=\"2\"></th><th colspan=\"4\">{}</th><th colspan=\"4\">{}</th></tr>",
+        escape(phrases.high_tide),
+        escape(phrases.low_tide)
+    );
+    let _ = writeln!(
+        out,
+        "<tr>{}</tr>",
+        "<th colspan=\"2\">AM</th><th colspan=\"2\">PM</th>".repeat(2)
+    );
+    let _ = writeln!(out, "</thead>\n<tbody>");
+    for row in rows(tides, first, times) {
+        let _ = write!(
+            out,
+            "<tr><td>{}</td><td>{}</td>",
+            row.date.day(),
+            escape(&times.format_date(row.date, "%a"))
+        );
+        for slot in row.slots {
+            match slot {
+                Some((date_time, height)) => {
+                    let _ = write!(
+                        out,
+                        "<td>{}</td><td>{height:.1}</td>",
+                        time(date_time, times)
+                    );
+                }
+                None => out.push_str("<td></td><td></td>"),
+            }
+        }
+        out.push_str("</tr>\n");
+    }
+    let _ = writeln!(out, "</tbody>\n</table>");
+    if !tides.footer_note.is_empty() {
+        let _ = writeln!(
+            out,
+            "<p><small>{}</small></p>",
+            escape(tides.footer_note.as_str())
+        );
+    }
+    let _ = writeln!(out, "</body>\n</html>");
+    out
+}
+
+/// Escape text for inclusion in HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
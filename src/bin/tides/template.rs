@@ -0,0 +1,271 @@
+use std::fmt::Write;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+
+use rjw_uktides::{StationId, TidalEvent};
+
+use crate::locale;
+use crate::output::rfc3339;
+use crate::zone::Times;
+
+/// A `--template` for printing each tidal event, parsed so that mistakes are reported
+/// before any request is made.
+///
+/// Placeholders are written `{name}` or `{name:spec}`, and literal braces as `{{` and `}}`:
+///
+/// - `{time}`: the time of the tide in the `--tz` time zone, as RFC 3339, or formatted
+///   with a strftime spec such as `{time:%H:%M}`.
+/// - `{type}`: "High tide" or "Low tide".
+/// - `{height}`: the height, in the units chosen with `--units`, to a number of decimal
+///   places with a spec such as `{height:.1}`.
+/// - `{station}`: the station ID.
+/// - `{countdown}`: the time until the tide, such as "in 2 h 10 m", or since it, such as
+///   "2 h 10 m ago", in the language of the locale.
+#[derive(Debug, Clone)]
+pub struct Template(Vec<Part>);
+
+#[derive(Debug, Clone)]
+enum Part {
+    Literal(String),
+    Time(Option<String>),
+    Type,
+    Height(Option<usize>),
+    Station,
+    Countdown,
+}
+
+impl Template {
+    /// Parse the template, for use as a clap value parser.
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| "unclosed '{' in template".to_owned())?;
+                    let (name, spec) = match rest[..end].split_once(':') {
+                        Some((name, spec)) => (name, Some(spec)),
+                        None => (&rest[..end], None),
+                    };
+                    chars = rest[end + 1..].chars();
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Self::placeholder(name, spec)?);
+                }
+                '}' => return Err("unmatched '}' in template; use '}}' for a brace".to_owned()),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self(parts))
+    }
+
+    fn placeholder(name: &str, spec: Option<&str>) -> Result<Part, String> {
+        let no_spec = |part| match spec {
+            None => Ok(part),
+            Some(spec) => Err(format!("{{{name}}} does not take a format, got '{spec}'")),
+        };
+        match name {
+            "time" => match spec {
+                Some(spec) if !formats_times(spec) => Err(format!("invalid time format '{spec}'")),
+                _ => Ok(Part::Time(spec.map(str::to_owned))),
+            },
+            "type" => no_spec(Part::Type),
+            "height" => match spec {
+                None => Ok(Part::Height(None)),
+                Some(spec) => spec
+                    .strip_prefix('.')
+                    .and_then(|places| places.parse().ok())
+                    .map(|places| Part::Height(Some(places)))
+                    .ok_or_else(|| {
+                        format!("invalid height format '{spec}', expected one such as '.1'")
+                    }),
+            },
+            "station" => no_spec(Part::Station),
+            "countdown" => no_spec(Part::Countdown),
+            _ => Err(format!(
+                "unknown placeholder {{{name}}}; \
+                 expected time, type, height, station or countdown"
+            )),
+        }
+    }
+
+    /// Render the template for the event, with the countdown measured from `now` and the
+    /// time in the time zone.
+    pub fn render(
+        &self,
+        station: &StationId,
+        event: &TidalEvent,
+        now: DateTime<Utc>,
+        times: Times,
+    ) -> String {
+        let mut out = String::new();
+        for part in &self.0 {
+            let _ = match part {
+                Part::Literal(text) => write!(out, "{text}"),
+                Part::Time(None) => {
+                    write!(
+                        out,
+                        "{}",
+                        rfc3339(event.date_time.with_timezone(&times.tz()))
+                    )
+                }
+                Part::Time(Some(spec)) => {
+                    write!(out, "{}", times.format_localized(event.date_time, spec))
+                }
+                Part::Type => write!(out, "{}", event.event_type),
+                Part::Height(None) => write!(out, "{}", event.height.0),
+                Part::Height(Some(places)) => write!(out, "{:.*}", places, event.height.0),
+                Part::Station => write!(out, "{station}"),
+                Part::Countdown => write!(out, "{}", relative(now, event.date_time)),
+            };
+        }
+        out
+    }
+}
+
+/// The time between `now` and `then` in days, hours and minutes, such as "2 h 10 m",
+/// whichever comes first.
+pub fn countdown(now: DateTime<Utc>, then: DateTime<Utc>) -> String {
+    let minutes = (then - now).num_minutes().abs();
+    let (days, hours, mins) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{days} d {hours} h")
+    } else if hours > 0 {
+        format!("{hours} h {mins} m")
+    } else {
+        format!("{mins} m")
+    }
+}
+
+/// The [`countdown`] to `then` as a phrase, such as "in 2 h 10 m", or "2 h 10 m ago" once
+/// it has passed.
+fn relative(now: DateTime<Utc>, then: DateTime<Utc>) -> String {
+    let phrases = locale::phrases();
+    if (then - now).num_minutes() < 0 {
+        format!("{} {}", countdown(now, then), phrases.ago)
+    } else {
+        format!("{} {}", phrases.within, countdown(now, then))
+    }
+}
+
+/// Whether chrono can format times with the strftime spec. It parses `%#z`, which is
+/// only for parsing, and panics when formatting it, and formatting fails rather than
+/// panics for any others it cannot write.
+fn formats_times(spec: &str) -> bool {
+    let parse_only: Vec<_> = StrftimeItems::new("%#z").collect();
+    if StrftimeItems::new(spec).any(|i| matches!(i, Item::Error) || parse_only.contains(&i)) {
+        return false;
+    }
+    write!(String::new(), "{}", Utc::now().format(spec)).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use chrono_tz::Europe::London;
+
+    use super::*;
+    use crate::zone::Clock;
+
+    const TIDES_BYTES: &[u8] = include_bytes!("../../../reference/tides.json");
+
+    /// The high tide at 04:07 UTC on 3 April 2023, of 4.16 m.
+    fn event() -> TidalEvent {
+        let tides = rjw_uktides::tides_from_reader(TIDES_BYTES).unwrap();
+        tides.tidal_event_list[0].clone()
+    }
+
+    fn render(template: &str, now: DateTime<Utc>) -> String {
+        let station = StationId("0053".to_owned());
+        let times = Times::new(London, Clock::TwentyFour);
+        Template::parse(template)
+            .unwrap()
+            .render(&station, &event(), now, times)
+    }
+
+    fn utc(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, 4, 3, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn renders_the_placeholders() {
+        let now = utc(4, 7);
+        assert_eq!(render("{time}", now), "2023-04-03T05:07:00+01:00");
+        assert_eq!(render("{time:%H:%M}", now), "05:07");
+        assert_eq!(render("{type}", now), event().event_type.to_string());
+        assert_eq!(render("{height}", now), "4.16");
+        assert_eq!(render("{height:.1}", now), "4.2");
+        assert_eq!(render("{station}: {height:.0} m", now), "0053: 4 m");
+    }
+
+    #[test]
+    fn renders_escaped_braces() {
+        let now = utc(4, 7);
+        assert_eq!(render("{{station}}", now), "{station}");
+        assert_eq!(render("{{{station}}}", now), "{0053}");
+        assert_eq!(render("}} {{", now), "} {");
+    }
+
+    #[test]
+    fn reports_mistakes() {
+        let err = |template| Template::parse(template).unwrap_err();
+        assert!(err("{tide}").starts_with("unknown placeholder {tide}"));
+        assert!(err("{time").starts_with("unclosed '{'"));
+        assert!(err("time}").starts_with("unmatched '}'"));
+        assert_eq!(err("{time:%Q}"), "invalid time format '%Q'");
+        assert_eq!(err("{time:%#z}"), "invalid time format '%#z'");
+        assert!(err("{height:1}").starts_with("invalid height format '1'"));
+        assert!(err("{height:.x}").starts_with("invalid height format '.x'"));
+        assert_eq!(err("{type:x}"), "{type} does not take a format, got 'x'");
+        assert_eq!(
+            err("{countdown:%H}"),
+            "{countdown} does not take a format, got '%H'"
+        );
+    }
+
+    #[test]
+    fn counts_down_before_and_after_the_tide() {
+        let phrases = locale::phrases();
+        assert_eq!(
+            render("{countdown}", utc(1, 57)),
+            format!("{} 2 h 10 m", phrases.within)
+        );
+        assert_eq!(
+            render("{countdown}", utc(4, 7)),
+            format!("{} 0 m", phrases.within)
+        );
+        assert_eq!(
+            render("{countdown}", utc(6, 17)),
+            format!("2 h 10 m {}", phrases.ago)
+        );
+        let days_before = Utc.with_ymd_and_hms(2023, 4, 1, 1, 0, 0).unwrap();
+        assert_eq!(
+            render("{countdown}", days_before),
+            format!("{} 2 d 3 h", phrases.within)
+        );
+    }
+
+    #[test]
+    fn counts_down_without_direction() {
+        assert_eq!(countdown(utc(1, 57), utc(4, 7)), "2 h 10 m");
+        assert_eq!(countdown(utc(4, 7), utc(1, 57)), "2 h 10 m");
+        assert_eq!(countdown(utc(4, 7), utc(4, 10)), "3 m");
+    }
+}
@@ -0,0 +1,416 @@
+//! An interactive terminal browser for stations and their predictions, drawn with ratatui.
+
+use std::collections::HashMap;
+use std::error::Error;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use clap::Args;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Margin, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::symbols::Marker;
+use ratatui::text::Line;
+use ratatui::widgets::{
+    Axis, Block, Borders, Chart, Dataset, GraphType, List, ListState, Paragraph,
+};
+use ratatui::{DefaultTerminal, Frame};
+
+use rjw_uktides::{Station, StationId, TidalEventType, TidePredictions};
+
+use crate::fetch::Fetcher;
+use crate::locale;
+use crate::units::Units;
+use crate::zone::Times;
+use crate::STATIONS_BAKED_BYTES;
+
+/// Width of the station list, including its border.
+const LIST_WIDTH: u16 = 33;
+/// Minutes between the heights plotted on the chart.
+const CHART_STEP_MINUTES: i64 = 10;
+const HELP: &str = "Type to search  ↑↓ station  Enter show  ←→ day  Esc quit";
+
+/// Browse stations and their predictions interactively.
+///
+/// Type to search the station list, press Enter to show the selected station's
+/// predictions, and use the left and right arrow keys to move between days.
+#[derive(Args, Clone, Debug)]
+pub struct TuiArgs {
+    /// Fetch the current station list rather than using the built-in list.
+    #[arg(long)]
+    fetch: bool,
+}
+
+pub fn tui(
+    args: TuiArgs,
+    fetcher: &Fetcher,
+    units: Units,
+    times: Times,
+) -> Result<(), Box<dyn Error>> {
+    let mut stations = if args.fetch {
+        fetcher.stations()?
+    } else {
+        rjw_uktides::stations_from_reader(STATIONS_BAKED_BYTES)?
+    };
+    stations.sort();
+    let mut browser = Browser::new(&stations, units, times, times.today());
+    let mut terminal = ratatui::try_init()?;
+    let result = browser.run(&mut terminal, fetcher);
+    ratatui::restore();
+    result
+}
+
+struct Browser<'a> {
+    units: Units,
+    times: Times,
+    stations: &'a [Station],
+    query: String,
+    /// The selected station among those matching the query, and the list's scrolling.
+    list: ListState,
+    /// The station whose predictions are shown.
+    shown: Option<&'a Station>,
+    /// The day shown, in the time zone.
+    day: NaiveDate,
+    /// Predictions fetched so far, or the reason they could not be.
+    predictions: HashMap<StationId, Result<TidePredictions, String>>,
+    /// A message shown in place of the help while fetching.
+    status: Option<String>,
+}
+
+impl<'a> Browser<'a> {
+    fn new(stations: &'a [Station], units: Units, times: Times, day: NaiveDate) -> Self {
+        Browser {
+            units,
+            times,
+            stations,
+            query: String::new(),
+            list: ListState::default().with_selected(Some(0)),
+            shown: None,
+            day,
+            predictions: HashMap::new(),
+            status: None,
+        }
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        fetcher: &Fetcher,
+    ) -> Result<(), Box<dyn Error>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let selected = self.list.selected().unwrap_or(0);
+            let last = self.matching().len().saturating_sub(1);
+            match key.code {
+                KeyCode::Esc => break,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                KeyCode::Up => self.list.select(Some(selected.saturating_sub(1))),
+                KeyCode::Down => self.list.select(Some((selected + 1).min(last))),
+                KeyCode::PageUp => self.list.select(Some(selected.saturating_sub(10))),
+                KeyCode::PageDown => self.list.select(Some((selected + 10).min(last))),
+                KeyCode::Left => self.day = self.day.pred_opt().unwrap_or(self.day),
+                KeyCode::Right => self.day = self.day.succ_opt().unwrap_or(self.day),
+                KeyCode::Enter => self.show(terminal, fetcher)?,
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.list.select(Some(0));
+                }
+                KeyCode::Char(c) if !c.is_control() => {
+                    self.query.push(c);
+                    self.list.select(Some(0));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Stations matching the search, or all of them if there is none.
+    fn matching(&self) -> Vec<&'a Station> {
+        if self.query.trim().is_empty() {
+            self.stations.iter().collect()
+        } else {
+            rjw_uktides::fuzzy_search_stations(self.stations, &self.query)
+        }
+    }
+
+    /// Show the selected station's predictions, fetching them if not already fetched.
+    fn show(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        fetcher: &Fetcher,
+    ) -> Result<(), Box<dyn Error>> {
+        let selected = self.list.selected().unwrap_or(0);
+        let Some(station) = self.matching().get(selected).copied() else {
+            return Ok(());
+        };
+        self.shown = Some(station);
+        if !self.predictions.contains_key(&station.id) {
+            self.status = Some(format!("Fetching predictions for {}…", station.name));
+            terminal.draw(|frame| self.draw(frame))?;
+            self.status = None;
+            let result = crate::fetch_tides(fetcher, &station.id).map(|mut tides| {
+                self.units.convert(&mut tides);
+                tides
+            });
+            self.predictions
+                .insert(station.id.clone(), result.map_err(|e| e.to_string()));
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [search, body, help] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list, pane] =
+            Layout::horizontal([Constraint::Length(LIST_WIDTH), Constraint::Min(0)]).areas(body);
+
+        frame.render_widget(Paragraph::new(format!("Search: {}", self.query)), search);
+        let items = self
+            .matching()
+            .into_iter()
+            .map(|s| format!("{:<6} {}", s.id, s.name));
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(Block::new().borders(Borders::RIGHT))
+                .highlight_style(Style::new().reversed()),
+            list,
+            &mut self.list,
+        );
+        self.draw_pane(frame, pane);
+        let status = self.status.as_deref().unwrap_or(HELP);
+        frame.render_widget(Paragraph::new(status), help);
+    }
+
+    /// Draw the shown station's predictions for the day, and a chart of its heights.
+    fn draw_pane(&self, frame: &mut Frame, area: Rect) {
+        let area = area.inner(Margin::new(1, 0));
+        let details = self.details();
+        let Some(Ok(tides)) = self.shown.and_then(|s| self.predictions.get(&s.id)) else {
+            frame.render_widget(Paragraph::new(details), area);
+            return;
+        };
+        let [text, chart] = Layout::vertical([
+            Constraint::Length(details.len() as u16 + 1),
+            Constraint::Min(0),
+        ])
+        .areas(area);
+        frame.render_widget(Paragraph::new(details), text);
+
+        let start = self.times.midnight(self.day);
+        let end = self.times.midnight(self.day.succ_opt().unwrap_or(self.day));
+        let hours = |time: DateTime<Utc>| (time - start).num_minutes() as f64 / 60.0;
+        let heights: Vec<(f64, f64)> = (0..)
+            .map(|step| start + Duration::minutes(step * CHART_STEP_MINUTES))
+            .take_while(|time| *time <= end)
+            .filter_map(|time| tides.height_at(time).map(|h| (hours(time), h.0)))
+            .collect();
+        if heights.is_empty() {
+            frame.render_widget(Paragraph::new("No heights available to chart."), chart);
+            return;
+        }
+        let events: Vec<(f64, f64)> = tides
+            .tidal_event_list
+            .iter()
+            .filter(|e| (start..end).contains(&e.date_time))
+            .map(|e| (hours(e.date_time), e.height.0))
+            .collect();
+        let min = heights.iter().map(|h| h.1).fold(f64::INFINITY, f64::min);
+        let max = heights
+            .iter()
+            .map(|h| h.1)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let symbol = self.units.symbol();
+        let day_hours = hours(end);
+        let datasets = vec![
+            Dataset::default()
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .cyan()
+                .data(&heights),
+            Dataset::default()
+                .marker(Marker::Dot)
+                .graph_type(GraphType::Scatter)
+                .yellow()
+                .data(&events),
+        ];
+        let chart_widget = Chart::new(datasets)
+            .x_axis(Axis::default().bounds([0.0, day_hours]).labels([
+                "00:00".to_owned(),
+                "12:00".to_owned(),
+                "24:00".to_owned(),
+            ]))
+            .y_axis(
+                Axis::default()
+                    .bounds([min, max])
+                    .labels([format!("{min:.1} {symbol}"), format!("{max:.1} {symbol}")]),
+            );
+        frame.render_widget(chart_widget, chart);
+    }
+
+    /// Lines describing the shown station's predictions for the day.
+    fn details(&self) -> Vec<Line<'static>> {
+        let Some(station) = self.shown else {
+            return vec!["Select a station and press Enter to show its predictions.".into()];
+        };
+        let mut lines: Vec<Line> = vec![
+            format!(
+                "{} ({}), {}",
+                station.name,
+                station.id,
+                locale::phrases().country(&station.country)
+            )
+            .bold()
+            .into(),
+            format!("◀ {} ▶", self.times.format_date(self.day, "%A %-d %B %Y")).into(),
+            Line::default(),
+        ];
+        let tides = match self.predictions.get(&station.id) {
+            Some(Ok(tides)) => tides,
+            Some(Err(e)) => {
+                lines.push(format!("Could not fetch predictions: {e}").into());
+                return lines;
+            }
+            None => return lines,
+        };
+        let events: Vec<_> = tides
+            .tidal_event_list
+            .iter()
+            .filter(|e| self.times.date(e.date_time) == self.day)
+            .collect();
+        if events.is_empty() {
+            lines.push("No tides predicted for this day.".into());
+        }
+        for event in events {
+            let line = format!(
+                "{}  {:<10} {:.1} {}",
+                self.times.format(event.date_time, "%H:%M %Z"),
+                locale::phrases().event(event.event_type),
+                event.height.0,
+                self.units.symbol()
+            );
+            lines.push(match event.event_type {
+                TidalEventType::HighWater => line.into(),
+                TidalEventType::LowWater => line.dim().into(),
+            });
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Tz;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use super::*;
+    use crate::zone::Clock;
+
+    const TIDES_BYTES: &[u8] = include_bytes!("../../../reference/tides.json");
+
+    fn stations() -> Vec<Station> {
+        let mut stations = rjw_uktides::stations_from_reader(STATIONS_BAKED_BYTES).unwrap();
+        stations.sort();
+        stations
+    }
+
+    fn browser(stations: &[Station]) -> Browser<'_> {
+        let times = Times::new(Tz::UTC, Clock::TwentyFour);
+        let day = NaiveDate::from_ymd_opt(2023, 4, 3).unwrap();
+        Browser::new(stations, Units::Metric, times, day)
+    }
+
+    /// The rows of the screen after drawing the browser.
+    fn render(browser: &mut Browser) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(100, 24)).unwrap();
+        terminal.draw(|frame| browser.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lists_the_stations_matching_the_search() {
+        let stations = stations();
+        let mut browser = browser(&stations);
+        let screen = render(&mut browser);
+        assert!(screen[0].starts_with("Search: "));
+        let first = format!("{:<6} {}", stations[0].id, stations[0].name);
+        assert!(screen[1].starts_with(&first), "{screen:#?}");
+        assert!(screen[1].contains("Select a station and press Enter"));
+        assert!(screen[23].starts_with(HELP));
+
+        browser.query = "sandown".to_owned();
+        let screen = render(&mut browser);
+        assert!(screen[0].starts_with("Search: sandown"));
+        assert!(screen[1].starts_with("0053   Sandown"), "{screen:#?}");
+        assert!(screen[2].starts_with("        "), "{screen:#?}");
+    }
+
+    #[test]
+    fn highlights_the_selected_station() {
+        let stations = stations();
+        let mut browser = browser(&stations);
+        browser.list.select(Some(2));
+        let mut terminal = Terminal::new(TestBackend::new(100, 24)).unwrap();
+        terminal.draw(|frame| browser.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let reversed = |y| {
+            buffer[(0, y)]
+                .modifier
+                .contains(ratatui::style::Modifier::REVERSED)
+        };
+        assert!(!reversed(2));
+        assert!(reversed(3));
+    }
+
+    #[test]
+    fn shows_the_days_tides_and_chart() {
+        let stations = stations();
+        let sandown = stations.iter().position(|s| s.id.0 == "0053").unwrap();
+        let mut browser = browser(&stations);
+        let tides = rjw_uktides::tides_from_reader(TIDES_BYTES).unwrap();
+        browser.shown = Some(&stations[sandown]);
+        browser
+            .predictions
+            .insert(stations[sandown].id.clone(), Ok(tides));
+        let screen = render(&mut browser).join("\n");
+        assert!(screen.contains("Sandown (0053)"), "{screen}");
+        assert!(screen.contains("◀ Monday 3 April 2023 ▶"), "{screen}");
+        assert!(screen.contains("04:07 UTC"), "{screen}");
+        assert!(screen.contains("4.2 m"), "{screen}");
+        assert!(screen.contains("22:45 UTC"), "{screen}");
+        // The next day's tides are not listed.
+        assert!(!screen.contains("04:57 UTC"), "{screen}");
+        // The chart's axis labels.
+        assert!(screen.contains("12:00"), "{screen}");
+        assert!(screen.contains("24:00"), "{screen}");
+    }
+
+    #[test]
+    fn reports_failed_fetches() {
+        let stations = stations();
+        let mut browser = browser(&stations);
+        browser.shown = Some(&stations[0]);
+        browser
+            .predictions
+            .insert(stations[0].id.clone(), Err("timed out".to_owned()));
+        let screen = render(&mut browser).join("\n");
+        assert!(screen.contains("Could not fetch predictions: timed out"));
+    }
+}
@@ -0,0 +1,44 @@
+//! Units for the heights in the output.
+
+use clap::ValueEnum;
+
+use rjw_uktides::TidePredictions;
+
+/// Metres in a foot.
+const METRES_PER_FOOT: f64 = 0.3048;
+
+/// Units for heights, which EasyTide gives in metres.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Units {
+    /// Metres.
+    #[default]
+    Metric,
+    /// Feet, as in older tide tables.
+    Imperial,
+}
+
+impl Units {
+    /// The symbol labelling heights in these units.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Units::Metric => "m",
+            Units::Imperial => "ft",
+        }
+    }
+
+    /// Convert the heights of the tides and height predictions to these units, for
+    /// output, rounded to hundredths as EasyTide's heights in metres are. The heights
+    /// remain in their `Metres` fields.
+    pub fn convert(self, tides: &mut TidePredictions) {
+        if self == Units::Metric {
+            return;
+        }
+        let feet = |metres: f64| (metres / METRES_PER_FOOT * 100.0).round() / 100.0;
+        for event in &mut tides.tidal_event_list {
+            event.height.0 = feet(event.height.0);
+        }
+        for height in &mut tides.tidal_height_occurrence_list {
+            height.height.0 = feet(height.height.0);
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! A continuously updated display of the tide at a station, for leaving on a screen.
+
+use std::error::Error;
+use std::io::{self, IsTerminal, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use clap::Args;
+
+use rjw_uktides::{Metres, StationId, TidalEvent, TidalEventType, TidePredictions};
+
+use crate::fetch::Fetcher;
+use crate::locale;
+use crate::units::Units;
+use crate::zone::Times;
+
+/// Clear the terminal and move the cursor to the top left.
+const CLEAR_SCREEN: &str = "\x1b[H\x1b[2J";
+
+/// Show a live countdown to the next tide and the current height, until interrupted.
+///
+/// The display is redrawn every second when output is to a terminal, and otherwise written
+/// once each time the predictions are refreshed.
+#[derive(Args, Clone, Debug)]
+pub struct WatchArgs {
+    /// ID of the tidal station to watch.
+    #[arg(short, long)]
+    station: StationId,
+
+    /// Minutes between refreshing the predictions.
+    #[arg(long, default_value_t = 15)]
+    interval_minutes: u64,
+
+    /// Report when a refresh moves an upcoming tide by more than the tolerances, or adds
+    /// or removes one, as when a surge forecast revises the predictions.
+    #[arg(long)]
+    alert_on_change: bool,
+
+    /// How far a tide's time must move to be reported, such as "10m".
+    #[arg(
+        long,
+        default_value = "10m",
+        value_parser = crate::duration::parse_duration,
+        requires = "alert_on_change"
+    )]
+    time_tolerance: chrono::Duration,
+
+    /// How far a tide's height must move to be reported, in the units shown.
+    #[arg(long, default_value_t = 0.1, requires = "alert_on_change")]
+    height_tolerance: f64,
+}
+
+pub fn watch(
+    args: WatchArgs,
+    fetcher: &Fetcher,
+    units: Units,
+    times: Times,
+) -> Result<(), Box<dyn Error>> {
+    let name = crate::station_name(&args.station)?;
+    let interval = Duration::from_secs(args.interval_minutes.max(1) * 60);
+    let terminal = io::stdout().is_terminal();
+    let mut tides = crate::fetch_tides(fetcher, &args.station)?;
+    units.convert(&mut tides);
+    let mut updated = Utc::now();
+    let mut last_refresh = Instant::now();
+    let mut refresh_error = None;
+    let mut alerts: Option<(DateTime<Utc>, Vec<String>)> = None;
+    loop {
+        if last_refresh.elapsed() >= interval {
+            last_refresh = Instant::now();
+            match fetcher.tides(&args.station) {
+                Ok(mut fresh) => {
+                    units.convert(&mut fresh);
+                    if args.alert_on_change {
+                        let changes = changes(&tides, &fresh, Utc::now(), &args, units, times);
+                        if !changes.is_empty() {
+                            #[cfg(feature = "notify")]
+                            let changes = notify_changes(&name, changes);
+                            alerts = Some((Utc::now(), changes));
+                        }
+                    }
+                    tides = fresh;
+                    updated = Utc::now();
+                    refresh_error = None;
+                }
+                Err(e) => refresh_error = Some(e.to_string()),
+            }
+            if !terminal {
+                println!();
+            }
+        }
+        let mut frame = render(
+            &name,
+            &tides,
+            Utc::now(),
+            updated,
+            args.interval_minutes,
+            units,
+            times,
+        );
+        if let Some(e) = &refresh_error {
+            frame.push_str(&format!(
+                "Refreshing failed, showing older predictions: {e}\n"
+            ));
+        }
+        if let Some((at, changes)) = &alerts {
+            frame.push_str(&format!(
+                "\nPredictions changed at {}:\n",
+                times.format(*at, "%H:%M %Z")
+            ));
+            for change in changes {
+                frame.push_str(&format!("  {change}\n"));
+            }
+        }
+        let mut stdout = io::stdout().lock();
+        if terminal {
+            write!(stdout, "{CLEAR_SCREEN}{frame}")?;
+            stdout.flush()?;
+            drop(stdout);
+            thread::sleep(Duration::from_secs(1));
+        } else {
+            write!(stdout, "{frame}")?;
+            stdout.flush()?;
+            drop(stdout);
+            // Each change is written once, with the refresh that found it.
+            alerts = None;
+            thread::sleep(interval.saturating_sub(last_refresh.elapsed()));
+        }
+    }
+}
+
+/// Descriptions of the upcoming tides that moved by more than the tolerances between the
+/// `old` and `new` predictions, or that were added or removed.
+fn changes(
+    old: &TidePredictions,
+    new: &TidePredictions,
+    now: DateTime<Utc>,
+    args: &WatchArgs,
+    units: Units,
+    times: Times,
+) -> Vec<String> {
+    let symbol = units.symbol();
+    let phrases = locale::phrases();
+    let tide = |event: &TidalEvent| {
+        format!(
+            "{} {} {:.2} {symbol}",
+            phrases.event(event.event_type),
+            times.format(event.date_time, "%a %H:%M %Z"),
+            event.height.0
+        )
+    };
+    let diff = rjw_uktides::diff_tides(old, new);
+    let mut changes: Vec<String> = diff
+        .exceeding(args.time_tolerance, Metres(args.height_tolerance))
+        .filter(|shift| shift.new.date_time > now)
+        .map(|shift| {
+            format!(
+                "{} is now {} {:.2} {symbol} ({:+} min, {:+.2} {symbol})",
+                tide(shift.old),
+                times.format(shift.new.date_time, "%a %H:%M %Z"),
+                shift.new.height.0,
+                shift.time_shift().num_minutes(),
+                shift.height_shift().0
+            )
+        })
+        .collect();
+    changes.extend(
+        diff.added
+            .iter()
+            .filter(|e| e.date_time > now)
+            .map(|e| format!("{} added", tide(e))),
+    );
+    changes.extend(
+        diff.removed
+            .iter()
+            .filter(|e| e.date_time > now)
+            .map(|e| format!("{} removed", tide(e))),
+    );
+    changes
+}
+
+/// Show the changes as a desktop notification, adding a line to them if it fails.
+#[cfg(feature = "notify")]
+fn notify_changes(name: &str, mut changes: Vec<String>) -> Vec<String> {
+    let summary = format!("Tides changed at {name}");
+    if let Err(e) = crate::notify::show(&summary, &changes.join("\n")) {
+        changes.push(e.to_string());
+    }
+    changes
+}
+
+/// The lines displayed for the station at `now`, with times in the time zone.
+fn render(
+    name: &str,
+    tides: &TidePredictions,
+    now: DateTime<Utc>,
+    updated: DateTime<Utc>,
+    interval_minutes: u64,
+    units: Units,
+    times: Times,
+) -> String {
+    let symbol = units.symbol();
+    let phrases = locale::phrases();
+    let width = 2 + locale::label_width(&[phrases.height, phrases.next]);
+    let mut frame = format!("{name}, {}\n\n", times.format(now, "%H:%M:%S %Z"));
+    let next = [TidalEventType::HighWater, TidalEventType::LowWater]
+        .iter()
+        .filter_map(|t| tides.next_event(now, *t))
+        .min_by_key(|e| e.date_time);
+    let height = tides.height_at(now).map(|h| h.0);
+    let height = match (height, next) {
+        (Some(height), Some(next)) => {
+            let direction = match next.event_type {
+                TidalEventType::HighWater => phrases.rising,
+                TidalEventType::LowWater => phrases.falling,
+            };
+            format!("{height:.2} {symbol}, {direction}")
+        }
+        (Some(height), None) => format!("{height:.2} {symbol}"),
+        (None, _) => phrases.not_available.to_owned(),
+    };
+    frame.push_str(&format!("{:<width$}{height}\n", phrases.height));
+    let next = match next {
+        Some(next) => format!(
+            "{} {:.1} {symbol} {} {}, {} {}",
+            phrases.event(next.event_type),
+            next.height.0,
+            phrases.at,
+            times.format(next.date_time, "%H:%M %Z"),
+            phrases.within,
+            countdown(now, next.date_time)
+        ),
+        None => phrases.no_upcoming_tides.to_owned(),
+    };
+    frame.push_str(&format!("{:<width$}{next}\n", phrases.next));
+    frame.push_str(&format!(
+        "\n{} {}, {} {interval_minutes} min.\n",
+        phrases.updated_at,
+        times.format(updated, "%H:%M %Z"),
+        phrases.refreshed_every
+    ));
+    frame
+}
+
+/// The time until `then` as hours, minutes and seconds, such as "2:05:09".
+fn countdown(now: DateTime<Utc>, then: DateTime<Utc>) -> String {
+    let seconds = (then - now).num_seconds().max(0);
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
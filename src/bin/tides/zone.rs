@@ -0,0 +1,149 @@
+//! Dates and times as shown to the user: in the time zone chosen with `--tz`, on the clock
+//! chosen with `--clock`, and with day and month names in the language of the locale.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use clap::ValueEnum;
+
+use crate::locale::{self, Names};
+
+/// The clock used to show times.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Clock {
+    /// Such as "5:42 pm".
+    #[value(name = "12")]
+    Twelve,
+    /// Such as "17:42".
+    #[value(name = "24")]
+    TwentyFour,
+}
+
+/// How to show dates and times.
+#[derive(Clone, Copy, Debug)]
+pub struct Times {
+    tz: Tz,
+    clock: Clock,
+    names: &'static Names,
+}
+
+impl Times {
+    /// Show times in the time zone on the clock, with names for the user's locale.
+    pub fn new(tz: Tz, clock: Clock) -> Self {
+        Times {
+            tz,
+            clock,
+            names: locale::names(),
+        }
+    }
+
+    pub fn tz(&self) -> Tz {
+        self.tz
+    }
+
+    /// Today's date in the time zone.
+    pub fn today(&self) -> NaiveDate {
+        self.date(Utc::now())
+    }
+
+    /// The date of the time in the time zone.
+    pub fn date(&self, date_time: DateTime<Utc>) -> NaiveDate {
+        date_time.with_timezone(&self.tz).date_naive()
+    }
+
+    /// The start of the date in the time zone, which is an hour after midnight on days
+    /// when the clocks go forward at midnight.
+    pub fn midnight(&self, date: NaiveDate) -> DateTime<Utc> {
+        let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+        self.tz
+            .from_local_datetime(&midnight)
+            .earliest()
+            .or_else(|| {
+                self.tz
+                    .from_local_datetime(&(midnight + Duration::hours(1)))
+                    .earliest()
+            })
+            .map_or_else(
+                || Utc.from_utc_datetime(&midnight),
+                |t| t.with_timezone(&Utc),
+            )
+    }
+
+    /// Format the time in the time zone with a strftime `pattern` written for the 24-hour
+    /// clock, whose "%H:%M" is shown on the 12-hour clock if that was chosen.
+    pub fn format(&self, date_time: DateTime<Utc>, pattern: &str) -> String {
+        let pattern = match self.clock {
+            Clock::Twelve => pattern
+                .replace("%H:%M:%S", "%-I:%M:%S %p")
+                .replace("%H:%M", "%-I:%M %p"),
+            Clock::TwentyFour => pattern.to_owned(),
+        };
+        self.format_localized(date_time, &pattern)
+    }
+
+    /// The widest a time formatted with "%H:%M" can be, for aligning columns of times.
+    pub fn time_width(&self) -> usize {
+        match self.clock {
+            Clock::Twelve => {
+                "12:00".len()
+                    + 1
+                    + self
+                        .names
+                        .am
+                        .chars()
+                        .count()
+                        .max(self.names.pm.chars().count())
+            }
+            Clock::TwentyFour => "12:00".len(),
+        }
+    }
+
+    /// Format the time in the time zone with a strftime `pattern` as given, except for
+    /// using the locale's names.
+    pub fn format_localized(&self, date_time: DateTime<Utc>, pattern: &str) -> String {
+        let local = date_time.with_timezone(&self.tz);
+        let pattern = self.names(pattern, local.date_naive(), local.hour() >= 12);
+        local.format(&pattern).to_string()
+    }
+
+    /// Format the date with a strftime `pattern`, using the locale's names.
+    pub fn format_date(&self, date: NaiveDate, pattern: &str) -> String {
+        date.format(&self.names(pattern, date, false)).to_string()
+    }
+
+    /// Replace the day, month, and morning or afternoon specifiers in the pattern with
+    /// the locale's names for the date and time.
+    fn names(&self, pattern: &str, date: NaiveDate, pm: bool) -> String {
+        let day = date.weekday().num_days_from_sunday() as usize;
+        let month = date.month0() as usize;
+        let mut out = String::with_capacity(pattern.len());
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+            let flag = chars.next_if(|f| matches!(f, '-' | '_' | '0'));
+            let name = match chars.peek() {
+                Some('A') => Some(self.names.days[day]),
+                Some('a') => Some(self.names.short_days[day]),
+                Some('B') => Some(self.names.months[month]),
+                Some('b' | 'h') => Some(self.names.short_months[month]),
+                Some('p') if pm => Some(self.names.pm),
+                Some('p') => Some(self.names.am),
+                _ => None,
+            };
+            match name {
+                Some(name) => {
+                    chars.next();
+                    out.push_str(name);
+                }
+                None => {
+                    out.push('%');
+                    out.extend(flag);
+                    out.extend(chars.next());
+                }
+            }
+        }
+        out
+    }
+}
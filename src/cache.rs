@@ -0,0 +1,252 @@
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{Client, Station};
+
+type Fetcher = Box<dyn Fn() -> Result<Vec<Station>, Box<dyn Error>> + Send + Sync>;
+type Listener = Box<dyn Fn(&[Station]) + Send + Sync>;
+
+/// Thread-safe in-memory cache of the station list.
+///
+/// The station list is large and changes rarely, so long-running services can share a
+/// `StationCache` rather than fetching the list for every request. [`get_or_fetch`] returns
+/// the cached list while it is younger than the configured maximum age, and fetches it again
+/// otherwise. Concurrent callers wait for a single fetch rather than each fetching the list.
+///
+/// Listeners registered with [`on_change`] are called whenever a fetch returns a list that
+/// differs from the one previously cached.
+///
+/// [`get_or_fetch`]: StationCache::get_or_fetch
+/// [`on_change`]: StationCache::on_change
+///
+/// # Examples
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// use rjw_uktides::StationCache;
+///
+/// let fetches = Arc::new(AtomicUsize::new(0));
+/// let counter = Arc::clone(&fetches);
+/// let cache = StationCache::with_fetcher(Duration::from_secs(3600), move || {
+///     counter.fetch_add(1, Ordering::SeqCst);
+///     let file = std::fs::File::open("./stations.json")?;
+///     Ok(rjw_uktides::stations_from_reader(file)?)
+/// });
+///
+/// let first = cache.get_or_fetch().expect("Failed to fetch stations.");
+/// let second = cache.get_or_fetch().expect("Failed to fetch stations.");
+/// assert!(Arc::ptr_eq(&first, &second));
+/// assert_eq!(fetches.load(Ordering::SeqCst), 1);
+/// ```
+pub struct StationCache {
+    max_age: Duration,
+    fetcher: Fetcher,
+    cached: Mutex<Option<CachedStations>>,
+    listeners: Mutex<Vec<Listener>>,
+}
+
+struct CachedStations {
+    fetched_at: Instant,
+    stations: Arc<Vec<Station>>,
+}
+
+impl StationCache {
+    /// Create an empty cache that fetches stations using `client`.
+    pub fn new(client: Client, max_age: Duration) -> Self {
+        Self::with_fetcher(max_age, move || client.fetch_stations())
+    }
+
+    /// Create an empty cache that fetches stations by calling `fetcher`.
+    pub fn with_fetcher<F>(max_age: Duration, fetcher: F) -> Self
+    where
+        F: Fn() -> Result<Vec<Station>, Box<dyn Error>> + Send + Sync + 'static,
+    {
+        Self {
+            max_age,
+            fetcher: Box::new(fetcher),
+            cached: Mutex::new(None),
+            listeners: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The maximum age of the cached list before it is fetched again.
+    pub fn max_age(&self) -> Duration {
+        self.max_age
+    }
+
+    /// The cached station list, if any, regardless of its age.
+    pub fn get(&self) -> Option<Arc<Vec<Station>>> {
+        let cached = self.cached.lock().expect("Station cache lock poisoned.");
+        cached.as_ref().map(|c| Arc::clone(&c.stations))
+    }
+
+    /// The cached station list if it is younger than the maximum age, otherwise the
+    /// freshly fetched list.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the list needs to be fetched and fetching
+    /// fails. The previously cached list, if any, is kept.
+    pub fn get_or_fetch(&self) -> Result<Arc<Vec<Station>>, Box<dyn Error>> {
+        let mut cached = self.cached.lock().expect("Station cache lock poisoned.");
+        if let Some(c) = cached.as_ref() {
+            if c.fetched_at.elapsed() < self.max_age {
+                return Ok(Arc::clone(&c.stations));
+            }
+        }
+        self.fetch_into(&mut cached)
+    }
+
+    /// Fetch the station list regardless of the age of the cached list.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if fetching fails. The previously cached list,
+    /// if any, is kept.
+    pub fn refresh(&self) -> Result<Arc<Vec<Station>>, Box<dyn Error>> {
+        let mut cached = self.cached.lock().expect("Station cache lock poisoned.");
+        self.fetch_into(&mut cached)
+    }
+
+    /// Discard the cached list, so that the next call to [`get_or_fetch`](Self::get_or_fetch)
+    /// fetches it again.
+    pub fn invalidate(&self) {
+        *self.cached.lock().expect("Station cache lock poisoned.") = None;
+    }
+
+    /// Register a function to be called with the new list whenever a fetch returns a
+    /// station list that differs from the cached list.
+    ///
+    /// Listeners are also called after the first successful fetch.
+    pub fn on_change<F>(&self, listener: F)
+    where
+        F: Fn(&[Station]) + Send + Sync + 'static,
+    {
+        let mut listeners = self.listeners.lock().expect("Station cache lock poisoned.");
+        listeners.push(Box::new(listener));
+    }
+
+    fn fetch_into(
+        &self,
+        cached: &mut Option<CachedStations>,
+    ) -> Result<Arc<Vec<Station>>, Box<dyn Error>> {
+        let stations = Arc::new((self.fetcher)()?);
+        let changed = match cached.as_ref() {
+            Some(c) => !same_stations(&c.stations, &stations),
+            None => true,
+        };
+        *cached = Some(CachedStations {
+            fetched_at: Instant::now(),
+            stations: Arc::clone(&stations),
+        });
+        if changed {
+            let listeners = self.listeners.lock().expect("Station cache lock poisoned.");
+            for listener in listeners.iter() {
+                listener(&stations);
+            }
+        }
+        Ok(stations)
+    }
+}
+
+impl std::fmt::Debug for StationCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StationCache")
+            .field("max_age", &self.max_age)
+            .field("cached", &self.get().map(|s| s.len()))
+            .finish_non_exhaustive()
+    }
+}
+
+/// Compare every field of the stations, as `Station` equality only considers the ID.
+fn same_stations(a: &[Station], b: &[Station]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| {
+            a.id == b.id
+                && a.name == b.name
+                && a.country == b.country
+                && a.location.latitude.0 == b.location.latitude.0
+                && a.location.longitude.0 == b.location.longitude.0
+                && a.continuous_heights_available == b.continuous_heights_available
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::testing::StationBuilder;
+
+    /// A cache whose fetches return the stations in `lists` in turn, failing once they run
+    /// out, with a count of the fetches made.
+    fn cache(max_age: Duration, lists: Vec<Vec<Station>>) -> (StationCache, Arc<AtomicUsize>) {
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&fetches);
+        let cache = StationCache::with_fetcher(max_age, move || {
+            let n = counter.fetch_add(1, Ordering::SeqCst);
+            lists.get(n).cloned().ok_or_else(|| "no more lists".into())
+        });
+        (cache, fetches)
+    }
+
+    fn stations(name: &str) -> Vec<Station> {
+        vec![StationBuilder::new("0001", name).build()]
+    }
+
+    #[test]
+    fn fetches_again_once_the_list_expires() {
+        let (cache, fetches) = cache(Duration::ZERO, vec![stations("A"), stations("B")]);
+        assert!(cache.get().is_none());
+        assert_eq!(cache.get_or_fetch().unwrap()[0].name, "A");
+        assert_eq!(cache.get_or_fetch().unwrap()[0].name, "B");
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn refreshes_and_invalidates_regardless_of_age() {
+        let lists = vec![stations("A"), stations("B"), stations("C")];
+        let (cache, fetches) = cache(Duration::from_secs(3600), lists);
+        assert_eq!(cache.get_or_fetch().unwrap()[0].name, "A");
+        assert_eq!(cache.refresh().unwrap()[0].name, "B");
+        cache.invalidate();
+        assert!(cache.get().is_none());
+        assert_eq!(cache.get_or_fetch().unwrap()[0].name, "C");
+        assert_eq!(fetches.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn keeps_the_cached_list_when_a_fetch_fails() {
+        let (cache, _) = cache(Duration::ZERO, vec![stations("A")]);
+        cache.get_or_fetch().unwrap();
+        assert!(cache.get_or_fetch().is_err());
+        assert_eq!(cache.get().unwrap()[0].name, "A");
+    }
+
+    #[test]
+    fn notifies_listeners_only_of_changes() {
+        let lists = vec![stations("A"), stations("A"), stations("B")];
+        let (cache, _) = cache(Duration::ZERO, lists);
+        let names = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&names);
+        cache.on_change(move |stations| seen.lock().unwrap().push(stations[0].name.clone()));
+        for _ in 0..3 {
+            cache.get_or_fetch().unwrap();
+        }
+        assert_eq!(*names.lock().unwrap(), ["A", "B"]);
+    }
+
+    #[test]
+    fn fetches_once_for_concurrent_callers() {
+        let (cache, fetches) = cache(Duration::from_secs(3600), vec![stations("A")]);
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| cache.get_or_fetch().unwrap());
+            }
+        });
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,446 @@
+//! C-compatible API for parsing EasyTide responses.
+//!
+//! Enabled by the `capi` feature, for use from C, C++, Swift and other languages with a C
+//! FFI. The declarations are in `include/uktides.h`. The crate is built as a Rust library
+//! only, so build the shared library with:
+//!
+//! ```sh
+//! cargo rustc --lib --release --no-default-features --features capi --crate-type cdylib
+//! ```
+//!
+//! Parsing functions return an opaque handle, or null if parsing fails, in which case
+//! [`uktides_last_error`] describes the failure. Handles must be released with the matching
+//! `_free` function. The accessor functions treat a null handle as empty, and write nothing
+//! to a null output pointer. Datetimes are Unix timestamps in seconds.
+//!
+//! # Examples
+//! ```
+//! use std::ffi::CStr;
+//!
+//! use rjw_uktides::capi::*;
+//!
+//! let data = std::fs::read("./reference/tides.json").unwrap();
+//! unsafe {
+//!     let tides = uktides_tides_parse(data.as_ptr(), data.len());
+//!     assert!(!tides.is_null());
+//!     let mut event = UktidesEvent::default();
+//!     assert!(uktides_tides_event(tides, 0, &mut event));
+//!     assert!(event.timestamp > 0);
+//!     assert!(!uktides_tides_event(tides, uktides_tides_event_count(tides), &mut event));
+//!     uktides_tides_free(tides);
+//!
+//!     let invalid = b"{}";
+//!     assert!(uktides_tides_parse(invalid.as_ptr(), invalid.len()).is_null());
+//!     let message = CStr::from_ptr(uktides_last_error());
+//!     assert!(!message.to_bytes().is_empty());
+//!
+//!     let data = std::fs::read("./stations.json").unwrap();
+//!     let stations = uktides_stations_parse(data.as_ptr(), data.len());
+//!     let mut station = UktidesStation::default();
+//!     assert!(uktides_stations_get(stations, 0, &mut station));
+//!     assert!(!CStr::from_ptr(station.name).to_bytes().is_empty());
+//!     uktides_stations_free(stations);
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::ptr;
+
+use crate::{stations_from_slice, tides_from_slice, Error, TidalEventType, TidePredictions};
+
+/// Event type of a high tide.
+pub const UKTIDES_HIGH_WATER: i32 = 0;
+/// Event type of a low tide.
+pub const UKTIDES_LOW_WATER: i32 = 1;
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_last_error(e: &Error) {
+    let message = CString::new(e.to_string()).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+/// Parsed tide predictions.
+pub struct UktidesTides(TidePredictions);
+
+/// A high or low tide.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UktidesEvent {
+    /// When the tide occurs.
+    pub timestamp: i64,
+    /// Predicted height in metres.
+    pub height: f64,
+    /// [`UKTIDES_HIGH_WATER`] or [`UKTIDES_LOW_WATER`].
+    pub event_type: i32,
+}
+
+/// A predicted tide height.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UktidesHeight {
+    /// Time of the prediction.
+    pub timestamp: i64,
+    /// Predicted height in metres.
+    pub height: f64,
+}
+
+/// Parsed station list.
+pub struct UktidesStations(Vec<StationStrings>);
+
+struct StationStrings {
+    id: CString,
+    name: CString,
+    country: CString,
+    latitude: f64,
+    longitude: f64,
+    continuous_heights_available: bool,
+}
+
+/// A tidal station. The strings are owned by the station list they were read from.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct UktidesStation {
+    pub id: *const c_char,
+    pub name: *const c_char,
+    pub country: *const c_char,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub continuous_heights_available: bool,
+}
+
+impl Default for UktidesStation {
+    fn default() -> Self {
+        Self {
+            id: ptr::null(),
+            name: ptr::null(),
+            country: ptr::null(),
+            latitude: 0.0,
+            longitude: 0.0,
+            continuous_heights_available: false,
+        }
+    }
+}
+
+/// A description of the most recent error on this thread. The string is valid until the
+/// next call to a parsing function on the same thread.
+#[no_mangle]
+pub extern "C" fn uktides_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ptr())
+}
+
+/// Parse tide predictions from the buffer, returning null on failure.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn uktides_tides_parse(data: *const u8, len: usize) -> *mut UktidesTides {
+    match tides_from_slice(buffer(data, len)) {
+        Ok(tides) => Box::into_raw(Box::new(UktidesTides(tides))),
+        Err(e) => {
+            set_last_error(&e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// The number of high and low tides, or zero if `tides` is null.
+///
+/// # Safety
+///
+/// `tides` must be null or a handle returned by [`uktides_tides_parse`] that has not been
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn uktides_tides_event_count(tides: *const UktidesTides) -> usize {
+    tides.as_ref().map_or(0, |t| t.0.tidal_event_list.len())
+}
+
+/// Write the high or low tide at `index` to `out`, returning false if `index` is out of range
+/// or either pointer is null.
+///
+/// # Safety
+///
+/// `tides` must be null or a handle returned by [`uktides_tides_parse`] that has not been
+/// freed, and `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn uktides_tides_event(
+    tides: *const UktidesTides,
+    index: usize,
+    out: *mut UktidesEvent,
+) -> bool {
+    let (Some(tides), Some(out)) = (tides.as_ref(), out.as_mut()) else {
+        return false;
+    };
+    let Some(event) = tides.0.tidal_event_list.get(index) else {
+        return false;
+    };
+    *out = UktidesEvent {
+        timestamp: event.date_time.timestamp(),
+        height: event.height.0,
+        event_type: match event.event_type {
+            TidalEventType::HighWater => UKTIDES_HIGH_WATER,
+            TidalEventType::LowWater => UKTIDES_LOW_WATER,
+        },
+    };
+    true
+}
+
+/// The number of half-hourly height predictions, or zero if `tides` is null.
+///
+/// # Safety
+///
+/// `tides` must be null or a handle returned by [`uktides_tides_parse`] that has not been
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn uktides_tides_height_count(tides: *const UktidesTides) -> usize {
+    tides
+        .as_ref()
+        .map_or(0, |t| t.0.tidal_height_occurrence_list.len())
+}
+
+/// Write the height prediction at `index` to `out`, returning false if `index` is out of
+/// range or either pointer is null.
+///
+/// # Safety
+///
+/// `tides` must be null or a handle returned by [`uktides_tides_parse`] that has not been
+/// freed, and `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn uktides_tides_height(
+    tides: *const UktidesTides,
+    index: usize,
+    out: *mut UktidesHeight,
+) -> bool {
+    let (Some(tides), Some(out)) = (tides.as_ref(), out.as_mut()) else {
+        return false;
+    };
+    let Some(height) = tides.0.tidal_height_occurrence_list.get(index) else {
+        return false;
+    };
+    *out = UktidesHeight {
+        timestamp: height.date_time.timestamp(),
+        height: height.height.0,
+    };
+    true
+}
+
+/// Free tide predictions. Passing null does nothing.
+///
+/// # Safety
+///
+/// `tides` must be null or a handle returned by [`uktides_tides_parse`] that has not been
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn uktides_tides_free(tides: *mut UktidesTides) {
+    if !tides.is_null() {
+        drop(Box::from_raw(tides));
+    }
+}
+
+/// Parse the station list from the buffer, returning null on failure.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn uktides_stations_parse(
+    data: *const u8,
+    len: usize,
+) -> *mut UktidesStations {
+    let stations = match stations_from_slice(buffer(data, len)) {
+        Ok(stations) => stations,
+        Err(e) => {
+            set_last_error(&e);
+            return ptr::null_mut();
+        }
+    };
+    // Interior NULs cannot be represented in C strings, so such strings are emptied.
+    let c_string = |s: &str| CString::new(s).unwrap_or_default();
+    let stations = stations
+        .iter()
+        .map(|s| StationStrings {
+            id: c_string(&s.id),
+            name: c_string(&s.name),
+            country: c_string(&s.country),
+            latitude: s.location.latitude.0,
+            longitude: s.location.longitude.0,
+            continuous_heights_available: s.continuous_heights_available,
+        })
+        .collect();
+    Box::into_raw(Box::new(UktidesStations(stations)))
+}
+
+/// The number of stations, or zero if `stations` is null.
+///
+/// # Safety
+///
+/// `stations` must be null or a handle returned by [`uktides_stations_parse`] that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn uktides_stations_count(stations: *const UktidesStations) -> usize {
+    stations.as_ref().map_or(0, |s| s.0.len())
+}
+
+/// Write the station at `index` to `out`, returning false if `index` is out of range or
+/// either pointer is null.
+///
+/// # Safety
+///
+/// `stations` must be null or a handle returned by [`uktides_stations_parse`] that has not
+/// been freed, and `out` must be null or valid for writes. The strings written to `out` are
+/// valid until the station list is freed.
+#[no_mangle]
+pub unsafe extern "C" fn uktides_stations_get(
+    stations: *const UktidesStations,
+    index: usize,
+    out: *mut UktidesStation,
+) -> bool {
+    let (Some(stations), Some(out)) = (stations.as_ref(), out.as_mut()) else {
+        return false;
+    };
+    let Some(station) = stations.0.get(index) else {
+        return false;
+    };
+    *out = UktidesStation {
+        id: station.id.as_ptr(),
+        name: station.name.as_ptr(),
+        country: station.country.as_ptr(),
+        latitude: station.latitude,
+        longitude: station.longitude,
+        continuous_heights_available: station.continuous_heights_available,
+    };
+    true
+}
+
+/// Free a station list. Passing null does nothing.
+///
+/// # Safety
+///
+/// `stations` must be null or a handle returned by [`uktides_stations_parse`] that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn uktides_stations_free(stations: *mut UktidesStations) {
+    if !stations.is_null() {
+        drop(Box::from_raw(stations));
+    }
+}
+
+/// View the buffer as a slice, allowing a null pointer for an empty buffer.
+unsafe fn buffer<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if data.is_null() {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::*;
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(uktides_last_error()) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn treats_null_data_as_empty() {
+        let tides = unsafe { uktides_tides_parse(ptr::null(), 10) };
+        assert!(tides.is_null());
+        assert!(!last_error().is_empty());
+        let stations = unsafe { uktides_stations_parse(ptr::null(), 10) };
+        assert!(stations.is_null());
+    }
+
+    #[test]
+    fn treats_null_handles_as_empty() {
+        let mut event = UktidesEvent::default();
+        let mut height = UktidesHeight::default();
+        let mut station = UktidesStation::default();
+        unsafe {
+            assert_eq!(uktides_tides_event_count(ptr::null()), 0);
+            assert_eq!(uktides_tides_height_count(ptr::null()), 0);
+            assert!(!uktides_tides_event(ptr::null(), 0, &mut event));
+            assert!(!uktides_tides_height(ptr::null(), 0, &mut height));
+            assert_eq!(uktides_stations_count(ptr::null()), 0);
+            assert!(!uktides_stations_get(ptr::null(), 0, &mut station));
+        }
+    }
+
+    #[test]
+    fn writes_nothing_to_null_outputs() {
+        let data = crate::testing::TIDES_BYTES;
+        let stations = crate::testing::STATIONS_BYTES;
+        unsafe {
+            let tides = uktides_tides_parse(data.as_ptr(), data.len());
+            assert!(!uktides_tides_event(tides, 0, ptr::null_mut()));
+            assert!(!uktides_tides_height(tides, 0, ptr::null_mut()));
+            uktides_tides_free(tides);
+            let stations = uktides_stations_parse(stations.as_ptr(), stations.len());
+            assert!(!uktides_stations_get(stations, 0, ptr::null_mut()));
+            uktides_stations_free(stations);
+        }
+    }
+
+    #[test]
+    fn reports_invalid_utf8() {
+        let data = b"{\"footerNote\": \"\xff\"}";
+        let tides = unsafe { uktides_tides_parse(data.as_ptr(), data.len()) };
+        assert!(tides.is_null());
+        assert!(last_error().starts_with("invalid JSON"), "{}", last_error());
+    }
+
+    #[test]
+    fn keeps_the_last_error_per_thread() {
+        let data = b"{";
+        unsafe { uktides_tides_parse(data.as_ptr(), data.len()) };
+        let message = last_error();
+        assert!(!message.is_empty());
+        let other = std::thread::spawn(last_error).join().unwrap();
+        assert!(other.is_empty());
+    }
+
+    #[test]
+    fn empties_strings_with_interior_nuls() {
+        let data = br#"{"type": "FeatureCollection", "features": [{
+            "type": "Feature",
+            "geometry": {"type": "Point", "coordinates": [-1.15, 50.65]},
+            "properties": {
+                "Id": "0053", "Name": "SAND\u0000OWN", "Country": "England",
+                "ContinuousHeightsAvailable": true
+            }
+        }]}"#;
+        let mut station = UktidesStation::default();
+        unsafe {
+            let stations = uktides_stations_parse(data.as_ptr(), data.len());
+            assert_eq!(uktides_stations_count(stations), 1);
+            assert!(uktides_stations_get(stations, 0, &mut station));
+            assert_eq!(CStr::from_ptr(station.id).to_bytes(), b"0053");
+            assert!(CStr::from_ptr(station.name).to_bytes().is_empty());
+            uktides_stations_free(stations);
+        }
+    }
+
+    #[test]
+    fn frees_handles_and_ignores_null() {
+        let data = crate::testing::TIDES_BYTES;
+        unsafe {
+            let tides = uktides_tides_parse(data.as_ptr(), data.len());
+            assert!(!tides.is_null());
+            let mut event = UktidesEvent::default();
+            let count = uktides_tides_event_count(tides);
+            assert!(uktides_tides_event(tides, count - 1, &mut event));
+            assert!(!uktides_tides_event(tides, count, &mut event));
+            uktides_tides_free(tides);
+            uktides_tides_free(ptr::null_mut());
+            uktides_stations_free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,491 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use reqwest::blocking::{Request, Response};
+use reqwest::header::{HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH};
+use reqwest::StatusCode;
+
+use crate::rate_limit::RateLimiter;
+use crate::{
+    stations_from_reader, stations_url, tides_from_slice, tides_url, Cache, CacheMetadata,
+    CachedResponse, RateLimit, RetryPolicy, Station, StationId, StationsSnapshot, TidePredictions,
+    DEFAULT_BASE_URL,
+};
+
+/// Blocking client for the EasyTide web service.
+///
+/// The [`fetch_stations`](crate::fetch_stations) and [`fetch_tides`](crate::fetch_tides)
+/// functions use a default client. Construct a client directly to reuse its connection pool
+/// across requests, or to send requests to a different host such as a caching proxy or a
+/// mock server.
+///
+/// HTTPS requests use the platform's TLS library with the default `native-tls` feature, or
+/// rustls with the `rustls` feature. Without either, only plain HTTP base URLs can be used.
+#[derive(Debug, Clone)]
+pub struct Client {
+    base_url: String,
+    http: reqwest::blocking::Client,
+    cache: Option<Arc<dyn Cache>>,
+    retry: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    timeout: Option<Duration>,
+    proxy: Option<reqwest::Proxy>,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Client {
+    /// Create a client for the public EasyTide web service.
+    pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    /// Create a client that sends requests to `base_url` instead of the public EasyTide
+    /// web service.
+    ///
+    /// The base URL should include the scheme and host, and optionally a port and path
+    /// prefix, without a trailing slash; for example `http://localhost:8080`.
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            http: reqwest::blocking::Client::new(),
+            cache: None,
+            retry: RetryPolicy::default(),
+            rate_limiter: None,
+            timeout: None,
+            proxy: None,
+        }
+    }
+
+    /// Store responses in `cache`, and use them to avoid refetching unchanged data.
+    ///
+    /// Cached responses are returned without contacting the server while they are fresh
+    /// according to the `Cache-Control: max-age` header they were served with. Otherwise
+    /// the request is sent with `If-None-Match` and `If-Modified-Since` headers, and the
+    /// cached response is returned if the server responds with 304 Not Modified.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "mock_server")]
+    /// # {
+    /// use rjw_uktides::mock_server::MockServer;
+    /// use rjw_uktides::{Client, FileCache};
+    ///
+    /// let dir = std::env::temp_dir().join("rjw-uktides-client-cache-doctest");
+    /// let server = MockServer::start().expect("Failed to start mock server.");
+    /// let client = Client::with_base_url(server.base_url()).with_cache(FileCache::new(&dir));
+    ///
+    /// let first = client.fetch_stations().expect("Failed to fetch stations.");
+    /// let second = client.fetch_stations().expect("Failed to revalidate stations.");
+    /// assert_eq!(first.len(), second.len());
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// # }
+    /// ```
+    pub fn with_cache(mut self, cache: impl Cache + 'static) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Retry failed requests according to `policy` instead of the default [`RetryPolicy`].
+    ///
+    /// Use [`RetryPolicy::none`] to disable retrying.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "mock_server")]
+    /// # {
+    /// use std::time::Duration;
+    ///
+    /// use rjw_uktides::mock_server::MockServer;
+    /// use rjw_uktides::{Client, RetryPolicy};
+    ///
+    /// let server = MockServer::start().expect("Failed to start mock server.");
+    /// let mut policy = RetryPolicy::default();
+    /// policy.initial_backoff = Duration::from_millis(10);
+    /// let client = Client::with_base_url(server.base_url()).with_retry(policy);
+    ///
+    /// server.fail_next(2);
+    /// client.fetch_stations().expect("Failed to fetch stations.");
+    /// assert_eq!(server.request_count(), 3);
+    ///
+    /// server.fail_next(1);
+    /// let client = client.with_retry(RetryPolicy::none());
+    /// assert!(client.fetch_stations().is_err());
+    /// # }
+    /// ```
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// The policy used to retry failed requests.
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// Limit the rate at which requests are sent, such as when fetching predictions for
+    /// every station.
+    ///
+    /// The limit is shared by clones of the client, so it applies across threads making
+    /// concurrent requests with clones of the same client.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "mock_server")]
+    /// # {
+    /// use std::time::{Duration, Instant};
+    ///
+    /// use rjw_uktides::mock_server::MockServer;
+    /// use rjw_uktides::{Client, RateLimit};
+    ///
+    /// let server = MockServer::start().expect("Failed to start mock server.");
+    /// let client = Client::with_base_url(server.base_url()).with_rate_limit(RateLimit::new(1200, 1));
+    ///
+    /// // One request is allowed immediately, then one every 50 ms.
+    /// let start = Instant::now();
+    /// for _ in 0..3 {
+    ///     client.fetch_stations().expect("Failed to fetch stations.");
+    /// }
+    /// assert!(start.elapsed() >= Duration::from_millis(100));
+    /// # }
+    /// ```
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(limit)));
+        self
+    }
+
+    /// The limit on the rate at which requests are sent, if any.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limiter.as_ref().map(|r| r.limit())
+    }
+
+    /// Give up on each attempt at a request after `timeout`, from connecting until the
+    /// response has been read, instead of the default of 30 seconds.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use rjw_uktides::Client;
+    ///
+    /// let client = Client::new().with_timeout(Duration::from_secs(5));
+    /// assert_eq!(client.timeout(), Some(Duration::from_secs(5)));
+    /// ```
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self.rebuild_http();
+        self
+    }
+
+    /// The timeout for each attempt at a request, if it has been changed from the default.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Send all requests through the proxy at `url`, such as `http://proxy.example:3128`.
+    ///
+    /// Without a proxy set here, the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY`
+    /// environment variables are respected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `url` is not a valid proxy URL.
+    ///
+    /// # Examples
+    /// ```
+    /// use rjw_uktides::Client;
+    ///
+    /// assert!(Client::new().with_proxy("http://proxy.example:3128").is_ok());
+    /// assert!(Client::new().with_proxy("ftp://proxy.example").is_err());
+    /// ```
+    pub fn with_proxy(mut self, url: &str) -> Result<Self, Box<dyn Error>> {
+        self.proxy = Some(reqwest::Proxy::all(url)?);
+        self.rebuild_http();
+        Ok(self)
+    }
+
+    /// Replace the HTTP client with one built with the timeout and proxy.
+    fn rebuild_http(&mut self) {
+        let mut builder = reqwest::blocking::Client::builder();
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        // As with `reqwest::blocking::Client::new`, this fails only if the TLS backend
+        // cannot be initialised.
+        self.http = builder.build().expect("Failed to build the HTTP client.");
+    }
+
+    /// The base URL to which requests are sent.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// The underlying HTTP client, for building requests to pass to [`send`](Self::send).
+    pub(crate) fn http(&self) -> &reqwest::blocking::Client {
+        &self.http
+    }
+
+    /// Fetch the list of all tidal stations.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails, the server responds with
+    /// an error status, or the response cannot be parsed as station data.
+    pub fn fetch_stations(&self) -> Result<Vec<Station>, Box<dyn Error>> {
+        let bytes = self.fetch_stations_raw()?;
+        Ok(stations_from_reader(bytes.as_ref())?)
+    }
+
+    /// Fetch the list of all tidal stations, recording when and from where it was fetched.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails, the server responds with
+    /// an error status, or the response cannot be parsed as station data.
+    pub fn fetch_stations_snapshot(&self) -> Result<StationsSnapshot, Box<dyn Error>> {
+        let stations = self.fetch_stations()?;
+        Ok(StationsSnapshot::new(
+            chrono::Utc::now(),
+            stations_url(&self.base_url),
+            stations,
+        ))
+    }
+
+    /// Fetch the list of all tidal stations as the raw JSON response body.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or the server responds with
+    /// an error status.
+    pub fn fetch_stations_raw(&self) -> Result<Bytes, Box<dyn Error>> {
+        let url = stations_url(&self.base_url);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("fetch_stations", %url).entered();
+        let bytes = self
+            .send(self.http.get(url).build()?)
+            .map_err(|e| e as Box<dyn Error>)?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(bytes = bytes.len(), "received stations response");
+        Ok(bytes)
+    }
+
+    /// Fetch tide predictions for the given station.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails, the server responds with
+    /// an error status, or the response cannot be parsed as tide predictions.
+    pub fn fetch_tides(&self, station: &StationId) -> Result<TidePredictions, Box<dyn Error>> {
+        let bytes = self.fetch_tides_raw(station)?;
+        Ok(tides_from_slice(&bytes)?)
+    }
+
+    /// Fetch tide predictions for the given station as the raw JSON response body.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or the server responds with
+    /// an error status.
+    pub fn fetch_tides_raw(&self, station: &StationId) -> Result<Bytes, Box<dyn Error>> {
+        self.tides_raw(station).map_err(|e| e as Box<dyn Error>)
+    }
+
+    /// Fetch tide predictions for each of the given stations, using up to `concurrency`
+    /// threads to send requests at the same time.
+    ///
+    /// Failures are reported separately for each station rather than failing the whole
+    /// batch. Stations listed more than once are only fetched once. A `concurrency` of zero
+    /// is treated as one. Combine with [`with_rate_limit`](Self::with_rate_limit) to fetch
+    /// large numbers of stations without overloading the service.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(feature = "mock_server")]
+    /// # {
+    /// use rjw_uktides::mock_server::MockServer;
+    /// use rjw_uktides::{Client, StationId};
+    ///
+    /// let server = MockServer::start().expect("Failed to start mock server.");
+    /// let client = Client::with_base_url(server.base_url());
+    ///
+    /// let sandown = StationId("0053".to_owned());
+    /// let unknown = StationId("nope".to_owned());
+    /// let results = client.fetch_tides_many(&[sandown.clone(), unknown.clone()], 4);
+    /// assert!(results[&sandown].is_ok());
+    /// assert!(results[&unknown].is_err());
+    /// # }
+    /// ```
+    pub fn fetch_tides_many(
+        &self,
+        stations: &[StationId],
+        concurrency: usize,
+    ) -> HashMap<StationId, Result<TidePredictions, Box<dyn Error + Send + Sync>>> {
+        let concurrency = concurrency.max(1);
+        let mut seen = HashSet::new();
+        let unique: Vec<&StationId> = stations.iter().filter(|s| seen.insert(*s)).collect();
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(HashMap::with_capacity(unique.len()));
+
+        std::thread::scope(|scope| {
+            for _ in 0..concurrency.min(unique.len()) {
+                scope.spawn(|| {
+                    while let Some(&station) = unique.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let result = self
+                            .tides_raw(station)
+                            .and_then(|bytes| Ok(tides_from_slice(&bytes)?));
+                        let mut results = results.lock().expect("Results lock poisoned.");
+                        results.insert(station.clone(), result);
+                    }
+                });
+            }
+        });
+        results.into_inner().expect("Results lock poisoned.")
+    }
+
+    fn tides_raw(&self, station: &StationId) -> Result<Bytes, Box<dyn Error + Send + Sync>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("fetch_tides", %station).entered();
+        let request = self.http.get(tides_url(&self.base_url, station)).build()?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(url = %request.url(), "requesting tide predictions");
+        let bytes = self.send(request)?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(bytes = bytes.len(), "received tide predictions response");
+        Ok(bytes)
+    }
+
+    /// Send the request, consulting and updating the cache if there is one.
+    pub(crate) fn send(&self, mut request: Request) -> Result<Bytes, Box<dyn Error + Send + Sync>> {
+        #[cfg(feature = "compression")]
+        request.headers_mut().insert(
+            reqwest::header::ACCEPT_ENCODING,
+            HeaderValue::from_static("gzip, deflate"),
+        );
+        let Some(cache) = &self.cache else {
+            return body(self.execute(request)?.error_for_status()?);
+        };
+        let key = request.url().to_string();
+        // A failing cache is treated as empty rather than failing the request.
+        let cached = cache.get(&key).ok().flatten();
+        if let Some(cached) = &cached {
+            if cached.metadata.is_fresh() {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(%key, "serving fresh cached response");
+                return Ok(cached.body.clone());
+            }
+            let headers = request.headers_mut();
+            let validators = [
+                (IF_NONE_MATCH, &cached.metadata.etag),
+                (IF_MODIFIED_SINCE, &cached.metadata.last_modified),
+            ];
+            for (name, value) in validators {
+                if let Some(value) = value.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                    headers.insert(name, value);
+                }
+            }
+        }
+
+        let response = self.execute(request)?;
+        if let (StatusCode::NOT_MODIFIED, Some(mut cached)) = (response.status(), cached) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(%key, "cached response revalidated");
+            if let Some(fresh) = CacheMetadata::from_headers(response.headers()) {
+                cached.metadata = CacheMetadata {
+                    etag: fresh.etag.or(cached.metadata.etag),
+                    last_modified: fresh.last_modified.or(cached.metadata.last_modified),
+                    ..fresh
+                };
+                let _ = cache.put(&key, &cached);
+            }
+            return Ok(cached.body);
+        }
+        let response = response.error_for_status()?;
+        let metadata = CacheMetadata::from_headers(response.headers());
+        let body = body(response)?;
+        if let Some(metadata) = metadata {
+            let cached = CachedResponse {
+                body: body.clone(),
+                metadata,
+            };
+            let _ = cache.put(&key, &cached);
+        }
+        Ok(body)
+    }
+
+    /// Execute the request, retrying it according to the retry policy.
+    fn execute(&self, request: Request) -> reqwest::Result<Response> {
+        let mut attempt = 1;
+        loop {
+            // Requests that cannot be cloned, such as those with streaming bodies, are
+            // only attempted once.
+            let Some(retry) = request.try_clone() else {
+                if let Some(limiter) = &self.rate_limiter {
+                    limiter.acquire();
+                }
+                return self.http.execute(request);
+            };
+            if let Some(limiter) = &self.rate_limiter {
+                limiter.acquire();
+            }
+            let result = self.http.execute(retry);
+            let Some(delay) = self.retry.retry_delay(attempt, &result) else {
+                return result;
+            };
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                url = %request.url(),
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                "retrying failed request"
+            );
+            std::thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+}
+
+/// Read the response body, decompressing it according to its `Content-Encoding` header.
+fn body(response: Response) -> Result<Bytes, Box<dyn Error + Send + Sync>> {
+    #[cfg(feature = "compression")]
+    let encoding = response
+        .headers()
+        .get(reqwest::header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned);
+    let body = response.bytes()?;
+    #[cfg(feature = "compression")]
+    if let Some(encoding) = encoding {
+        if let Some(decoded) = crate::compression::decode_content(&encoding, &body)? {
+            return Ok(decoded.into());
+        }
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::MockServer;
+
+    #[test]
+    fn fetch_tides_many_treats_zero_concurrency_as_one() {
+        let server = MockServer::start().unwrap();
+        let client = Client::with_base_url(server.base_url());
+        let stations = [StationId("0053".to_owned()), StationId("0014".to_owned())];
+        let results = client.fetch_tides_many(&stations, 0);
+        assert_eq!(results.len(), 2);
+        assert!(results.values().all(Result::is_ok));
+        assert_eq!(server.request_count(), 2);
+    }
+}
@@ -0,0 +1,220 @@
+//! Transparent decompression of gzip and zlib-compressed data.
+//!
+//! Enabled by the `compression` feature, using flate2. Gzip data with several members, as
+//! produced by concatenating gzip files, is decompressed in full.
+
+use std::io::{self, BufRead, BufReader, Read, Take};
+
+use flate2::read::{MultiGzDecoder, ZlibDecoder};
+
+/// The most data a compressed stream may decompress to, far more than any API response, so
+/// that a small compressed payload cannot exhaust memory.
+const MAX_DECOMPRESSED_BYTES: u64 = 64 * 1024 * 1024;
+
+/// A reader over data that may have been compressed.
+pub(crate) enum MaybeCompressed<R> {
+    Plain(BufReader<R>),
+    Gzip(Limited<MultiGzDecoder<BufReader<R>>>),
+    Zlib(Limited<ZlibDecoder<BufReader<R>>>),
+}
+
+impl<R: Read> Read for MaybeCompressed<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(rdr) => rdr.read(buf),
+            Self::Gzip(rdr) => rdr.read(buf),
+            Self::Zlib(rdr) => rdr.read(buf),
+        }
+    }
+}
+
+/// Wrap the reader so that gzip or zlib-compressed data is decompressed, and other data is
+/// passed through unchanged.
+pub(crate) fn reader<R: Read>(rdr: R) -> io::Result<MaybeCompressed<R>> {
+    let mut rdr = BufReader::new(rdr);
+    let head = rdr.fill_buf()?;
+    Ok(if is_gzip(head) {
+        MaybeCompressed::Gzip(Limited::new(
+            MultiGzDecoder::new(rdr),
+            MAX_DECOMPRESSED_BYTES,
+        ))
+    } else if is_zlib(head) {
+        MaybeCompressed::Zlib(Limited::new(ZlibDecoder::new(rdr), MAX_DECOMPRESSED_BYTES))
+    } else {
+        MaybeCompressed::Plain(rdr)
+    })
+}
+
+/// Decode an HTTP response body according to its `Content-Encoding`, or return `None` if
+/// the encoding is not supported.
+#[cfg(all(
+    any(feature = "blocking", feature = "async"),
+    not(target_arch = "wasm32")
+))]
+pub(crate) fn decode_content(encoding: &str, data: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    use flate2::read::DeflateDecoder;
+
+    let decoder: Box<dyn Read + '_> = match encoding.trim() {
+        "gzip" | "x-gzip" => Box::new(MultiGzDecoder::new(data)),
+        // Some servers send raw DEFLATE data rather than the zlib format HTTP specifies.
+        "deflate" if is_zlib(data) => Box::new(ZlibDecoder::new(data)),
+        "deflate" => Box::new(DeflateDecoder::new(data)),
+        _ => return Ok(None),
+    };
+    let mut out = Vec::new();
+    Limited::new(decoder, MAX_DECOMPRESSED_BYTES).read_to_end(&mut out)?;
+    Ok(Some(out))
+}
+
+fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&[0x1f, 0x8b])
+}
+
+fn is_zlib(data: &[u8]) -> bool {
+    matches!(data, [cmf, flg, ..]
+        if cmf & 0x0f == 8 && cmf >> 4 <= 7 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0)
+}
+
+/// A reader that fails once more than a limit has been read, rather than ending quietly as
+/// [`Read::take`] does, so that oversized data is not mistaken for truncated data.
+pub(crate) struct Limited<R> {
+    inner: Take<R>,
+}
+
+impl<R: Read> Limited<R> {
+    fn new(inner: R, limit: u64) -> Self {
+        Self {
+            inner: inner.take(limit + 1),
+        }
+    }
+}
+
+impl<R: Read> Read for Limited<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if self.inner.limit() == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "decompressed data is too large",
+            ));
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use flate2::Compression;
+
+    use super::*;
+
+    const DATA: &[u8] = br#"{"footerNote": "compressed"}"#;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn read_all(data: &[u8]) -> io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        reader(data)?.read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn passes_plain_data_through() {
+        assert_eq!(read_all(DATA).unwrap(), DATA);
+        assert_eq!(read_all(b"").unwrap(), b"");
+    }
+
+    #[test]
+    fn decompresses_gzip_and_zlib() {
+        assert_eq!(read_all(&gzip(DATA)).unwrap(), DATA);
+        assert_eq!(read_all(&zlib(DATA)).unwrap(), DATA);
+    }
+
+    #[test]
+    fn decompresses_every_gzip_member() {
+        let mut data = gzip(&DATA[..10]);
+        data.extend(gzip(&DATA[10..]));
+        assert_eq!(read_all(&data).unwrap(), DATA);
+    }
+
+    #[test]
+    fn decompresses_the_bundled_gzip_reference_file() {
+        let out = read_all(crate::testing::TIDES_GZIP_BYTES).unwrap();
+        assert_eq!(out, crate::testing::TIDES_BYTES);
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let data = gzip(DATA);
+        assert!(read_all(&data[..data.len() - 4]).is_err());
+        let data = zlib(DATA);
+        assert!(read_all(&data[..data.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn rejects_checksum_mismatches() {
+        let mut data = gzip(DATA);
+        let crc = data.len() - 8;
+        data[crc] ^= 0xff;
+        assert!(read_all(&data).is_err());
+
+        let mut data = zlib(DATA);
+        let adler = data.len() - 1;
+        data[adler] ^= 0xff;
+        assert!(read_all(&data).is_err());
+    }
+
+    #[test]
+    fn rejects_corrupt_data() {
+        let mut data = gzip(DATA);
+        // Mark the first block as using the reserved block type.
+        data[10] |= 0b110;
+        assert!(read_all(&data).is_err());
+    }
+
+    #[test]
+    fn fails_rather_than_truncating_over_the_limit() {
+        let data = gzip(&[b'a'; 1000]);
+        let mut out = Vec::new();
+        let mut rdr = Limited::new(MultiGzDecoder::new(&data[..]), 1000);
+        rdr.read_to_end(&mut out).unwrap();
+        assert_eq!(out.len(), 1000);
+
+        let mut rdr = Limited::new(MultiGzDecoder::new(&data[..]), 999);
+        let err = rdr.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(all(
+        any(feature = "blocking", feature = "async"),
+        not(target_arch = "wasm32")
+    ))]
+    #[test]
+    fn decodes_content_encodings() {
+        use flate2::write::DeflateEncoder;
+
+        let mut deflate = DeflateEncoder::new(Vec::new(), Compression::default());
+        deflate.write_all(DATA).unwrap();
+        let deflate = deflate.finish().unwrap();
+        let decode = |encoding, data: &[u8]| decode_content(encoding, data).unwrap();
+        assert_eq!(decode("gzip", &gzip(DATA)).unwrap(), DATA);
+        assert_eq!(decode(" x-gzip ", &gzip(DATA)).unwrap(), DATA);
+        assert_eq!(decode("deflate", &zlib(DATA)).unwrap(), DATA);
+        assert_eq!(decode("deflate", &deflate).unwrap(), DATA);
+        assert!(decode("br", DATA).is_none());
+        assert!(decode_content("gzip", DATA).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use std::io::{self, Write};
+
+use chrono::SecondsFormat;
+
+use crate::{Station, TidalEventType, TidePredictions};
+
+/// Write the high and low tides as CSV with a header row, in chronological order.
+///
+/// The columns are `time` (RFC 3339 in UTC), `type` (`high` or `low`), `height` (metres
+/// above chart datum), and `approximate_time` and `approximate_height` (`true` or `false`).
+///
+/// # Errors
+///
+/// This function will return an error if writing to the writer fails.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+///     .expect("Failed to read file as tides data.");
+/// let mut csv = Vec::new();
+/// rjw_uktides::write_tides_csv(&mut csv, &tides).expect("Failed to write CSV.");
+/// let csv = String::from_utf8(csv).unwrap();
+/// let mut lines = csv.lines();
+/// assert_eq!(
+///     lines.next(),
+///     Some("time,type,height,approximate_time,approximate_height")
+/// );
+/// assert_eq!(
+///     lines.next(),
+///     Some("2023-04-03T04:07:00Z,high,4.16,false,false")
+/// );
+/// ```
+pub fn write_tides_csv(mut wtr: impl Write, tides: &TidePredictions) -> io::Result<()> {
+    writeln!(wtr, "time,type,height,approximate_time,approximate_height")?;
+    let mut events: Vec<_> = tides.tidal_event_list.iter().collect();
+    events.sort();
+    for event in events {
+        let event_type = match event.event_type {
+            TidalEventType::HighWater => "high",
+            TidalEventType::LowWater => "low",
+        };
+        writeln!(
+            wtr,
+            "{},{event_type},{},{},{}",
+            event.date_time.to_rfc3339_opts(SecondsFormat::Secs, true),
+            event.height.0,
+            event.is_approximate_time.is_some(),
+            event.is_approximate_height.is_some(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the stations as CSV with a header row, in the order given.
+///
+/// The columns are `id`, `name`, `country`, `latitude`, `longitude` and
+/// `continuous_heights_available` (`true` or `false`).
+///
+/// # Errors
+///
+/// This function will return an error if writing to the writer fails.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// let stations = rjw_uktides::stations_from_reader(File::open("./stations.json").unwrap())
+///     .expect("Failed to read file as stations data.");
+/// let mut csv = Vec::new();
+/// rjw_uktides::write_stations_csv(&mut csv, &stations).expect("Failed to write CSV.");
+/// let csv = String::from_utf8(csv).unwrap();
+/// assert_eq!(csv.lines().count(), stations.len() + 1);
+/// ```
+pub fn write_stations_csv(mut wtr: impl Write, stations: &[Station]) -> io::Result<()> {
+    writeln!(
+        wtr,
+        "id,name,country,latitude,longitude,continuous_heights_available"
+    )?;
+    for station in stations {
+        writeln!(
+            wtr,
+            "{},{},{},{},{},{}",
+            escape_field(&station.id.0),
+            escape_field(&station.name),
+            escape_field(&station.country),
+            station.location.latitude.0,
+            station.location.longitude.0,
+            station.continuous_heights_available,
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the stations as CSV with a header row, in the order given, with each station's
+/// location as Well-Known Text for GIS software, such as PostGIS's `COPY` or QGIS's
+/// delimited text layers.
+///
+/// The columns are `wkt` (`POINT(longitude latitude)`, see [`Coordinates::to_wkt`]),
+/// `id`, `name`, `country` and `continuous_heights_available` (`true` or `false`).
+///
+/// [`Coordinates::to_wkt`]: crate::Coordinates::to_wkt
+///
+/// # Errors
+///
+/// This function will return an error if writing to the writer fails.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// let stations = rjw_uktides::stations_from_reader(File::open("./stations.json").unwrap())
+///     .expect("Failed to read file as stations data.");
+/// let mut csv = Vec::new();
+/// rjw_uktides::write_stations_wkt_csv(&mut csv, &stations).expect("Failed to write CSV.");
+/// let csv = String::from_utf8(csv).unwrap();
+/// assert_eq!(csv.lines().count(), stations.len() + 1);
+/// assert!(csv.lines().skip(1).all(|line| line.starts_with("POINT(")));
+/// ```
+pub fn write_stations_wkt_csv(mut wtr: impl Write, stations: &[Station]) -> io::Result<()> {
+    writeln!(wtr, "wkt,id,name,country,continuous_heights_available")?;
+    for station in stations {
+        writeln!(
+            wtr,
+            "{},{},{},{},{}",
+            station.location.to_wkt(),
+            escape_field(&station.id.0),
+            escape_field(&station.name),
+            escape_field(&station.country),
+            station.continuous_heights_available,
+        )?;
+    }
+    Ok(())
+}
+
+/// Quote the field if it contains a comma, quote or line break, doubling any quotes.
+fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
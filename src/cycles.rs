@@ -0,0 +1,283 @@
+use chrono::Duration;
+
+use crate::{Metres, TidalEvent, TidalEventType, TidePredictions};
+
+/// A tidal cycle from one low tide, up to high tide and back down to the next low tide, as
+/// yielded by [`TidePredictions::cycles`].
+#[derive(Debug, Clone, Copy)]
+pub struct TidalCycle<'a> {
+    /// The low tide the cycle starts from.
+    pub low: &'a TidalEvent,
+    /// The high tide between the two low tides, the higher one if there are two.
+    pub high: &'a TidalEvent,
+    /// The low tide the cycle ends at, which starts the next cycle.
+    pub next_low: &'a TidalEvent,
+}
+
+impl TidalCycle<'_> {
+    /// How long the cycle lasts, from low tide to the next low tide.
+    pub fn duration(&self) -> Duration {
+        self.next_low.date_time - self.low.date_time
+    }
+
+    /// How long the tide rises, from low tide to high tide.
+    pub fn rise_duration(&self) -> Duration {
+        self.high.date_time - self.low.date_time
+    }
+
+    /// How long the tide falls, from high tide to the next low tide.
+    pub fn fall_duration(&self) -> Duration {
+        self.next_low.date_time - self.high.date_time
+    }
+
+    /// How far the tide rises, from low tide to high tide.
+    pub fn rise(&self) -> Metres {
+        Metres(self.high.height.0 - self.low.height.0)
+    }
+
+    /// How far the tide falls, from high tide to the next low tide.
+    pub fn fall(&self) -> Metres {
+        Metres(self.high.height.0 - self.next_low.height.0)
+    }
+
+    /// The range of the cycle, the mean of the rise and fall.
+    pub fn range(&self) -> Metres {
+        Metres((self.rise().0 + self.fall().0) / 2.0)
+    }
+
+    /// The average rate at which the tide rises, in metres per hour.
+    pub fn rise_rate(&self) -> f64 {
+        self.rise().0 / hours(self.rise_duration())
+    }
+
+    /// The average rate at which the tide falls, in metres per hour.
+    pub fn fall_rate(&self) -> f64 {
+        self.fall().0 / hours(self.fall_duration())
+    }
+}
+
+/// Figures for all the tidal cycles in the predictions, as given by
+/// [`TidePredictions::cycle_statistics`].
+#[derive(Debug, Clone, Copy)]
+pub struct CycleStatistics {
+    /// The number of complete cycles.
+    pub cycles: usize,
+    /// The mean length of a cycle, about 12 hours 25 minutes for semidiurnal tides.
+    pub mean_duration: Duration,
+    /// The mean range of a cycle.
+    pub mean_range: Metres,
+    /// The smallest range of a cycle, usually at neaps.
+    pub min_range: Metres,
+    /// The largest range of a cycle, usually at springs.
+    pub max_range: Metres,
+    /// The mean rate at which the tide rises, in metres per hour.
+    pub mean_rise_rate: f64,
+    /// The mean rate at which the tide falls, in metres per hour.
+    pub mean_fall_rate: f64,
+    /// The mean time the tide rises divided by the mean time it falls. Below 1 the tide
+    /// rises faster than it falls, as in many estuaries; above 1 it falls faster.
+    pub asymmetry: f64,
+}
+
+impl TidePredictions {
+    /// The complete tidal cycles in the predictions, from each low tide to the next, in
+    /// order.
+    ///
+    /// Tides before the first low tide and after the last are not part of a cycle. Where
+    /// there is a double high water, the higher of the two is the cycle's high tide, and
+    /// where two low tides come together without a high tide between them, the first
+    /// does not start a cycle.
+    ///
+    /// The tides are expected to be in chronological order, as they are from EasyTide and
+    /// after [`merge`](TidePredictions::merge).
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    ///
+    /// use chrono::Duration;
+    ///
+    /// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+    ///     .expect("Failed to read file as tides data.");
+    /// let cycles: Vec<_> = tides.cycles().collect();
+    /// assert!(!cycles.is_empty());
+    /// assert!(cycles.windows(2).all(|w| w[0].next_low.date_time == w[1].low.date_time));
+    /// for cycle in &cycles {
+    ///     assert_eq!(cycle.rise_duration() + cycle.fall_duration(), cycle.duration());
+    ///     assert!(cycle.rise().0 > 0.0 && cycle.fall().0 > 0.0);
+    ///     assert!(cycle.duration() > Duration::hours(11) && cycle.duration() < Duration::hours(14));
+    /// }
+    /// ```
+    pub fn cycles(&self) -> impl Iterator<Item = TidalCycle<'_>> {
+        let events = &self.tidal_event_list;
+        let lows: Vec<_> = events
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.event_type == TidalEventType::LowWater)
+            .map(|(i, _)| i)
+            .collect();
+        (0..lows.len().saturating_sub(1)).filter_map(move |n| {
+            let (start, end) = (lows[n], lows[n + 1]);
+            let high = events[start + 1..end]
+                .iter()
+                .max_by(|a, b| a.height.0.total_cmp(&b.height.0))?;
+            Some(TidalCycle {
+                low: &events[start],
+                high,
+                next_low: &events[end],
+            })
+        })
+    }
+
+    /// The mean length, range, rates of rise and fall, and asymmetry of the tidal cycles
+    /// in the predictions, or `None` if there is not a complete cycle.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    ///
+    /// use chrono::Duration;
+    ///
+    /// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+    ///     .expect("Failed to read file as tides data.");
+    /// let stats = tides.cycle_statistics().unwrap();
+    /// assert_eq!(stats.cycles, tides.cycles().count());
+    /// let lunar_half_day = Duration::minutes(12 * 60 + 25);
+    /// assert!((stats.mean_duration - lunar_half_day).num_minutes().abs() < 30);
+    /// assert!(stats.min_range.0 <= stats.mean_range.0 && stats.mean_range.0 <= stats.max_range.0);
+    /// assert!(stats.asymmetry > 0.5 && stats.asymmetry < 2.0);
+    /// ```
+    pub fn cycle_statistics(&self) -> Option<CycleStatistics> {
+        let cycles: Vec<_> = self.cycles().collect();
+        if cycles.is_empty() {
+            return None;
+        }
+        let count = cycles.len();
+        let mean = |values: &mut dyn Iterator<Item = f64>| values.sum::<f64>() / count as f64;
+        let total = |durations: &mut dyn Iterator<Item = Duration>| {
+            durations.fold(Duration::zero(), |sum, d| sum + d)
+        };
+        let ranges = || cycles.iter().map(|c| c.range().0);
+        Some(CycleStatistics {
+            cycles: count,
+            mean_duration: total(&mut cycles.iter().map(|c| c.duration())) / count as i32,
+            mean_range: Metres(mean(&mut ranges())),
+            min_range: Metres(ranges().fold(f64::INFINITY, f64::min)),
+            max_range: Metres(ranges().fold(f64::NEG_INFINITY, f64::max)),
+            mean_rise_rate: mean(&mut cycles.iter().map(|c| c.rise_rate())),
+            mean_fall_rate: mean(&mut cycles.iter().map(|c| c.fall_rate())),
+            asymmetry: hours(total(&mut cycles.iter().map(|c| c.rise_duration())))
+                / hours(total(&mut cycles.iter().map(|c| c.fall_duration()))),
+        })
+    }
+}
+
+/// The duration in fractional hours.
+fn hours(duration: Duration) -> f64 {
+    duration.num_seconds() as f64 / 3600.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{utc, TidalEventBuilder, TidePredictionsBuilder};
+
+    fn high(at: &str, height: f64) -> TidalEventBuilder {
+        TidalEventBuilder::high().at(at).height(height)
+    }
+
+    fn low(at: &str, height: f64) -> TidalEventBuilder {
+        TidalEventBuilder::low().at(at).height(height)
+    }
+
+    #[test]
+    fn measures_a_cycle() {
+        let tides = TidePredictionsBuilder::new()
+            .event(low("2023-04-03T00:00", 1.0))
+            .event(high("2023-04-03T06:00", 5.0))
+            .event(low("2023-04-03T12:30", 2.0))
+            .build();
+        let cycles: Vec<_> = tides.cycles().collect();
+        assert_eq!(cycles.len(), 1);
+        let cycle = cycles[0];
+        assert_eq!(cycle.duration(), Duration::minutes(12 * 60 + 30));
+        assert_eq!(cycle.rise().0, 4.0);
+        assert_eq!(cycle.fall().0, 3.0);
+        assert_eq!(cycle.range().0, 3.5);
+        assert_eq!(cycle.rise_rate(), 4.0 / 6.0);
+        assert_eq!(cycle.fall_rate(), 3.0 / 6.5);
+    }
+
+    #[test]
+    fn leaves_tides_outside_the_first_and_last_low_tides_out_of_cycles() {
+        let tides = TidePredictionsBuilder::new()
+            .event(high("2023-04-02T18:00", 4.8))
+            .event(low("2023-04-03T00:00", 1.0))
+            .event(high("2023-04-03T06:00", 5.0))
+            .event(low("2023-04-03T12:30", 2.0))
+            .event(high("2023-04-03T18:45", 4.9))
+            .build();
+        let cycles: Vec<_> = tides.cycles().collect();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].low.date_time, utc("2023-04-03T00:00"));
+        assert_eq!(cycles[0].next_low.date_time, utc("2023-04-03T12:30"));
+    }
+
+    #[test]
+    fn takes_the_higher_of_a_double_high_water() {
+        let tides = TidePredictionsBuilder::new()
+            .event(low("2023-04-03T00:00", 0.5))
+            .event(high("2023-04-03T05:00", 4.2))
+            .event(high("2023-04-03T07:00", 4.4))
+            .event(low("2023-04-03T12:30", 0.6))
+            .build();
+        let cycle = tides.cycles().next().unwrap();
+        assert_eq!(cycle.high.date_time, utc("2023-04-03T07:00"));
+    }
+
+    #[test]
+    fn skips_low_tides_without_a_high_tide_between_them() {
+        let tides = TidePredictionsBuilder::new()
+            .event(low("2023-04-03T00:00", 0.5))
+            .event(low("2023-04-03T02:00", 0.4))
+            .event(high("2023-04-03T07:00", 4.4))
+            .event(low("2023-04-03T12:30", 0.6))
+            .build();
+        let cycles: Vec<_> = tides.cycles().collect();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].low.date_time, utc("2023-04-03T02:00"));
+    }
+
+    #[test]
+    fn has_no_statistics_without_a_complete_cycle() {
+        let tides = TidePredictionsBuilder::new()
+            .event(low("2023-04-03T00:00", 1.0))
+            .event(high("2023-04-03T06:00", 5.0))
+            .build();
+        assert_eq!(tides.cycles().count(), 0);
+        assert!(tides.cycle_statistics().is_none());
+        assert!(TidePredictionsBuilder::new()
+            .build()
+            .cycle_statistics()
+            .is_none());
+    }
+
+    #[test]
+    fn finds_the_spring_and_neap_ranges() {
+        // A spring cycle with a range of 5m, then a neap cycle with a range of 2m.
+        let tides = TidePredictionsBuilder::new()
+            .event(low("2023-04-03T00:00", 0.5))
+            .event(high("2023-04-03T06:00", 5.5))
+            .event(low("2023-04-03T12:00", 0.5))
+            .event(high("2023-04-03T19:00", 2.5))
+            .event(low("2023-04-04T01:00", 0.5))
+            .build();
+        let stats = tides.cycle_statistics().unwrap();
+        assert_eq!(stats.cycles, 2);
+        assert_eq!(stats.max_range.0, 5.0);
+        assert_eq!(stats.min_range.0, 2.0);
+        assert_eq!(stats.mean_range.0, 3.5);
+        assert_eq!(stats.mean_duration, Duration::minutes(12 * 60 + 30));
+        assert_eq!(stats.asymmetry, 13.0 / 12.0);
+    }
+}
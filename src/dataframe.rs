@@ -0,0 +1,165 @@
+//! Conversion of tide predictions into Polars DataFrames.
+//!
+//! Enabled by the `polars` feature.
+
+use polars::prelude::*;
+
+use crate::TidePredictions;
+
+impl TidePredictions {
+    /// The high and low tides and the continuous heights as Polars DataFrames, for analysis
+    /// without going through CSV.
+    ///
+    /// The events have the columns `time`, `type` (`"high"` or `"low"`), `height` in
+    /// metres, `approximate_time` and `approximate_height`, in chronological order, as
+    /// with [`write_tides_csv`](crate::write_tides_csv). The heights have the columns
+    /// `time` and `height`. Times are datetimes in UTC with millisecond precision, and
+    /// heights are 64-bit floats.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if Polars fails to build a column.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    ///
+    /// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+    ///     .expect("Failed to read file as tides data.");
+    /// let (events, heights) = tides.to_dataframes().unwrap();
+    /// assert_eq!(events.height(), tides.tidal_event_list.len());
+    /// assert_eq!(heights.height(), tides.tidal_height_occurrence_list.len());
+    /// ```
+    pub fn to_dataframes(&self) -> PolarsResult<(DataFrame, DataFrame)> {
+        let mut events: Vec<_> = self.tidal_event_list.iter().collect();
+        events.sort();
+        let events = DataFrame::new(
+            events.len(),
+            vec![
+                utc_times(
+                    "time",
+                    events.iter().map(|e| e.date_time.timestamp_millis()),
+                )?,
+                column(
+                    "type",
+                    events
+                        .iter()
+                        .map(|e| e.event_type.name())
+                        .collect::<Vec<_>>(),
+                ),
+                column(
+                    "height",
+                    events.iter().map(|e| e.height.0).collect::<Vec<_>>(),
+                ),
+                column(
+                    "approximate_time",
+                    events
+                        .iter()
+                        .map(|e| e.is_approximate_time.is_some())
+                        .collect::<Vec<_>>(),
+                ),
+                column(
+                    "approximate_height",
+                    events
+                        .iter()
+                        .map(|e| e.is_approximate_height.is_some())
+                        .collect::<Vec<_>>(),
+                ),
+            ],
+        )?;
+        let heights = &self.tidal_height_occurrence_list;
+        let heights = DataFrame::new(
+            heights.len(),
+            vec![
+                utc_times(
+                    "time",
+                    heights.iter().map(|h| h.date_time.timestamp_millis()),
+                )?,
+                column(
+                    "height",
+                    heights.iter().map(|h| h.height.0).collect::<Vec<_>>(),
+                ),
+            ],
+        )?;
+        Ok((events, heights))
+    }
+}
+
+fn column<T>(name: &str, values: T) -> Column
+where
+    Series: NamedFrom<T, [T::Item]>,
+    T: IntoIterator + AsRef<[T::Item]>,
+{
+    Series::new(name.into(), values).into()
+}
+
+/// A datetime column in UTC from milliseconds since the Unix epoch.
+fn utc_times(name: &str, millis: impl Iterator<Item = i64>) -> PolarsResult<Column> {
+    let millis = Series::new(name.into(), millis.collect::<Vec<_>>());
+    let times = millis.cast(&DataType::Datetime(
+        TimeUnit::Milliseconds,
+        Some(TimeZone::UTC),
+    ))?;
+    Ok(times.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{utc, TidalEventBuilder, TidePredictionsBuilder};
+
+    #[test]
+    fn converts_events_and_heights_to_typed_columns() {
+        let tides = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::low().at("2023-04-03T12:15").height(0.5))
+            .event(
+                TidalEventBuilder::high()
+                    .at("2023-04-03T06:00")
+                    .height(4.25),
+            )
+            .height("2023-04-03T06:00", 4.25)
+            .height("2023-04-03T06:30", 4.0)
+            .build();
+        let (events, heights) = tides.to_dataframes().unwrap();
+
+        let utc_millis = DataType::Datetime(TimeUnit::Milliseconds, Some(TimeZone::UTC));
+        assert_eq!(
+            events.dtypes(),
+            [
+                utc_millis.clone(),
+                DataType::String,
+                DataType::Float64,
+                DataType::Boolean,
+                DataType::Boolean
+            ]
+        );
+        assert_eq!(heights.dtypes(), [utc_millis, DataType::Float64]);
+
+        // Events are sorted into chronological order.
+        let times: Vec<_> = events["time"]
+            .datetime()
+            .unwrap()
+            .physical()
+            .iter()
+            .collect();
+        let expected = [utc("2023-04-03T06:00"), utc("2023-04-03T12:15")];
+        assert_eq!(times, expected.map(|t| Some(t.timestamp_millis())));
+        let types: Vec<_> = events["type"].str().unwrap().iter().collect();
+        assert_eq!(types, [Some("high"), Some("low")]);
+        let event_heights: Vec<_> = events["height"].f64().unwrap().iter().collect();
+        assert_eq!(event_heights, [Some(4.25), Some(0.5)]);
+
+        let heights: Vec<_> = heights["height"].f64().unwrap().iter().collect();
+        assert_eq!(heights, [Some(4.25), Some(4.0)]);
+    }
+
+    #[test]
+    fn converts_empty_predictions() {
+        let (events, heights) = TidePredictionsBuilder::new()
+            .build()
+            .to_dataframes()
+            .unwrap();
+        assert_eq!(events.shape(), (0, 5));
+        assert_eq!(heights.shape(), (0, 2));
+    }
+}
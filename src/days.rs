@@ -0,0 +1,319 @@
+use chrono::{DateTime, Days, NaiveDate, TimeZone, Utc};
+use chrono_tz::Europe::London;
+
+use crate::{
+    LunarPhase, LunarPhaseType, Metres, TidalEvent, TidalEventType, TidalHeightOccurrence,
+    TidePredictions,
+};
+
+/// The days after a lunar phase on which its spring or neap tides are expected, from the
+/// second to the fourth day.
+const SPRINGS_NEAPS_DAYS: std::ops::RangeInclusive<u64> = 1..=3;
+
+/// The predictions for a single calendar date, as yielded by [`TidePredictions::days`].
+#[derive(Debug, Clone, Copy)]
+pub struct DayPredictions<'a> {
+    /// The date, in the time zone used to divide the predictions into days.
+    pub date: NaiveDate,
+    /// The high and low tides on the date.
+    pub events: &'a [TidalEvent],
+    /// The continuous heights on the date.
+    pub heights: &'a [TidalHeightOccurrence],
+    /// The lunar phase on the date, if there is one.
+    pub lunar_phase: Option<&'a LunarPhase>,
+    /// Whether the date is a spring or neap day, from the lunar phases before it.
+    springs_neaps: Option<SpringsNeaps>,
+}
+
+/// Whether tides are at their largest or smallest in the lunar cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpringsNeaps {
+    /// The largest tides, following new and full moons.
+    Springs,
+    /// The smallest tides, following first and last quarter moons.
+    Neaps,
+}
+
+/// The figures for a day's tides, as given by [`DayPredictions::summary`].
+#[derive(Debug, Clone, Copy)]
+pub struct DaySummary<'a> {
+    /// The date summarised.
+    pub date: NaiveDate,
+    /// The highest tide on the date, if there are any tides.
+    pub highest: Option<&'a TidalEvent>,
+    /// The lowest tide on the date, if there are any tides.
+    pub lowest: Option<&'a TidalEvent>,
+    /// The largest rise or fall between successive tides on the date, if there are at
+    /// least two tides.
+    pub max_range: Option<Metres>,
+    /// The number of high tides on the date.
+    pub high_waters: usize,
+    /// The number of low tides on the date.
+    pub low_waters: usize,
+    /// Whether there is a double high or low water, as at Southampton, shown as more than
+    /// two high or low tides in the day or two of the same type in a row.
+    pub double_tide: bool,
+    /// Whether the date is a spring or neap day, the second to fourth days after a new
+    /// or full moon or a quarter moon, if the lunar phases show it is either.
+    pub springs_neaps: Option<SpringsNeaps>,
+}
+
+impl<'a> DayPredictions<'a> {
+    /// The highest and lowest tides, the largest range, the number of tides, and whether
+    /// the date is a spring or neap day.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    ///
+    /// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+    ///     .expect("Failed to read file as tides data.");
+    /// let day = tides.days().nth(1).unwrap();
+    /// let summary = day.summary();
+    /// assert_eq!(summary.high_waters + summary.low_waters, day.events.len());
+    /// let (highest, lowest) = (summary.highest.unwrap(), summary.lowest.unwrap());
+    /// assert!(day.events.iter().all(|e| lowest.height.0 <= e.height.0 && e.height.0 <= highest.height.0));
+    /// assert!(summary.max_range.unwrap().0 <= highest.height.0 - lowest.height.0);
+    /// assert!(!summary.double_tide);
+    /// ```
+    pub fn summary(&self) -> DaySummary<'a> {
+        let events = self.events;
+        let count = |event_type| events.iter().filter(|e| e.event_type == event_type).count();
+        let (high_waters, low_waters) = (
+            count(TidalEventType::HighWater),
+            count(TidalEventType::LowWater),
+        );
+        let repeated = events
+            .windows(2)
+            .any(|w| w[0].event_type == w[1].event_type);
+        DaySummary {
+            date: self.date,
+            highest: events
+                .iter()
+                .max_by(|a, b| a.height.0.total_cmp(&b.height.0)),
+            lowest: events
+                .iter()
+                .min_by(|a, b| a.height.0.total_cmp(&b.height.0)),
+            max_range: events
+                .windows(2)
+                .map(|w| (w[1].height.0 - w[0].height.0).abs())
+                .max_by(f64::total_cmp)
+                .map(Metres),
+            high_waters,
+            low_waters,
+            double_tide: high_waters > 2 || low_waters > 2 || repeated,
+            springs_neaps: self.springs_neaps,
+        }
+    }
+}
+
+impl TidePredictions {
+    /// The predictions divided into UK calendar dates, from the first date with a tide or
+    /// height prediction to the last, including any dates in between without one.
+    ///
+    /// The tides, heights and lunar phases are expected to be in chronological order, as
+    /// they are from EasyTide and after [`merge`](TidePredictions::merge).
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    ///
+    /// use chrono::NaiveDate;
+    ///
+    /// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+    ///     .expect("Failed to read file as tides data.");
+    /// let days: Vec<_> = tides.days().collect();
+    /// assert_eq!(days[0].date, NaiveDate::from_ymd_opt(2023, 4, 3).unwrap());
+    /// assert_eq!(days[0].events[0].date_time, tides.tidal_event_list[0].date_time);
+    /// assert_eq!(days.iter().map(|d| d.events.len()).sum::<usize>(), tides.tidal_event_list.len());
+    /// assert_eq!(days[0].heights.len(), 46);
+    /// ```
+    pub fn days(&self) -> impl Iterator<Item = DayPredictions<'_>> {
+        self.days_in(London)
+    }
+
+    /// The predictions divided into calendar dates in the time zone `tz`, as with
+    /// [`days`](TidePredictions::days).
+    pub fn days_in<Tz: TimeZone>(&self, tz: Tz) -> impl Iterator<Item = DayPredictions<'_>> {
+        let date = move |t: DateTime<Utc>| t.with_timezone(&tz).date_naive();
+        let first = [
+            self.tidal_event_list.first().map(|e| e.date_time),
+            self.tidal_height_occurrence_list
+                .first()
+                .map(|h| h.date_time),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        let last = [
+            self.tidal_event_list.last().map(|e| e.date_time),
+            self.tidal_height_occurrence_list
+                .last()
+                .map(|h| h.date_time),
+        ]
+        .into_iter()
+        .flatten()
+        .max();
+        let dates = first
+            .zip(last)
+            .map(|(first, last)| date(first)..=date(last));
+        dates
+            .into_iter()
+            .flat_map(|dates| {
+                dates
+                    .start()
+                    .iter_days()
+                    .take_while(move |d| d <= dates.end())
+            })
+            .map(move |day| DayPredictions {
+                date: day,
+                events: on_date(&self.tidal_event_list, day, |e| date(e.date_time)),
+                heights: on_date(&self.tidal_height_occurrence_list, day, |h| {
+                    date(h.date_time)
+                }),
+                lunar_phase: on_date(&self.lunar_phase_list, day, |p| date(p.date_time)).first(),
+                springs_neaps: springs_neaps(&self.lunar_phase_list, day, &date),
+            })
+    }
+}
+
+/// The items in `items`, which are in chronological order, falling on `day`.
+fn on_date<T>(items: &[T], day: NaiveDate, date: impl Fn(&T) -> NaiveDate) -> &[T] {
+    let start = items.partition_point(|item| date(item) < day);
+    let end = start + items[start..].partition_point(|item| date(item) == day);
+    &items[start..end]
+}
+
+/// Whether `day` is a spring or neap day, from the most recent lunar phase falling the
+/// right number of days before it.
+fn springs_neaps(
+    phases: &[LunarPhase],
+    day: NaiveDate,
+    date: impl Fn(DateTime<Utc>) -> NaiveDate,
+) -> Option<SpringsNeaps> {
+    phases
+        .iter()
+        .filter(|p| {
+            SPRINGS_NEAPS_DAYS
+                .clone()
+                .any(|days| day.checked_sub_days(Days::new(days)) == Some(date(p.date_time)))
+        })
+        .max_by_key(|p| p.date_time)
+        .map(|p| match p.lunar_phase_type {
+            LunarPhaseType::NewMoon | LunarPhaseType::FullMoon => SpringsNeaps::Springs,
+            LunarPhaseType::FirstQuarter | LunarPhaseType::LastQuarter => SpringsNeaps::Neaps,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{TidalEventBuilder, TidePredictionsBuilder};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn days(tides: &TidePredictions) -> Vec<(NaiveDate, usize, usize)> {
+        tides
+            .days()
+            .map(|d| (d.date, d.events.len(), d.heights.len()))
+            .collect()
+    }
+
+    #[test]
+    fn groups_by_uk_date_in_summer_time() {
+        // 23:30 UTC is 00:30 the next day in British Summer Time.
+        let tides = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::high().at("2023-04-03T12:00"))
+            .event(TidalEventBuilder::low().at("2023-04-03T23:30"))
+            .height("2023-04-03T22:30", 2.0)
+            .height("2023-04-03T23:00", 1.9)
+            .build();
+        assert_eq!(
+            days(&tides),
+            [(date(2023, 4, 3), 1, 1), (date(2023, 4, 4), 1, 1)]
+        );
+    }
+
+    #[test]
+    fn groups_by_uk_date_in_winter_time() {
+        let tides = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::high().at("2023-01-09T12:00"))
+            .event(TidalEventBuilder::low().at("2023-01-09T23:30"))
+            .height("2023-01-09T23:30", 1.9)
+            .height("2023-01-10T00:00", 2.0)
+            .build();
+        assert_eq!(
+            days(&tides),
+            [(date(2023, 1, 9), 2, 1), (date(2023, 1, 10), 0, 1)]
+        );
+    }
+
+    #[test]
+    fn groups_by_date_in_the_given_time_zone() {
+        let tides = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::high().at("2023-04-03T12:00"))
+            .event(TidalEventBuilder::low().at("2023-04-03T23:30"))
+            .build();
+        let dates: Vec<_> = tides
+            .days_in(Utc)
+            .map(|d| (d.date, d.events.len()))
+            .collect();
+        assert_eq!(dates, [(date(2023, 4, 3), 2)]);
+    }
+
+    #[test]
+    fn includes_days_without_predictions() {
+        let tides = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::high().at("2023-04-03T12:00"))
+            .event(TidalEventBuilder::low().at("2023-04-05T12:00"))
+            .build();
+        let empty = tides.days().nth(1).unwrap();
+        assert_eq!(empty.date, date(2023, 4, 4));
+        assert!(empty.events.is_empty() && empty.heights.is_empty());
+        assert!(empty.lunar_phase.is_none());
+        let summary = empty.summary();
+        assert!(summary.highest.is_none() && summary.lowest.is_none());
+        assert!(summary.max_range.is_none());
+        assert_eq!(summary.high_waters + summary.low_waters, 0);
+    }
+
+    #[test]
+    fn classifies_the_second_to_fourth_days_after_a_phase() {
+        let tides = TidePredictionsBuilder::new()
+            .height("2023-04-05T00:00", 2.0)
+            .height("2023-04-17T00:00", 2.0)
+            .lunar_phase("2023-04-06T04:34", LunarPhaseType::FullMoon)
+            .lunar_phase("2023-04-13T09:11", LunarPhaseType::LastQuarter)
+            .build();
+        let classes: Vec<_> = tides.days().map(|d| d.summary().springs_neaps).collect();
+        // The days start on the 5th.
+        let day = |d: usize| classes[d - 5];
+        assert_eq!(day(6), None);
+        assert_eq!(day(7), Some(SpringsNeaps::Springs));
+        assert_eq!(day(9), Some(SpringsNeaps::Springs));
+        assert_eq!(day(10), None);
+        assert_eq!(day(13), None);
+        assert_eq!(day(14), Some(SpringsNeaps::Neaps));
+        assert_eq!(day(16), Some(SpringsNeaps::Neaps));
+        assert_eq!(day(17), None);
+    }
+
+    #[test]
+    fn classifies_by_the_latest_phase_when_they_overlap() {
+        // Phases two days apart would not happen, but the nearer one wins.
+        let tides = TidePredictionsBuilder::new()
+            .height("2023-04-10T00:00", 2.0)
+            .lunar_phase("2023-04-06T12:00", LunarPhaseType::FullMoon)
+            .lunar_phase("2023-04-08T12:00", LunarPhaseType::LastQuarter)
+            .build();
+        let day = tides.days().next().unwrap();
+        assert_eq!(day.summary().springs_neaps, Some(SpringsNeaps::Neaps));
+    }
+
+    #[test]
+    fn has_no_days_without_predictions() {
+        assert_eq!(TidePredictionsBuilder::new().build().days().count(), 0);
+    }
+}
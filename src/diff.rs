@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+
+use chrono::Duration;
+
+use crate::atlas::apart;
+use crate::{pair_tides, Metres, Station, TidalEvent, TidePredictions};
+
+/// How far apart tides of the same type in two sets of predictions can be and still be
+/// taken for the same tide, in minutes. Successive tides of a type are about 12 hours 25
+/// minutes apart.
+const TIDE_MATCH_MINUTES: i64 = 3 * 60;
+
+/// The differences between two lists of stations, such as a saved list and the current
+/// one, each in order of station ID.
+#[derive(Debug, Clone, Default)]
+pub struct StationsDiff<'a> {
+    /// Stations only in the new list.
+    pub added: Vec<&'a Station>,
+    /// Stations only in the old list.
+    pub removed: Vec<&'a Station>,
+    /// Stations in both lists whose details differ.
+    pub changed: Vec<StationChange<'a>>,
+}
+
+impl StationsDiff<'_> {
+    /// Whether the lists contain the same stations with the same details.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// A station whose details differ between two lists.
+#[derive(Debug, Clone)]
+pub struct StationChange<'a> {
+    /// The station in the old list.
+    pub old: &'a Station,
+    /// The station in the new list.
+    pub new: &'a Station,
+    /// The names of the fields that differ: `"name"`, `"country"`, `"location"` and
+    /// `"continuous_heights_available"`.
+    pub fields: Vec<&'static str>,
+}
+
+/// Compare the `old` list of stations with the `new` one, matching stations by ID.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// let old = rjw_uktides::stations_from_reader(File::open("./stations.json").unwrap())
+///     .expect("Failed to read file as stations data.");
+/// let mut new = old.clone();
+/// let retired = new.remove(0);
+/// new[0].name = "RENAMED".to_owned();
+///
+/// let diff = rjw_uktides::diff_stations(&old, &new);
+/// assert!(diff.added.is_empty());
+/// assert_eq!(diff.removed[0].id, retired.id);
+/// assert_eq!(diff.changed[0].new.name, "RENAMED");
+/// assert_eq!(diff.changed[0].fields, ["name"]);
+/// assert!(rjw_uktides::diff_stations(&old, &old).is_empty());
+/// ```
+pub fn diff_stations<'a>(old: &'a [Station], new: &'a [Station]) -> StationsDiff<'a> {
+    let old_by_id: HashMap<_, _> = old.iter().map(|s| (&s.id, s)).collect();
+    let new_by_id: HashMap<_, _> = new.iter().map(|s| (&s.id, s)).collect();
+
+    let mut diff = StationsDiff {
+        added: new
+            .iter()
+            .filter(|s| !old_by_id.contains_key(&s.id))
+            .collect(),
+        removed: old
+            .iter()
+            .filter(|s| !new_by_id.contains_key(&s.id))
+            .collect(),
+        changed: old
+            .iter()
+            .filter_map(|old| {
+                let new = new_by_id.get(&old.id)?;
+                let fields = changed_fields(old, new);
+                (!fields.is_empty()).then_some(StationChange { old, new, fields })
+            })
+            .collect(),
+    };
+    diff.added.sort();
+    diff.removed.sort();
+    diff.changed.sort_by(|a, b| a.old.cmp(b.old));
+    diff
+}
+
+fn changed_fields(old: &Station, new: &Station) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if old.name != new.name {
+        fields.push("name");
+    }
+    if old.country != new.country {
+        fields.push("country");
+    }
+    if old.location.latitude.0 != new.location.latitude.0
+        || old.location.longitude.0 != new.location.longitude.0
+    {
+        fields.push("location");
+    }
+    if old.continuous_heights_available != new.continuous_heights_available {
+        fields.push("continuous_heights_available");
+    }
+    fields
+}
+
+/// The differences between the tides in two sets of predictions for a station, such as
+/// those fetched an hour ago and those fetched now.
+#[derive(Debug, Clone, Default)]
+pub struct TidesDiff<'a> {
+    /// Tides only in the new predictions, within the time both cover.
+    pub added: Vec<&'a TidalEvent>,
+    /// Tides only in the old predictions, within the time both cover.
+    pub removed: Vec<&'a TidalEvent>,
+    /// Tides in both predictions whose time or height differs, in order of the old tide.
+    pub shifted: Vec<TideShift<'a>>,
+}
+
+impl<'a> TidesDiff<'a> {
+    /// Whether the predictions give the same tides over the time both cover.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.shifted.is_empty()
+    }
+
+    /// The shifted tides whose time moved by more than `time` or height by more than
+    /// `height`, either way.
+    pub fn exceeding(
+        &self,
+        time: Duration,
+        height: Metres,
+    ) -> impl Iterator<Item = &TideShift<'a>> {
+        self.shifted.iter().filter(move |shift| {
+            apart(shift.old.date_time, shift.new.date_time) > time
+                || shift.height_shift().0.abs() > height.0
+        })
+    }
+}
+
+/// A tide whose time or height differs between two sets of predictions.
+#[derive(Debug, Clone, Copy)]
+pub struct TideShift<'a> {
+    /// The tide in the old predictions.
+    pub old: &'a TidalEvent,
+    /// The tide in the new predictions.
+    pub new: &'a TidalEvent,
+}
+
+impl TideShift<'_> {
+    /// How much later the tide is in the new predictions, negative if it is earlier.
+    pub fn time_shift(&self) -> Duration {
+        self.new.date_time - self.old.date_time
+    }
+
+    /// How much higher the tide is in the new predictions, negative if it is lower.
+    pub fn height_shift(&self) -> Metres {
+        Metres(self.new.height.0 - self.old.height.0)
+    }
+}
+
+/// Compare the tides in the `old` predictions with those in the `new` ones, pairing them
+/// with [`pair_tides`] so that each tide matches the nearest unmatched tide of the same
+/// type within three hours.
+///
+/// Only the time both predictions cover is compared, so tides that have dropped off the
+/// start of the old predictions or been added to the end of the new ones are not listed
+/// as removed or added. The heights should be in the same units.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// use chrono::Duration;
+/// use rjw_uktides::Metres;
+///
+/// let old = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+///     .expect("Failed to read file as tides data.");
+/// let mut new = old.clone();
+/// new.tidal_event_list[2].date_time += Duration::minutes(20);
+/// new.tidal_event_list[3].height.0 += 0.05;
+///
+/// let diff = rjw_uktides::diff_tides(&old, &new);
+/// assert!(diff.added.is_empty() && diff.removed.is_empty());
+/// assert_eq!(diff.shifted.len(), 2);
+/// assert_eq!(diff.shifted[0].time_shift(), Duration::minutes(20));
+/// let alerts: Vec<_> = diff.exceeding(Duration::minutes(10), Metres(0.1)).collect();
+/// assert_eq!(alerts.len(), 1);
+/// assert!(rjw_uktides::diff_tides(&old, &old).is_empty());
+///
+/// // A tide that has gone, an hour after another of the same type, is removed rather
+/// // than matched with the same new tide as its neighbour.
+/// let mut doubled = old.clone();
+/// let mut extra = doubled.tidal_event_list[0].clone();
+/// extra.date_time += Duration::hours(1);
+/// doubled.tidal_event_list.insert(1, extra);
+/// let diff = rjw_uktides::diff_tides(&doubled, &old);
+/// assert_eq!(diff.removed.len(), 1);
+/// assert!(diff.added.is_empty() && diff.shifted.is_empty());
+/// ```
+pub fn diff_tides<'a>(old: &'a TidePredictions, new: &'a TidePredictions) -> TidesDiff<'a> {
+    let within = Duration::minutes(TIDE_MATCH_MINUTES);
+    let (old_events, new_events) = (&old.tidal_event_list, &new.tidal_event_list);
+    let bounds = |events: &[TidalEvent]| {
+        let times = events.iter().map(|e| e.date_time);
+        times.clone().min().zip(times.max())
+    };
+    let Some(((old_first, old_last), (new_first, new_last))) =
+        bounds(old_events).zip(bounds(new_events))
+    else {
+        return TidesDiff::default();
+    };
+    let covered = |e: &TidalEvent| {
+        e.date_time >= old_first.max(new_first) - within
+            && e.date_time <= old_last.min(new_last) + within
+    };
+    let mut diff = TidesDiff::default();
+    for pair in pair_tides(old_events, new_events, within) {
+        match pair {
+            (Some(old), Some(new))
+                if old.date_time != new.date_time || old.height.0 != new.height.0 =>
+            {
+                diff.shifted.push(TideShift { old, new })
+            }
+            (Some(old), None) if covered(old) => diff.removed.push(old),
+            (None, Some(new)) if covered(new) => diff.added.push(new),
+            _ => {}
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{StationBuilder, TidalEventBuilder, TidePredictionsBuilder};
+
+    fn ids<'a>(stations: &[&'a Station]) -> Vec<&'a str> {
+        stations.iter().map(|s| s.id.0.as_str()).collect()
+    }
+
+    #[test]
+    fn lists_added_removed_and_changed_stations() {
+        let old = [
+            StationBuilder::new("0001", "St Mary's").build(),
+            StationBuilder::new("0002", "Penzance").build(),
+            StationBuilder::new("0003", "Newlyn").build(),
+        ];
+        let new = [
+            StationBuilder::new("0004", "Mousehole").build(),
+            StationBuilder::new("0003", "Newlyn")
+                .country("Wales")
+                .location(50.1, -5.5)
+                .continuous_heights_available(false)
+                .build(),
+            StationBuilder::new("0001", "St Mary's").build(),
+        ];
+        let diff = diff_stations(&old, &new);
+        assert_eq!(ids(&diff.added), ["0004"]);
+        assert_eq!(ids(&diff.removed), ["0002"]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].old.id.0, "0003");
+        assert_eq!(
+            diff.changed[0].fields,
+            ["country", "location", "continuous_heights_available"]
+        );
+    }
+
+    #[test]
+    fn lists_stations_in_order_of_id() {
+        let old = [];
+        let new = [
+            StationBuilder::new("0010", "Ten").build(),
+            StationBuilder::new("0002", "Two").build(),
+        ];
+        assert_eq!(ids(&diff_stations(&old, &new).added), ["0002", "0010"]);
+        assert_eq!(ids(&diff_stations(&new, &old).removed), ["0002", "0010"]);
+    }
+
+    fn day() -> TidePredictionsBuilder {
+        TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::high().at("2023-04-03T06:00").height(4.0))
+            .event(TidalEventBuilder::low().at("2023-04-03T12:15").height(1.0))
+            .event(TidalEventBuilder::high().at("2023-04-03T18:30").height(4.1))
+    }
+
+    #[test]
+    fn lists_added_removed_and_shifted_tides() {
+        let old = day()
+            .event(TidalEventBuilder::low().at("2023-04-04T00:45").height(0.9))
+            .build();
+        let new = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::high().at("2023-04-03T06:20").height(4.0))
+            .event(TidalEventBuilder::high().at("2023-04-03T18:30").height(4.3))
+            .event(TidalEventBuilder::low().at("2023-04-04T00:45").height(0.9))
+            .event(TidalEventBuilder::low().at("2023-04-03T09:00").height(1.2))
+            .build();
+        let diff = diff_tides(&old, &new);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(
+            diff.removed[0].date_time.to_rfc3339(),
+            "2023-04-03T12:15:00+00:00"
+        );
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(
+            diff.added[0].date_time.to_rfc3339(),
+            "2023-04-03T09:00:00+00:00"
+        );
+        assert_eq!(diff.shifted.len(), 2);
+        assert_eq!(diff.shifted[0].time_shift(), Duration::minutes(20));
+        assert!((diff.shifted[1].height_shift().0 - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ignores_tides_outside_the_time_both_cover() {
+        let old = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::low().at("2023-04-02T23:45").height(0.9))
+            .event(TidalEventBuilder::high().at("2023-04-03T06:00").height(4.0))
+            .event(TidalEventBuilder::low().at("2023-04-03T12:15").height(1.0))
+            .build();
+        let new = day()
+            .event(TidalEventBuilder::low().at("2023-04-04T00:45").height(0.9))
+            .build();
+        let diff = diff_tides(&old, &new);
+        assert!(diff.is_empty(), "{diff:?}");
+    }
+
+    #[test]
+    fn empty_predictions_have_no_differences() {
+        let empty = TidePredictionsBuilder::new().build();
+        assert!(diff_tides(&empty, &day().build()).is_empty());
+        assert!(diff_tides(&day().build(), &empty).is_empty());
+    }
+}
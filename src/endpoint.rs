@@ -0,0 +1,83 @@
+use crate::{Station, StationId};
+
+/// The EasyTide web service used by the official EasyTide website.
+pub const DEFAULT_BASE_URL: &str = "https://easytide.admiralty.co.uk";
+
+const STATIONS_PATH: &str = "/Home/GetStations";
+const TIDES_PATH: &str = "/Home/GetPredictionData";
+
+/// The URL from which the list of all stations is fetched, for the service at `base_url`.
+///
+/// # Examples
+/// ```
+/// use rjw_uktides::{stations_url, DEFAULT_BASE_URL};
+///
+/// assert_eq!(
+///     stations_url(DEFAULT_BASE_URL),
+///     "https://easytide.admiralty.co.uk/Home/GetStations"
+/// );
+/// ```
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", ret))]
+pub fn stations_url(base_url: &str) -> String {
+    format!("{base_url}{STATIONS_PATH}")
+}
+
+/// The URL from which tide predictions for the station are fetched, for the service at
+/// `base_url`.
+///
+/// # Examples
+/// ```
+/// use rjw_uktides::{tides_url, StationId, DEFAULT_BASE_URL};
+///
+/// assert_eq!(
+///     tides_url(DEFAULT_BASE_URL, &StationId("0053".to_owned())),
+///     "https://easytide.admiralty.co.uk/Home/GetPredictionData?stationId=0053"
+/// );
+/// ```
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", ret))]
+pub fn tides_url(base_url: &str, station: &StationId) -> String {
+    format!(
+        "{base_url}{TIDES_PATH}?stationId={}",
+        percent_encode(&station.0)
+    )
+}
+
+impl StationId {
+    /// The page for the station on the public EasyTide website.
+    ///
+    /// # Examples
+    /// ```
+    /// use rjw_uktides::StationId;
+    ///
+    /// assert_eq!(
+    ///     StationId("0053".to_owned()).easytide_url(),
+    ///     "https://easytide.admiralty.co.uk/?PortID=0053"
+    /// );
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", ret))]
+    pub fn easytide_url(&self) -> String {
+        format!("{DEFAULT_BASE_URL}/?PortID={}", percent_encode(&self.0))
+    }
+}
+
+impl Station {
+    /// The page for the station on the public EasyTide website.
+    ///
+    /// See [`StationId::easytide_url`].
+    pub fn easytide_url(&self) -> String {
+        self.id.easytide_url()
+    }
+}
+
+/// Percent-encode all but the unreserved characters of a URL query value.
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                char::from(b).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
@@ -0,0 +1,197 @@
+use std::fmt::Display;
+use std::ops::Range;
+
+/// An error encountered while parsing stations or tide predictions.
+///
+/// Use [`Error::kind`] to distinguish between failures to read the data, malformed JSON,
+/// and JSON that does not describe stations or tide predictions. The underlying
+/// parser error is available through [`std::error::Error::source`].
+///
+/// # Examples
+/// ```
+/// use rjw_uktides::ErrorKind;
+///
+/// let err = rjw_uktides::tides_from_reader(&b"{\"footerNote\": "[..]).unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::Syntax);
+///
+/// let err = rjw_uktides::tides_from_reader(&b"{\"footerNote\": 1}"[..]).unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::Data);
+/// assert_eq!(err.line(), 1);
+///
+/// // The span points to where the parser went wrong in the input.
+/// let input = b"{\"footerNote\" 1}";
+/// let err = rjw_uktides::tides_from_reader(&input[..]).unwrap_err();
+/// assert_eq!(&input[err.span_in(input).unwrap()], b"1");
+/// ```
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    source: Source,
+}
+
+/// The parser error underlying an [`Error`], depending on the JSON backend in use.
+#[derive(Debug)]
+enum Source {
+    Json(serde_json::Error),
+    #[cfg(feature = "sonic-rs")]
+    Sonic(sonic_rs::Error),
+}
+
+/// The broad category of a parsing [`Error`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The reader failed while the data was being read from it.
+    Io,
+    /// The data is not syntactically valid JSON, or ends unexpectedly.
+    Syntax,
+    /// The data is valid JSON but does not match the structure or values expected
+    /// from the Admiralty API.
+    Data,
+}
+
+impl Error {
+    /// The broad category of the error.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// The one-based line in the input at which the error occurred.
+    ///
+    /// This is zero for IO errors that did not occur at a particular position.
+    pub fn line(&self) -> usize {
+        match &self.source {
+            Source::Json(e) => e.line(),
+            #[cfg(feature = "sonic-rs")]
+            Source::Sonic(e) => e.line(),
+        }
+    }
+
+    /// The one-based column in the input at which the error occurred.
+    ///
+    /// This is zero for IO errors that did not occur at a particular position.
+    pub fn column(&self) -> usize {
+        match &self.source {
+            Source::Json(e) => e.column(),
+            #[cfg(feature = "sonic-rs")]
+            Source::Sonic(e) => e.column(),
+        }
+    }
+
+    /// The bytes of `input` at which the error occurred, for pointing to the offending
+    /// part of the data in an error report.
+    ///
+    /// `input` must be the data that was parsed. The span is the character at the
+    /// error's line and column, where the parser stopped, or empty at the end of the
+    /// input; for a value of the wrong type this is the character just after the value.
+    /// Returns `None` for errors that did not occur at a position in the input.
+    pub fn span_in(&self, input: &[u8]) -> Option<Range<usize>> {
+        if self.line() == 0 {
+            return None;
+        }
+        let line_start = input
+            .split_inclusive(|&b| b == b'\n')
+            .take(self.line() - 1)
+            .map(<[u8]>::len)
+            .sum::<usize>();
+        let start = line_start + self.column().saturating_sub(1);
+        if start > input.len() {
+            return None;
+        }
+        // Extend the span over the rest of a multi-byte UTF-8 character.
+        let len = match input.get(start) {
+            None => 0,
+            Some(b) if *b >= 0xf0 => 4,
+            Some(b) if *b >= 0xe0 => 3,
+            Some(b) if *b >= 0xc0 => 2,
+            Some(_) => 1,
+        };
+        Some(start..(start + len).min(input.len()))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(source: serde_json::Error) -> Self {
+        use serde_json::error::Category;
+        let kind = match source.classify() {
+            Category::Io => ErrorKind::Io,
+            Category::Syntax | Category::Eof => ErrorKind::Syntax,
+            Category::Data => ErrorKind::Data,
+        };
+        Self {
+            kind,
+            source: Source::Json(source),
+        }
+    }
+}
+
+#[cfg(feature = "sonic-rs")]
+impl From<sonic_rs::Error> for Error {
+    fn from(source: sonic_rs::Error) -> Self {
+        use sonic_rs::error::Category;
+        let kind = match source.classify() {
+            Category::Io => ErrorKind::Io,
+            Category::Syntax | Category::Eof => ErrorKind::Syntax,
+            _ => ErrorKind::Data,
+        };
+        Self {
+            kind,
+            source: Source::Sonic(source),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            ErrorKind::Io => write!(f, "failed to read data from the reader"),
+            ErrorKind::Syntax => write!(
+                f,
+                "invalid JSON at line {} column {}",
+                self.line(),
+                self.column()
+            ),
+            ErrorKind::Data => write!(
+                f,
+                "unexpected data at line {} column {}",
+                self.line(),
+                self.column()
+            ),
+        }
+    }
+}
+
+/// The error has a code and help, but no source code, as it does not keep the input; label
+/// the input at [`Error::span_in`] to point to where the error occurred.
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for Error {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let code = match self.kind {
+            ErrorKind::Io => "rjw_uktides::io",
+            ErrorKind::Syntax => "rjw_uktides::syntax",
+            ErrorKind::Data => "rjw_uktides::data",
+        };
+        Some(Box::new(code))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let help = match self.kind {
+            ErrorKind::Io => return None,
+            ErrorKind::Syntax => "the data is not valid JSON, or ends unexpectedly",
+            ErrorKind::Data => {
+                "the data is valid JSON, but not stations or tide predictions as EasyTide \
+                 gives them; the service may have changed"
+            }
+        };
+        Some(Box::new(help))
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.source {
+            Source::Json(e) => Some(e),
+            #[cfg(feature = "sonic-rs")]
+            Source::Sonic(e) => Some(e),
+        }
+    }
+}
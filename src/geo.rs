@@ -0,0 +1,154 @@
+use crate::{Coordinates, Station};
+
+/// The mean radius of the Earth, in kilometres.
+const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+impl Coordinates {
+    /// The great-circle distance to `other` in kilometres, using the haversine formula.
+    ///
+    /// # Examples
+    /// ```
+    /// use rjw_uktides::{Coordinates, DecimalDegrees};
+    ///
+    /// let sandown = Coordinates {
+    ///     latitude: DecimalDegrees(50.65),
+    ///     longitude: DecimalDegrees(-1.15),
+    /// };
+    /// let ryde = Coordinates {
+    ///     latitude: DecimalDegrees(50.733333),
+    ///     longitude: DecimalDegrees(-1.15),
+    /// };
+    /// assert!((sandown.distance_km(&ryde) - 9.27).abs() < 0.01);
+    /// ```
+    pub fn distance_km(&self, other: &Coordinates) -> f64 {
+        let (lat1, lat2) = (self.latitude.0.to_radians(), other.latitude.0.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude.0 - self.longitude.0).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+
+    /// Whether the point is inside the polygon with the given vertices, in order around
+    /// its edge. The polygon may be closed by repeating the first vertex at the end.
+    ///
+    /// Edges are treated as straight lines of latitude and longitude, which is close
+    /// enough for areas the size of an estuary or stretch of coast. Points exactly on an
+    /// edge may be inside or outside. A polygon with fewer than three vertices contains
+    /// no points.
+    ///
+    /// # Examples
+    /// ```
+    /// use rjw_uktides::{Coordinates, DecimalDegrees};
+    ///
+    /// let point = |latitude, longitude| Coordinates {
+    ///     latitude: DecimalDegrees(latitude),
+    ///     longitude: DecimalDegrees(longitude),
+    /// };
+    /// let triangle = [point(50.0, -2.0), point(51.0, -2.0), point(50.0, -1.0)];
+    /// assert!(point(50.2, -1.8).is_within_polygon(&triangle));
+    /// assert!(!point(50.9, -1.1).is_within_polygon(&triangle));
+    /// ```
+    pub fn is_within_polygon(&self, polygon: &[Coordinates]) -> bool {
+        if polygon.len() < 3 {
+            return false;
+        }
+        let (x, y) = (self.longitude.0, self.latitude.0);
+        let mut inside = false;
+        let mut previous = &polygon[polygon.len() - 1];
+        for vertex in polygon {
+            let (x1, y1) = (previous.longitude.0, previous.latitude.0);
+            let (x2, y2) = (vertex.longitude.0, vertex.latitude.0);
+            // Count the edges crossed by a line from the point heading east.
+            if (y1 > y) != (y2 > y) && x < x1 + (y - y1) * (x2 - x1) / (y2 - y1) {
+                inside = !inside;
+            }
+            previous = vertex;
+        }
+        inside
+    }
+
+    /// The point as Well-Known Text, `POINT(longitude latitude)`, as read by PostGIS and
+    /// QGIS.
+    ///
+    /// # Examples
+    /// ```
+    /// use rjw_uktides::{Coordinates, DecimalDegrees};
+    ///
+    /// let sandown = Coordinates {
+    ///     latitude: DecimalDegrees(50.65),
+    ///     longitude: DecimalDegrees(-1.15),
+    /// };
+    /// assert_eq!(sandown.to_wkt(), "POINT(-1.15 50.65)");
+    /// ```
+    pub fn to_wkt(&self) -> String {
+        format!("POINT({} {})", self.longitude.0, self.latitude.0)
+    }
+}
+
+/// The `n` stations closest to `point`, nearest first, with their distances in kilometres.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// use rjw_uktides::{Coordinates, DecimalDegrees};
+///
+/// let stations = rjw_uktides::stations_from_reader(File::open("./stations.json").unwrap())
+///     .expect("Failed to read file as stations data.");
+/// let point = Coordinates {
+///     latitude: DecimalDegrees(50.65),
+///     longitude: DecimalDegrees(-1.15),
+/// };
+/// let nearest = rjw_uktides::nearest_stations(&stations, &point, 3);
+/// assert_eq!(nearest[0].0.name, "Sandown");
+/// assert!(nearest.windows(2).all(|w| w[0].1 <= w[1].1));
+/// ```
+pub fn nearest_stations<'a>(
+    stations: &'a [Station],
+    point: &Coordinates,
+    n: usize,
+) -> Vec<(&'a Station, f64)> {
+    let mut by_distance: Vec<_> = stations
+        .iter()
+        .map(|s| (s, s.location.distance_km(point)))
+        .collect();
+    by_distance.sort_by(|a, b| a.1.total_cmp(&b.1));
+    by_distance.truncate(n);
+    by_distance
+}
+
+/// The stations inside the polygon with the given vertices, in their order in
+/// `stations`, as with [`Coordinates::is_within_polygon`].
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// use rjw_uktides::{Coordinates, DecimalDegrees};
+///
+/// let stations = rjw_uktides::stations_from_reader(File::open("./stations.json").unwrap())
+///     .expect("Failed to read file as stations data.");
+/// let point = |latitude, longitude| Coordinates {
+///     latitude: DecimalDegrees(latitude),
+///     longitude: DecimalDegrees(longitude),
+/// };
+/// // Around the Isle of Wight.
+/// let polygon = [
+///     point(50.55, -1.62),
+///     point(50.55, -1.05),
+///     point(50.78, -1.05),
+///     point(50.78, -1.62),
+/// ];
+/// let within = rjw_uktides::stations_within_polygon(&stations, &polygon);
+/// assert!(within.iter().any(|s| s.name == "Sandown"));
+/// assert!(!within.iter().any(|s| s.name == "Dover"));
+/// ```
+pub fn stations_within_polygon<'a>(
+    stations: &'a [Station],
+    polygon: &[Coordinates],
+) -> Vec<&'a Station> {
+    stations
+        .iter()
+        .filter(|s| s.location.is_within_polygon(polygon))
+        .collect()
+}
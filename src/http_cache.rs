@@ -0,0 +1,422 @@
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use bytes::Bytes;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::snapshot::fnv1a;
+
+/// Storage for raw HTTP responses, used by [`Client`](crate::Client) to avoid refetching
+/// unchanged data.
+///
+/// Responses are keyed by their full request URL. Implementations must be safe to share
+/// between threads; errors from a cache are not fatal to a request and are treated as a
+/// cache miss.
+pub trait Cache: Debug + Send + Sync {
+    /// Retrieve the stored response for `key`, if any.
+    fn get(&self, key: &str) -> io::Result<Option<CachedResponse>>;
+
+    /// Store the response for `key`, replacing any previous response.
+    fn put(&self, key: &str, response: &CachedResponse) -> io::Result<()>;
+}
+
+/// A stored HTTP response body along with the metadata needed to revalidate it.
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    /// The response body.
+    pub body: Bytes,
+    /// Validation metadata from the response headers.
+    pub metadata: CacheMetadata,
+}
+
+/// Validation and expiry metadata for a [`CachedResponse`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheMetadata {
+    /// The `ETag` response header, sent back as `If-None-Match` when revalidating.
+    pub etag: Option<String>,
+    /// The `Last-Modified` response header, sent back as `If-Modified-Since` when revalidating.
+    pub last_modified: Option<String>,
+    /// When the response was stored or last revalidated.
+    pub stored_at: DateTime<Utc>,
+    /// When the response stops being fresh, from the `Cache-Control: max-age` response
+    /// header. Responses without an expiry are always revalidated.
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl CacheMetadata {
+    /// Whether the response can be used without revalidating it with the server.
+    pub fn is_fresh(&self) -> bool {
+        self.expires_at.is_some_and(|expires| Utc::now() < expires)
+    }
+
+    /// Build metadata from response headers, or `None` if the response must not be stored.
+    pub(crate) fn from_headers(headers: &reqwest::header::HeaderMap) -> Option<Self> {
+        use reqwest::header::{CACHE_CONTROL, ETAG, LAST_MODIFIED};
+
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok())
+                .map(str::to_owned)
+        };
+        let cache_control = header(CACHE_CONTROL).unwrap_or_default();
+        let directives: Vec<&str> = cache_control.split(',').map(str::trim).collect();
+        if directives.contains(&"no-store") {
+            return None;
+        }
+        let stored_at = Utc::now();
+        let max_age = if directives.contains(&"no-cache") {
+            None
+        } else {
+            directives
+                .iter()
+                .find_map(|d| d.strip_prefix("max-age="))
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::seconds)
+        };
+        Some(Self {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            stored_at,
+            expires_at: max_age.map(|age| stored_at + age),
+        })
+    }
+}
+
+/// A [`Cache`] storing responses as files in a directory.
+///
+/// Each response is stored as a pair of files named after a hash of its URL: the body,
+/// with a `.json` extension, and its metadata, with a `.meta.json` extension. Both are
+/// written to temporary files and renamed into place, and the metadata records a hash of
+/// the body, so that processes sharing the directory never read a partly-written body or
+/// one stored with another response's metadata.
+///
+/// # Examples
+/// ```
+/// use rjw_uktides::{Cache, CacheMetadata, CachedResponse, FileCache};
+///
+/// let dir = std::env::temp_dir().join("rjw-uktides-file-cache-doctest");
+/// let cache = FileCache::new(&dir);
+/// let response = CachedResponse {
+///     body: "{}".into(),
+///     metadata: CacheMetadata {
+///         etag: Some("\"v1\"".to_owned()),
+///         ..CacheMetadata::default()
+///     },
+/// };
+/// cache.put("https://example.com/data", &response).expect("Failed to store response.");
+///
+/// let stored = cache.get("https://example.com/data").unwrap().unwrap();
+/// assert_eq!(stored.body, response.body);
+/// assert_eq!(stored.metadata, response.metadata);
+/// assert!(cache.get("https://example.com/other").unwrap().is_none());
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileCache {
+    dir: PathBuf,
+}
+
+impl FileCache {
+    /// Create a cache storing files in `dir`, which is created when first needed.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The directory in which responses are stored.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn paths(&self, key: &str) -> (PathBuf, PathBuf) {
+        let name = format!("{:016x}", fnv1a(key.as_bytes()));
+        (
+            self.dir.join(format!("{name}.json")),
+            self.dir.join(format!("{name}.meta.json")),
+        )
+    }
+}
+
+impl Cache for FileCache {
+    fn get(&self, key: &str) -> io::Result<Option<CachedResponse>> {
+        let (body_path, meta_path) = self.paths(key);
+        let stored: StoredMetadata = match fs::read(meta_path) {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let body = match fs::read(body_path) {
+            Ok(data) => Bytes::from(data),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if fnv1a(&body) != stored.body_hash {
+            // The body was replaced by another process after the metadata was written.
+            return Ok(None);
+        }
+        Ok(Some(CachedResponse {
+            body,
+            metadata: stored.metadata,
+        }))
+    }
+
+    fn put(&self, key: &str, response: &CachedResponse) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let (body_path, meta_path) = self.paths(key);
+        let stored = StoredMetadata {
+            metadata: response.metadata.clone(),
+            body_hash: fnv1a(&response.body),
+        };
+        // The body is renamed into place first, so that until the metadata follows, a
+        // reader finds either the previous metadata, which does not match the new body, or
+        // no metadata at all.
+        write_atomically(&body_path, &response.body)?;
+        write_atomically(&meta_path, &serde_json::to_vec(&stored)?)
+    }
+}
+
+/// The contents of a `.meta.json` file: a response's metadata and the hash of its body.
+#[derive(Serialize, Deserialize)]
+struct StoredMetadata {
+    #[serde(flatten)]
+    metadata: CacheMetadata,
+    body_hash: u64,
+}
+
+/// Write `contents` to a temporary file beside `path`, then rename it to `path`, so that
+/// readers see either the previous file or the whole of the new one.
+fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temp = PathBuf::from(temp);
+    let result = fs::write(&temp, contents).and_then(|()| fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderMap, HeaderValue, CACHE_CONTROL, ETAG, LAST_MODIFIED};
+
+    use super::*;
+    use crate::mock_server::{MockServer, LAST_MODIFIED as SERVER_LAST_MODIFIED};
+    use crate::{stations_url, Client};
+
+    fn response(body: &'static str, metadata: CacheMetadata) -> CachedResponse {
+        CachedResponse {
+            body: body.into(),
+            metadata,
+        }
+    }
+
+    fn headers(pairs: &[(reqwest::header::HeaderName, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.clone(), HeaderValue::from_static(value)))
+            .collect()
+    }
+
+    #[test]
+    fn file_cache_round_trips_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FileCache::new(dir.path().join("nested"));
+        assert!(cache.get("key").unwrap().is_none());
+        let metadata = CacheMetadata {
+            etag: Some("\"v1\"".to_owned()),
+            last_modified: Some(SERVER_LAST_MODIFIED.to_owned()),
+            stored_at: Utc::now(),
+            expires_at: Some(Utc::now() + Duration::minutes(5)),
+        };
+        cache
+            .put("key", &response("first", metadata.clone()))
+            .unwrap();
+        cache
+            .put("key", &response("second", metadata.clone()))
+            .unwrap();
+        let stored = cache.get("key").unwrap().unwrap();
+        assert_eq!(stored.body, "second");
+        assert_eq!(stored.metadata, metadata);
+        assert!(cache.get("other").unwrap().is_none());
+    }
+
+    #[test]
+    fn file_cache_reports_corrupt_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FileCache::new(dir.path());
+        cache
+            .put("key", &response("{}", CacheMetadata::default()))
+            .unwrap();
+        let (_, meta_path) = cache.paths("key");
+        fs::write(meta_path, b"{not json").unwrap();
+        let err = cache.get("key").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn file_cache_misses_when_the_body_is_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FileCache::new(dir.path());
+        cache
+            .put("key", &response("{}", CacheMetadata::default()))
+            .unwrap();
+        let (body_path, _) = cache.paths("key");
+        fs::remove_file(body_path).unwrap();
+        assert!(cache.get("key").unwrap().is_none());
+    }
+
+    #[test]
+    fn file_cache_misses_a_body_written_without_its_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FileCache::new(dir.path());
+        let (body_path, _) = cache.paths("key");
+        fs::write(&body_path, b"{}").unwrap();
+        assert!(cache.get("key").unwrap().is_none());
+
+        // Another process replacing the body after the metadata was written.
+        cache
+            .put("key", &response("first", CacheMetadata::default()))
+            .unwrap();
+        fs::write(&body_path, b"second").unwrap();
+        assert!(cache.get("key").unwrap().is_none());
+    }
+
+    #[test]
+    fn file_cache_leaves_no_temporary_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FileCache::new(dir.path());
+        cache
+            .put("key", &response("{}", CacheMetadata::default()))
+            .unwrap();
+        let mut names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        let name = format!("{:016x}", fnv1a(b"key"));
+        assert_eq!(names, [format!("{name}.json"), format!("{name}.meta.json")]);
+    }
+
+    #[test]
+    fn responses_are_fresh_until_they_expire() {
+        let at = |minutes| CacheMetadata {
+            expires_at: Some(Utc::now() + Duration::minutes(minutes)),
+            ..CacheMetadata::default()
+        };
+        assert!(at(5).is_fresh());
+        assert!(!at(-5).is_fresh());
+        assert!(!CacheMetadata::default().is_fresh());
+    }
+
+    #[test]
+    fn metadata_follows_the_response_headers() {
+        let metadata = CacheMetadata::from_headers(&headers(&[
+            (ETAG, "\"v1\""),
+            (LAST_MODIFIED, SERVER_LAST_MODIFIED),
+            (CACHE_CONTROL, "public, max-age=60"),
+        ]))
+        .unwrap();
+        assert_eq!(metadata.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(
+            metadata.last_modified.as_deref(),
+            Some(SERVER_LAST_MODIFIED)
+        );
+        assert_eq!(
+            metadata.expires_at,
+            Some(metadata.stored_at + Duration::seconds(60))
+        );
+
+        let no_cache = headers(&[(CACHE_CONTROL, "no-cache, max-age=60")]);
+        let metadata = CacheMetadata::from_headers(&no_cache).unwrap();
+        assert!(metadata.expires_at.is_none());
+
+        let no_store = headers(&[(CACHE_CONTROL, "max-age=60, no-store")]);
+        assert!(CacheMetadata::from_headers(&no_store).is_none());
+    }
+
+    /// A client for `server` caching in `dir`, with `stored` already cached for the
+    /// stations URL.
+    fn client_with(server: &MockServer, dir: &Path, stored: CacheMetadata) -> Client {
+        let cache = FileCache::new(dir);
+        let key = stations_url(&server.base_url());
+        cache.put(&key, &response("cached", stored)).unwrap();
+        Client::with_base_url(server.base_url()).with_cache(cache)
+    }
+
+    #[test]
+    fn serves_fresh_responses_without_a_request() {
+        let server = MockServer::start().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let stored = CacheMetadata {
+            expires_at: Some(Utc::now() + Duration::minutes(5)),
+            ..CacheMetadata::default()
+        };
+        let client = client_with(&server, dir.path(), stored);
+        assert_eq!(client.fetch_stations_raw().unwrap(), "cached");
+        assert_eq!(server.request_count(), 0);
+    }
+
+    #[test]
+    fn revalidates_with_the_etag() {
+        let server = MockServer::start().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let stored = CacheMetadata {
+            etag: Some("\"stations\"".to_owned()),
+            ..CacheMetadata::default()
+        };
+        let client = client_with(&server, dir.path(), stored);
+        // The server responds 304, so the cached body is served.
+        assert_eq!(client.fetch_stations_raw().unwrap(), "cached");
+        assert_eq!(server.request_count(), 1);
+    }
+
+    #[test]
+    fn revalidates_with_the_last_modified_date() {
+        let server = MockServer::start().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let stored = CacheMetadata {
+            last_modified: Some(SERVER_LAST_MODIFIED.to_owned()),
+            ..CacheMetadata::default()
+        };
+        let client = client_with(&server, dir.path(), stored);
+        assert_eq!(client.fetch_stations_raw().unwrap(), "cached");
+
+        // The 304 response's validators are stored for next time.
+        let key = stations_url(&server.base_url());
+        let updated = FileCache::new(dir.path()).get(&key).unwrap().unwrap();
+        assert_eq!(updated.body, "cached");
+        assert_eq!(updated.metadata.etag.as_deref(), Some("\"stations\""));
+        assert_eq!(
+            updated.metadata.last_modified.as_deref(),
+            Some(SERVER_LAST_MODIFIED)
+        );
+    }
+
+    #[test]
+    fn replaces_responses_that_have_changed() {
+        let server = MockServer::start().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let stored = CacheMetadata {
+            etag: Some("\"old\"".to_owned()),
+            ..CacheMetadata::default()
+        };
+        let client = client_with(&server, dir.path(), stored);
+        let body = client.fetch_stations_raw().unwrap();
+        assert_eq!(body, crate::testing::STATIONS_BYTES);
+
+        let key = stations_url(&server.base_url());
+        let updated = FileCache::new(dir.path()).get(&key).unwrap().unwrap();
+        assert_eq!(updated.body, body);
+        assert_eq!(updated.metadata.etag.as_deref(), Some("\"stations\""));
+    }
+}
@@ -0,0 +1,128 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+
+use crate::{StationId, TidalEventType, TidePredictions};
+
+impl TidePredictions {
+    /// Format the high and low tides as an iCalendar (RFC 5545) calendar for the station,
+    /// with one event per tide.
+    ///
+    /// Each event's UID is derived from the station and the time of the tide, so calendar
+    /// clients that reimport the predictions update the events in place instead of
+    /// duplicating them.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    ///
+    /// use rjw_uktides::StationId;
+    ///
+    /// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+    ///     .expect("Failed to read file as tides data.");
+    /// let ics = tides.to_ics(&StationId("0053".to_owned()), "Sandown");
+    /// assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+    /// assert_eq!(
+    ///     ics.matches("BEGIN:VEVENT").count(),
+    ///     tides.tidal_event_list.len()
+    /// );
+    /// ```
+    pub fn to_ics(&self, station: &StationId, station_name: &str) -> String {
+        self.to_ics_with_units(station, station_name, "m")
+    }
+
+    /// Format the calendar as [`to_ics`](Self::to_ics) does, labelling the heights with
+    /// `units`, such as "ft", for predictions whose heights have been converted from
+    /// metres.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    ///
+    /// use rjw_uktides::StationId;
+    ///
+    /// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+    ///     .expect("Failed to read file as tides data.");
+    /// let ics = tides.to_ics_with_units(&StationId("0053".to_owned()), "Sandown", "ft");
+    /// assert!(ics.contains("SUMMARY:High tide 4.2 ft\r\n"));
+    /// ```
+    pub fn to_ics_with_units(
+        &self,
+        station: &StationId,
+        station_name: &str,
+        units: &str,
+    ) -> String {
+        let stamp = ics_date_time(Utc::now());
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_owned(),
+            "VERSION:2.0".to_owned(),
+            "PRODID:-//rjw-uktides//Tide predictions//EN".to_owned(),
+            "CALSCALE:GREGORIAN".to_owned(),
+            "METHOD:PUBLISH".to_owned(),
+            format!(
+                "X-WR-CALNAME:{}",
+                escape_text(&format!("{station_name} tides"))
+            ),
+        ];
+        for event in &self.tidal_event_list {
+            let start = ics_date_time(event.date_time);
+            let kind = match event.event_type {
+                TidalEventType::HighWater => "high",
+                TidalEventType::LowWater => "low",
+            };
+            let mut summary = format!("{} {:.1} {units}", event.event_type, event.height.0);
+            if event.is_approximate_time.is_some() || event.is_approximate_height.is_some() {
+                summary.push_str(" (approximate)");
+            }
+            let description = format!(
+                "{} at {station_name}, {:.2} {units} above chart datum.",
+                event.event_type, event.height.0
+            );
+            lines.extend([
+                "BEGIN:VEVENT".to_owned(),
+                format!("UID:{station}-{start}-{kind}@rjw-uktides"),
+                format!("DTSTAMP:{stamp}"),
+                format!("DTSTART:{start}"),
+                format!("SUMMARY:{}", escape_text(&summary)),
+                format!("DESCRIPTION:{}", escape_text(&description)),
+                format!("URL:{}", station.easytide_url()),
+                "TRANSP:TRANSPARENT".to_owned(),
+                "END:VEVENT".to_owned(),
+            ]);
+        }
+        lines.push("END:VCALENDAR".to_owned());
+
+        let mut ics = String::new();
+        for line in lines {
+            fold_line(&mut ics, &line);
+        }
+        ics
+    }
+}
+
+/// A UTC datetime in the iCalendar basic format, such as `20230403T051200Z`.
+fn ics_date_time(date_time: DateTime<Utc>) -> String {
+    date_time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape the characters that are special in iCalendar text values.
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Append the content line to `out`, folded so no line exceeds 75 octets.
+fn fold_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    let _ = write!(out, "\r\n");
+}
@@ -0,0 +1,154 @@
+//! JavaScript bindings for the parsing functions, for packaging with `wasm-bindgen`.
+//!
+//! Enabled by the `wasm-bindgen` feature when compiling for `wasm32-unknown-unknown`. The
+//! crate is built as a Rust library only, so build the WebAssembly module as a `cdylib`
+//! and generate the bindings with the `wasm-bindgen` CLI:
+//!
+//! ```sh
+//! cargo rustc --lib --release --target wasm32-unknown-unknown --no-default-features \
+//!     --features wasm-bindgen --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg \
+//!     target/wasm32-unknown-unknown/release/rjw_uktides.wasm
+//! ```
+//!
+//! Parsed data is returned as plain JavaScript objects with camelCase property names,
+//! datetimes as ISO 8601 strings in UTC, and tide and moon phase types as strings.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    stations_from_slice, stations_url, tides_from_slice, tides_url, LunarPhaseType, StationId,
+    TidalEventType, DEFAULT_BASE_URL,
+};
+
+/// Parse the response from the stations endpoint into an array of station objects.
+#[wasm_bindgen(js_name = stationsFromJson)]
+pub fn stations_from_json(json: &str) -> Result<JsValue, JsError> {
+    let stations = stations_from_slice(json.as_bytes())?;
+    let stations: Vec<JsStation<'_>> = stations
+        .iter()
+        .map(|s| JsStation {
+            id: &s.id,
+            name: &s.name,
+            country: &s.country,
+            latitude: s.location.latitude.0,
+            longitude: s.location.longitude.0,
+            continuous_heights_available: s.continuous_heights_available,
+        })
+        .collect();
+    to_js(&stations)
+}
+
+/// Parse the response from the tide predictions endpoint into a predictions object.
+#[wasm_bindgen(js_name = tidesFromJson)]
+pub fn tides_from_json(json: &str) -> Result<JsValue, JsError> {
+    let tides = tides_from_slice(json.as_bytes())?;
+    let tides = JsTides {
+        footer_note: tides.footer_note.as_str(),
+        tidal_events: tides
+            .tidal_event_list
+            .iter()
+            .map(|e| JsTidalEvent {
+                date_time: iso(e.date_time),
+                event_type: match e.event_type {
+                    TidalEventType::HighWater => "high",
+                    TidalEventType::LowWater => "low",
+                },
+                height: e.height.0,
+            })
+            .collect(),
+        heights: tides
+            .tidal_height_occurrence_list
+            .iter()
+            .map(|h| JsHeight {
+                date_time: iso(h.date_time),
+                height: h.height.0,
+            })
+            .collect(),
+        lunar_phases: tides
+            .lunar_phase_list
+            .iter()
+            .map(|p| JsLunarPhase {
+                date_time: iso(p.date_time),
+                phase: match p.lunar_phase_type {
+                    LunarPhaseType::NewMoon => "newMoon",
+                    LunarPhaseType::FirstQuarter => "firstQuarter",
+                    LunarPhaseType::FullMoon => "fullMoon",
+                    LunarPhaseType::LastQuarter => "lastQuarter",
+                },
+            })
+            .collect(),
+    };
+    to_js(&tides)
+}
+
+/// The URL from which the list of all stations is fetched, from the public EasyTide web
+/// service or the service at `baseUrl`.
+#[wasm_bindgen(js_name = stationsUrl)]
+pub fn js_stations_url(base_url: Option<String>) -> String {
+    stations_url(base_url.as_deref().unwrap_or(DEFAULT_BASE_URL))
+}
+
+/// The URL from which tide predictions for the station are fetched, from the public
+/// EasyTide web service or the service at `baseUrl`.
+#[wasm_bindgen(js_name = tidesUrl)]
+pub fn js_tides_url(station_id: String, base_url: Option<String>) -> String {
+    tides_url(
+        base_url.as_deref().unwrap_or(DEFAULT_BASE_URL),
+        &StationId(station_id),
+    )
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsStation<'a> {
+    id: &'a str,
+    name: &'a str,
+    country: &'a str,
+    latitude: f64,
+    longitude: f64,
+    continuous_heights_available: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsTides<'a> {
+    footer_note: &'a str,
+    tidal_events: Vec<JsTidalEvent>,
+    heights: Vec<JsHeight>,
+    lunar_phases: Vec<JsLunarPhase>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsTidalEvent {
+    date_time: String,
+    event_type: &'static str,
+    height: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsHeight {
+    date_time: String,
+    height: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsLunarPhase {
+    date_time: String,
+    phase: &'static str,
+}
+
+fn iso(dt: DateTime<Utc>) -> String {
+    dt.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Convert to a plain JavaScript object by way of JSON.
+fn to_js(value: &impl Serialize) -> Result<JsValue, JsError> {
+    let json = serde_json::to_string(value)?;
+    js_sys::JSON::parse(&json).map_err(|_| JsError::new("failed to convert to a JavaScript value"))
+}
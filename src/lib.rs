@@ -1,23 +1,144 @@
+pub mod admiralty;
+#[cfg(all(feature = "arrow", not(target_arch = "wasm32")))]
+pub mod arrow;
+#[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+mod async_client;
+mod atlas;
+mod backend;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+mod cache;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+mod client;
+#[cfg(feature = "compression")]
+mod compression;
+mod csv;
+mod cycles;
+#[cfg(all(feature = "polars", not(target_arch = "wasm32")))]
+mod dataframe;
+mod days;
+mod diff;
+mod endpoint;
+mod error;
+mod geo;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+mod http_cache;
+mod ics;
+#[cfg(all(feature = "wasm-bindgen", target_arch = "wasm32"))]
+mod js;
+mod merge;
+#[cfg(all(
+    any(
+        feature = "mock_server",
+        all(test, any(feature = "blocking", feature = "async"))
+    ),
+    not(target_arch = "wasm32")
+))]
+pub mod mock_server;
+pub mod named;
+mod notes;
+pub mod observations;
+mod offsets;
+mod osgb;
 mod parse;
+#[cfg(all(feature = "python", not(target_arch = "wasm32")))]
+mod python;
+#[cfg(all(
+    any(feature = "blocking", feature = "async"),
+    not(target_arch = "wasm32")
+))]
+mod rate_limit;
+mod regions;
+#[cfg(all(
+    any(feature = "blocking", feature = "async"),
+    not(target_arch = "wasm32")
+))]
+mod retry;
+mod search;
+mod snapshot;
+mod solar;
+#[cfg(all(feature = "store", not(target_arch = "wasm32")))]
+pub mod store;
+mod stream;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod trace;
+mod validate;
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+mod wasm;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+mod watcher;
+mod window;
 
-use std::error::Error;
-
+#[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+pub use async_client::*;
+pub use atlas::*;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub use cache::*;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub use client::*;
+pub use csv::*;
+pub use cycles::*;
+pub use days::*;
+pub use diff::*;
+pub use endpoint::*;
+pub use error::*;
+pub use geo::*;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub use http_cache::*;
+pub use notes::*;
+pub use offsets::*;
+pub use osgb::*;
 pub use parse::*;
-use reqwest::blocking::Client;
+#[cfg(all(
+    any(feature = "blocking", feature = "async"),
+    not(target_arch = "wasm32")
+))]
+pub use rate_limit::*;
+pub use regions::*;
+#[cfg(all(
+    any(feature = "blocking", feature = "async"),
+    not(target_arch = "wasm32")
+))]
+pub use retry::*;
+pub use search::*;
+pub use snapshot::*;
+pub use stream::*;
+pub use validate::*;
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub use wasm::*;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub use watcher::*;
+pub use window::*;
 
-const STATIONS_URL: &str = "https://easytide.admiralty.co.uk/Home/GetStations";
+/// Fetch the list of all tidal stations from the EasyTide web service.
+///
+/// See [`Client::fetch_stations`].
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub fn fetch_stations() -> Result<Vec<Station>, Box<dyn std::error::Error>> {
+    Client::new().fetch_stations()
+}
 
-pub fn fetch_stations() -> Result<Vec<Station>, Box<dyn Error>> {
-    let bytes = reqwest::blocking::get(STATIONS_URL)?.bytes()?;
-    stations_from_reader(bytes.as_ref())
+/// Fetch tide predictions for the given station from the EasyTide web service.
+///
+/// See [`Client::fetch_tides`].
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub fn fetch_tides(station: &StationId) -> Result<TidePredictions, Box<dyn std::error::Error>> {
+    Client::new().fetch_tides(station)
 }
 
-pub fn fetch_tides(station: &StationId) -> Result<TidePredictions, Box<dyn Error>> {
-    let url = "https://easytide.admiralty.co.uk/Home/GetPredictionData";
-    let response = Client::new()
-        .get(url)
-        .query(&[("stationId", station.0.as_str())])
-        .send()?;
-    let body = response.text()?;
-    tides_from_reader(body.as_bytes())
+/// Fetch tide predictions for each of the given stations from the EasyTide web service,
+/// using up to `concurrency` threads.
+///
+/// See [`Client::fetch_tides_many`].
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub fn fetch_tides_many(
+    stations: &[StationId],
+    concurrency: usize,
+) -> std::collections::HashMap<
+    StationId,
+    Result<TidePredictions, Box<dyn std::error::Error + Send + Sync>>,
+> {
+    Client::new().fetch_tides_many(stations, concurrency)
 }
@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::TidePredictions;
+
+impl TidePredictions {
+    /// Combine `newer` predictions into these ones, such as when keeping a history of
+    /// predictions fetched on different days.
+    ///
+    /// Events, heights and lunar phases from both are kept in chronological order. Where
+    /// both have an entry at the same time, the one from `newer` replaces the existing one,
+    /// as later predictions are assumed to be more accurate. The footer note is taken from
+    /// `newer`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    ///
+    /// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+    ///     .expect("Failed to read file as tides data.");
+    /// let mut older = tides.clone();
+    /// older.tidal_event_list.truncate(4);
+    /// let mut newer = tides.clone();
+    /// newer.tidal_event_list.drain(..2);
+    ///
+    /// older.merge(newer);
+    /// assert_eq!(older.tidal_event_list.len(), tides.tidal_event_list.len());
+    /// ```
+    pub fn merge(&mut self, newer: TidePredictions) {
+        merge_by_time(&mut self.tidal_event_list, newer.tidal_event_list, |e| {
+            e.date_time
+        });
+        merge_by_time(
+            &mut self.tidal_height_occurrence_list,
+            newer.tidal_height_occurrence_list,
+            |h| h.date_time,
+        );
+        merge_by_time(&mut self.lunar_phase_list, newer.lunar_phase_list, |p| {
+            p.date_time
+        });
+        self.footer_note = newer.footer_note;
+    }
+}
+
+/// Replace `existing` with the union of it and `newer` in chronological order, preferring
+/// the entries of `newer` where both have one at the same time.
+fn merge_by_time<T>(existing: &mut Vec<T>, newer: Vec<T>, time: impl Fn(&T) -> DateTime<Utc>) {
+    let mut by_time = BTreeMap::new();
+    for item in existing.drain(..).chain(newer) {
+        by_time.insert(time(&item), item);
+    }
+    existing.extend(by_time.into_values());
+}
@@ -0,0 +1,218 @@
+//! A mock EasyTide server for offline integration tests.
+//!
+//! Enable the `mock_server` feature (typically only as a dev-dependency) to use this module.
+//! [`MockServer`] serves the stations and tide predictions bundled with the crate (see the
+//! [`testing`](crate::testing) module) at the same paths as the EasyTide web service, so code
+//! using the built-in [`Client`](crate::Client) can be tested end-to-end without network access.
+//!
+//! Requests for tide predictions for any station in the bundled list receive the same week
+//! of predictions; requests for unknown stations or other paths receive a 404 response.
+//! Successful responses carry an `ETag`, a `Last-Modified` date and `Cache-Control:
+//! no-cache`, and requests with a matching `If-None-Match` or `If-Modified-Since` header
+//! receive a 304 response, for testing [`Cache`](crate::Cache) revalidation. With the
+//! `compression` feature enabled, tide predictions are served gzip-compressed to requests
+//! that accept it. [`MockServer::fail_next`] makes the server respond with 503 errors, for
+//! testing retries.
+//!
+//! # Examples
+//! ```
+//! use rjw_uktides::mock_server::MockServer;
+//! use rjw_uktides::{Client, StationId};
+//!
+//! let server = MockServer::start().expect("Failed to start mock server.");
+//! let client = Client::with_base_url(server.base_url());
+//!
+//! let stations = client.fetch_stations().expect("Failed to fetch stations.");
+//! assert!(!stations.is_empty());
+//!
+//! let tides = client
+//!     .fetch_tides(&StationId("0053".to_owned()))
+//!     .expect("Failed to fetch tides.");
+//! assert!(!tides.tidal_event_list.is_empty());
+//!
+//! assert!(client.fetch_tides(&StationId("nope".to_owned())).is_err());
+//! assert_eq!(server.request_count(), 3);
+//! ```
+
+use std::collections::HashSet;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use crate::testing::{STATIONS_BYTES, TIDES_BYTES};
+
+const STATIONS_ETAG: &str = "\"stations\"";
+const TIDES_ETAG: &str = "\"tides\"";
+/// The `Last-Modified` date of every successful response.
+pub const LAST_MODIFIED: &str = "Mon, 03 Apr 2023 00:00:00 GMT";
+
+/// A mock EasyTide server running on a background thread, bound to a random local port.
+///
+/// The server shuts down when dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    shutdown: Arc<AtomicBool>,
+    requests: Arc<AtomicUsize>,
+    failures: Arc<AtomicUsize>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Start the server on a random port on the loopback interface.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the server cannot bind to a local port.
+    pub fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let requests = Arc::new(AtomicUsize::new(0));
+        let failures = Arc::new(AtomicUsize::new(0));
+        let station_ids: HashSet<String> = crate::testing::stations()
+            .into_iter()
+            .map(|s| s.id.0)
+            .collect();
+
+        let handle = {
+            let shutdown = Arc::clone(&shutdown);
+            let requests = Arc::clone(&requests);
+            let failures = Arc::clone(&failures);
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let Ok(stream) = stream else { continue };
+                    requests.fetch_add(1, Ordering::SeqCst);
+                    // Errors writing to a single client are not fatal to the server.
+                    let fail = failures
+                        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                        .is_ok();
+                    let _ = respond(stream, &station_ids, fail);
+                }
+            })
+        };
+
+        Ok(Self {
+            addr,
+            shutdown,
+            requests,
+            failures,
+            handle: Some(handle),
+        })
+    }
+
+    /// The base URL of the server, suitable for [`Client::with_base_url`](crate::Client::with_base_url).
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// The number of requests the server has received.
+    pub fn request_count(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    /// Respond to the next `count` requests with 503 Service Unavailable, whatever their path.
+    pub fn fail_next(&self, count: usize) {
+        self.failures.store(count, Ordering::SeqCst);
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake the accept loop so that it sees the shutdown flag.
+        let _ = TcpStream::connect(self.addr);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Read a single HTTP request from the stream and write the matching response.
+fn respond(mut stream: TcpStream, station_ids: &HashSet<String>, fail: bool) -> io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Consume the headers, noting any validators; the request body, if any, is ignored.
+    let mut if_none_match = None;
+    let mut if_modified_since = None;
+    let mut accepts_gzip = false;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("if-none-match") {
+                if_none_match = Some(value.trim().to_owned());
+            } else if name.eq_ignore_ascii_case("if-modified-since") {
+                if_modified_since = Some(value.trim().to_owned());
+            } else if name.eq_ignore_ascii_case("accept-encoding") {
+                accepts_gzip = value.split(',').any(|e| e.trim() == "gzip");
+            }
+        }
+        header.clear();
+    }
+
+    let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let found = match path {
+        "/Home/GetStations" => Some((STATIONS_BYTES, STATIONS_ETAG)),
+        "/Home/GetPredictionData" => query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == "stationId")
+            .filter(|(_, id)| station_ids.contains(*id))
+            .map(|_| (TIDES_BYTES, TIDES_ETAG)),
+        _ => None,
+    };
+
+    let (status, body, etag) = match found {
+        _ if fail => ("503 Service Unavailable", &b"Service Unavailable"[..], None),
+        Some((_, etag))
+            if if_none_match.as_deref() == Some(etag)
+                || if_modified_since.as_deref() == Some(LAST_MODIFIED) =>
+        {
+            ("304 Not Modified", &b""[..], Some(etag))
+        }
+        Some((body, etag)) => ("200 OK", body, Some(etag)),
+        None => ("404 Not Found", &b"Not Found"[..], None),
+    };
+    let (body, gzipped) = match gzipped(body).filter(|_| accepts_gzip) {
+        Some(compressed) => (compressed, true),
+        None => (body, false),
+    };
+    write!(stream, "HTTP/1.1 {status}\r\n")?;
+    if gzipped {
+        write!(stream, "Content-Encoding: gzip\r\n")?;
+    }
+    if let Some(etag) = etag {
+        write!(
+            stream,
+            "Content-Type: application/json\r\nETag: {etag}\r\nLast-Modified: {LAST_MODIFIED}\r\n\
+             Cache-Control: no-cache\r\n"
+        )?;
+    }
+    write!(
+        stream,
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+/// The gzip-compressed version of a response body, if there is one.
+#[cfg(feature = "compression")]
+fn gzipped(body: &'static [u8]) -> Option<&'static [u8]> {
+    std::ptr::eq(body, TIDES_BYTES).then_some(crate::testing::TIDES_GZIP_BYTES)
+}
+
+/// The gzip-compressed version of a response body, which is never available without
+/// the `compression` feature.
+#[cfg(not(feature = "compression"))]
+fn gzipped(_body: &'static [u8]) -> Option<&'static [u8]> {
+    None
+}
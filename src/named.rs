@@ -0,0 +1,239 @@
+//! Serializing tide and lunar phase types as readable names rather than numbers.
+//!
+//! By default, [`TidalEventType`] and [`LunarPhaseType`] serialize to the numbers the
+//! EasyTide service uses, so that predictions round-trip through [`tides_from_reader`].
+//! For JSON meant to be read by people, the [`event_type`] and [`lunar_phase_type`]
+//! modules serialize them with the names from [`TidalEventType::name`] and
+//! [`LunarPhaseType::name`], for use with `#[serde(with = "...")]` on your own types, and
+//! [`Named`] serializes a whole set of predictions that way.
+//!
+//! Deserializing with these modules accepts either the names or the numbers, so it needs
+//! a self-describing format such as JSON.
+//!
+//! [`tides_from_reader`]: crate::tides_from_reader
+//!
+//! # Examples
+//! ```
+//! use rjw_uktides::TidalEventType;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Alert {
+//!     #[serde(with = "rjw_uktides::named::event_type")]
+//!     event: TidalEventType,
+//! }
+//!
+//! let json = serde_json::to_string(&Alert { event: TidalEventType::HighWater }).unwrap();
+//! assert_eq!(json, r#"{"event":"high"}"#);
+//! let alert: Alert = serde_json::from_str(r#"{"event":1}"#).unwrap();
+//! assert_eq!(alert.event, TidalEventType::LowWater);
+//! ```
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::de::{Unexpected, Visitor};
+use serde::{Deserializer, Serialize, Serializer};
+
+use crate::parse::{serialize_date_without_tz, serialize_datetime_without_tz};
+use crate::{LunarPhase, LunarPhaseType, Metres, TidalEvent, TidalEventType, TidePredictions};
+
+/// Serialize a [`TidalEventType`] as `"high"` or `"low"`, and deserialize it from either
+/// of those or the EasyTide numbers.
+pub mod event_type {
+    use super::*;
+
+    pub fn serialize<S>(event_type: &TidalEventType, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(event_type.name())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<TidalEventType, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(NameOrNumber {
+            expecting: "\"high\", \"low\", 0 or 1",
+            from_name: |name| {
+                [TidalEventType::HighWater, TidalEventType::LowWater]
+                    .into_iter()
+                    .find(|t| t.name() == name)
+            },
+            from_number: |num| match num {
+                0 => Some(TidalEventType::HighWater),
+                1 => Some(TidalEventType::LowWater),
+                _ => None,
+            },
+        })
+    }
+}
+
+/// Serialize a [`LunarPhaseType`] as `"new_moon"`, `"first_quarter"`, `"full_moon"` or
+/// `"last_quarter"`, and deserialize it from either those or the EasyTide numbers.
+pub mod lunar_phase_type {
+    use super::*;
+
+    pub fn serialize<S>(phase: &LunarPhaseType, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(phase.name())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<LunarPhaseType, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        const PHASES: [LunarPhaseType; 4] = [
+            LunarPhaseType::NewMoon,
+            LunarPhaseType::FirstQuarter,
+            LunarPhaseType::FullMoon,
+            LunarPhaseType::LastQuarter,
+        ];
+        deserializer.deserialize_any(NameOrNumber {
+            expecting: "a lunar phase name or an integer from 1 to 4",
+            from_name: |name| PHASES.into_iter().find(|p| p.name() == name),
+            from_number: |num| {
+                let index = usize::try_from(num).ok()?.checked_sub(1)?;
+                PHASES.get(index).copied()
+            },
+        })
+    }
+}
+
+/// Visitor for the `deserialize` functions, accepting a name or a number.
+struct NameOrNumber<T> {
+    expecting: &'static str,
+    from_name: fn(&str) -> Option<T>,
+    from_number: fn(u64) -> Option<T>,
+}
+
+impl<T> Visitor<'_> for NameOrNumber<T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str(self.expecting)
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        (self.from_name)(value).ok_or_else(|| E::invalid_value(Unexpected::Str(value), &self))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        (self.from_number)(value)
+            .ok_or_else(|| E::invalid_value(Unexpected::Unsigned(value), &self))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        u64::try_from(value)
+            .ok()
+            .and_then(self.from_number)
+            .ok_or_else(|| E::invalid_value(Unexpected::Signed(value), &self))
+    }
+}
+
+/// Tide predictions that serialize in the same format as [`TidePredictions`], except that
+/// event types and lunar phases are readable names.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// use rjw_uktides::named::Named;
+///
+/// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+///     .expect("Failed to read file as tides data.");
+/// let json = serde_json::to_value(Named(&tides)).unwrap();
+/// let event_type = &json["tidalEventList"][0]["eventType"];
+/// assert!(event_type == "high" || event_type == "low");
+/// assert_eq!(json["footerNote"], tides.footer_note.as_str());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Named<'a>(pub &'a TidePredictions);
+
+impl Serialize for Named<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let tides = self.0;
+        NamedPredictions {
+            footer_note: tides.footer_note.as_str(),
+            lunar_phase_list: tides
+                .lunar_phase_list
+                .iter()
+                .map(NamedPhase::from)
+                .collect(),
+            tidal_event_list: tides
+                .tidal_event_list
+                .iter()
+                .map(NamedEvent::from)
+                .collect(),
+            tidal_height_occurrence_list: &tides.tidal_height_occurrence_list,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NamedPredictions<'a> {
+    footer_note: &'a str,
+    lunar_phase_list: Vec<NamedPhase<'a>>,
+    tidal_event_list: Vec<NamedEvent<'a>>,
+    tidal_height_occurrence_list: &'a [crate::TidalHeightOccurrence],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NamedEvent<'a> {
+    #[serde(serialize_with = "serialize_date_without_tz")]
+    date: &'a NaiveDate,
+    #[serde(serialize_with = "serialize_datetime_without_tz")]
+    date_time: &'a DateTime<Utc>,
+    #[serde(with = "event_type")]
+    event_type: &'a TidalEventType,
+    height: &'a Metres,
+    is_approximate_height: &'a Option<String>,
+    is_approximate_time: &'a Option<String>,
+}
+
+impl<'a> From<&'a TidalEvent> for NamedEvent<'a> {
+    fn from(e: &'a TidalEvent) -> Self {
+        NamedEvent {
+            date: &e.date,
+            date_time: &e.date_time,
+            event_type: &e.event_type,
+            height: &e.height,
+            is_approximate_height: &e.is_approximate_height,
+            is_approximate_time: &e.is_approximate_time,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NamedPhase<'a> {
+    #[serde(serialize_with = "serialize_datetime_without_tz")]
+    date_time: &'a DateTime<Utc>,
+    #[serde(with = "lunar_phase_type")]
+    lunar_phase_type: &'a LunarPhaseType,
+}
+
+impl<'a> From<&'a LunarPhase> for NamedPhase<'a> {
+    fn from(p: &'a LunarPhase) -> Self {
+        NamedPhase {
+            date_time: &p.date_time,
+            lunar_phase_type: &p.lunar_phase_type,
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// Phrases, in lowercase, identifying the advisory that high water can last a while.
+const HIGH_WATER_STAND_PHRASES: &[&str] = &["high water duration", "high water stand"];
+
+/// Phrases, in lowercase, identifying the disclaimer about the use of the predictions.
+const DISCLAIMER_PHRASES: &[&str] = &[
+    "disclaimer",
+    "for navigation",
+    "navigational purposes",
+    "no liability",
+    "not accept",
+    "guidance only",
+    "crown copyright",
+];
+
+/// The note attached to a set of predictions, as [`TidePredictions::footer_note`].
+///
+/// The text is kept as sent, and serializes to the same string, but can be split into
+/// its separate [`Note`]s, each tagged with the kind of message it is, so that known
+/// boilerplate can be left out while anything unexpected is still shown.
+///
+/// [`TidePredictions::footer_note`]: crate::TidePredictions::footer_note
+///
+/// # Examples
+/// ```
+/// use rjw_uktides::{NoteKind, Notes};
+///
+/// let notes = Notes::from(
+///     "Note: The high water duration period can occur over an extended time period. \
+///      Harbour works may affect heights.",
+/// );
+/// let kinds: Vec<_> = notes.iter().map(|n| n.kind).collect();
+/// assert_eq!(kinds, [NoteKind::HighWaterStand, NoteKind::Other]);
+/// assert!(notes.contains(NoteKind::HighWaterStand));
+///
+/// let unknown: Vec<_> = notes.unknown().map(|n| n.text).collect();
+/// assert_eq!(unknown, ["Harbour works may affect heights."]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Notes(String);
+
+/// One message within [`Notes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Note<'a> {
+    /// The kind of message, if it is one that is recognised.
+    pub kind: NoteKind,
+    /// The text of the message, as sent.
+    pub text: &'a str,
+}
+
+/// The kinds of message recognised in [`Notes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NoteKind {
+    /// The advisory that the high water stand, the period around high water when the
+    /// height barely changes, can be long, so the time of high water is less certain.
+    HighWaterStand,
+    /// A disclaimer about the accuracy of the predictions or their use for navigation.
+    Disclaimer,
+    /// Any other message.
+    Other,
+}
+
+impl NoteKind {
+    /// Whether this is a recognised message that is repeated with every prediction,
+    /// rather than anything particular to the station.
+    pub fn is_boilerplate(self) -> bool {
+        !matches!(self, NoteKind::Other)
+    }
+
+    fn classify(text: &str) -> Self {
+        let text = text.to_lowercase();
+        let has = |phrases: &[&str]| phrases.iter().any(|p| text.contains(p));
+        if has(HIGH_WATER_STAND_PHRASES) {
+            NoteKind::HighWaterStand
+        } else if has(DISCLAIMER_PHRASES) {
+            NoteKind::Disclaimer
+        } else {
+            NoteKind::Other
+        }
+    }
+}
+
+impl Notes {
+    /// Wrap the text of a note.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self(text.into())
+    }
+
+    /// The text of the note, as sent.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The text of the note, as sent.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// Whether there is no note.
+    pub fn is_empty(&self) -> bool {
+        self.0.trim().is_empty()
+    }
+
+    /// The separate messages in the note, in order.
+    ///
+    /// Messages are separated by line breaks or the ends of sentences.
+    pub fn iter(&self) -> impl Iterator<Item = Note<'_>> {
+        self.0
+            .lines()
+            .flat_map(sentences)
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(|text| Note {
+                kind: NoteKind::classify(text),
+                text,
+            })
+    }
+
+    /// Whether the note includes a message of the given kind.
+    pub fn contains(&self, kind: NoteKind) -> bool {
+        self.iter().any(|note| note.kind == kind)
+    }
+
+    /// The messages that are not recognised, which may be worth showing even when the
+    /// boilerplate is not.
+    pub fn unknown(&self) -> impl Iterator<Item = Note<'_>> {
+        self.iter().filter(|note| !note.kind.is_boilerplate())
+    }
+}
+
+impl Display for Notes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for Notes {
+    fn from(text: String) -> Self {
+        Self(text)
+    }
+}
+
+impl From<&str> for Notes {
+    fn from(text: &str) -> Self {
+        Self(text.to_owned())
+    }
+}
+
+impl From<Notes> for String {
+    fn from(notes: Notes) -> Self {
+        notes.0
+    }
+}
+
+/// Split the line after each full stop, exclamation or question mark that is followed by
+/// a space and a capital letter, so that abbreviations such as "approx. 2m" stay whole.
+fn sentences(line: &str) -> impl Iterator<Item = &str> {
+    let mut rest = line;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let bytes = rest.as_bytes();
+        let end = (0..bytes.len())
+            .find(|&i| {
+                matches!(bytes[i], b'.' | b'!' | b'?')
+                    && bytes.get(i + 1) == Some(&b' ')
+                    && rest[i + 2..]
+                        .trim_start()
+                        .starts_with(|c: char| c.is_uppercase())
+            })
+            .map_or(rest.len(), |i| i + 1);
+        let (sentence, remainder) = rest.split_at(end);
+        rest = remainder;
+        Some(sentence)
+    })
+}
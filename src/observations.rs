@@ -0,0 +1,249 @@
+//! Observed tide heights from Environment Agency tide gauges.
+//!
+//! The Environment Agency [flood-monitoring API] publishes readings from tide gauges around
+//! the coast of England every 15 minutes. As with the EasyTide functions, this module only
+//! builds request URLs and parses responses, leaving the requests themselves to the caller.
+//! [`compare`] aligns the observed heights with EasyTide's predictions.
+//!
+//! Note that the gauges measure heights in metres above Ordnance Datum (mAOD), while EasyTide
+//! predicts heights above chart datum, which is usually several metres lower. The difference
+//! between the two is roughly constant for each gauge, so will appear as an offset in the
+//! residuals returned by [`compare`].
+//!
+//! [flood-monitoring API]: https://environment.data.gov.uk/flood-monitoring/doc/reference
+//!
+//! # Examples
+//! ```
+//! use std::fs::File;
+//!
+//! use rjw_uktides::observations;
+//!
+//! let file = File::open("./reference/ea-readings.json")
+//!     .expect("Failed to open readings reference file.");
+//! let observed = observations::observations_from_reader(file)
+//!     .expect("Failed to read file as readings data.");
+//! let predicted = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+//!     .expect("Failed to read file as tides data.");
+//!
+//! let comparison = observations::compare(&predicted, &observed);
+//! assert_eq!(comparison.len(), observed.len());
+//!
+//! // The mean residual is mostly the offset between Ordnance Datum and chart datum.
+//! let mean = comparison.iter().map(|c| c.residual.0).sum::<f64>() / comparison.len() as f64;
+//! assert!((-3.0..-2.5).contains(&mean));
+//! ```
+
+use std::io::Read;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Deserializer};
+
+use crate::trace::parse_json;
+use crate::{Coordinates, DecimalDegrees, Error, Metres, TidePredictions};
+
+/// The Environment Agency flood-monitoring API.
+pub const EA_BASE_URL: &str = "https://environment.data.gov.uk/flood-monitoring";
+
+/// The URL from which the list of tide gauges is fetched, for the service at `base_url`.
+///
+/// # Examples
+/// ```
+/// use rjw_uktides::observations::{tide_gauges_url, EA_BASE_URL};
+///
+/// assert_eq!(
+///     tide_gauges_url(EA_BASE_URL),
+///     "https://environment.data.gov.uk/flood-monitoring/id/stations?type=TideGauge"
+/// );
+/// ```
+pub fn tide_gauges_url(base_url: &str) -> String {
+    format!("{base_url}/id/stations?type=TideGauge")
+}
+
+/// The URL from which readings for the gauge since the given time are fetched, for the
+/// service at `base_url`.
+///
+/// # Examples
+/// ```
+/// use rjw_uktides::observations::{readings_url, EA_BASE_URL};
+///
+/// let since = "2023-04-03T00:00:00Z".parse().unwrap();
+/// assert_eq!(
+///     readings_url(EA_BASE_URL, "E71539", since),
+///     "https://environment.data.gov.uk/flood-monitoring/id/stations/E71539/readings?_sorted&since=2023-04-03T00:00:00Z"
+/// );
+/// ```
+pub fn readings_url(base_url: &str, gauge_reference: &str, since: DateTime<Utc>) -> String {
+    format!(
+        "{base_url}/id/stations/{gauge_reference}/readings?_sorted&since={}",
+        since.to_rfc3339_opts(SecondsFormat::Secs, true)
+    )
+}
+
+/// An Environment Agency tide gauge.
+#[derive(Debug, Clone)]
+pub struct TideGauge {
+    /// The gauge's reference, used to request its readings.
+    pub reference: String,
+    /// The name of the gauge, usually the place where it is located.
+    pub label: String,
+    /// The location of the gauge, which is missing for a small number of gauges.
+    pub location: Option<Coordinates>,
+}
+
+/// A tide height observed by a gauge, in metres above Ordnance Datum.
+#[derive(Debug, Clone, Copy)]
+pub struct Observation {
+    /// When the reading was taken.
+    pub date_time: DateTime<Utc>,
+    /// The observed height.
+    pub height: Metres,
+}
+
+/// An observed height alongside the height predicted for the same time.
+#[derive(Debug, Clone, Copy)]
+pub struct Comparison {
+    /// When the reading was taken.
+    pub date_time: DateTime<Utc>,
+    /// The observed height, above Ordnance Datum.
+    pub observed: Metres,
+    /// The predicted height, above chart datum.
+    pub predicted: Metres,
+    /// The observed height less the predicted height.
+    pub residual: Metres,
+}
+
+/// Attempt to parse the list of tide gauges from the reader.
+///
+/// # Errors
+///
+/// This function will return an error in the same cases as
+/// [`stations_from_reader`](crate::stations_from_reader).
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// let file = File::open("./reference/ea-stations.json")
+///     .expect("Failed to open gauges reference file.");
+/// let gauges = rjw_uktides::observations::tide_gauges_from_reader(file)
+///     .expect("Failed to read file as gauges data.");
+/// assert_eq!(gauges[0].reference, "E71539");
+/// assert_eq!(gauges[1].label, "Sheerness");
+/// assert!(gauges[2].location.is_none());
+/// ```
+pub fn tide_gauges_from_reader(rdr: impl Read) -> Result<Vec<TideGauge>, Error> {
+    let response: Items<RawGauge> = parse_json(rdr, "tide gauges")?;
+    Ok(response
+        .items
+        .into_iter()
+        .map(|g| TideGauge {
+            reference: g.station_reference,
+            label: g.label.0,
+            location: g.lat.zip(g.long).map(|(lat, long)| Coordinates {
+                latitude: DecimalDegrees(lat),
+                longitude: DecimalDegrees(long),
+            }),
+        })
+        .collect())
+}
+
+/// Attempt to parse gauge readings from the reader, sorted from earliest to latest.
+///
+/// # Errors
+///
+/// This function will return an error in the same cases as
+/// [`tides_from_reader`](crate::tides_from_reader).
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// let file = File::open("./reference/ea-readings.json")
+///     .expect("Failed to open readings reference file.");
+/// let readings = rjw_uktides::observations::observations_from_reader(file)
+///     .expect("Failed to read file as readings data.");
+/// assert!(readings.windows(2).all(|w| w[0].date_time < w[1].date_time));
+/// ```
+pub fn observations_from_reader(rdr: impl Read) -> Result<Vec<Observation>, Error> {
+    let response: Items<RawReading> = parse_json(rdr, "gauge readings")?;
+    let mut observations: Vec<Observation> = response
+        .items
+        .into_iter()
+        .map(|r| Observation {
+            date_time: r.date_time,
+            height: Metres(r.value.0),
+        })
+        .collect();
+    observations.sort_by_key(|o| o.date_time);
+    Ok(observations)
+}
+
+/// Pair each observation with the predicted height at the same time, linearly interpolated
+/// between the half-hourly height predictions (see [`TidePredictions::height_at`]).
+///
+/// Observations outside the period covered by the height predictions are skipped, so the
+/// result is empty if the predictions were parsed without their continuous heights.
+pub fn compare(predictions: &TidePredictions, observations: &[Observation]) -> Vec<Comparison> {
+    observations
+        .iter()
+        .filter_map(|o| {
+            let predicted = predictions.height_at(o.date_time)?;
+            Some(Comparison {
+                date_time: o.date_time,
+                observed: o.height,
+                predicted,
+                residual: Metres(o.height.0 - predicted.0),
+            })
+        })
+        .collect()
+}
+
+/// The list wrapper of every flood-monitoring API response.
+#[derive(Debug, Deserialize)]
+struct Items<T> {
+    items: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawGauge {
+    station_reference: String,
+    label: FirstOf<String>,
+    lat: Option<f64>,
+    long: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawReading {
+    date_time: DateTime<Utc>,
+    value: FirstOf<f64>,
+}
+
+/// A value that the API sometimes repeats in an array, such as the labels of some gauges
+/// and duplicated readings, of which only the first is kept.
+#[derive(Debug)]
+struct FirstOf<T>(T);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for FirstOf<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum OneOrMany<T> {
+            One(T),
+            Many(Vec<T>),
+        }
+
+        match OneOrMany::deserialize(deserializer)? {
+            OneOrMany::One(value) => Ok(Self(value)),
+            OneOrMany::Many(values) => values
+                .into_iter()
+                .next()
+                .map(Self)
+                .ok_or_else(|| serde::de::Error::invalid_length(0, &"at least one value")),
+        }
+    }
+}
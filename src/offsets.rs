@@ -0,0 +1,340 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::atlas::apart;
+use crate::{Metres, TidalEvent, TidalEventType, TidePredictions};
+
+/// The furthest apart the same tide can be at two stations, a little under half the time
+/// between successive high tides, so that a tide is never paired with the next one.
+const MAX_OFFSET_MINUTES: i64 = 5 * 60;
+
+/// Time and height differences between a reference port and a secondary port, for
+/// predicting the tides at the secondary port from those at the reference port.
+///
+/// As in the secondary port tables of tide tables, there are separate differences for
+/// high and low water. Differences are positive when the tide is later or higher at the
+/// secondary port.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// use chrono::Duration;
+/// use rjw_uktides::{PortOffsets, TidalEventType};
+///
+/// let reference = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+///     .expect("Failed to read file as tides data.");
+/// // A secondary port where high water is 20 minutes later and 0.3m higher, and low water
+/// // is 35 minutes later and 0.1m lower.
+/// let mut secondary = reference.clone();
+/// for event in &mut secondary.tidal_event_list {
+///     let (minutes, metres) = match event.event_type {
+///         TidalEventType::HighWater => (20, 0.3),
+///         TidalEventType::LowWater => (35, -0.1),
+///     };
+///     event.date_time += Duration::minutes(minutes);
+///     event.height.0 += metres;
+/// }
+///
+/// let offsets = PortOffsets::between(&reference, &secondary).unwrap();
+/// assert_eq!(offsets.high_water_time, Duration::minutes(20));
+/// assert_eq!(offsets.low_water_time, Duration::minutes(35));
+/// assert!((offsets.high_water_height.0 - 0.3).abs() < 1e-9);
+/// assert_eq!(offsets.tides_compared, reference.tidal_event_list.len());
+///
+/// let predicted = offsets.apply(&reference);
+/// assert_eq!(predicted.tidal_event_list, secondary.tidal_event_list);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PortOffsets {
+    /// How much later high water is at the secondary port.
+    pub high_water_time: Duration,
+    /// How much later low water is at the secondary port.
+    pub low_water_time: Duration,
+    /// How much higher high water is at the secondary port.
+    pub high_water_height: Metres,
+    /// How much higher low water is at the secondary port.
+    pub low_water_height: Metres,
+    /// The number of pairs of corresponding tides the differences are averaged over.
+    pub tides_compared: usize,
+}
+
+impl PortOffsets {
+    /// The mean differences between the tides at the reference port and the same tides at
+    /// the secondary port, over the period covered by both sets of predictions.
+    ///
+    /// The tides are paired with [`pair_tides`], each tide at the reference port with the
+    /// nearest unpaired tide of the same type at the secondary port within five hours.
+    /// Returns `None` unless at least one high water and one low water can be paired.
+    pub fn between(reference: &TidePredictions, secondary: &TidePredictions) -> Option<Self> {
+        let max_offset = Duration::minutes(MAX_OFFSET_MINUTES);
+        let mut high = Differences::default();
+        let mut low = Differences::default();
+        let pairs = pair_tides(
+            &reference.tidal_event_list,
+            &secondary.tidal_event_list,
+            max_offset,
+        );
+        for (a, b) in pairs {
+            let (Some(a), Some(b)) = (a, b) else {
+                continue;
+            };
+            let differences = match a.event_type {
+                TidalEventType::HighWater => &mut high,
+                TidalEventType::LowWater => &mut low,
+            };
+            differences.add(b.date_time - a.date_time, b.height.0 - a.height.0);
+        }
+        let (high_water_time, high_water_height) = high.mean()?;
+        let (low_water_time, low_water_height) = low.mean()?;
+        Some(Self {
+            high_water_time,
+            low_water_time,
+            high_water_height,
+            low_water_height,
+            tides_compared: high.count + low.count,
+        })
+    }
+
+    /// Predictions for the secondary port, made by applying the differences to the
+    /// predictions for the reference port.
+    ///
+    /// The continuous heights are adjusted by differences interpolated between those of
+    /// the tides either side of them, and the lunar phases and note are kept as they are.
+    pub fn apply(&self, reference: &TidePredictions) -> TidePredictions {
+        let mut predictions = reference.clone();
+        for event in &mut predictions.tidal_event_list {
+            let (time, height) = self.for_type(event.event_type);
+            event.date_time += time;
+            event.date = event.date_time.date_naive();
+            event.height.0 += height.0;
+        }
+        for occurrence in &mut predictions.tidal_height_occurrence_list {
+            let (time, height) = self.interpolated(reference, occurrence.date_time);
+            occurrence.date_time += time;
+            occurrence.height.0 += height;
+        }
+        predictions
+    }
+
+    /// The time and height differences for tides of the type.
+    fn for_type(&self, event_type: TidalEventType) -> (Duration, Metres) {
+        match event_type {
+            TidalEventType::HighWater => (self.high_water_time, self.high_water_height),
+            TidalEventType::LowWater => (self.low_water_time, self.low_water_height),
+        }
+    }
+
+    /// The time and height differences at `at`, in proportion to how far it is from the
+    /// tide before it to the tide after, or those of the nearest tide outside the tides.
+    fn interpolated(&self, reference: &TidePredictions, at: DateTime<Utc>) -> (Duration, f64) {
+        let events = &reference.tidal_event_list;
+        let (before, after) = match reference.bracketing_events(at) {
+            Some(bracket) => bracket,
+            None => match events.iter().min_by_key(|e| apart(e.date_time, at)) {
+                Some(nearest) => (nearest, nearest),
+                None => return (Duration::zero(), 0.0),
+            },
+        };
+        let (before_time, before_height) = self.for_type(before.event_type);
+        let (after_time, after_height) = self.for_type(after.event_type);
+        let span = (after.date_time - before.date_time).num_seconds();
+        let fraction = if span == 0 {
+            0.0
+        } else {
+            (at - before.date_time).num_seconds() as f64 / span as f64
+        };
+        let seconds = before_time.num_seconds() as f64
+            + (after_time - before_time).num_seconds() as f64 * fraction;
+        let height = before_height.0 + (after_height.0 - before_height.0) * fraction;
+        (Duration::seconds(seconds.round() as i64), height)
+    }
+}
+
+/// Pair each tide in `first` with the same tide in `second`, in order of time.
+///
+/// The same tide is the nearest of the same type no more than `within` away, and each tide
+/// is in at most one pair: the closest tides are paired first, so that when two tides in
+/// `first` are nearest the same tide in `second`, the further one is left without a
+/// counterpart. Tides in either list without a counterpart are paired with `None`.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// use chrono::Duration;
+///
+/// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+///     .expect("Failed to read file as tides data.");
+/// let second = &tides.tidal_event_list;
+/// // An extra tide an hour after the first, like the second of a double high water.
+/// let mut first = second.clone();
+/// let mut extra = first[0].clone();
+/// extra.date_time += Duration::hours(1);
+/// first.insert(1, extra);
+///
+/// let pairs = rjw_uktides::pair_tides(&first, second, Duration::hours(3));
+/// assert_eq!(pairs.len(), first.len());
+/// assert_eq!(pairs[0], (Some(&first[0]), Some(&second[0])));
+/// assert_eq!(pairs[1], (Some(&first[1]), None));
+/// assert!(pairs[2..].iter().all(|(a, b)| a.is_some() && b.is_some()));
+/// ```
+pub fn pair_tides<'a>(
+    first: &'a [TidalEvent],
+    second: &'a [TidalEvent],
+    within: Duration,
+) -> Vec<(Option<&'a TidalEvent>, Option<&'a TidalEvent>)> {
+    let mut candidates: Vec<_> = first
+        .iter()
+        .enumerate()
+        .flat_map(|(i, a)| {
+            second
+                .iter()
+                .enumerate()
+                .filter(move |(_, b)| b.event_type == a.event_type)
+                .map(move |(j, b)| (apart(a.date_time, b.date_time), i, j))
+        })
+        .filter(|(apart, _, _)| *apart <= within)
+        .collect();
+    candidates.sort();
+    let mut counterparts = vec![None; first.len()];
+    let mut used = vec![false; second.len()];
+    for (_, i, j) in candidates {
+        if counterparts[i].is_none() && !used[j] {
+            counterparts[i] = Some(&second[j]);
+            used[j] = true;
+        }
+    }
+    let mut pairs: Vec<_> = first.iter().map(Some).zip(counterparts).collect();
+    pairs.extend(
+        second
+            .iter()
+            .zip(used)
+            .filter(|(_, used)| !used)
+            .map(|(b, _)| (None, Some(b))),
+    );
+    pairs.sort_by_key(|(a, b)| a.or(*b).map(|e| e.date_time));
+    pairs
+}
+
+/// Running totals of the differences between pairs of tides of one type.
+#[derive(Default)]
+struct Differences {
+    count: usize,
+    seconds: i64,
+    metres: f64,
+}
+
+impl Differences {
+    fn add(&mut self, time: Duration, metres: f64) {
+        self.count += 1;
+        self.seconds += time.num_seconds();
+        self.metres += metres;
+    }
+
+    /// The mean time and height differences, if there are any.
+    fn mean(&self) -> Option<(Duration, Metres)> {
+        if self.count == 0 {
+            return None;
+        }
+        let count = self.count as f64;
+        Some((
+            Duration::seconds((self.seconds as f64 / count).round() as i64),
+            Metres(self.metres / count),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{utc, TidalEventBuilder, TidePredictionsBuilder};
+
+    fn offsets() -> PortOffsets {
+        PortOffsets {
+            high_water_time: Duration::minutes(20),
+            low_water_time: Duration::minutes(40),
+            high_water_height: Metres(0.5),
+            low_water_height: Metres(-0.1),
+            tides_compared: 2,
+        }
+    }
+
+    fn reference() -> TidePredictions {
+        TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::high().at("2023-04-03T06:00").height(4.0))
+            .event(TidalEventBuilder::low().at("2023-04-03T12:00").height(1.0))
+            .height("2023-04-03T05:00", 3.8)
+            .height("2023-04-03T09:00", 2.5)
+            .height("2023-04-03T13:00", 1.1)
+            .build()
+    }
+
+    #[test]
+    fn applies_the_differences_for_each_type_of_tide() {
+        let predicted = offsets().apply(&reference());
+        let high = &predicted.tidal_event_list[0];
+        assert_eq!(high.date_time, utc("2023-04-03T06:20"));
+        assert!((high.height.0 - 4.5).abs() < 1e-9);
+        let low = &predicted.tidal_event_list[1];
+        assert_eq!(low.date_time, utc("2023-04-03T12:40"));
+        assert!((low.height.0 - 0.9).abs() < 1e-9);
+    }
+
+    #[test]
+    fn moves_the_date_of_tides_shifted_past_midnight() {
+        let reference = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::low().at("2023-04-03T23:50").height(1.0))
+            .build();
+        let predicted = offsets().apply(&reference);
+        let low = &predicted.tidal_event_list[0];
+        assert_eq!(low.date, utc("2023-04-04T00:30").date_naive());
+    }
+
+    #[test]
+    fn interpolates_the_differences_for_continuous_heights() {
+        let heights = offsets().apply(&reference()).tidal_height_occurrence_list;
+        // Before the first tide and after the last, the nearest tide's differences apply.
+        assert_eq!(heights[0].date_time, utc("2023-04-03T05:20"));
+        assert!((heights[0].height.0 - 4.3).abs() < 1e-9);
+        assert_eq!(heights[2].date_time, utc("2023-04-03T13:40"));
+        assert!((heights[2].height.0 - 1.0).abs() < 1e-9);
+        // Halfway between the tides, halfway between their differences.
+        assert_eq!(heights[1].date_time, utc("2023-04-03T09:30"));
+        assert!((heights[1].height.0 - 2.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn applies_no_differences_to_heights_without_tides() {
+        let reference = TidePredictionsBuilder::new()
+            .height("2023-04-03T05:00", 3.8)
+            .build();
+        let heights = offsets().apply(&reference).tidal_height_occurrence_list;
+        assert_eq!(heights[0].date_time, utc("2023-04-03T05:00"));
+        assert_eq!(heights[0].height.0, 3.8);
+    }
+
+    #[test]
+    fn needs_a_pair_of_each_type_of_tide() {
+        let reference = reference();
+        let predicted = offsets().apply(&reference);
+        let mut only_high = predicted.clone();
+        only_high
+            .tidal_event_list
+            .retain(|e| e.event_type == TidalEventType::HighWater);
+        assert!(PortOffsets::between(&reference, &only_high).is_none());
+        let empty = TidePredictionsBuilder::new().build();
+        assert!(PortOffsets::between(&reference, &empty).is_none());
+        assert!(PortOffsets::between(&empty, &predicted).is_none());
+        assert!(PortOffsets::between(&reference, &predicted).is_some());
+    }
+
+    #[test]
+    fn does_not_pair_tides_more_than_five_hours_apart() {
+        let reference = reference();
+        let mut secondary = reference.clone();
+        for event in &mut secondary.tidal_event_list {
+            event.date_time += Duration::minutes(MAX_OFFSET_MINUTES + 1);
+        }
+        assert!(PortOffsets::between(&reference, &secondary).is_none());
+    }
+}
@@ -0,0 +1,248 @@
+//! Ordnance Survey National Grid references, converted to latitude and longitude.
+//!
+//! The conversion follows the Ordnance Survey's "A guide to coordinate systems in Great
+//! Britain": the grid reference is converted to OSGB36 latitude and longitude with the
+//! inverse transverse Mercator projection, and then to WGS 84 with a Helmert
+//! transformation, which is accurate to about five metres.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::{Coordinates, DecimalDegrees};
+
+/// The Airy 1830 ellipsoid, on which OSGB36 is based: semi-major and semi-minor axes.
+const AIRY_A: f64 = 6_377_563.396;
+const AIRY_B: f64 = 6_356_256.909;
+
+/// The GRS80 ellipsoid, used for WGS 84: semi-major and semi-minor axes.
+const GRS80_A: f64 = 6_378_137.0;
+const GRS80_B: f64 = 6_356_752.314_1;
+
+/// The National Grid's scale factor on the central meridian, true origin in degrees, and
+/// the grid coordinates of the true origin.
+const F0: f64 = 0.999_601_271_7;
+const LAT0: f64 = 49.0;
+const LON0: f64 = -2.0;
+const E0: f64 = 400_000.0;
+const N0: f64 = -100_000.0;
+
+/// The Helmert transformation from OSGB36 to WGS 84: translations in metres, scale in
+/// parts per million, and rotations in arcseconds.
+const HELMERT_T: [f64; 3] = [446.448, -125.157, 542.060];
+const HELMERT_S: f64 = -20.4894;
+const HELMERT_R: [f64; 3] = [0.1502, 0.2470, 0.8421];
+
+/// The first letters of the 500 km squares that cover Great Britain.
+const SQUARES_500KM: &str = "HJNOST";
+
+/// A point on the Ordnance Survey National Grid, in metres east and north of the grid's
+/// false origin, southwest of the Isles of Scilly.
+///
+/// Parse a grid reference such as "SZ 59 84" or "SZ5984" with [`str::parse`]. The point
+/// is the centre of the square the reference gives, so "SZ 59 84" is 500 m east and
+/// north of the square's southwest corner at SZ 590 840.
+///
+/// # Examples
+/// ```
+/// use rjw_uktides::GridReference;
+///
+/// let sandown: GridReference = "SZ 59 84".parse().unwrap();
+/// assert_eq!((sandown.easting, sandown.northing), (459_500.0, 84_500.0));
+/// assert_eq!(sandown.to_string(), "SZ 59500 84500");
+///
+/// // The Ordnance Survey's worked example, at TG 51409 13177.
+/// let point: GridReference = "TG5140913177".parse().unwrap();
+/// let coordinates = point.to_coordinates();
+/// assert!((coordinates.latitude.0 - 52.657976).abs() < 0.0001);
+/// assert!((coordinates.longitude.0 - 1.716050).abs() < 0.0001);
+///
+/// assert!("SZ 591 84".parse::<GridReference>().is_err());
+/// assert!("AA 59 84".parse::<GridReference>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridReference {
+    /// Metres east of the false origin.
+    pub easting: f64,
+    /// Metres north of the false origin.
+    pub northing: f64,
+}
+
+impl GridReference {
+    /// The point's WGS 84 latitude and longitude, as used by GPS and the station list.
+    pub fn to_coordinates(&self) -> Coordinates {
+        let (lat, lon) = osgb36_lat_lon(self.easting, self.northing);
+        let (lat, lon) = osgb36_to_wgs84(lat, lon);
+        Coordinates {
+            latitude: DecimalDegrees(lat.to_degrees()),
+            longitude: DecimalDegrees(lon.to_degrees()),
+        }
+    }
+}
+
+impl FromStr for GridReference {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid grid reference {s:?}; expected two letters and an even number of \
+                 digits, such as \"SZ 59 84\""
+            )
+        };
+        let compact: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let compact = compact.to_ascii_uppercase();
+        let mut chars = compact.chars();
+        let (Some(Some(first)), Some(Some(second))) = (
+            chars.next().map(letter_index),
+            chars.next().map(letter_index),
+        ) else {
+            return Err(invalid());
+        };
+        let digits = chars.as_str();
+        if !SQUARES_500KM
+            .chars()
+            .any(|c| letter_index(c) == Some(first))
+            || !digits.len().is_multiple_of(2)
+            || digits.len() > 10
+            || !digits.bytes().all(|b| b.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+        // The letters are numbered in a 5 by 5 grid from the northwest corner.
+        let easting_100km = ((first - 2) % 5) * 5 + second % 5;
+        let northing_100km = (19 - (first / 5) * 5) - second / 5;
+        let (east, north) = digits.split_at(digits.len() / 2);
+        let square = 10f64.powi(5 - east.len() as i32);
+        let offset = |digits: &str| digits.parse::<f64>().unwrap_or(0.0) * square + square / 2.0;
+        Ok(Self {
+            easting: easting_100km as f64 * 100_000.0 + offset(east),
+            northing: northing_100km as f64 * 100_000.0 + offset(north),
+        })
+    }
+}
+
+impl Display for GridReference {
+    /// The grid reference to the metre, such as "SZ 59500 84500".
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (e, n) = (self.easting.floor() as i64, self.northing.floor() as i64);
+        let (e_100km, n_100km) = (e.div_euclid(100_000), n.div_euclid(100_000));
+        let first = (19 - n_100km) - (19 - n_100km) % 5 + (e_100km + 10) / 5;
+        let second = (19 - n_100km) * 5 % 25 + e_100km % 5;
+        let letter = |index: i64| {
+            let index = u8::try_from(index).unwrap_or(0);
+            // The letter I is not used.
+            char::from(b'A' + index + u8::from(index > 7))
+        };
+        write!(
+            f,
+            "{}{} {:05} {:05}",
+            letter(first),
+            letter(second),
+            e.rem_euclid(100_000),
+            n.rem_euclid(100_000)
+        )
+    }
+}
+
+/// The letter's position in the alphabet without I, from 0 for A.
+fn letter_index(c: char) -> Option<i64> {
+    match c {
+        'A'..='H' => Some(c as i64 - 'A' as i64),
+        'J'..='Z' => Some(c as i64 - 'A' as i64 - 1),
+        _ => None,
+    }
+}
+
+/// The OSGB36 latitude and longitude, in radians, of the grid coordinates.
+fn osgb36_lat_lon(easting: f64, northing: f64) -> (f64, f64) {
+    let (a, b) = (AIRY_A, AIRY_B);
+    let e2 = 1.0 - (b * b) / (a * a);
+    let n = (a - b) / (a + b);
+    let (lat0, lon0) = (LAT0.to_radians(), LON0.to_radians());
+    let meridional_arc = |lat: f64| {
+        let (d, s) = (lat - lat0, lat + lat0);
+        b * F0
+            * ((1.0 + n + 1.25 * n.powi(2) + 1.25 * n.powi(3)) * d
+                - (3.0 * n + 3.0 * n.powi(2) + 21.0 / 8.0 * n.powi(3)) * d.sin() * s.cos()
+                + (15.0 / 8.0 * n.powi(2) + 15.0 / 8.0 * n.powi(3))
+                    * (2.0 * d).sin()
+                    * (2.0 * s).cos()
+                - 35.0 / 24.0 * n.powi(3) * (3.0 * d).sin() * (3.0 * s).cos())
+    };
+    let mut lat = lat0;
+    let mut arc = 0.0;
+    // Iterate until the latitude is within 0.01 mm of the northing.
+    loop {
+        lat += (northing - N0 - arc) / (a * F0);
+        arc = meridional_arc(lat);
+        if (northing - N0 - arc).abs() < 0.000_01 {
+            break;
+        }
+    }
+    let sin2 = lat.sin().powi(2);
+    let nu = a * F0 / (1.0 - e2 * sin2).sqrt();
+    let rho = a * F0 * (1.0 - e2) / (1.0 - e2 * sin2).powf(1.5);
+    let eta2 = nu / rho - 1.0;
+    let (tan, sec) = (lat.tan(), 1.0 / lat.cos());
+    let (tan2, tan4, tan6) = (tan.powi(2), tan.powi(4), tan.powi(6));
+    let vii = tan / (2.0 * rho * nu);
+    let viii = tan / (24.0 * rho * nu.powi(3)) * (5.0 + 3.0 * tan2 + eta2 - 9.0 * tan2 * eta2);
+    let ix = tan / (720.0 * rho * nu.powi(5)) * (61.0 + 90.0 * tan2 + 45.0 * tan4);
+    let x = sec / nu;
+    let xi = sec / (6.0 * nu.powi(3)) * (nu / rho + 2.0 * tan2);
+    let xii = sec / (120.0 * nu.powi(5)) * (5.0 + 28.0 * tan2 + 24.0 * tan4);
+    let xiia = sec / (5040.0 * nu.powi(7)) * (61.0 + 662.0 * tan2 + 1320.0 * tan4 + 720.0 * tan6);
+    let de = easting - E0;
+    (
+        lat - vii * de.powi(2) + viii * de.powi(4) - ix * de.powi(6),
+        lon0 + x * de - xi * de.powi(3) + xii * de.powi(5) - xiia * de.powi(7),
+    )
+}
+
+/// The WGS 84 latitude and longitude, in radians, of the OSGB36 latitude and longitude.
+fn osgb36_to_wgs84(lat: f64, lon: f64) -> (f64, f64) {
+    // Cartesian coordinates on the Airy ellipsoid, at the height of the ellipsoid.
+    let e2 = 1.0 - AIRY_B.powi(2) / AIRY_A.powi(2);
+    let nu = AIRY_A / (1.0 - e2 * lat.sin().powi(2)).sqrt();
+    let (x, y, z) = (
+        nu * lat.cos() * lon.cos(),
+        nu * lat.cos() * lon.sin(),
+        (1.0 - e2) * nu * lat.sin(),
+    );
+    let s = 1.0 + HELMERT_S / 1e6;
+    let [rx, ry, rz] = HELMERT_R.map(|r| (r / 3600.0).to_radians());
+    let [tx, ty, tz] = HELMERT_T;
+    let (x, y, z) = (
+        tx + s * x - rz * y + ry * z,
+        ty + rz * x + s * y - rx * z,
+        tz - ry * x + rx * y + s * z,
+    );
+    // Back to latitude and longitude on the GRS80 ellipsoid.
+    let e2 = 1.0 - GRS80_B.powi(2) / GRS80_A.powi(2);
+    let p = x.hypot(y);
+    let mut lat = z.atan2(p * (1.0 - e2));
+    for _ in 0..10 {
+        let nu = GRS80_A / (1.0 - e2 * lat.sin().powi(2)).sqrt();
+        lat = (z + e2 * nu * lat.sin()).atan2(p);
+    }
+    (lat, y.atan2(x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_non_ascii_letters() {
+        for input in ["Sé 59 84", "é", "SZ 5é 84", "ÉÉ5984"] {
+            assert!(input.parse::<GridReference>().is_err(), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn accepts_lowercase_and_any_spacing() {
+        let expected: GridReference = "SZ 59 84".parse().unwrap();
+        assert_eq!("sz5984".parse::<GridReference>(), Ok(expected));
+        assert_eq!(" SZ  5984 ".parse::<GridReference>(), Ok(expected));
+    }
+}
@@ -1,25 +1,27 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Display;
 use std::io::Read;
 use std::str::FromStr;
-use std::{error::Error, fmt::Display};
 
-use chrono::{DateTime, NaiveDate, TimeZone, Utc};
-use serde::{self, de::Unexpected, Deserialize, Deserializer};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::de::{Unexpected, Visitor};
+use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::trace::{parse_json, parse_json_slice};
+use crate::{Error, Notes};
 
 /// Attempt to parse data from the reader as tide predictions.
 ///
 /// The data should be JSON sourced from the Admiralty (semi-)public
-/// Home/GetPredictions endpoint.
+/// Home/GetPredictions endpoint. With the `compression` feature enabled,
+/// gzip or zlib-compressed data is decompressed transparently.
 ///
 /// # Errors
 ///
-/// This function will return an error if it cannot parse the data
-/// from the reader as JSON or as JSON that encodes tide predictions.
-/// Currently the error will only be a `serde_json::Error` but is
-/// boxed to hide changes in the implementation in the future.
-///
-/// (`serde_json::Error` itself just contains a Boxed error, but this
-/// extra indirection isn't expected to cause performance problems as
-/// this function is effectively the "end of the line" for the error.)
+/// This function will return an error if it cannot read the data from
+/// the reader, or parse it as JSON or as JSON that encodes tide predictions.
+/// [`Error::kind`] distinguishes between these cases.
 ///
 /// # Examples
 /// ```
@@ -31,48 +33,154 @@ use serde::{self, de::Unexpected, Deserialize, Deserializer};
 /// let bufreader = BufReader::new(file);
 /// let tides = rjw_uktides::tides_from_reader(bufreader)
 ///     .expect("Failed to read file as tides data.");
+///
+/// # #[cfg(feature = "compression")]
+/// # {
+/// let file = File::open("./reference/tides.json.gz")
+///     .expect("Failed to open compressed tides reference file.");
+/// let decompressed = rjw_uktides::tides_from_reader(file)
+///     .expect("Failed to read compressed file as tides data.");
+/// assert_eq!(decompressed.tidal_event_list, tides.tidal_event_list);
+/// # }
 /// ```
-pub fn tides_from_reader(rdr: impl Read) -> Result<TidePredictions, Box<dyn Error>> {
-    let tides = serde_json::from_reader(rdr)?;
+pub fn tides_from_reader(rdr: impl Read) -> Result<TidePredictions, Error> {
+    let tides = parse_json(rdr, "tide predictions")?;
     Ok(tides)
 }
 
+/// Attempt to parse the tide predictions for several stations, such as responses saved to
+/// files, from a reader for each station.
+///
+/// Each reader is parsed as with [`tides_from_reader`], and read to the end before the
+/// next is started. A reader that fails does not stop the others, so the result for each
+/// station is kept separately, and with the `tracing` feature a warning is recorded for
+/// each station that fails. If a station is given more than once, the last result for it
+/// is kept.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// use rjw_uktides::{ErrorKind, StationId};
+///
+/// let readers = [
+///     (StationId("0053".to_owned()), File::open("./reference/tides.json").unwrap()),
+///     (StationId("0001".to_owned()), File::open("./stations.json").unwrap()),
+/// ];
+/// let tides = rjw_uktides::tides_from_readers(readers);
+/// assert!(tides[&StationId("0053".to_owned())].is_ok());
+/// let err = tides[&StationId("0001".to_owned())].as_ref().unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::Data);
+/// ```
+pub fn tides_from_readers(
+    readers: impl IntoIterator<Item = (StationId, impl Read)>,
+) -> HashMap<StationId, Result<TidePredictions, Error>> {
+    readers
+        .into_iter()
+        .map(|(station, rdr)| {
+            let tides = tides_from_reader(rdr);
+            #[cfg(feature = "tracing")]
+            if let Err(e) = &tides {
+                tracing::warn!(%station, error = %e, "skipping tide predictions for station");
+            }
+            (station, tides)
+        })
+        .collect()
+}
+
 /// Attempt to extract tide station information from the reader.
 ///
 /// The data should be JSON sourced from the Admiralty (semi-)public
 /// Home/GetStations endpoint. The "features" property of the returned
-/// JSON is returned as a `Vec` of `Station`.
+/// JSON is returned as a `Vec` of `Station`. With the `compression`
+/// feature enabled, gzip or zlib-compressed data is decompressed
+/// transparently.
 ///
 /// The [`Station`] struct simplifies the nested structure of the
 /// JSON returned by the GetStations endpoint.
 ///
 /// # Errors
 ///
-/// This function will return an error if it cannot parse the data
-/// from the reader as JSON or as JSON that encodes station data.
-/// Currently the error will only be a `serde_json::Error` but is
-/// boxed to hide changes in the implementation in the future.
-///
-/// (`serde_json::Error` itself just contains a Boxed error, but this
-/// extra indirection isn't expected to cause performance problems as
-/// this function is effectively the "end of the line" for the error.)
+/// This function will return an error if it cannot read the data from
+/// the reader, or parse it as JSON or as JSON that encodes station data.
+/// [`Error::kind`] distinguishes between these cases.
 ///
 /// # Examples
 /// ```
 /// use std::fs::File;
 /// use std::io::BufReader;
 ///
-/// let file = File::open("./reference/stations.json")
+/// let file = File::open("./stations.json")
 ///     .expect("Failed to open stations reference file.");
 /// let bufreader = BufReader::new(file);
-/// let stations = tidescli::stations_from_reader(bufreader)
+/// let stations = rjw_uktides::stations_from_reader(bufreader)
 ///     .expect("Failed to read file as stations data.");
 /// ```
-pub fn stations_from_reader(rdr: impl Read) -> Result<Vec<Station>, Box<dyn Error>> {
-    let stations: StationsData = serde_json::from_reader(rdr)?;
+pub fn stations_from_reader(rdr: impl Read) -> Result<Vec<Station>, Error> {
+    let stations: StationsData = parse_json(rdr, "stations")?;
     Ok(stations.features)
 }
 
+/// Attempt to parse tide predictions from an in-memory buffer.
+///
+/// This behaves like [`tides_from_reader`] but is faster when the whole response is
+/// already in memory, as the parser can work on the buffer directly.
+///
+/// # Errors
+///
+/// This function will return an error if it cannot parse the data as JSON or as JSON
+/// that encodes tide predictions.
+///
+/// # Examples
+/// ```
+/// let data = std::fs::read("./reference/tides.json")
+///     .expect("Failed to read tides reference file.");
+/// let tides = rjw_uktides::tides_from_slice(&data)
+///     .expect("Failed to read file as tides data.");
+/// ```
+pub fn tides_from_slice(data: &[u8]) -> Result<TidePredictions, Error> {
+    parse_json_slice(data, "tide predictions")
+}
+
+/// Attempt to extract tide station information from an in-memory buffer, borrowing
+/// strings from it where possible.
+///
+/// This behaves like [`stations_from_reader`] but returns [`StationRef`] values whose
+/// ID, name and country borrow from `data` instead of being allocated afresh, which
+/// helps services that repeatedly parse cached responses. Use [`StationRef::into_owned`]
+/// to convert any station that needs to outlive the buffer.
+///
+/// # Errors
+///
+/// This function will return an error if it cannot parse the data as JSON or as JSON
+/// that encodes station data.
+///
+/// # Examples
+/// ```
+/// use std::borrow::Cow;
+///
+/// let data = std::fs::read("./stations.json")
+///     .expect("Failed to read stations reference file.");
+/// let stations = rjw_uktides::stations_from_slice(&data)
+///     .expect("Failed to read file as stations data.");
+/// assert!(matches!(stations[0].name, Cow::Borrowed(_)));
+/// ```
+pub fn stations_from_slice(data: &[u8]) -> Result<Vec<StationRef<'_>>, Error> {
+    let stations: StationsDataRef = parse_json_slice(data, "stations")?;
+    let stations = stations
+        .features
+        .into_iter()
+        .map(|feature| StationRef {
+            id: feature.properties.id,
+            name: feature.properties.name,
+            country: feature.properties.country,
+            location: feature.geometry.coordinates,
+            continuous_heights_available: feature.properties.continuous_heights_available,
+        })
+        .collect();
+    Ok(stations)
+}
+
 /// Parse ISO 8601 datetimes missing a timezone and with optional fractional seconds as UTC.
 ///
 /// The Admiralty tides API returns dates as datetimes without a timezone specifier, and returns
@@ -83,19 +191,40 @@ pub fn stations_from_reader(rdr: impl Read) -> Result<Vec<Station>, Box<dyn Erro
 ///
 /// # Errors
 ///
-/// This function will return an error if `serde_json` fails to deserialize the data as a `String`
-/// or if `chrono` fails to parse that `String` in `%Y-%m-%dT%H:%M:%S` format.
+/// This function will return an error if `serde_json` fails to deserialize the data as a string
+/// or if `chrono` fails to parse that string in `%Y-%m-%dT%H:%M:%S` format.
 fn deserialize_datetime_without_tz<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    const FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
-    let value = String::deserialize(deserializer)?;
-    let date = value
-        .rfind('.')
-        .map_or_else(|| value.as_str(), |idx| value.split_at(idx).0);
-    Utc.datetime_from_str(date, FORMAT)
-        .map_err(serde::de::Error::custom)
+    deserializer.deserialize_str(DateTimeWithoutTzVisitor)
+}
+
+/// Visitor for [`deserialize_datetime_without_tz`].
+///
+/// Parses the string slice handed over by the deserializer directly, rather than first
+/// deserializing an owned `String`, as the tides response contains hundreds of datetimes.
+struct DateTimeWithoutTzVisitor;
+
+impl<'de> Visitor<'de> for DateTimeWithoutTzVisitor {
+    type Value = DateTime<Utc>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a datetime string in %Y-%m-%dT%H:%M:%S format")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        const FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+        let date = value
+            .rfind('.')
+            .map_or_else(|| value, |idx| value.split_at(idx).0);
+        NaiveDateTime::parse_from_str(date, FORMAT)
+            .map(|naive| Utc.from_utc_datetime(&naive))
+            .map_err(E::custom)
+    }
 }
 
 fn deserialize_date_without_tz<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
@@ -106,50 +235,77 @@ where
     match datetime {
         Ok(d) => Ok(d.date_naive()),
         Err(e) => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(error = %e, "Failed to parse naive date as naive datetime");
+            #[cfg(not(feature = "tracing"))]
             eprintln!("Failed to parse naive date as naive datetime: {e:?}");
             Err(e)
         }
     }
 }
 
-/// Deserialize the "features" object of the GetStations endpoint result as `Station` structs.
+/// Serialize a datetime as UTC in `%Y-%m-%dT%H:%M:%S` format, without a timezone specifier.
 ///
-/// The Admiralty public stations API contains unnecessary keys and unnecessarily nested data
-/// (it appears to be from a GIS system). This parses the "features" object instead into a
-/// `Vec` of [`Station`] structs which are simpler.
+/// This is the inverse of [`deserialize_datetime_without_tz`], matching the Admiralty API.
+pub(crate) fn serialize_datetime_without_tz<S>(
+    datetime: &DateTime<Utc>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_str(&datetime.format("%Y-%m-%dT%H:%M:%S"))
+}
+
+/// Serialize a date as a datetime at midnight, without a timezone specifier.
 ///
-/// # Errors
+/// This is the inverse of [`deserialize_date_without_tz`], matching the Admiralty API.
+pub(crate) fn serialize_date_without_tz<S>(
+    date: &NaiveDate,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_str(&date.format("%Y-%m-%dT00:00:00"))
+}
+
+/// Serialize stations as the "features" object of the GetStations endpoint result.
 ///
-/// This function will return an error if `serde_json` fails to parse the JSON into the format
-/// expected from the Admiralty API. The conversion from the (internal) `StationFeature` structs
-/// into `Station` structs will not fail.
-fn deserialize_stations<'de, D>(deserializer: D) -> Result<Vec<Station>, D::Error>
+/// This is the inverse of deserializing each [`Station`] from a feature.
+pub(crate) fn serialize_stations<S>(stations: &[Station], serializer: S) -> Result<S::Ok, S::Error>
 where
-    D: Deserializer<'de>,
+    S: Serializer,
 {
-    let features: Vec<StationFeature> = Vec::deserialize(deserializer)?;
-    let stations = features
-        .into_iter()
-        .map(|feature| Station {
-            id: feature.properties.id,
-            name: feature.properties.name,
-            country: feature.properties.country,
-            location: feature.geometry.coordinates,
-            continuous_heights_available: feature.properties.continuous_heights_available,
+    serializer.collect_seq(stations.iter().map(|s| {
+        serde_json::json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Point",
+                "coordinates": [s.location.longitude.0, s.location.latitude.0],
+            },
+            "properties": {
+                "Id": s.id,
+                "Name": s.name,
+                "Country": s.country,
+                "ContinuousHeightsAvailable": s.continuous_heights_available,
+            },
         })
-        .collect();
-    Ok(stations)
+    }))
 }
 
 /// A wrapper for all of the tide prediction data from the Admiralty API.
-#[derive(Debug, Deserialize)]
+///
+/// Serializing produces JSON in the same format as the Admiralty API, which can be parsed
+/// again with [`tides_from_reader`]. Datetimes are serialized to whole-second precision.
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TidePredictions {
     /// A note appended to the whole response.
     ///
     /// This is usually a warning that the "high water duration period can occur over an extended
-    /// time period."
-    pub footer_note: String,
+    /// time period." See [`Notes`] for picking out the messages it contains.
+    pub footer_note: Notes,
     /// Moon phase data.
     pub lunar_phase_list: Vec<LunarPhase>,
     /// Low- and high-tide event data.
@@ -158,19 +314,121 @@ pub struct TidePredictions {
     /// occur.
     pub tidal_event_list: Vec<TidalEvent>,
     /// Half-hourly tide height predictions.
-    pub tidal_height_occurrence_list: Vec<TidalHeightOccurence>,
+    pub tidal_height_occurrence_list: Vec<TidalHeightOccurrence>,
+}
+
+impl TidePredictions {
+    /// The predicted tide height at the given time, linearly interpolated between the
+    /// half-hourly height predictions.
+    ///
+    /// Returns `None` if the time is outside the period covered by the height predictions,
+    /// including when the predictions were parsed without their continuous heights.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    ///
+    /// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+    ///     .expect("Failed to read file as tides data.");
+    /// let first = &tides.tidal_height_occurrence_list[0];
+    /// let height = tides.height_at(first.date_time).unwrap();
+    /// assert_eq!(height.0, first.height.0);
+    /// assert!(tides.height_at(first.date_time - chrono::Duration::minutes(1)).is_none());
+    /// ```
+    pub fn height_at(&self, at: DateTime<Utc>) -> Option<Metres> {
+        let heights = &self.tidal_height_occurrence_list;
+        let after = heights.partition_point(|h| h.date_time < at);
+        let next = heights.get(after)?;
+        if next.date_time == at {
+            return Some(next.height);
+        }
+        let prev = heights.get(after.checked_sub(1)?)?;
+        let span = (next.date_time - prev.date_time).num_seconds() as f64;
+        let elapsed = (at - prev.date_time).num_seconds() as f64;
+        Some(Metres(
+            prev.height.0 + (next.height.0 - prev.height.0) * elapsed / span,
+        ))
+    }
+
+    /// The first high or low tide, as given by `event_type`, after the given time.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    ///
+    /// use rjw_uktides::TidalEventType;
+    ///
+    /// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+    ///     .expect("Failed to read file as tides data.");
+    /// let first = &tides.tidal_event_list[0];
+    /// let next = tides
+    ///     .next_event(first.date_time, first.event_type)
+    ///     .expect("Failed to find the next tide of the same type.");
+    /// assert!(next.date_time > first.date_time);
+    /// ```
+    pub fn next_event(
+        &self,
+        after: DateTime<Utc>,
+        event_type: TidalEventType,
+    ) -> Option<&TidalEvent> {
+        self.tidal_event_list
+            .iter()
+            .filter(|e| e.event_type == event_type && e.date_time > after)
+            .min_by_key(|e| e.date_time)
+    }
+
+    /// The high or low tides immediately before and after the given time, or `None` if
+    /// the time is not between two tides in the predictions.
+    ///
+    /// The tide is rising (flooding) if the earlier tide is low water, and falling
+    /// (ebbing) if it is high water.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    ///
+    /// use rjw_uktides::TidalEventType;
+    ///
+    /// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+    ///     .expect("Failed to read file as tides data.");
+    /// let first = &tides.tidal_event_list[0];
+    /// let at = first.date_time + chrono::Duration::hours(1);
+    /// let (before, after) = tides.bracketing_events(at).unwrap();
+    /// assert_eq!(before.date_time, first.date_time);
+    /// assert_ne!(before.event_type, after.event_type);
+    /// assert!(tides.bracketing_events(first.date_time - chrono::Duration::hours(1)).is_none());
+    /// ```
+    pub fn bracketing_events(&self, at: DateTime<Utc>) -> Option<(&TidalEvent, &TidalEvent)> {
+        let before = self
+            .tidal_event_list
+            .iter()
+            .filter(|e| e.date_time <= at)
+            .max_by_key(|e| e.date_time)?;
+        let after = self
+            .tidal_event_list
+            .iter()
+            .filter(|e| e.date_time > at)
+            .min_by_key(|e| e.date_time)?;
+        Some((before, after))
+    }
 }
 
 /// An instance of low or high tide.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TidalEvent {
     /// The day on which this tide occurs.
-    #[serde(deserialize_with = "deserialize_date_without_tz")]
+    #[serde(
+        deserialize_with = "deserialize_date_without_tz",
+        serialize_with = "serialize_date_without_tz"
+    )]
     pub date: NaiveDate,
 
     /// The predicted datetime at which the tide measurement will occur.
-    #[serde(deserialize_with = "deserialize_datetime_without_tz")]
+    #[serde(
+        deserialize_with = "deserialize_datetime_without_tz",
+        serialize_with = "serialize_datetime_without_tz"
+    )]
     pub date_time: DateTime<Utc>,
 
     /// Discriminator between high and low tide.
@@ -207,18 +465,29 @@ impl PartialOrd for TidalEvent {
 }
 
 /// Tide height in metres as an `f64`, wrapped in a newtype to make the measurement unit clear.
-#[derive(Debug, Clone, Copy, Deserialize)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
 pub struct Metres(pub f64);
 
 /// Represents either low or high tide.
 ///
 /// The Admiralty API response encodes low tide as 1 and high tide as 0.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum TidalEventType {
     HighWater,
     LowWater,
 }
 
+impl TidalEventType {
+    /// A readable name for the event type, `"high"` or `"low"`, as serialized by
+    /// [`named::event_type`](crate::named::event_type).
+    pub fn name(self) -> &'static str {
+        match self {
+            TidalEventType::HighWater => "high",
+            TidalEventType::LowWater => "low",
+        }
+    }
+}
+
 impl Display for TidalEventType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let text = match self {
@@ -229,6 +498,19 @@ impl Display for TidalEventType {
     }
 }
 
+impl Serialize for TidalEventType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let num = match self {
+            Self::HighWater => 0,
+            Self::LowWater => 1,
+        };
+        serializer.serialize_u64(num)
+    }
+}
+
 impl<'de> Deserialize<'de> for TidalEventType {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -248,21 +530,30 @@ impl<'de> Deserialize<'de> for TidalEventType {
 }
 
 /// Prediction of the tide height in metres at a particular time.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct TidalHeightOccurence {
+pub struct TidalHeightOccurrence {
     /// Time of prediction, typically every half-hour.
     pub date_time: DateTime<Utc>,
     /// Predicted tide height as a newtype-wrapped `f64`.
     pub height: Metres,
 }
 
+/// Former, misspelled name of [`TidalHeightOccurrence`].
+#[deprecated(
+    note = "renamed to `TidalHeightOccurrence`; this alias will be removed in the next release"
+)]
+pub type TidalHeightOccurence = TidalHeightOccurrence;
+
 /// Prediction of a particular lunar phase.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct LunarPhase {
     /// Datetime of the lunar phase occurrence.
-    #[serde(deserialize_with = "deserialize_datetime_without_tz")]
+    #[serde(
+        deserialize_with = "deserialize_datetime_without_tz",
+        serialize_with = "serialize_datetime_without_tz"
+    )]
     pub date_time: DateTime<Utc>,
 
     /// The lunar phase itself.
@@ -282,7 +573,7 @@ pub struct LunarPhase {
 /// 2. First quarter moon.
 /// 3. Full moon.
 /// 4. Last quarter moon.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum LunarPhaseType {
     NewMoon,
     FirstQuarter,
@@ -290,6 +581,47 @@ pub enum LunarPhaseType {
     LastQuarter,
 }
 
+impl LunarPhaseType {
+    /// A readable name for the phase, `"new_moon"`, `"first_quarter"`, `"full_moon"` or
+    /// `"last_quarter"`, as serialized by
+    /// [`named::lunar_phase_type`](crate::named::lunar_phase_type).
+    pub fn name(self) -> &'static str {
+        match self {
+            LunarPhaseType::NewMoon => "new_moon",
+            LunarPhaseType::FirstQuarter => "first_quarter",
+            LunarPhaseType::FullMoon => "full_moon",
+            LunarPhaseType::LastQuarter => "last_quarter",
+        }
+    }
+}
+
+impl Display for LunarPhaseType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            LunarPhaseType::NewMoon => "New moon",
+            LunarPhaseType::FirstQuarter => "First quarter",
+            LunarPhaseType::FullMoon => "Full moon",
+            LunarPhaseType::LastQuarter => "Last quarter",
+        };
+        write!(f, "{text}")
+    }
+}
+
+impl Serialize for LunarPhaseType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let num = match self {
+            Self::NewMoon => 1,
+            Self::FirstQuarter => 2,
+            Self::FullMoon => 3,
+            Self::LastQuarter => 4,
+        };
+        serializer.serialize_u64(num)
+    }
+}
+
 impl<'de> Deserialize<'de> for LunarPhaseType {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -324,11 +656,10 @@ struct StationsData {
     #[serde(skip, rename = "type")]
     _type: String,
 
-    #[serde(deserialize_with = "deserialize_stations")]
     features: Vec<Station>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct StationId(pub String);
 
 impl From<String> for StationId {
@@ -380,6 +711,44 @@ pub struct Station {
     pub continuous_heights_available: bool,
 }
 
+/// Deserializes a single GeoJSON feature from the GetStations endpoint result, such as
+/// one stored separately from the rest of the list, without the feature's unnecessary
+/// keys and nesting.
+///
+/// # Examples
+/// ```
+/// use rjw_uktides::Station;
+///
+/// let feature = r#"{
+///     "type": "Feature",
+///     "geometry": { "type": "Point", "coordinates": [-1.15, 50.65] },
+///     "properties": {
+///         "Id": "0053",
+///         "Name": "Sandown",
+///         "Country": "England",
+///         "ContinuousHeightsAvailable": true
+///     }
+/// }"#;
+/// let station: Station = serde_json::from_str(feature).unwrap();
+/// assert_eq!(station.name, "Sandown");
+/// assert_eq!(station.location.latitude.0, 50.65);
+/// ```
+impl<'de> Deserialize<'de> for Station {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let feature = StationFeature::deserialize(deserializer)?;
+        Ok(Station {
+            id: feature.properties.id,
+            name: feature.properties.name,
+            country: feature.properties.country,
+            location: feature.geometry.coordinates,
+            continuous_heights_available: feature.properties.continuous_heights_available,
+        })
+    }
+}
+
 impl PartialEq for Station {
     fn eq(&self, other: &Self) -> bool {
         self.id == other.id
@@ -399,13 +768,50 @@ impl PartialOrd for Station {
         Some(self.cmp(other))
     }
 }
-#[derive(Debug, Deserialize, Copy, Clone)]
-pub struct DecimalDegrees(f64);
+/// Details of a specific tidal measurement station, borrowing its strings from the
+/// buffer it was parsed from.
+///
+/// Returned by [`stations_from_slice`]; see [`Station`] for details of the fields.
+#[derive(Debug, Clone)]
+pub struct StationRef<'a> {
+    /// ID used to identify the station when requesting tidal predictions.
+    pub id: Cow<'a, str>,
+    /// The name of the location of the station.
+    pub name: Cow<'a, str>,
+    /// The "country" in which the station is placed.
+    pub country: Cow<'a, str>,
+    /// Geographic coordinates (latitude and longitude) of the station.
+    pub location: Coordinates,
+    /// Whether the station can provide continuous height measurements.
+    pub continuous_heights_available: bool,
+}
+
+impl StationRef<'_> {
+    /// Convert into an owned [`Station`], allocating any borrowed strings.
+    pub fn into_owned(self) -> Station {
+        Station {
+            id: StationId(self.id.into_owned()),
+            name: self.name.into_owned(),
+            country: self.country.into_owned(),
+            location: self.location,
+            continuous_heights_available: self.continuous_heights_available,
+        }
+    }
+}
+
+impl From<StationRef<'_>> for Station {
+    fn from(value: StationRef<'_>) -> Self {
+        value.into_owned()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Copy, Clone)]
+pub struct DecimalDegrees(pub f64);
 
 /// Geographic coordinates (latitude and longitude) of the station.
 ///
 /// It is not clear which coordinate system these are from; perhaps WGS 84.
-#[derive(Debug, Deserialize, Copy, Clone)]
+#[derive(Debug, Deserialize, Serialize, Copy, Clone)]
 pub struct Coordinates {
     // Order is important here as this struct is represented by an array in the JSON.
     /// Longitude, in decimal degrees.
@@ -444,3 +850,31 @@ struct StationFeatureProperties {
     country: String,
     continuous_heights_available: bool,
 }
+
+/// Borrowing counterpart of [`StationsData`], used by [`stations_from_slice`].
+#[derive(Debug, Deserialize)]
+struct StationsDataRef<'a> {
+    #[serde(borrow)]
+    features: Vec<StationFeatureRef<'a>>,
+}
+
+/// Borrowing counterpart of [`StationFeature`].
+#[derive(Debug, Deserialize)]
+struct StationFeatureRef<'a> {
+    geometry: StationFeatureGeometry,
+    #[serde(borrow)]
+    properties: StationFeaturePropertiesRef<'a>,
+}
+
+/// Borrowing counterpart of [`StationFeatureProperties`].
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct StationFeaturePropertiesRef<'a> {
+    #[serde(borrow)]
+    id: Cow<'a, str>,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    #[serde(borrow)]
+    country: Cow<'a, str>,
+    continuous_heights_available: bool,
+}
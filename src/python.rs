@@ -0,0 +1,342 @@
+//! Python bindings to the parsing functions and URL builders.
+//!
+//! Enabled by the `python` feature. Build and install into the current virtual environment
+//! with `maturin develop` from the repository root, which builds the `uktides` extension
+//! module as configured in `pyproject.toml`, passing `--crate-type cdylib` itself as the
+//! crate is built as a Rust library only. Parsed data is returned as frozen,
+//! dataclass-like objects, with datetimes as timezone-aware `datetime.datetime` values in
+//! UTC.
+//!
+//! ```python
+//! import uktides
+//!
+//! with open("stations.json") as f:
+//!     stations = uktides.stations_from_json(f.read())
+//! print(uktides.tides_url(stations[0].id))
+//! ```
+
+use chrono::{DateTime, Utc};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::{LunarPhaseType, StationId, TidalEventType, DEFAULT_BASE_URL};
+
+/// A tidal station.
+#[pyclass(module = "uktides", name = "Station", frozen, get_all)]
+#[derive(Debug, Clone, PartialEq)]
+struct PyStation {
+    id: String,
+    name: String,
+    country: String,
+    latitude: f64,
+    longitude: f64,
+    continuous_heights_available: bool,
+}
+
+#[pymethods]
+impl PyStation {
+    fn __repr__(&self) -> String {
+        format!(
+            "Station(id={:?}, name={:?}, country={:?}, latitude={}, longitude={}, continuous_heights_available={})",
+            self.id,
+            self.name,
+            self.country,
+            self.latitude,
+            self.longitude,
+            py_bool(self.continuous_heights_available),
+        )
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+/// A high or low tide, with `event_type` either `"high"` or `"low"`.
+#[pyclass(module = "uktides", name = "TidalEvent", frozen, get_all)]
+#[derive(Debug, Clone, PartialEq)]
+struct PyTidalEvent {
+    date_time: DateTime<Utc>,
+    event_type: &'static str,
+    height: f64,
+}
+
+#[pymethods]
+impl PyTidalEvent {
+    fn __repr__(&self) -> String {
+        format!(
+            "TidalEvent(date_time={:?}, event_type={:?}, height={})",
+            self.date_time.to_rfc3339(),
+            self.event_type,
+            self.height,
+        )
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+/// A predicted tide height in metres.
+#[pyclass(module = "uktides", name = "TidalHeight", frozen, get_all)]
+#[derive(Debug, Clone, PartialEq)]
+struct PyTidalHeight {
+    date_time: DateTime<Utc>,
+    height: f64,
+}
+
+#[pymethods]
+impl PyTidalHeight {
+    fn __repr__(&self) -> String {
+        format!(
+            "TidalHeight(date_time={:?}, height={})",
+            self.date_time.to_rfc3339(),
+            self.height,
+        )
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+/// A phase of the moon, with `phase` one of `"new_moon"`, `"first_quarter"`,
+/// `"full_moon"` or `"last_quarter"`.
+#[pyclass(module = "uktides", name = "LunarPhase", frozen, get_all)]
+#[derive(Debug, Clone, PartialEq)]
+struct PyLunarPhase {
+    date_time: DateTime<Utc>,
+    phase: &'static str,
+}
+
+#[pymethods]
+impl PyLunarPhase {
+    fn __repr__(&self) -> String {
+        format!(
+            "LunarPhase(date_time={:?}, phase={:?})",
+            self.date_time.to_rfc3339(),
+            self.phase,
+        )
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+/// Tide predictions for a station.
+#[pyclass(module = "uktides", name = "TidePredictions", frozen, get_all)]
+#[derive(Debug, Clone, PartialEq)]
+struct PyTidePredictions {
+    footer_note: String,
+    tidal_events: Vec<PyTidalEvent>,
+    heights: Vec<PyTidalHeight>,
+    lunar_phases: Vec<PyLunarPhase>,
+}
+
+#[pymethods]
+impl PyTidePredictions {
+    fn __repr__(&self) -> String {
+        format!(
+            "TidePredictions(footer_note={:?}, tidal_events=[{} events], heights=[{} heights], lunar_phases=[{} phases])",
+            self.footer_note,
+            self.tidal_events.len(),
+            self.heights.len(),
+            self.lunar_phases.len(),
+        )
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+/// Parse the response from the stations endpoint into a list of stations.
+#[pyfunction]
+fn stations_from_json(json: &str) -> PyResult<Vec<PyStation>> {
+    let stations = crate::stations_from_slice(json.as_bytes()).map_err(value_error)?;
+    Ok(stations
+        .into_iter()
+        .map(|s| PyStation {
+            id: s.id.into_owned(),
+            name: s.name.into_owned(),
+            country: s.country.into_owned(),
+            latitude: s.location.latitude.0,
+            longitude: s.location.longitude.0,
+            continuous_heights_available: s.continuous_heights_available,
+        })
+        .collect())
+}
+
+/// Parse the response from the tide predictions endpoint.
+#[pyfunction]
+fn tides_from_json(json: &str) -> PyResult<PyTidePredictions> {
+    let tides = crate::tides_from_slice(json.as_bytes()).map_err(value_error)?;
+    Ok(PyTidePredictions {
+        footer_note: tides.footer_note.into_string(),
+        tidal_events: tides
+            .tidal_event_list
+            .into_iter()
+            .map(|e| PyTidalEvent {
+                date_time: e.date_time,
+                event_type: match e.event_type {
+                    TidalEventType::HighWater => "high",
+                    TidalEventType::LowWater => "low",
+                },
+                height: e.height.0,
+            })
+            .collect(),
+        heights: tides
+            .tidal_height_occurrence_list
+            .into_iter()
+            .map(|h| PyTidalHeight {
+                date_time: h.date_time,
+                height: h.height.0,
+            })
+            .collect(),
+        lunar_phases: tides
+            .lunar_phase_list
+            .into_iter()
+            .map(|p| PyLunarPhase {
+                date_time: p.date_time,
+                phase: match p.lunar_phase_type {
+                    LunarPhaseType::NewMoon => "new_moon",
+                    LunarPhaseType::FirstQuarter => "first_quarter",
+                    LunarPhaseType::FullMoon => "full_moon",
+                    LunarPhaseType::LastQuarter => "last_quarter",
+                },
+            })
+            .collect(),
+    })
+}
+
+/// The URL from which the list of all stations is fetched, from the public EasyTide web
+/// service or the service at `base_url`.
+#[pyfunction]
+#[pyo3(signature = (base_url=None))]
+fn stations_url(base_url: Option<&str>) -> String {
+    crate::stations_url(base_url.unwrap_or(DEFAULT_BASE_URL))
+}
+
+/// The URL from which tide predictions for the station are fetched, from the public
+/// EasyTide web service or the service at `base_url`.
+#[pyfunction]
+#[pyo3(signature = (station_id, base_url=None))]
+fn tides_url(station_id: &str, base_url: Option<&str>) -> String {
+    crate::tides_url(
+        base_url.unwrap_or(DEFAULT_BASE_URL),
+        &StationId(station_id.to_owned()),
+    )
+}
+
+fn value_error(e: crate::Error) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+fn py_bool(b: bool) -> &'static str {
+    if b {
+        "True"
+    } else {
+        "False"
+    }
+}
+
+#[pymodule]
+fn uktides(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("DEFAULT_BASE_URL", DEFAULT_BASE_URL)?;
+    m.add_class::<PyStation>()?;
+    m.add_class::<PyTidalEvent>()?;
+    m.add_class::<PyTidalHeight>()?;
+    m.add_class::<PyLunarPhase>()?;
+    m.add_class::<PyTidePredictions>()?;
+    m.add_function(wrap_pyfunction!(stations_from_json, m)?)?;
+    m.add_function(wrap_pyfunction!(tides_from_json, m)?)?;
+    m.add_function(wrap_pyfunction!(stations_url, m)?)?;
+    m.add_function(wrap_pyfunction!(tides_url, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use pyo3::types::PyDict;
+
+    use super::*;
+    use crate::testing::{STATIONS_BYTES, TIDES_BYTES};
+
+    /// Run the Python `code` with the module imported as `uktides`, and the reference
+    /// responses as strings in `stations_json` and `tides_json`.
+    fn run(code: &str) {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let globals = PyDict::new(py);
+            let module = pyo3::wrap_pymodule!(uktides)(py);
+            globals.set_item("uktides", module).unwrap();
+            let stations = std::str::from_utf8(STATIONS_BYTES).unwrap();
+            globals.set_item("stations_json", stations).unwrap();
+            let tides = std::str::from_utf8(TIDES_BYTES).unwrap();
+            globals.set_item("tides_json", tides).unwrap();
+            let code = CString::new(code).unwrap();
+            if let Err(e) = py.run(&code, Some(&globals), None) {
+                panic!("{e}");
+            }
+        });
+    }
+
+    #[test]
+    fn parses_stations() {
+        run(r#"
+stations = uktides.stations_from_json(stations_json)
+station = stations[0]
+assert station.id == "1603"
+assert station.name == "BRAYE"
+assert station.country == "Channel Islands"
+assert (station.latitude, station.longitude) == (49.716666, -2.2)
+assert station.continuous_heights_available is True
+assert repr(station).startswith('Station(id="1603", name="BRAYE"'), repr(station)
+assert station == uktides.stations_from_json(stations_json)[0]
+assert station != stations[1]
+"#);
+    }
+
+    #[test]
+    fn parses_tide_predictions() {
+        run(r#"
+import datetime
+
+tides = uktides.tides_from_json(tides_json)
+assert tides.tidal_events and tides.heights and tides.lunar_phases
+assert {e.event_type for e in tides.tidal_events} == {"high", "low"}
+assert all(isinstance(h.height, float) for h in tides.heights)
+assert all(e.date_time.utcoffset() == datetime.timedelta(0) for e in tides.tidal_events)
+assert {p.phase for p in tides.lunar_phases} <= {
+    "new_moon", "first_quarter", "full_moon", "last_quarter"
+}
+assert tides == uktides.tides_from_json(tides_json)
+"#);
+    }
+
+    #[test]
+    fn raises_value_error_for_invalid_json() {
+        run(r#"
+for parse in (uktides.stations_from_json, uktides.tides_from_json):
+    try:
+        parse("{}")
+    except ValueError:
+        pass
+    else:
+        raise AssertionError(f"{parse.__name__} accepted invalid JSON")
+"#);
+    }
+
+    #[test]
+    fn builds_urls() {
+        run(r#"
+assert uktides.stations_url() == uktides.DEFAULT_BASE_URL + "/Home/GetStations"
+assert uktides.stations_url("http://localhost") == "http://localhost/Home/GetStations"
+assert uktides.tides_url("0053", base_url="http://localhost") \
+    == "http://localhost/Home/GetPredictionData?stationId=0053"
+"#);
+    }
+}
@@ -0,0 +1,175 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A limit on the rate at which [`Client`](crate::Client) and `AsyncClient` send requests.
+///
+/// Requests are limited using a token bucket: up to `burst` requests can be sent at once,
+/// after which requests are delayed so that no more than `requests_per_minute` are sent
+/// each minute on average. Each attempt of a retried request counts towards the limit;
+/// responses served from a [`Cache`](crate::Cache) do not.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "blocking")]
+/// # {
+/// use rjw_uktides::{Client, RateLimit};
+///
+/// // At most one request a second, after an initial burst of five.
+/// let client = Client::new().with_rate_limit(RateLimit::new(60, 5));
+/// assert_eq!(client.rate_limit(), Some(RateLimit::new(60, 5)));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    requests_per_minute: u32,
+    burst: u32,
+}
+
+impl RateLimit {
+    /// Create a limit of `requests_per_minute` on average, with bursts of up to `burst`
+    /// requests.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if either `requests_per_minute` or `burst` is zero.
+    pub fn new(requests_per_minute: u32, burst: u32) -> Self {
+        assert!(
+            requests_per_minute > 0,
+            "Rate limit must be greater than zero."
+        );
+        assert!(
+            burst > 0,
+            "Rate limit burst size must be greater than zero."
+        );
+        Self {
+            requests_per_minute,
+            burst,
+        }
+    }
+
+    /// The average number of requests allowed each minute.
+    pub fn requests_per_minute(&self) -> u32 {
+        self.requests_per_minute
+    }
+
+    /// The number of requests that can be sent at once.
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+}
+
+/// Token bucket enforcing a [`RateLimit`], shared between clones of a client.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Available tokens, negative when requests are waiting for tokens to be refilled.
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            bucket: Mutex::new(Bucket {
+                tokens: f64::from(limit.burst),
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    pub(crate) fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// Block until a request can be sent.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn acquire(&self) {
+        if let Some(wait) = self.reserve(Instant::now()) {
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Wait until a request can be sent, without blocking the thread.
+    #[cfg(feature = "async")]
+    pub(crate) async fn acquire_async(&self) {
+        if let Some(wait) = self.reserve(Instant::now()) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take a token at `now`, returning how long to wait before sending the request if
+    /// there was none available.
+    fn reserve(&self, now: Instant) -> Option<Duration> {
+        let per_second = f64::from(self.limit.requests_per_minute) / 60.0;
+        let mut bucket = self.bucket.lock().expect("Rate limiter lock poisoned.");
+        let refill = now
+            .saturating_duration_since(bucket.refilled_at)
+            .as_secs_f64()
+            * per_second;
+        bucket.tokens = (bucket.tokens + refill).min(f64::from(self.limit.burst));
+        bucket.refilled_at = bucket.refilled_at.max(now);
+        // The token is taken now, so that concurrent callers queue behind this one
+        // rather than all waking at the same time.
+        bucket.tokens -= 1.0;
+        let wait =
+            (bucket.tokens < 0.0).then(|| Duration::from_secs_f64(-bucket.tokens / per_second));
+        #[cfg(feature = "tracing")]
+        if let Some(wait) = wait {
+            tracing::debug!(wait_ms = wait.as_millis() as u64, "waiting for rate limit");
+        }
+        wait
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(wait: Option<Duration>) -> u128 {
+        wait.map_or(0, |w| w.as_millis())
+    }
+
+    #[test]
+    fn allows_a_burst_then_spaces_requests_out() {
+        // One request a second, after a burst of two.
+        let limiter = RateLimiter::new(RateLimit::new(60, 2));
+        let start = Instant::now();
+        let waits: Vec<_> = (0..5).map(|_| millis(limiter.reserve(start))).collect();
+        assert_eq!(waits, [0, 0, 1000, 2000, 3000]);
+    }
+
+    #[test]
+    fn refills_tokens_over_time() {
+        let limiter = RateLimiter::new(RateLimit::new(60, 2));
+        let start = Instant::now();
+        assert_eq!(millis(limiter.reserve(start)), 0);
+        assert_eq!(millis(limiter.reserve(start)), 0);
+        // Half a second later, half a token has been refilled.
+        let later = start + Duration::from_millis(500);
+        assert_eq!(millis(limiter.reserve(later)), 500);
+        // Long after, the bucket is full again but holds no more than the burst.
+        let much_later = start + Duration::from_secs(60);
+        let waits: Vec<_> = (0..3)
+            .map(|_| millis(limiter.reserve(much_later)))
+            .collect();
+        assert_eq!(waits, [0, 0, 1000]);
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn delays_blocking_requests() {
+        let limiter = RateLimiter::new(RateLimit::new(1200, 1));
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire();
+        }
+        // One request immediately, then one every 50 ms.
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use crate::{Coordinates, DecimalDegrees, Station};
+
+/// A stretch of coast, for presenting stations in groups rather than as one long list.
+///
+/// Regions are in order around the coast of Great Britain, starting from the Isles of
+/// Scilly and going east, followed by the Isle of Man, Northern Ireland and the Channel
+/// Islands.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Region {
+    IslesOfScilly,
+    Cornwall,
+    SouthDevon,
+    Dorset,
+    Solent,
+    Sussex,
+    DoverStrait,
+    ThamesEstuary,
+    EastAnglia,
+    TheWash,
+    Humber,
+    Yorkshire,
+    Northumbria,
+    FirthOfForth,
+    FirthOfTay,
+    Aberdeenshire,
+    MorayFirth,
+    Caithness,
+    Orkney,
+    Shetland,
+    NorthWestHighlands,
+    OuterHebrides,
+    WestHighlands,
+    FirthOfClyde,
+    SolwayFirth,
+    MorecambeBay,
+    LiverpoolBay,
+    Anglesey,
+    CardiganBay,
+    Pembrokeshire,
+    CarmarthenBay,
+    BristolChannel,
+    IsleOfMan,
+    NorthernIreland,
+    ChannelIslands,
+}
+
+impl Region {
+    /// Every region, in order around the coast.
+    pub const ALL: [Region; 35] = [
+        Region::IslesOfScilly,
+        Region::Cornwall,
+        Region::SouthDevon,
+        Region::Dorset,
+        Region::Solent,
+        Region::Sussex,
+        Region::DoverStrait,
+        Region::ThamesEstuary,
+        Region::EastAnglia,
+        Region::TheWash,
+        Region::Humber,
+        Region::Yorkshire,
+        Region::Northumbria,
+        Region::FirthOfForth,
+        Region::FirthOfTay,
+        Region::Aberdeenshire,
+        Region::MorayFirth,
+        Region::Caithness,
+        Region::Orkney,
+        Region::Shetland,
+        Region::NorthWestHighlands,
+        Region::OuterHebrides,
+        Region::WestHighlands,
+        Region::FirthOfClyde,
+        Region::SolwayFirth,
+        Region::MorecambeBay,
+        Region::LiverpoolBay,
+        Region::Anglesey,
+        Region::CardiganBay,
+        Region::Pembrokeshire,
+        Region::CarmarthenBay,
+        Region::BristolChannel,
+        Region::IsleOfMan,
+        Region::NorthernIreland,
+        Region::ChannelIslands,
+    ];
+
+    /// The region of the station: the one with a point on its coast nearest the station,
+    /// among those in the station's country.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    ///
+    /// use rjw_uktides::Region;
+    ///
+    /// let stations = rjw_uktides::stations_from_reader(File::open("./stations.json").unwrap())
+    ///     .expect("Failed to read file as stations data.");
+    /// let sandown = rjw_uktides::find_station_by_name(&stations, "sandown").unwrap();
+    /// assert_eq!(Region::of(sandown), Region::Solent);
+    /// let leith = rjw_uktides::find_station_by_name(&stations, "leith").unwrap();
+    /// assert_eq!(Region::of(leith), Region::FirthOfForth);
+    /// ```
+    pub fn of(station: &Station) -> Region {
+        let in_country = |r: &Region| r.countries().contains(&station.country.as_str());
+        let any_in_country = Region::ALL.iter().any(in_country);
+        let distance = |r: &Region| {
+            r.points()
+                .map(|point| station.location.distance_km(&point))
+                .fold(f64::INFINITY, f64::min)
+        };
+        Region::ALL
+            .into_iter()
+            .filter(|r| !any_in_country || in_country(r))
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .expect("there is at least one region")
+    }
+
+    /// The name of the region, such as "Bristol Channel".
+    pub fn name(self) -> &'static str {
+        self.details().0
+    }
+
+    /// The countries, as in [`Station::country`], with coast in the region.
+    pub fn countries(self) -> &'static [&'static str] {
+        self.details().1
+    }
+
+    /// Points along the region's coast, by which stations are assigned to it.
+    fn points(self) -> impl Iterator<Item = Coordinates> {
+        self.details()
+            .2
+            .iter()
+            .map(|&(latitude, longitude)| Coordinates {
+                latitude: DecimalDegrees(latitude),
+                longitude: DecimalDegrees(longitude),
+            })
+    }
+
+    fn details(self) -> (&'static str, &'static [&'static str], &'static [(f64, f64)]) {
+        const ENGLAND: &[&str] = &["England"];
+        const SCOTLAND: &[&str] = &["Scotland"];
+        const WALES: &[&str] = &["Wales"];
+        match self {
+            Region::IslesOfScilly => ("Isles of Scilly", ENGLAND, &[(49.93, -6.3)]),
+            Region::Cornwall => (
+                "Cornwall",
+                ENGLAND,
+                &[(50.1, -5.3), (50.33, -4.6), (50.5, -4.9)],
+            ),
+            Region::SouthDevon => ("South Devon", ENGLAND, &[(50.35, -4.1), (50.6, -3.4)]),
+            Region::Dorset => ("Dorset", ENGLAND, &[(50.62, -2.2)]),
+            Region::Solent => ("Solent", ENGLAND, &[(50.76, -1.3)]),
+            Region::Sussex => ("Sussex", ENGLAND, &[(50.8, -0.2), (50.85, 0.5)]),
+            Region::DoverStrait => ("Dover Strait", ENGLAND, &[(51.1, 1.3)]),
+            Region::ThamesEstuary => ("Thames Estuary", ENGLAND, &[(51.5, 0.6)]),
+            Region::EastAnglia => ("East Anglia", ENGLAND, &[(52.1, 1.5)]),
+            Region::TheWash => ("The Wash", ENGLAND, &[(52.9, 0.5)]),
+            Region::Humber => ("Humber", ENGLAND, &[(53.6, -0.3)]),
+            Region::Yorkshire => ("Yorkshire", ENGLAND, &[(54.3, -0.4)]),
+            Region::Northumbria => ("Northumbria", ENGLAND, &[(55.1, -1.5)]),
+            Region::FirthOfForth => ("Firth of Forth", SCOTLAND, &[(56.0, -2.9)]),
+            Region::FirthOfTay => ("Firth of Tay", SCOTLAND, &[(56.45, -2.7)]),
+            Region::Aberdeenshire => ("Aberdeenshire", SCOTLAND, &[(57.2, -2.0)]),
+            Region::MorayFirth => ("Moray Firth", SCOTLAND, &[(57.7, -3.8)]),
+            Region::Caithness => ("Caithness", SCOTLAND, &[(58.5, -3.3)]),
+            Region::Orkney => ("Orkney", SCOTLAND, &[(59.05, -2.9)]),
+            Region::Shetland => ("Shetland", SCOTLAND, &[(60.3, -1.2)]),
+            Region::NorthWestHighlands => ("North West Highlands", SCOTLAND, &[(58.3, -5.2)]),
+            Region::OuterHebrides => ("Outer Hebrides", SCOTLAND, &[(58.2, -6.6), (57.3, -7.3)]),
+            Region::WestHighlands => ("West Highlands", SCOTLAND, &[(57.3, -6.0), (56.6, -5.8)]),
+            Region::FirthOfClyde => ("Firth of Clyde", SCOTLAND, &[(55.7, -5.0)]),
+            Region::SolwayFirth => ("Solway Firth", &["England", "Scotland"], &[(54.9, -3.6)]),
+            Region::MorecambeBay => ("Morecambe Bay", ENGLAND, &[(54.1, -3.0)]),
+            Region::LiverpoolBay => ("Liverpool Bay", &["England", "Wales"], &[(53.4, -3.2)]),
+            Region::Anglesey => ("Anglesey", WALES, &[(53.3, -4.4)]),
+            Region::CardiganBay => ("Cardigan Bay", WALES, &[(52.85, -4.3), (52.3, -4.2)]),
+            Region::Pembrokeshire => ("Pembrokeshire", WALES, &[(51.75, -5.0)]),
+            Region::CarmarthenBay => ("Carmarthen Bay", WALES, &[(51.65, -4.4)]),
+            Region::BristolChannel => (
+                "Bristol Channel",
+                &["England", "Wales"],
+                &[(51.45, -3.3), (51.2, -3.9), (51.6, -2.7)],
+            ),
+            Region::IsleOfMan => ("Isle of Man", &["Isle of Man"], &[(54.2, -4.6)]),
+            Region::NorthernIreland => ("Northern Ireland", &["Northern Ireland"], &[(54.6, -5.8)]),
+            Region::ChannelIslands => ("Channel Islands", &["Channel Islands"], &[(49.4, -2.4)]),
+        }
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The stations grouped by [`Region`], with the regions in order around the coast and
+/// the stations in each in their order in `stations`. Regions without stations are left
+/// out.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// use rjw_uktides::Region;
+///
+/// let stations = rjw_uktides::stations_from_reader(File::open("./stations.json").unwrap())
+///     .expect("Failed to read file as stations data.");
+/// let regions = rjw_uktides::stations_by_region(&stations);
+/// assert!(regions[&Region::Solent].iter().any(|s| s.name == "Sandown"));
+/// assert_eq!(regions.values().map(Vec::len).sum::<usize>(), stations.len());
+/// ```
+pub fn stations_by_region(stations: &[Station]) -> BTreeMap<Region, Vec<&Station>> {
+    let mut regions: BTreeMap<Region, Vec<&Station>> = BTreeMap::new();
+    for station in stations {
+        regions
+            .entry(Region::of(station))
+            .or_default()
+            .push(station);
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use super::*;
+
+    fn stations() -> Vec<Station> {
+        crate::stations_from_reader(File::open("./stations.json").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn stations_near_boundaries_are_in_their_regions() {
+        let stations = stations();
+        for (id, region) in [
+            // The Isles of Scilly and the Cornish coast either side of them.
+            ("0001", Region::IslesOfScilly),
+            ("0002", Region::Cornwall),
+            ("0545", Region::Cornwall),
+            ("0535", Region::BristolChannel),
+            // East Sussex and Kent, either side of Dungeness.
+            ("0085", Region::Sussex),
+            ("0086", Region::Sussex),
+            ("0088", Region::DoverStrait),
+            ("0089", Region::DoverStrait),
+            ("0108", Region::ThamesEstuary),
+            // Either side of the border with Scotland, on both coasts.
+            ("0209", Region::Northumbria),
+            ("0221", Region::FirthOfForth),
+            ("0432", Region::SolwayFirth),
+            ("0415", Region::FirthOfClyde),
+            // North Wales.
+            ("0478", Region::Anglesey),
+            ("0451", Region::LiverpoolBay),
+            ("0485", Region::CardiganBay),
+            // The north of Scotland, including a second St Mary's.
+            ("0267", Region::Caithness),
+            ("0281", Region::Orkney),
+            ("0287", Region::Shetland),
+            // Islands with their own country.
+            ("0468", Region::IsleOfMan),
+            ("0638", Region::NorthernIreland),
+            ("1603", Region::ChannelIslands),
+            ("1604", Region::ChannelIslands),
+            ("1605", Region::ChannelIslands),
+        ] {
+            let station = stations.iter().find(|s| s.id.0 == id).unwrap();
+            assert_eq!(Region::of(station), region, "{id} {}", station.name);
+        }
+    }
+
+    #[test]
+    fn every_station_is_in_a_region_of_its_country() {
+        let stations = stations();
+        for station in &stations {
+            let region = Region::of(station);
+            assert!(
+                region.countries().contains(&station.country.as_str()),
+                "{} {} in {} is in {region}",
+                station.id,
+                station.name,
+                station.country
+            );
+        }
+        let regions = stations_by_region(&stations);
+        assert_eq!(regions.len(), Region::ALL.len(), "regions without stations");
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+
+/// Policy controlling how [`Client`](crate::Client) and `AsyncClient` retry failed requests.
+///
+/// The default policy makes up to three attempts, waiting 500 ms before the second and
+/// doubling the wait for each subsequent attempt, up to 10 seconds. Requests are retried
+/// if the server responds with 429 Too Many Requests, 500 Internal Server Error,
+/// 502 Bad Gateway, 503 Service Unavailable or 504 Gateway Timeout, or if the connection
+/// fails or times out. A `Retry-After` header giving a number of seconds is respected, up
+/// to the maximum backoff.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "blocking")]
+/// # {
+/// use std::time::Duration;
+///
+/// use rjw_uktides::{Client, RetryPolicy};
+///
+/// let mut policy = RetryPolicy::default();
+/// policy.max_attempts = 5;
+/// policy.initial_backoff = Duration::from_secs(1);
+/// let client = Client::new().with_retry(policy);
+/// assert_eq!(client.retry_policy().max_attempts, 5);
+///
+/// let client = Client::new().with_retry(RetryPolicy::none());
+/// assert_eq!(client.retry_policy().max_attempts, 1);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first. A value of 0 or 1 disables
+    /// retrying.
+    pub max_attempts: u32,
+    /// The wait before the first retry.
+    pub initial_backoff: Duration,
+    /// The maximum wait between attempts.
+    pub max_backoff: Duration,
+    /// Randomise each wait to between half and all of the backoff, so that clients failing
+    /// at the same time do not retry at the same time.
+    pub jitter: bool,
+    /// Response status codes for which the request is retried.
+    pub retry_statuses: Vec<u16>,
+    /// Retry requests that fail to connect or time out.
+    pub retry_transport_errors: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            retry_statuses: vec![429, 500, 502, 503, 504],
+            retry_transport_errors: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// The wait before the next attempt if `result`, from attempt number `attempt`, should
+    /// be retried.
+    pub(crate) fn retry_delay(
+        &self,
+        attempt: u32,
+        result: &reqwest::Result<impl Response>,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let retry_after = match result {
+            Ok(response) if self.retry_statuses.contains(&response.status().as_u16()) => response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_secs),
+            Err(e) if self.retry_transport_errors && (e.is_connect() || e.is_timeout()) => None,
+            _ => return None,
+        };
+        Some(
+            retry_after
+                .unwrap_or_else(|| self.backoff(attempt))
+                .min(self.max_backoff),
+        )
+    }
+
+    /// The exponential backoff after attempt number `attempt`, with jitter if enabled.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+        if !self.jitter {
+            return backoff;
+        }
+        // Randomly seeded hasher, to avoid a dependency on a random number crate.
+        let random = RandomState::new().build_hasher().finish();
+        let fraction = 0.5 + (random as f64 / u64::MAX as f64) / 2.0;
+        backoff.mul_f64(fraction)
+    }
+}
+
+/// The parts of blocking and asynchronous responses that decide whether to retry.
+pub(crate) trait Response {
+    fn status(&self) -> StatusCode;
+    fn headers(&self) -> &HeaderMap;
+}
+
+#[cfg(feature = "blocking")]
+impl Response for reqwest::blocking::Response {
+    fn status(&self) -> StatusCode {
+        self.status()
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        self.headers()
+    }
+}
+
+#[cfg(feature = "async")]
+impl Response for reqwest::Response {
+    fn status(&self) -> StatusCode {
+        self.status()
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        self.headers()
+    }
+}
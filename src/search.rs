@@ -0,0 +1,150 @@
+use std::cmp::Reverse;
+
+use crate::Station;
+
+/// Find the stations whose names fuzzily match `query`, best match first.
+///
+/// Matching ignores case and punctuation, and treats "saint" and "st" as the same word,
+/// so "st helier" matches "ST. HELIER". Names that equal or start with the query rank
+/// above those that merely contain it, which in turn rank above names containing the
+/// query's letters in order ("sndwn") or a near-miss spelling ("sandwon").
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// let stations = rjw_uktides::stations_from_reader(File::open("./stations.json").unwrap())
+///     .expect("Failed to read file as stations data.");
+/// let matches = rjw_uktides::fuzzy_search_stations(&stations, "st helier");
+/// assert_eq!(matches[0].name, "ST. HELIER");
+/// let matches = rjw_uktides::fuzzy_search_stations(&stations, "sandwon");
+/// assert_eq!(matches[0].name, "Sandown");
+/// ```
+pub fn fuzzy_search_stations<'a>(stations: &'a [Station], query: &str) -> Vec<&'a Station> {
+    scored_matches(stations, query)
+        .into_iter()
+        .map(|(_, s)| s)
+        .collect()
+}
+
+/// The score by which the best match must beat the next to identify a station, so that a
+/// name starting with the query does not win over names containing it as a whole word.
+const CLEAR_MARGIN: u32 = 200;
+
+/// The station that `name` unambiguously identifies, if any.
+///
+/// The name is matched as in [`fuzzy_search_stations`], and a station is returned only if
+/// its name is exactly the query or it matches clearly better than every other station.
+/// So "ramsgate" finds Ramsgate, but "port" finds nothing because many names start with
+/// it, and nor does "sand", which starts "Sandown" but is also a word in "Shivering Sand".
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// let stations = rjw_uktides::stations_from_reader(File::open("./stations.json").unwrap())
+///     .expect("Failed to read file as stations data.");
+/// let sandown = rjw_uktides::find_station_by_name(&stations, "sandown").unwrap();
+/// assert_eq!(sandown.id.0, "0053");
+/// assert!(rjw_uktides::find_station_by_name(&stations, "port").is_none());
+/// assert!(rjw_uktides::find_station_by_name(&stations, "sand").is_none());
+/// ```
+pub fn find_station_by_name<'a>(stations: &'a [Station], name: &str) -> Option<&'a Station> {
+    match scored_matches(stations, name).as_slice() {
+        [(_, only)] => Some(*only),
+        [(EXACT, station), (next, _), ..] if *next < EXACT => Some(*station),
+        [(best, station), (next, _), ..] if best - next >= CLEAR_MARGIN => Some(*station),
+        _ => None,
+    }
+}
+
+/// The stations matching `query` with their scores, best match first.
+fn scored_matches<'a>(stations: &'a [Station], query: &str) -> Vec<(u32, &'a Station)> {
+    let query = normalise(query);
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut scored: Vec<_> = stations
+        .iter()
+        .filter_map(|s| match_score(&normalise(&s.name), &query).map(|score| (score, s)))
+        .collect();
+    scored.sort_by_key(|(score, s)| (Reverse(*score), s.name.len(), s.name.clone()));
+    scored
+}
+
+/// Lowercase the words of `text`, dropping punctuation and abbreviating "saint".
+fn normalise(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| if word == "saint" { "st" } else { word })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The score of a name that is exactly the query.
+const EXACT: u32 = 1000;
+
+/// How well `name` matches `query`, both normalised, or `None` if it does not match.
+fn match_score(name: &str, query: &str) -> Option<u32> {
+    let name_words: Vec<&str> = name.split(' ').collect();
+    let query_words: Vec<&str> = query.split(' ').collect();
+
+    if name == query {
+        Some(EXACT)
+    } else if name.starts_with(query) {
+        Some(900)
+    } else if name_words.iter().any(|w| w.starts_with(query)) || name.contains(query) {
+        Some(800)
+    } else if query_words
+        .iter()
+        .all(|q| name_words.iter().any(|w| w.starts_with(q)))
+    {
+        Some(700)
+    } else if let Some(gaps) = subsequence_gaps(name, query).filter(|&g| g <= query.len()) {
+        Some(500u32.saturating_sub(gaps as u32 * 10).max(300))
+    } else {
+        let mut total = 0;
+        for q in &query_words {
+            let allowed = (q.chars().count() / 3).max(1);
+            let best = name_words
+                .iter()
+                .map(|w| edit_distance(q, w))
+                .min()
+                .filter(|&d| d <= allowed)?;
+            total += best;
+        }
+        Some(200u32.saturating_sub(total as u32 * 20))
+    }
+}
+
+/// The number of skipped characters if the letters of `query` appear in order in `name`.
+fn subsequence_gaps(name: &str, query: &str) -> Option<usize> {
+    let mut gaps = 0;
+    let mut name_chars = name.chars().filter(|c| *c != ' ');
+    for q in query.chars().filter(|c| *c != ' ') {
+        loop {
+            let c = name_chars.next()?;
+            if c == q {
+                break;
+            }
+            gaps += 1;
+        }
+    }
+    Some(gaps)
+}
+
+/// The Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,116 @@
+use std::io::Read;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::parse::serialize_stations;
+use crate::{Error, Station};
+
+/// A station list with where and when it was fetched, and a checksum of its contents.
+///
+/// Serializes to the EasyTide GeoJSON format with `fetchedAt`, `source` and `checksum`
+/// members added, so a saved snapshot can also be read with
+/// [`stations_from_reader`](crate::stations_from_reader).
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// use chrono::Utc;
+/// use rjw_uktides::StationsSnapshot;
+///
+/// let stations = rjw_uktides::stations_from_reader(File::open("./stations.json").unwrap())
+///     .expect("Failed to read file as stations data.");
+/// let snapshot = StationsSnapshot::new(Utc::now(), "./stations.json", stations.clone());
+///
+/// let json = serde_json::to_vec(&snapshot).unwrap();
+/// let read = StationsSnapshot::from_reader(json.as_slice()).unwrap();
+/// assert_eq!(read.checksum, snapshot.checksum);
+/// assert!(read.is_intact());
+/// assert!(!read.differs_from(&snapshot));
+/// assert_eq!(rjw_uktides::stations_from_reader(json.as_slice()).unwrap().len(), stations.len());
+///
+/// // The order of the stations does not affect the checksum, but their contents do.
+/// let mut changed = stations;
+/// changed.reverse();
+/// assert_eq!(StationsSnapshot::checksum_of(&changed), snapshot.checksum);
+/// changed[0].name.push_str(" Harbour");
+/// assert_ne!(StationsSnapshot::checksum_of(&changed), snapshot.checksum);
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type", rename = "FeatureCollection")]
+pub struct StationsSnapshot {
+    /// When the list was fetched.
+    pub fetched_at: DateTime<Utc>,
+    /// Where the list was fetched from, such as the URL of the service.
+    pub source: String,
+    /// A checksum of the stations, as given by [`checksum_of`](Self::checksum_of).
+    pub checksum: String,
+    /// The stations.
+    #[serde(rename = "features", serialize_with = "serialize_stations")]
+    pub stations: Vec<Station>,
+}
+
+impl StationsSnapshot {
+    /// Record the stations fetched from `source` at `fetched_at`, with their checksum.
+    pub fn new(
+        fetched_at: DateTime<Utc>,
+        source: impl Into<String>,
+        stations: Vec<Station>,
+    ) -> Self {
+        Self {
+            fetched_at,
+            source: source.into(),
+            checksum: Self::checksum_of(&stations),
+            stations,
+        }
+    }
+
+    /// Read a snapshot saved as JSON.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data cannot be parsed as a snapshot,
+    /// including a station list without the snapshot's members.
+    pub fn from_reader(reader: impl Read) -> Result<Self, Error> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// A checksum of the contents of the stations, as 16 hexadecimal digits, which
+    /// changes if any station is added, removed or changed but not if they are reordered.
+    ///
+    /// This is a 64-bit FNV-1a hash, for detecting changes rather than tampering.
+    pub fn checksum_of(stations: &[Station]) -> String {
+        let mut sorted: Vec<_> = stations.iter().collect();
+        sorted.sort_by(|a, b| a.id.cmp(&b.id));
+        let mut data = Vec::new();
+        for s in sorted {
+            for field in [&s.id.0, &s.name, &s.country] {
+                data.extend(field.as_bytes());
+                data.push(0);
+            }
+            data.extend(s.location.longitude.0.to_be_bytes());
+            data.extend(s.location.latitude.0.to_be_bytes());
+            data.push(u8::from(s.continuous_heights_available));
+        }
+        format!("{:016x}", fnv1a(&data))
+    }
+
+    /// Whether the checksum matches the stations, so the stations have not been changed
+    /// since the snapshot was taken.
+    pub fn is_intact(&self) -> bool {
+        self.checksum == Self::checksum_of(&self.stations)
+    }
+
+    /// Whether the stations differ from those in `other`, by their checksums.
+    pub fn differs_from(&self, other: &StationsSnapshot) -> bool {
+        self.checksum != other.checksum
+    }
+}
+
+/// 64-bit FNV-1a hash, used where a hash must be stable across Rust releases.
+pub(crate) fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
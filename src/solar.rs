@@ -0,0 +1,76 @@
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+
+use crate::{Coordinates, TimeWindow};
+
+/// The Julian day number of the J2000 epoch, noon UTC on 1 January 2000.
+const J2000: f64 = 2_451_545.0;
+
+/// The Julian day number of the Unix epoch.
+const UNIX_EPOCH_JULIAN_DAY: f64 = 2_440_587.5;
+
+/// The obliquity of the ecliptic, in degrees.
+const OBLIQUITY: f64 = 23.4397;
+
+/// The altitude of the centre of the sun at sunrise and sunset, in degrees, allowing for
+/// refraction and the radius of the sun's disc.
+const SUNRISE_ALTITUDE: f64 = -0.833;
+
+impl Coordinates {
+    /// The time from sunrise to sunset at this place on the date, or `None` if the sun
+    /// does not rise or set, which does not happen in the UK.
+    ///
+    /// This uses the sunrise equation, which is accurate to a minute or two, plenty for
+    /// planning by daylight.
+    ///
+    /// # Examples
+    /// ```
+    /// use chrono::{NaiveDate, Timelike};
+    /// use rjw_uktides::{Coordinates, DecimalDegrees};
+    ///
+    /// let london = Coordinates {
+    ///     latitude: DecimalDegrees(51.5),
+    ///     longitude: DecimalDegrees(-0.12),
+    /// };
+    /// let midsummer = NaiveDate::from_ymd_opt(2023, 6, 21).unwrap();
+    /// let daylight = london.daylight(midsummer).unwrap();
+    /// // Sunrise is at about 04:43 and sunset about 21:21 British Summer Time.
+    /// assert_eq!((daylight.start.hour(), daylight.start.minute() / 10), (3, 4));
+    /// assert_eq!((daylight.end.hour(), daylight.end.minute() / 10), (20, 2));
+    /// ```
+    pub fn daylight(&self, date: NaiveDate) -> Option<TimeWindow> {
+        let epoch = NaiveDate::from_ymd_opt(2000, 1, 1).expect("valid date");
+        let day = (date - epoch).num_days() as f64;
+        // Mean solar noon at this longitude, in days since J2000.
+        let noon = day - self.longitude.0 / 360.0;
+        let anomaly = (357.5291 + 0.985_600_28 * noon)
+            .rem_euclid(360.0)
+            .to_radians();
+        let centre = 1.9148 * anomaly.sin()
+            + 0.0200 * (2.0 * anomaly).sin()
+            + 0.0003 * (3.0 * anomaly).sin();
+        let ecliptic_longitude = (anomaly.to_degrees() + centre + 180.0 + 102.9372)
+            .rem_euclid(360.0)
+            .to_radians();
+        let transit =
+            J2000 + noon + 0.0053 * anomaly.sin() - 0.0069 * (2.0 * ecliptic_longitude).sin();
+        let declination = (ecliptic_longitude.sin() * OBLIQUITY.to_radians().sin()).asin();
+        let latitude = self.latitude.0.to_radians();
+        let cos_hour_angle = (SUNRISE_ALTITUDE.to_radians().sin()
+            - latitude.sin() * declination.sin())
+            / (latitude.cos() * declination.cos());
+        if !(-1.0..=1.0).contains(&cos_hour_angle) {
+            return None;
+        }
+        let half_day = cos_hour_angle.acos().to_degrees() / 360.0;
+        Some(TimeWindow {
+            start: from_julian_day(transit - half_day)?,
+            end: from_julian_day(transit + half_day)?,
+        })
+    }
+}
+
+/// The time at the Julian day number, to the second.
+fn from_julian_day(julian_day: f64) -> Option<DateTime<Utc>> {
+    let seconds = ((julian_day - UNIX_EPOCH_JULIAN_DAY) * 86_400.0).round() as i64;
+    Utc.timestamp_opt(seconds, 0).single()
+}
@@ -0,0 +1,674 @@
+//! Persisting stations and fetched predictions in SQLite, with queries over them.
+//!
+//! Enabled by the `store` feature. A [`Store`] is an SQLite database holding the station
+//! list and, for each station, every set of predictions fetched, with when it was fetched
+//! and when its last tide is, so stale predictions can be found without reading them.
+//! The high and low tides of each fetch are also stored in their own table, indexed by
+//! time, so the tides between two dates are found by a query rather than by reading back
+//! every fetch.
+//!
+//! The tables are:
+//!
+//! - `stations`, with columns `id`, `name`, `country`, `lat`, `lon`,
+//!   `continuous_heights` and `fetched_at`;
+//! - `fetches`, with columns `id`, `station`, `fetched_at`, `last_event_at` and
+//!   `predictions`, the predictions in the EasyTide response format;
+//! - `events`, with columns `fetch_id`, `date`, `date_time`, `type` (`"high"` or
+//!   `"low"`), `height`, `approximate_time` and `approximate_height`.
+//!
+//! Times are stored as text in UTC, as written by rusqlite, so they sort chronologically.
+//!
+//! # Examples
+//! ```
+//! use std::fs::File;
+//!
+//! use chrono::{Duration, Utc};
+//! use rjw_uktides::store::Store;
+//! use rjw_uktides::StationId;
+//!
+//! let store = Store::open_in_memory().expect("Failed to open store.");
+//! let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+//!     .expect("Failed to read file as tides data.");
+//! let station = StationId("0053".to_owned());
+//! store.put_predictions(&station, Utc::now(), &tides).expect("Failed to store predictions.");
+//!
+//! let stations = rjw_uktides::stations_from_reader(File::open("./stations.json").unwrap())
+//!     .expect("Failed to read file as stations data.");
+//! store.put_stations(Utc::now(), &stations).expect("Failed to store stations.");
+//! assert_eq!(store.stations().unwrap().unwrap().stations.len(), stations.len());
+//!
+//! let latest = store.latest_predictions(&station).unwrap().unwrap();
+//! assert_eq!(latest.predictions.tidal_event_list, tides.tidal_event_list);
+//! assert!(latest.age() < Duration::hours(1));
+//! // The reference predictions are from 2023, so have no tides to come.
+//! assert!(latest.is_stale(Duration::hours(1)));
+//! assert_eq!(store.stale_stations(Duration::hours(1)).unwrap(), [station.clone()]);
+//!
+//! let first = &tides.tidal_event_list[0];
+//! let events = store
+//!     .events_between(&station, first.date_time, first.date_time + Duration::hours(12))
+//!     .unwrap();
+//! assert_eq!(events[0], *first);
+//! ```
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use chrono::{DateTime, Duration, NaiveDate, SubsecRound, Utc};
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Row, MAIN_DB};
+
+use crate::{
+    Coordinates, DecimalDegrees, Metres, Station, StationId, TidalEvent, TidalEventType,
+    TidePredictions,
+};
+
+pub use rusqlite::Error;
+
+/// The statements creating the store's tables, run each time it is opened.
+const SCHEMA_SQL: &str = "
+    PRAGMA foreign_keys = ON;
+    CREATE TABLE IF NOT EXISTS stations (
+        id TEXT PRIMARY KEY NOT NULL,
+        name TEXT NOT NULL,
+        country TEXT NOT NULL,
+        lat REAL NOT NULL,
+        lon REAL NOT NULL,
+        continuous_heights INTEGER NOT NULL,
+        fetched_at TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS fetches (
+        id INTEGER PRIMARY KEY,
+        station TEXT NOT NULL,
+        fetched_at TEXT NOT NULL,
+        last_event_at TEXT,
+        predictions TEXT NOT NULL,
+        UNIQUE (station, fetched_at)
+    );
+    CREATE TABLE IF NOT EXISTS events (
+        fetch_id INTEGER NOT NULL REFERENCES fetches (id) ON DELETE CASCADE,
+        date TEXT NOT NULL,
+        date_time TEXT NOT NULL,
+        type TEXT NOT NULL,
+        height REAL NOT NULL,
+        approximate_time TEXT,
+        approximate_height TEXT
+    );
+    CREATE INDEX IF NOT EXISTS events_by_time ON events (date_time, fetch_id);
+    CREATE INDEX IF NOT EXISTS events_by_fetch ON events (fetch_id);
+";
+
+/// The statement creating the table written by [`write_stations_sqlite`].
+const STATIONS_TABLE_SQL: &str = "CREATE TABLE stations (id TEXT NOT NULL, name TEXT NOT NULL, \
+     country TEXT NOT NULL, lat REAL NOT NULL, lon REAL NOT NULL, \
+     continuous_heights INTEGER NOT NULL)";
+
+/// An SQLite database of stations and fetched predictions.
+#[derive(Debug)]
+pub struct Store {
+    conn: Connection,
+}
+
+/// The station list as stored, with when it was fetched.
+#[derive(Debug, Clone)]
+pub struct StoredStations {
+    pub fetched_at: DateTime<Utc>,
+    pub stations: Vec<Station>,
+}
+
+/// A set of predictions for a station, with when they were fetched.
+#[derive(Debug, Clone)]
+pub struct StoredPredictions {
+    pub station: StationId,
+    pub fetched_at: DateTime<Utc>,
+    pub predictions: TidePredictions,
+}
+
+impl StoredStations {
+    /// How long ago the list was fetched.
+    pub fn age(&self) -> Duration {
+        Utc::now() - self.fetched_at
+    }
+
+    /// Whether the list is older than `max_age`.
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        self.age() > max_age
+    }
+}
+
+impl StoredPredictions {
+    /// How long ago the predictions were fetched.
+    pub fn age(&self) -> Duration {
+        Utc::now() - self.fetched_at
+    }
+
+    /// Whether the predictions are older than `max_age`, or have no tides left to come.
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        let now = Utc::now();
+        self.age() > max_age
+            || !self
+                .predictions
+                .tidal_event_list
+                .iter()
+                .any(|e| e.date_time > now)
+    }
+}
+
+impl Store {
+    /// Open the store in the database file at `path`, creating it if necessary.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the database cannot be opened or its tables
+    /// created.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Open a store held in memory, which is lost when it is dropped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be created.
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, Error> {
+        conn.execute_batch(SCHEMA_SQL)?;
+        Ok(Self { conn })
+    }
+
+    /// The connection to the database, for queries the store does not provide.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Store the station list, replacing any stored previously.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if writing to the database fails, including if
+    /// two stations have the same ID.
+    pub fn put_stations(
+        &self,
+        fetched_at: DateTime<Utc>,
+        stations: &[Station],
+    ) -> Result<(), Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM stations", [])?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO stations \
+                 (id, name, country, lat, lon, continuous_heights, fetched_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for s in stations {
+                insert.execute(params![
+                    s.id.0,
+                    s.name,
+                    s.country,
+                    s.location.latitude.0,
+                    s.location.longitude.0,
+                    s.continuous_heights_available,
+                    fetched_at,
+                ])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// The stored station list, in the order it was stored, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if reading from the database fails.
+    pub fn stations(&self) -> Result<Option<StoredStations>, Error> {
+        let mut select = self.conn.prepare(
+            "SELECT id, name, country, lat, lon, continuous_heights, fetched_at \
+             FROM stations ORDER BY rowid",
+        )?;
+        let mut rows = select.query([])?;
+        let mut fetched_at = None;
+        let mut stations = Vec::new();
+        while let Some(row) = rows.next()? {
+            fetched_at = Some(row.get(6)?);
+            stations.push(Station {
+                id: StationId(row.get(0)?),
+                name: row.get(1)?,
+                country: row.get(2)?,
+                location: Coordinates {
+                    latitude: DecimalDegrees(row.get(3)?),
+                    longitude: DecimalDegrees(row.get(4)?),
+                },
+                continuous_heights_available: row.get(5)?,
+            });
+        }
+        Ok(fetched_at.map(|fetched_at| StoredStations {
+            fetched_at,
+            stations,
+        }))
+    }
+
+    /// Store predictions for the station fetched at `fetched_at`, to the second. Predictions
+    /// already stored for the same second are replaced.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if writing to the database fails.
+    pub fn put_predictions(
+        &self,
+        station: &StationId,
+        fetched_at: DateTime<Utc>,
+        predictions: &TidePredictions,
+    ) -> Result<(), Error> {
+        let fetched_at = fetched_at.trunc_subsecs(0);
+        let json = serde_json::to_string(predictions)
+            .map_err(|e| Error::ToSqlConversionFailure(Box::new(e)))?;
+        let last_event_at = predictions
+            .tidal_event_list
+            .iter()
+            .map(|e| e.date_time)
+            .max();
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM fetches WHERE station = ?1 AND fetched_at = ?2",
+            params![station.0, fetched_at],
+        )?;
+        tx.execute(
+            "INSERT INTO fetches (station, fetched_at, last_event_at, predictions) \
+             VALUES (?1, ?2, ?3, ?4)",
+            params![station.0, fetched_at, last_event_at, json],
+        )?;
+        let fetch_id = tx.last_insert_rowid();
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO events (fetch_id, date, date_time, type, height, \
+                 approximate_time, approximate_height) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for e in &predictions.tidal_event_list {
+                insert.execute(params![
+                    fetch_id,
+                    e.date,
+                    e.date_time,
+                    e.event_type.name(),
+                    e.height.0,
+                    e.is_approximate_time,
+                    e.is_approximate_height,
+                ])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// The times at which predictions for the station were fetched, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if reading from the database fails.
+    pub fn fetches(&self, station: &StationId) -> Result<Vec<DateTime<Utc>>, Error> {
+        let mut select = self
+            .conn
+            .prepare("SELECT fetched_at FROM fetches WHERE station = ?1 ORDER BY fetched_at")?;
+        let fetches = select.query_map([&station.0], |row| row.get(0))?;
+        fetches.collect()
+    }
+
+    /// The predictions for the station fetched at `fetched_at`, to the second, if there
+    /// are any.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if reading from the database fails, or the
+    /// stored predictions cannot be parsed.
+    pub fn predictions(
+        &self,
+        station: &StationId,
+        fetched_at: DateTime<Utc>,
+    ) -> Result<Option<StoredPredictions>, Error> {
+        self.conn
+            .query_row(
+                "SELECT station, fetched_at, predictions FROM fetches \
+                 WHERE station = ?1 AND fetched_at = ?2",
+                params![station.0, fetched_at.trunc_subsecs(0)],
+                stored_predictions,
+            )
+            .optional()
+    }
+
+    /// The most recently fetched predictions for the station, if there are any.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if reading from the database fails, or the
+    /// stored predictions cannot be parsed.
+    pub fn latest_predictions(
+        &self,
+        station: &StationId,
+    ) -> Result<Option<StoredPredictions>, Error> {
+        self.conn
+            .query_row(
+                "SELECT station, fetched_at, predictions FROM fetches WHERE station = ?1 \
+                 ORDER BY fetched_at DESC LIMIT 1",
+                [&station.0],
+                stored_predictions,
+            )
+            .optional()
+    }
+
+    /// The stations whose most recent predictions were fetched more than `max_age` ago,
+    /// or have no tides left to come, ordered by ID.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if reading from the database fails.
+    pub fn stale_stations(&self, max_age: Duration) -> Result<Vec<StationId>, Error> {
+        let now = Utc::now();
+        let mut select = self.conn.prepare(
+            "SELECT station FROM fetches AS f \
+             WHERE fetched_at = (SELECT MAX(fetched_at) FROM fetches WHERE station = f.station) \
+             AND (fetched_at < ?1 OR last_event_at IS NULL OR last_event_at <= ?2) \
+             ORDER BY station",
+        )?;
+        let stations =
+            select.query_map(params![now - max_age, now], |row| row.get(0).map(StationId))?;
+        stations.collect()
+    }
+
+    /// The high and low tides for the station from `start` up to but not including `end`,
+    /// in chronological order, combined from every fetch with the most recent prediction
+    /// of each tide taking precedence, as with [`TidePredictions::merge`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if reading from the database fails.
+    pub fn events_between(
+        &self,
+        station: &StationId,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<TidalEvent>, Error> {
+        let mut select = self.conn.prepare(
+            "SELECT date, date_time, type, height, approximate_time, approximate_height \
+             FROM (
+                 SELECT e.*, ROW_NUMBER() OVER (
+                     PARTITION BY e.date_time ORDER BY f.fetched_at DESC
+                 ) AS newest
+                 FROM events AS e JOIN fetches AS f ON f.id = e.fetch_id
+                 WHERE f.station = ?1 AND e.date_time >= ?2 AND e.date_time < ?3
+             ) \
+             WHERE newest = 1 ORDER BY date_time",
+        )?;
+        let events = select.query_map(params![station.0, start, end], tidal_event)?;
+        events.collect()
+    }
+}
+
+/// Read a row of `station`, `fetched_at` and `predictions` from the fetches table.
+fn stored_predictions(row: &Row) -> Result<StoredPredictions, Error> {
+    let json: String = row.get(2)?;
+    let predictions = crate::tides_from_reader(json.as_bytes())
+        .map_err(|e| Error::FromSqlConversionFailure(2, Type::Text, Box::new(e)))?;
+    Ok(StoredPredictions {
+        station: StationId(row.get(0)?),
+        fetched_at: row.get(1)?,
+        predictions,
+    })
+}
+
+/// Read a row of the events table, without its `fetch_id`.
+fn tidal_event(row: &Row) -> Result<TidalEvent, Error> {
+    let event_type = match row.get_ref(2)?.as_str()? {
+        "high" => TidalEventType::HighWater,
+        "low" => TidalEventType::LowWater,
+        other => {
+            let message = format!("unknown event type {other:?}");
+            return Err(Error::FromSqlConversionFailure(
+                2,
+                Type::Text,
+                message.into(),
+            ));
+        }
+    };
+    let date: NaiveDate = row.get(0)?;
+    Ok(TidalEvent {
+        date,
+        date_time: row.get(1)?,
+        event_type,
+        height: Metres(row.get(3)?),
+        is_approximate_time: row.get(4)?,
+        is_approximate_height: row.get(5)?,
+    })
+}
+
+/// Write the stations as an SQLite database, for looking stations up with SQL.
+///
+/// The database has a single table, `stations`, with columns `id`, `name`, `country`,
+/// `lat` and `lon`, in decimal degrees, and `continuous_heights`, which is 1 if the
+/// station provides continuous heights and 0 otherwise. Rows are in the order given.
+///
+/// # Errors
+///
+/// This function will return an error if writing fails.
+pub fn write_stations_sqlite<W: Write>(mut writer: W, stations: &[Station]) -> io::Result<()> {
+    let database = stations_database(stations).map_err(io::Error::other)?;
+    writer.write_all(&database)?;
+    writer.flush()
+}
+
+/// The contents of a database file holding the table written by [`write_stations_sqlite`].
+fn stations_database(stations: &[Station]) -> Result<Vec<u8>, Error> {
+    let conn = Connection::open_in_memory()?;
+    conn.execute_batch(STATIONS_TABLE_SQL)?;
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT INTO stations (id, name, country, lat, lon, continuous_heights) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for s in stations {
+            insert.execute(params![
+                s.id.0,
+                s.name,
+                s.country,
+                s.location.latitude.0,
+                s.location.longitude.0,
+                s.continuous_heights_available,
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(conn.serialize(MAIN_DB)?.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{utc, StationBuilder, TidalEventBuilder, TidePredictionsBuilder};
+
+    fn station() -> StationId {
+        StationId("0053".to_owned())
+    }
+
+    #[test]
+    fn round_trips_the_station_list_in_order() {
+        let store = Store::open_in_memory().unwrap();
+        assert!(store.stations().unwrap().is_none());
+
+        let stations = vec![
+            StationBuilder::new("0002", "B")
+                .location(50.5, -1.25)
+                .build(),
+            StationBuilder::new("0001", "A")
+                .continuous_heights_available(false)
+                .build(),
+        ];
+        store
+            .put_stations(utc("2023-04-01T09:00"), &stations)
+            .unwrap();
+        store
+            .put_stations(utc("2023-04-02T09:00"), &stations)
+            .unwrap();
+
+        let stored = store.stations().unwrap().unwrap();
+        assert_eq!(stored.fetched_at, utc("2023-04-02T09:00"));
+        let ids: Vec<_> = stored.stations.iter().map(|s| s.id.0.as_str()).collect();
+        assert_eq!(ids, ["0002", "0001"]);
+        assert_eq!(stored.stations[0].location.longitude.0, -1.25);
+        assert!(!stored.stations[1].continuous_heights_available);
+    }
+
+    #[test]
+    fn replaces_predictions_fetched_in_the_same_second() {
+        let store = Store::open_in_memory().unwrap();
+        let first = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::high().at("2023-04-03T06:00"))
+            .build();
+        let second = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::low().at("2023-04-03T12:00"))
+            .build();
+        let fetched_at = utc("2023-04-03T00:00:30");
+        store
+            .put_predictions(&station(), fetched_at, &first)
+            .unwrap();
+        store
+            .put_predictions(
+                &station(),
+                fetched_at + Duration::milliseconds(500),
+                &second,
+            )
+            .unwrap();
+
+        assert_eq!(store.fetches(&station()).unwrap(), [fetched_at]);
+        let stored = store.predictions(&station(), fetched_at).unwrap().unwrap();
+        assert_eq!(stored.predictions.tidal_event_list, second.tidal_event_list);
+        // The events of the replaced fetch are removed with it.
+        let events = store
+            .events_between(&station(), utc("2023-04-03T00:00"), utc("2023-04-04T00:00"))
+            .unwrap();
+        assert_eq!(events, second.tidal_event_list);
+    }
+
+    #[test]
+    fn queries_the_latest_predictions_and_stale_stations() {
+        let store = Store::open_in_memory().unwrap();
+        assert!(store.latest_predictions(&station()).unwrap().is_none());
+
+        let past = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::high().at("2023-04-03T06:00"))
+            .build();
+        let future = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::high().at_datetime(Utc::now() + Duration::days(1)))
+            .build();
+        let other = StationId("0001".to_owned());
+        store
+            .put_predictions(&station(), Utc::now() - Duration::hours(2), &future)
+            .unwrap();
+        store
+            .put_predictions(&station(), Utc::now(), &past)
+            .unwrap();
+        store.put_predictions(&other, Utc::now(), &future).unwrap();
+
+        let latest = store.latest_predictions(&station()).unwrap().unwrap();
+        assert_eq!(latest.predictions.tidal_event_list, past.tidal_event_list);
+        // The latest fetch for 0053 has no tides to come, though an older one does.
+        assert_eq!(
+            store.stale_stations(Duration::hours(1)).unwrap(),
+            [station()]
+        );
+        assert_eq!(
+            store.stale_stations(-Duration::hours(1)).unwrap(),
+            [other, station()]
+        );
+    }
+
+    #[test]
+    fn prefers_the_newest_prediction_of_each_tide_between_dates() {
+        let store = Store::open_in_memory().unwrap();
+        let older = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::high().at("2023-04-03T06:00").height(4.0))
+            .event(TidalEventBuilder::low().at("2023-04-03T12:00").height(1.0))
+            .build();
+        let newer = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::low().at("2023-04-03T12:00").height(1.5))
+            .event(TidalEventBuilder::high().at("2023-04-03T18:00").height(4.5))
+            .build();
+        store
+            .put_predictions(&station(), utc("2023-04-02T00:00"), &newer)
+            .unwrap();
+        store
+            .put_predictions(&station(), utc("2023-04-01T00:00"), &older)
+            .unwrap();
+
+        let events = store
+            .events_between(&station(), utc("2023-04-03T06:00"), utc("2023-04-03T18:00"))
+            .unwrap();
+        let mut merged = older.clone();
+        merged.merge(newer);
+        assert_eq!(events, merged.tidal_event_list[..2]);
+        assert_eq!(events[1].height.0, 1.5);
+        assert!(store
+            .events_between(
+                &StationId("0001".to_owned()),
+                utc("2023-04-03T00:00"),
+                utc("2023-04-04T00:00")
+            )
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn writes_a_stations_database_file() {
+        let stations = vec![
+            StationBuilder::new("0053", "Sandown")
+                .location(50.65, -1.15)
+                .build(),
+            StationBuilder::new("0001", "St Mary's")
+                .country("Isles of Scilly")
+                .continuous_heights_available(false)
+                .build(),
+        ];
+        let file = tempfile::NamedTempFile::new().unwrap();
+        write_stations_sqlite(file.as_file(), &stations).unwrap();
+
+        let conn = Connection::open(file.path()).unwrap();
+        let mut select = conn
+            .prepare("SELECT * FROM stations ORDER BY rowid")
+            .unwrap();
+        let rows: Vec<(String, String, String, f64, f64, bool)> = select
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                ))
+            })
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            [
+                (
+                    "0053".to_owned(),
+                    "Sandown".to_owned(),
+                    "England".to_owned(),
+                    50.65,
+                    -1.15,
+                    true
+                ),
+                (
+                    "0001".to_owned(),
+                    "St Mary's".to_owned(),
+                    "Isles of Scilly".to_owned(),
+                    0.0,
+                    0.0,
+                    false
+                ),
+            ]
+        );
+    }
+}
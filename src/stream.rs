@@ -0,0 +1,232 @@
+//! Tide predictions parsing that streams or skips the continuous heights instead of
+//! collecting them.
+
+use std::fmt;
+use std::io::Read;
+
+use serde::de::{DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use crate::trace::parse_json_seed;
+use crate::{Error, LunarPhase, Notes, TidalEvent, TidalHeightOccurrence, TidePredictions};
+
+/// Attempt to parse tide predictions from the reader, passing each continuous height
+/// prediction to `on_height` as it is parsed instead of collecting them.
+///
+/// The `tidal_height_occurrence_list` of the returned [`TidePredictions`] is always empty.
+/// This avoids materialising the hundreds of half-hourly heights in the response for callers
+/// that only need to summarise them, or that want to store them elsewhere.
+///
+/// # Errors
+///
+/// This function will return an error in the same cases as [`tides_from_reader`](crate::tides_from_reader).
+/// Heights parsed before the error was encountered will already have been passed to `on_height`.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// let file = File::open("./reference/tides.json")
+///     .expect("Failed to open tides reference file.");
+/// let mut highest = f64::MIN;
+/// let tides = rjw_uktides::tides_from_reader_streaming(file, |h| highest = highest.max(h.height.0))
+///     .expect("Failed to read file as tides data.");
+/// assert!(tides.tidal_height_occurrence_list.is_empty());
+/// assert!(highest > 0.0);
+/// ```
+pub fn tides_from_reader_streaming<F>(
+    rdr: impl Read,
+    mut on_height: F,
+) -> Result<TidePredictions, Error>
+where
+    F: FnMut(TidalHeightOccurrence),
+{
+    let seed = TidePredictionsSeed {
+        on_height: Some(&mut on_height),
+    };
+    parse_json_seed(rdr, seed, "tide predictions")
+}
+
+/// Options controlling how [`tides_from_reader_with`] parses tide predictions.
+///
+/// The default options parse the whole response, the same as [`tides_from_reader`](crate::tides_from_reader).
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ParseOptions {
+    /// Skip over the continuous heights without parsing them, leaving
+    /// `tidal_height_occurrence_list` empty.
+    ///
+    /// This reduces latency and memory use for callers that only want the high and low
+    /// tide events.
+    pub skip_continuous_heights: bool,
+}
+
+/// Attempt to parse tide predictions from the reader according to `opts`.
+///
+/// # Errors
+///
+/// This function will return an error in the same cases as [`tides_from_reader`](crate::tides_from_reader).
+/// When skipping continuous heights, the skipped data must still be valid JSON but is not
+/// otherwise checked.
+///
+/// # Examples
+/// ```
+/// use std::fs::File;
+///
+/// let file = File::open("./reference/tides.json")
+///     .expect("Failed to open tides reference file.");
+/// let mut opts = rjw_uktides::ParseOptions::default();
+/// opts.skip_continuous_heights = true;
+/// let tides = rjw_uktides::tides_from_reader_with(file, &opts)
+///     .expect("Failed to read file as tides data.");
+/// assert!(tides.tidal_height_occurrence_list.is_empty());
+/// assert!(!tides.tidal_event_list.is_empty());
+/// ```
+pub fn tides_from_reader_with(
+    rdr: impl Read,
+    opts: &ParseOptions,
+) -> Result<TidePredictions, Error> {
+    if !opts.skip_continuous_heights {
+        return crate::tides_from_reader(rdr);
+    }
+    let seed = TidePredictionsSeed::<fn(TidalHeightOccurrence)> { on_height: None };
+    parse_json_seed(rdr, seed, "tide predictions")
+}
+
+/// Field names of the predictions response, as used by the derived `Deserialize` impl
+/// of [`TidePredictions`].
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "camelCase")]
+enum Field {
+    FooterNote,
+    LunarPhaseList,
+    TidalEventList,
+    TidalHeightOccurrenceList,
+    #[serde(other)]
+    Other,
+}
+
+/// Deserializes [`TidePredictions`], handing the continuous heights to a callback, or
+/// skipping them entirely if there is no callback.
+struct TidePredictionsSeed<'f, F> {
+    on_height: Option<&'f mut F>,
+}
+
+impl<'de, F> DeserializeSeed<'de> for TidePredictionsSeed<'_, F>
+where
+    F: FnMut(TidalHeightOccurrence),
+{
+    type Value = TidePredictions;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F> Visitor<'de> for TidePredictionsSeed<'_, F>
+where
+    F: FnMut(TidalHeightOccurrence),
+{
+    type Value = TidePredictions;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a tide predictions object")
+    }
+
+    fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        use serde::de::Error;
+
+        let mut footer_note: Option<Notes> = None;
+        let mut lunar_phase_list: Option<Vec<LunarPhase>> = None;
+        let mut tidal_event_list: Option<Vec<TidalEvent>> = None;
+        let mut seen_heights = false;
+        while let Some(key) = map.next_key()? {
+            match key {
+                Field::FooterNote if footer_note.is_some() => {
+                    return Err(A::Error::duplicate_field("footerNote"))
+                }
+                Field::FooterNote => footer_note = Some(map.next_value()?),
+                Field::LunarPhaseList if lunar_phase_list.is_some() => {
+                    return Err(A::Error::duplicate_field("lunarPhaseList"))
+                }
+                Field::LunarPhaseList => lunar_phase_list = Some(map.next_value()?),
+                Field::TidalEventList if tidal_event_list.is_some() => {
+                    return Err(A::Error::duplicate_field("tidalEventList"))
+                }
+                Field::TidalEventList => tidal_event_list = Some(map.next_value()?),
+                Field::TidalHeightOccurrenceList if seen_heights => {
+                    return Err(A::Error::duplicate_field("tidalHeightOccurrenceList"))
+                }
+                Field::TidalHeightOccurrenceList => {
+                    match self.on_height.as_deref_mut() {
+                        Some(on_height) => map.next_value_seed(HeightsSeed { on_height })?,
+                        None => {
+                            map.next_value::<serde::de::IgnoredAny>()?;
+                        }
+                    }
+                    seen_heights = true;
+                }
+                Field::Other => {
+                    map.next_value::<serde::de::IgnoredAny>()?;
+                }
+            }
+        }
+        if !seen_heights {
+            return Err(A::Error::missing_field("tidalHeightOccurrenceList"));
+        }
+        Ok(TidePredictions {
+            footer_note: footer_note.ok_or_else(|| A::Error::missing_field("footerNote"))?,
+            lunar_phase_list: lunar_phase_list
+                .ok_or_else(|| A::Error::missing_field("lunarPhaseList"))?,
+            tidal_event_list: tidal_event_list
+                .ok_or_else(|| A::Error::missing_field("tidalEventList"))?,
+            tidal_height_occurrence_list: Vec::new(),
+        })
+    }
+}
+
+/// Deserializes the continuous heights array, passing each element to a callback.
+struct HeightsSeed<'f, F> {
+    on_height: &'f mut F,
+}
+
+impl<'de, F> DeserializeSeed<'de> for HeightsSeed<'_, F>
+where
+    F: FnMut(TidalHeightOccurrence),
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F> Visitor<'de> for HeightsSeed<'_, F>
+where
+    F: FnMut(TidalHeightOccurrence),
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of tide height predictions")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        while let Some(height) = seq.next_element()? {
+            (self.on_height)(height);
+        }
+        Ok(())
+    }
+}
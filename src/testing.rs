@@ -0,0 +1,228 @@
+//! Fixtures and builders for testing code that uses this crate.
+//!
+//! Enable the `testing` feature (typically only as a dev-dependency) to use this module.
+//! It provides canned stations and tide predictions parsed from data bundled with the crate,
+//! and builders for constructing events and predictions in unit tests without writing JSON.
+//!
+//! # Examples
+//! ```
+//! use rjw_uktides::testing::{TidalEventBuilder, TidePredictionsBuilder};
+//!
+//! let tides = TidePredictionsBuilder::new()
+//!     .event(TidalEventBuilder::high().at("2025-08-17T06:14").height(4.2))
+//!     .event(TidalEventBuilder::low().at("2025-08-17T12:31").height(0.8))
+//!     .build();
+//! assert_eq!(tides.tidal_event_list.len(), 2);
+//! assert!(tides.validate().is_ok());
+//! ```
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+
+use crate::{
+    Coordinates, DecimalDegrees, LunarPhase, LunarPhaseType, Metres, Station, StationId,
+    TidalEvent, TidalEventType, TidalHeightOccurrence, TidePredictions,
+};
+
+pub(crate) const STATIONS_BYTES: &[u8] = include_bytes!("../stations.json");
+pub(crate) const TIDES_BYTES: &[u8] = include_bytes!("../reference/tides.json");
+#[cfg(feature = "compression")]
+pub(crate) const TIDES_GZIP_BYTES: &[u8] = include_bytes!("../reference/tides.json.gz");
+
+/// The full list of stations bundled with the crate.
+pub fn stations() -> Vec<Station> {
+    crate::stations_from_reader(STATIONS_BYTES).expect("Bundled stations data is valid.")
+}
+
+/// The Sandown (Isle of Wight) station, ID 0053.
+pub fn sandown() -> Station {
+    StationBuilder::new("0053", "Sandown")
+        .country("England")
+        .location(50.65, -1.15)
+        .build()
+}
+
+/// A week of tide predictions, with events, continuous heights and a lunar phase,
+/// starting on 2023-04-03.
+pub fn tide_predictions() -> TidePredictions {
+    crate::tides_from_reader(TIDES_BYTES).expect("Bundled tides data is valid.")
+}
+
+/// Parse a UTC datetime in `%Y-%m-%dT%H:%M` format, optionally followed by `:%S`.
+///
+/// # Panics
+///
+/// Panics if the string is not in either format, as this is intended for test code.
+pub fn utc(datetime: &str) -> DateTime<Utc> {
+    NaiveDateTime::parse_from_str(datetime, "%Y-%m-%dT%H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(datetime, "%Y-%m-%dT%H:%M"))
+        .map(|naive| Utc.from_utc_datetime(&naive))
+        .unwrap_or_else(|e| panic!("Invalid test datetime {datetime:?}: {e}"))
+}
+
+/// Builder for [`Station`] values.
+#[derive(Debug, Clone)]
+pub struct StationBuilder {
+    station: Station,
+}
+
+impl StationBuilder {
+    /// Start building a station in England at 0°N 0°E with continuous heights available.
+    pub fn new(id: &str, name: &str) -> Self {
+        Self {
+            station: Station {
+                id: StationId(id.to_owned()),
+                name: name.to_owned(),
+                country: "England".to_owned(),
+                location: Coordinates {
+                    longitude: DecimalDegrees(0.0),
+                    latitude: DecimalDegrees(0.0),
+                },
+                continuous_heights_available: true,
+            },
+        }
+    }
+
+    /// Set the station's country.
+    pub fn country(mut self, country: &str) -> Self {
+        self.station.country = country.to_owned();
+        self
+    }
+
+    /// Set the station's location in decimal degrees.
+    pub fn location(mut self, latitude: f64, longitude: f64) -> Self {
+        self.station.location = Coordinates {
+            longitude: DecimalDegrees(longitude),
+            latitude: DecimalDegrees(latitude),
+        };
+        self
+    }
+
+    /// Set whether the station provides continuous heights.
+    pub fn continuous_heights_available(mut self, available: bool) -> Self {
+        self.station.continuous_heights_available = available;
+        self
+    }
+
+    pub fn build(self) -> Station {
+        self.station
+    }
+}
+
+/// Builder for [`TidalEvent`] values.
+///
+/// Events default to midnight on 2023-04-03 UTC with a height of zero.
+#[derive(Debug, Clone)]
+pub struct TidalEventBuilder {
+    event_type: TidalEventType,
+    date_time: DateTime<Utc>,
+    height: f64,
+}
+
+impl TidalEventBuilder {
+    /// Start building a high tide event.
+    pub fn high() -> Self {
+        Self::new(TidalEventType::HighWater)
+    }
+
+    /// Start building a low tide event.
+    pub fn low() -> Self {
+        Self::new(TidalEventType::LowWater)
+    }
+
+    fn new(event_type: TidalEventType) -> Self {
+        Self {
+            event_type,
+            date_time: utc("2023-04-03T00:00"),
+            height: 0.0,
+        }
+    }
+
+    /// Set the time of the event, as parsed by [`utc`].
+    pub fn at(mut self, datetime: &str) -> Self {
+        self.date_time = utc(datetime);
+        self
+    }
+
+    /// Set the time of the event.
+    pub fn at_datetime(mut self, date_time: DateTime<Utc>) -> Self {
+        self.date_time = date_time;
+        self
+    }
+
+    /// Set the height of the event, in metres.
+    pub fn height(mut self, metres: f64) -> Self {
+        self.height = metres;
+        self
+    }
+
+    pub fn build(self) -> TidalEvent {
+        TidalEvent {
+            date: self.date_time.date_naive(),
+            date_time: self.date_time,
+            event_type: self.event_type,
+            height: Metres(self.height),
+            is_approximate_height: None,
+            is_approximate_time: None,
+        }
+    }
+}
+
+/// Builder for [`TidePredictions`] values.
+///
+/// Predictions start with no events, heights or lunar phases, and an empty footer note.
+#[derive(Debug, Default)]
+pub struct TidePredictionsBuilder {
+    footer_note: String,
+    events: Vec<TidalEvent>,
+    heights: Vec<TidalHeightOccurrence>,
+    lunar_phases: Vec<LunarPhase>,
+}
+
+impl TidePredictionsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the footer note.
+    pub fn footer_note(mut self, note: &str) -> Self {
+        self.footer_note = note.to_owned();
+        self
+    }
+
+    /// Add a tidal event.
+    pub fn event(mut self, event: TidalEventBuilder) -> Self {
+        self.events.push(event.build());
+        self
+    }
+
+    /// Add a continuous height prediction, with the time parsed by [`utc`].
+    pub fn height(mut self, datetime: &str, metres: f64) -> Self {
+        self.heights.push(TidalHeightOccurrence {
+            date_time: utc(datetime),
+            height: Metres(metres),
+        });
+        self
+    }
+
+    /// Add a lunar phase, with the time parsed by [`utc`].
+    pub fn lunar_phase(mut self, datetime: &str, phase: LunarPhaseType) -> Self {
+        self.lunar_phases.push(LunarPhase {
+            date_time: utc(datetime),
+            lunar_phase_type: phase,
+        });
+        self
+    }
+
+    /// Build the predictions, sorting the events, heights and lunar phases by time.
+    pub fn build(mut self) -> TidePredictions {
+        self.events.sort();
+        self.heights.sort_by_key(|h| h.date_time);
+        self.lunar_phases.sort_by_key(|p| p.date_time);
+        TidePredictions {
+            footer_note: self.footer_note.into(),
+            lunar_phase_list: self.lunar_phases,
+            tidal_event_list: self.events,
+            tidal_height_occurrence_list: self.heights,
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Optional `tracing` instrumentation.
+//!
+//! With the `tracing` feature disabled, these helpers compile down to the plain
+//! uninstrumented operations.
+
+use std::io::Read;
+
+use serde::de::{Deserialize, DeserializeOwned, DeserializeSeed};
+
+use crate::backend::{from_reader, from_reader_seed, from_slice};
+use crate::Error;
+
+/// Deserialize JSON from the reader, recording the payload size and parse duration.
+#[cfg(feature = "tracing")]
+pub(crate) fn parse_json<T: DeserializeOwned>(
+    rdr: impl Read,
+    payload: &'static str,
+) -> Result<T, Error> {
+    let _span = tracing::debug_span!("parse_json", payload).entered();
+    let start = std::time::Instant::now();
+    let mut rdr = CountingReader {
+        inner: rdr,
+        bytes: 0,
+    };
+    let result = from_reader(&mut rdr);
+    let elapsed = start.elapsed();
+    match &result {
+        Ok(_) => tracing::debug!(bytes = rdr.bytes, ?elapsed, "parsed {payload}"),
+        Err(e) => {
+            tracing::warn!(bytes = rdr.bytes, ?elapsed, error = %e, "failed to parse {payload}")
+        }
+    }
+    result
+}
+
+/// Deserialize JSON from the reader.
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn parse_json<T: DeserializeOwned>(
+    rdr: impl Read,
+    _payload: &'static str,
+) -> Result<T, Error> {
+    from_reader(rdr)
+}
+
+/// Deserialize JSON from the buffer, recording the payload size and parse duration.
+#[cfg(feature = "tracing")]
+pub(crate) fn parse_json_slice<'a, T: Deserialize<'a>>(
+    data: &'a [u8],
+    payload: &'static str,
+) -> Result<T, Error> {
+    let _span = tracing::debug_span!("parse_json_slice", payload).entered();
+    let start = std::time::Instant::now();
+    let result = from_slice(data);
+    let elapsed = start.elapsed();
+    match &result {
+        Ok(_) => tracing::debug!(bytes = data.len(), ?elapsed, "parsed {payload}"),
+        Err(e) => {
+            tracing::warn!(bytes = data.len(), ?elapsed, error = %e, "failed to parse {payload}")
+        }
+    }
+    result
+}
+
+/// Deserialize JSON from the buffer.
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn parse_json_slice<'a, T: Deserialize<'a>>(
+    data: &'a [u8],
+    _payload: &'static str,
+) -> Result<T, Error> {
+    from_slice(data)
+}
+
+/// Deserialize JSON from the reader with the given seed, recording the payload size and
+/// parse duration.
+#[cfg(feature = "tracing")]
+pub(crate) fn parse_json_seed<T, V>(
+    rdr: impl Read,
+    seed: T,
+    payload: &'static str,
+) -> Result<V, Error>
+where
+    T: for<'de> DeserializeSeed<'de, Value = V>,
+{
+    let _span = tracing::debug_span!("parse_json_seed", payload).entered();
+    let start = std::time::Instant::now();
+    let mut rdr = CountingReader {
+        inner: rdr,
+        bytes: 0,
+    };
+    let result = from_reader_seed(&mut rdr, seed);
+    let elapsed = start.elapsed();
+    match &result {
+        Ok(_) => tracing::debug!(bytes = rdr.bytes, ?elapsed, "parsed {payload}"),
+        Err(e) => {
+            tracing::warn!(bytes = rdr.bytes, ?elapsed, error = %e, "failed to parse {payload}")
+        }
+    }
+    result
+}
+
+/// Deserialize JSON from the reader with the given seed.
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn parse_json_seed<T, V>(
+    rdr: impl Read,
+    seed: T,
+    _payload: &'static str,
+) -> Result<V, Error>
+where
+    T: for<'de> DeserializeSeed<'de, Value = V>,
+{
+    from_reader_seed(rdr, seed)
+}
+
+/// Reader adapter that counts the bytes read through it.
+#[cfg(feature = "tracing")]
+struct CountingReader<R> {
+    inner: R,
+    bytes: usize,
+}
+
+#[cfg(feature = "tracing")]
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bytes += n;
+        Ok(n)
+    }
+}
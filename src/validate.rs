@@ -0,0 +1,399 @@
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{Metres, TidalEventType, TidePredictions};
+
+/// Range of tide heights, in metres, considered plausible for UK waters.
+///
+/// The largest ranges around the UK are in the Bristol Channel (around 15m at springs), and
+/// heights are measured from chart datum so can dip slightly below zero at extreme low water.
+const PLAUSIBLE_HEIGHTS: RangeInclusive<f64> = -3.0..=16.0;
+
+/// Expected interval between continuous height predictions.
+const HEIGHT_INTERVAL_MINUTES: i64 = 30;
+
+impl TidePredictions {
+    /// Check the predictions for anomalies that suggest the data is untrustworthy.
+    ///
+    /// The checks performed are:
+    ///
+    /// - Tidal events, lunar phases and continuous heights are in chronological order.
+    /// - High and low tide events alternate.
+    /// - All heights are within a range plausible for UK waters.
+    /// - The continuous heights are spaced half an hour apart, without gaps.
+    /// - Tidal events fall within the period covered by the continuous heights.
+    ///
+    /// An empty list of continuous heights is not itself reported as an anomaly, as the
+    /// upstream API omits them for stations that do not provide continuous heights.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    ///
+    /// let file = File::open("./reference/tides.json")
+    ///     .expect("Failed to open tides reference file.");
+    /// let tides = rjw_uktides::tides_from_reader(file)
+    ///     .expect("Failed to read file as tides data.");
+    /// let report = tides.validate();
+    /// assert!(report.is_ok(), "{report}");
+    /// ```
+    pub fn validate(&self) -> ValidationReport {
+        let mut anomalies = Vec::new();
+        self.check_chronological(&mut anomalies);
+        self.check_alternation(&mut anomalies);
+        self.check_heights(&mut anomalies);
+        self.check_height_gaps(&mut anomalies);
+        self.check_heights_window(&mut anomalies);
+        ValidationReport { anomalies }
+    }
+
+    fn check_chronological(&self, anomalies: &mut Vec<Anomaly>) {
+        let series = [
+            (
+                Series::TidalEvents,
+                self.tidal_event_list
+                    .iter()
+                    .map(|e| e.date_time)
+                    .collect::<Vec<_>>(),
+            ),
+            (
+                Series::LunarPhases,
+                self.lunar_phase_list.iter().map(|p| p.date_time).collect(),
+            ),
+            (
+                Series::ContinuousHeights,
+                self.tidal_height_occurrence_list
+                    .iter()
+                    .map(|h| h.date_time)
+                    .collect(),
+            ),
+        ];
+        for (series, times) in series {
+            for pair in times.windows(2) {
+                if pair[1] <= pair[0] {
+                    anomalies.push(Anomaly::NonMonotonic {
+                        series,
+                        previous: pair[0],
+                        current: pair[1],
+                    });
+                }
+            }
+        }
+    }
+
+    fn check_alternation(&self, anomalies: &mut Vec<Anomaly>) {
+        for pair in self.tidal_event_list.windows(2) {
+            if pair[0].event_type == pair[1].event_type {
+                anomalies.push(Anomaly::MissingAlternation {
+                    event_type: pair[1].event_type,
+                    date_time: pair[1].date_time,
+                });
+            }
+        }
+    }
+
+    fn check_heights(&self, anomalies: &mut Vec<Anomaly>) {
+        let events = self
+            .tidal_event_list
+            .iter()
+            .map(|e| (Series::TidalEvents, e.date_time, e.height));
+        let heights = self
+            .tidal_height_occurrence_list
+            .iter()
+            .map(|h| (Series::ContinuousHeights, h.date_time, h.height));
+        for (series, date_time, height) in events.chain(heights) {
+            if !PLAUSIBLE_HEIGHTS.contains(&height.0) {
+                anomalies.push(Anomaly::ImplausibleHeight {
+                    series,
+                    date_time,
+                    height,
+                });
+            }
+        }
+    }
+
+    fn check_height_gaps(&self, anomalies: &mut Vec<Anomaly>) {
+        let interval = Duration::minutes(HEIGHT_INTERVAL_MINUTES);
+        for pair in self.tidal_height_occurrence_list.windows(2) {
+            let (start, end) = (pair[0].date_time, pair[1].date_time);
+            // Out-of-order heights are reported by the chronological check.
+            if end > start && end - start > interval {
+                anomalies.push(Anomaly::HeightGap { start, end });
+            }
+        }
+    }
+
+    fn check_heights_window(&self, anomalies: &mut Vec<Anomaly>) {
+        let times = self
+            .tidal_height_occurrence_list
+            .iter()
+            .map(|h| h.date_time);
+        let (Some(first), Some(last)) = (times.clone().min(), times.max()) else {
+            return;
+        };
+        for event in &self.tidal_event_list {
+            if event.date_time < first || event.date_time > last {
+                anomalies.push(Anomaly::EventOutsideHeightsWindow {
+                    date_time: event.date_time,
+                    first,
+                    last,
+                });
+            }
+        }
+    }
+}
+
+/// The result of [`TidePredictions::validate`], listing any anomalies found.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    /// Anomalies found in the predictions, in the order the checks were performed.
+    pub anomalies: Vec<Anomaly>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if no anomalies were found.
+    pub fn is_ok(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_ok() {
+            return write!(f, "No anomalies found.");
+        }
+        write!(f, "{} anomalies found:", self.anomalies.len())?;
+        for anomaly in &self.anomalies {
+            write!(f, "\n- {anomaly}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The timestamped series within [`TidePredictions`] in which an anomaly was found.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Series {
+    TidalEvents,
+    LunarPhases,
+    ContinuousHeights,
+}
+
+impl Display for Series {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Series::TidalEvents => "tidal events",
+            Series::LunarPhases => "lunar phases",
+            Series::ContinuousHeights => "continuous heights",
+        };
+        write!(f, "{text}")
+    }
+}
+
+/// A single problem found when validating tide predictions.
+#[derive(Debug, Clone)]
+pub enum Anomaly {
+    /// An entry does not come strictly after the one preceding it.
+    NonMonotonic {
+        series: Series,
+        previous: DateTime<Utc>,
+        current: DateTime<Utc>,
+    },
+    /// Two consecutive tidal events are of the same type.
+    MissingAlternation {
+        event_type: TidalEventType,
+        date_time: DateTime<Utc>,
+    },
+    /// A height lies outside the range plausible for UK waters.
+    ImplausibleHeight {
+        series: Series,
+        date_time: DateTime<Utc>,
+        height: Metres,
+    },
+    /// Consecutive continuous heights are more than half an hour apart.
+    HeightGap {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+    /// A tidal event falls outside the period covered by the continuous heights.
+    EventOutsideHeightsWindow {
+        date_time: DateTime<Utc>,
+        first: DateTime<Utc>,
+        last: DateTime<Utc>,
+    },
+}
+
+impl Display for Anomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Anomaly::NonMonotonic {
+                series,
+                previous,
+                current,
+            } => write!(f, "{series} out of order: {current} follows {previous}"),
+            Anomaly::MissingAlternation {
+                event_type,
+                date_time,
+            } => write!(f, "consecutive {event_type} events, second at {date_time}"),
+            Anomaly::ImplausibleHeight {
+                series,
+                date_time,
+                height,
+            } => write!(
+                f,
+                "implausible height in {series}: {:.2}m at {date_time}",
+                height.0
+            ),
+            Anomaly::HeightGap { start, end } => {
+                write!(f, "gap in continuous heights from {start} to {end}")
+            }
+            Anomaly::EventOutsideHeightsWindow {
+                date_time,
+                first,
+                last,
+            } => write!(
+                f,
+                "tidal event at {date_time} outside continuous heights window {first} to {last}"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{utc, TidalEventBuilder, TidePredictionsBuilder};
+    use crate::{LunarPhase, LunarPhaseType};
+
+    /// A high and a low tide, with continuous heights every half hour around them.
+    fn valid() -> TidePredictions {
+        let mut tides = TidePredictionsBuilder::new()
+            .event(TidalEventBuilder::high().at("2023-04-03T01:00").height(4.0))
+            .event(TidalEventBuilder::low().at("2023-04-03T02:00").height(1.0));
+        for time in ["00:30", "01:00", "01:30", "02:00", "02:30"] {
+            tides = tides.height(&format!("2023-04-03T{time}"), 2.5);
+        }
+        tides.build()
+    }
+
+    #[test]
+    fn accepts_valid_predictions() {
+        let report = valid().validate();
+        assert!(report.is_ok(), "{report}");
+        assert_eq!(report.to_string(), "No anomalies found.");
+    }
+
+    #[test]
+    fn accepts_predictions_without_continuous_heights() {
+        let mut tides = valid();
+        tides.tidal_height_occurrence_list.clear();
+        assert!(tides.validate().is_ok());
+    }
+
+    #[test]
+    fn reports_non_monotonic_times_in_each_series() {
+        let mut tides = valid();
+        tides.tidal_height_occurrence_list.swap(1, 2);
+        tides.lunar_phase_list = ["2023-04-06T04:34", "2023-04-06T04:34"]
+            .map(|t| LunarPhase {
+                date_time: utc(t),
+                lunar_phase_type: LunarPhaseType::FullMoon,
+            })
+            .into();
+        let series: Vec<_> = tides
+            .validate()
+            .anomalies
+            .iter()
+            .filter_map(|a| match a {
+                Anomaly::NonMonotonic { series, .. } => Some(*series),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(series, [Series::LunarPhases, Series::ContinuousHeights]);
+    }
+
+    #[test]
+    fn reports_broken_alternation() {
+        let mut tides = valid();
+        tides.tidal_event_list[1].event_type = TidalEventType::HighWater;
+        let report = tides.validate();
+        assert!(
+            matches!(
+                report.anomalies[..],
+                [Anomaly::MissingAlternation {
+                    event_type: TidalEventType::HighWater,
+                    date_time,
+                }] if date_time == utc("2023-04-03T02:00")
+            ),
+            "{report}"
+        );
+    }
+
+    #[test]
+    fn reports_implausible_and_nan_heights() {
+        let mut tides = valid();
+        tides.tidal_event_list[0].height = Metres(16.5);
+        tides.tidal_height_occurrence_list[2].height = Metres(f64::NAN);
+        tides.tidal_height_occurrence_list[3].height = Metres(-3.5);
+        let implausible: Vec<_> = tides
+            .validate()
+            .anomalies
+            .iter()
+            .filter_map(|a| match a {
+                Anomaly::ImplausibleHeight {
+                    series, date_time, ..
+                } => Some((*series, *date_time)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            implausible,
+            [
+                (Series::TidalEvents, utc("2023-04-03T01:00")),
+                (Series::ContinuousHeights, utc("2023-04-03T01:30")),
+                (Series::ContinuousHeights, utc("2023-04-03T02:00")),
+            ]
+        );
+    }
+
+    #[test]
+    fn accepts_heights_at_the_limits_of_plausibility() {
+        let mut tides = valid();
+        tides.tidal_height_occurrence_list[0].height = Metres(*PLAUSIBLE_HEIGHTS.start());
+        tides.tidal_height_occurrence_list[4].height = Metres(*PLAUSIBLE_HEIGHTS.end());
+        assert!(tides.validate().is_ok());
+    }
+
+    #[test]
+    fn reports_gaps_in_continuous_heights() {
+        let mut tides = valid();
+        tides.tidal_height_occurrence_list.remove(2);
+        let report = tides.validate();
+        assert!(
+            matches!(
+                report.anomalies[..],
+                [Anomaly::HeightGap { start, end }]
+                    if start == utc("2023-04-03T01:00") && end == utc("2023-04-03T02:00")
+            ),
+            "{report}"
+        );
+    }
+
+    #[test]
+    fn reports_events_outside_the_heights_window() {
+        let mut tides = valid();
+        tides.tidal_event_list[1].date_time = utc("2023-04-03T02:31");
+        let report = tides.validate();
+        assert!(
+            matches!(
+                report.anomalies[..],
+                [Anomaly::EventOutsideHeightsWindow { date_time, first, last }]
+                    if date_time == utc("2023-04-03T02:31")
+                        && first == utc("2023-04-03T00:30")
+                        && last == utc("2023-04-03T02:30")
+            ),
+            "{report}"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! Client for WebAssembly in the browser, using the JavaScript `fetch` API.
+//!
+//! Enabled by the `wasm` feature when compiling for `wasm32-unknown-unknown`, where the
+//! blocking `Client` is unavailable. Build the library alone with
+//! `cargo build --lib --target wasm32-unknown-unknown --features wasm`, as the command-line
+//! tool does not support WebAssembly.
+//!
+//! Requests from a web page are subject to the browser's CORS rules, which the EasyTide web
+//! service may not permit. Use [`WasmClient::with_base_url`] to send requests through a
+//! proxy that does.
+
+use std::error::Error;
+
+use bytes::Bytes;
+use js_sys::{Promise, Uint8Array};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::Response;
+
+use crate::{
+    stations_from_reader, stations_url, tides_from_slice, tides_url, Station, StationId,
+    TidePredictions, DEFAULT_BASE_URL,
+};
+
+#[wasm_bindgen]
+extern "C" {
+    /// The global `fetch` function, available in both windows and workers.
+    #[wasm_bindgen(js_name = fetch)]
+    fn global_fetch(input: &str) -> Promise;
+}
+
+/// Asynchronous client for the EasyTide web service, for use in the browser.
+///
+/// The browser handles connection reuse, caching and response compression, according to
+/// its usual rules for `fetch` requests.
+#[derive(Debug, Clone)]
+pub struct WasmClient {
+    base_url: String,
+}
+
+impl Default for WasmClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WasmClient {
+    /// Create a client for the public EasyTide web service.
+    pub fn new() -> Self {
+        Self::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    /// Create a client that sends requests to `base_url` instead of the public EasyTide
+    /// web service, such as a proxy that adds CORS headers.
+    ///
+    /// The base URL should include the scheme and host, and optionally a port and path
+    /// prefix, without a trailing slash; for example `https://example.com/easytide`.
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+        }
+    }
+
+    /// The base URL to which requests are sent.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Fetch the list of all tidal stations.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails, the server responds with
+    /// an error status, or the response cannot be parsed as station data.
+    pub async fn fetch_stations(&self) -> Result<Vec<Station>, Box<dyn Error>> {
+        let bytes = self.fetch_stations_raw().await?;
+        Ok(stations_from_reader(bytes.as_ref())?)
+    }
+
+    /// Fetch the list of all tidal stations as the raw JSON response body.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or the server responds with
+    /// an error status.
+    pub async fn fetch_stations_raw(&self) -> Result<Bytes, Box<dyn Error>> {
+        get(&stations_url(&self.base_url)).await
+    }
+
+    /// Fetch tide predictions for the given station.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails, the server responds with
+    /// an error status, or the response cannot be parsed as tide predictions.
+    pub async fn fetch_tides(
+        &self,
+        station: &StationId,
+    ) -> Result<TidePredictions, Box<dyn Error>> {
+        let bytes = self.fetch_tides_raw(station).await?;
+        Ok(tides_from_slice(&bytes)?)
+    }
+
+    /// Fetch tide predictions for the given station as the raw JSON response body.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails or the server responds with
+    /// an error status.
+    pub async fn fetch_tides_raw(&self, station: &StationId) -> Result<Bytes, Box<dyn Error>> {
+        get(&tides_url(&self.base_url, station)).await
+    }
+}
+
+/// Fetch the URL, returning the response body if the response status is successful.
+async fn get(url: &str) -> Result<Bytes, Box<dyn Error>> {
+    let response: Response = JsFuture::from(global_fetch(url))
+        .await
+        .map_err(js_error)?
+        .dyn_into()
+        .map_err(js_error)?;
+    if !response.ok() {
+        return Err(format!(
+            "HTTP status {} {} for url ({url})",
+            response.status(),
+            response.status_text()
+        )
+        .into());
+    }
+    let buffer = JsFuture::from(response.array_buffer().map_err(js_error)?)
+        .await
+        .map_err(js_error)?;
+    Ok(Uint8Array::new(&buffer).to_vec().into())
+}
+
+/// Convert a JavaScript exception into an error.
+fn js_error(value: JsValue) -> Box<dyn Error> {
+    value
+        .dyn_ref::<js_sys::Error>()
+        .map(|e| String::from(e.message()))
+        .or_else(|| value.as_string())
+        .unwrap_or_else(|| format!("{value:?}"))
+        .into()
+}
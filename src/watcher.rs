@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use crate::{Client, StationId, TidalEvent, TidePredictions};
+
+/// The most stations a [`Watcher`] fetches at the same time.
+const MAX_CONCURRENCY: usize = 4;
+
+type Listener = Box<dyn Fn(&WatchEvent) + Send + Sync>;
+
+/// Something a [`Watcher`] has noticed about one of its stations.
+#[derive(Debug, Clone)]
+pub enum WatchEvent {
+    /// Predictions for the station were fetched for the first time, or differ from the
+    /// ones fetched before.
+    Changed {
+        station: StationId,
+        predictions: Arc<TidePredictions>,
+    },
+    /// A tide at the station is now one of the watcher's lead times away.
+    Upcoming {
+        station: StationId,
+        event: TidalEvent,
+        lead: chrono::Duration,
+    },
+    /// Fetching the station's predictions failed. Its previous predictions are kept.
+    FetchFailed {
+        station: StationId,
+        error: Arc<dyn Error + Send + Sync>,
+    },
+}
+
+/// Keeps the latest predictions for a set of stations, fetching them on an interval, and
+/// reports changes to them and tides coming up.
+///
+/// Events are passed to the functions registered with [`on_event`](Self::on_event) and
+/// sent to the receivers returned by [`subscribe`](Self::subscribe). Predictions are
+/// fetched with the watcher's [`Client`], so are limited by its rate limit, if it has one.
+///
+/// Call [`poll`](Self::poll) and [`check_lead_times`](Self::check_lead_times) to drive the
+/// watcher yourself, or [`spawn`](Self::spawn) it on a thread of its own.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "mock_server")]
+/// # {
+/// use std::time::Duration;
+///
+/// use rjw_uktides::mock_server::MockServer;
+/// use rjw_uktides::{Client, StationId, WatchEvent, Watcher};
+///
+/// let server = MockServer::start().expect("Failed to start mock server.");
+/// let sandown = StationId("0053".to_owned());
+/// let watcher = Watcher::new(
+///     Client::with_base_url(server.base_url()),
+///     [sandown.clone()],
+///     Duration::from_secs(15 * 60),
+/// )
+/// .with_lead_times([chrono::Duration::minutes(30)]);
+/// let events = watcher.subscribe();
+///
+/// watcher.poll();
+/// assert!(matches!(events.try_recv(), Ok(WatchEvent::Changed { .. })));
+/// assert!(watcher.latest(&sandown).is_some());
+///
+/// // The predictions are the same the second time, so there is no event.
+/// watcher.poll();
+/// assert!(events.try_recv().is_err());
+/// # }
+/// ```
+pub struct Watcher {
+    client: Client,
+    stations: Vec<StationId>,
+    interval: Duration,
+    lead_times: Vec<chrono::Duration>,
+    state: Mutex<State>,
+    listeners: Mutex<Vec<Listener>>,
+    senders: Mutex<Vec<Sender<WatchEvent>>>,
+}
+
+struct State {
+    latest: HashMap<StationId, Arc<TidePredictions>>,
+    /// The time up to which tides coming up have been reported.
+    checked_until: DateTime<Utc>,
+}
+
+impl Watcher {
+    /// Create a watcher for the stations, fetching their predictions with `client` every
+    /// `interval`.
+    pub fn new(
+        client: Client,
+        stations: impl IntoIterator<Item = StationId>,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            client,
+            stations: stations.into_iter().collect(),
+            interval,
+            lead_times: Vec::new(),
+            state: Mutex::new(State {
+                latest: HashMap::new(),
+                checked_until: Utc::now(),
+            }),
+            listeners: Mutex::new(Vec::new()),
+            senders: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Report each tide when it is each of `lead_times` away, such as an hour before and
+    /// ten minutes before.
+    pub fn with_lead_times(
+        mut self,
+        lead_times: impl IntoIterator<Item = chrono::Duration>,
+    ) -> Self {
+        self.lead_times = lead_times.into_iter().collect();
+        self
+    }
+
+    /// The stations being watched.
+    pub fn stations(&self) -> &[StationId] {
+        &self.stations
+    }
+
+    /// The latest predictions fetched for the station, if any.
+    pub fn latest(&self, station: &StationId) -> Option<Arc<TidePredictions>> {
+        let state = self.state.lock().expect("Watcher lock poisoned.");
+        state.latest.get(station).cloned()
+    }
+
+    /// Register a function to be called with each event, on the thread that noticed it.
+    pub fn on_event<F>(&self, listener: F)
+    where
+        F: Fn(&WatchEvent) + Send + Sync + 'static,
+    {
+        let mut listeners = self.listeners.lock().expect("Watcher lock poisoned.");
+        listeners.push(Box::new(listener));
+    }
+
+    /// A receiver for every event from now on. Events stop being sent to it once it is
+    /// dropped.
+    pub fn subscribe(&self) -> Receiver<WatchEvent> {
+        let (sender, receiver) = mpsc::channel();
+        let mut senders = self.senders.lock().expect("Watcher lock poisoned.");
+        senders.push(sender);
+        receiver
+    }
+
+    /// Fetch the predictions for every station, reporting those that have changed and
+    /// those that could not be fetched.
+    pub fn poll(&self) {
+        let concurrency = self.stations.len().clamp(1, MAX_CONCURRENCY);
+        let mut results = self.client.fetch_tides_many(&self.stations, concurrency);
+        for station in &self.stations {
+            let event = match results.remove(station) {
+                Some(Ok(predictions)) => {
+                    let mut state = self.state.lock().expect("Watcher lock poisoned.");
+                    let changed = state
+                        .latest
+                        .get(station)
+                        .is_none_or(|latest| !same_predictions(latest, &predictions));
+                    if !changed {
+                        continue;
+                    }
+                    let predictions = Arc::new(predictions);
+                    state
+                        .latest
+                        .insert(station.clone(), Arc::clone(&predictions));
+                    WatchEvent::Changed {
+                        station: station.clone(),
+                        predictions,
+                    }
+                }
+                Some(Err(error)) => WatchEvent::FetchFailed {
+                    station: station.clone(),
+                    error: Arc::from(error),
+                },
+                // Stations listed more than once are only fetched once.
+                None => continue,
+            };
+            self.emit(&event);
+        }
+    }
+
+    /// Report the tides that have come within a lead time of happening since the last
+    /// check, up to `now`.
+    pub fn check_lead_times(&self, now: DateTime<Utc>) {
+        let mut upcoming = Vec::new();
+        {
+            let mut state = self.state.lock().expect("Watcher lock poisoned.");
+            for (station, event, lead, due) in self.due(&state) {
+                if due <= now {
+                    upcoming.push((due, station.clone(), event.clone(), lead));
+                }
+            }
+            state.checked_until = state.checked_until.max(now);
+        }
+        upcoming.sort_by_key(|(due, ..)| *due);
+        for (_, station, event, lead) in upcoming {
+            self.emit(&WatchEvent::Upcoming {
+                station,
+                event,
+                lead,
+            });
+        }
+    }
+
+    /// Poll every interval and report tides coming up, until a message is received on
+    /// `stop` or its sender is dropped.
+    pub fn run_until(&self, stop: &Receiver<()>) {
+        let mut next_poll = Instant::now();
+        loop {
+            if Instant::now() >= next_poll {
+                self.poll();
+                next_poll = Instant::now() + self.interval;
+            }
+            let now = Utc::now();
+            self.check_lead_times(now);
+            let until_poll = next_poll.saturating_duration_since(Instant::now());
+            let next_due = {
+                let state = self.state.lock().expect("Watcher lock poisoned.");
+                self.due(&state).map(|(.., due)| due).min()
+            };
+            let wait = next_due
+                .and_then(|due| (due - now).to_std().ok())
+                .map_or(until_poll, |wait| wait.min(until_poll));
+            match stop.recv_timeout(wait) {
+                Err(RecvTimeoutError::Timeout) => continue,
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+
+    /// Run the watcher on a new thread, as with [`run_until`](Self::run_until), until the
+    /// returned handle is stopped or dropped.
+    pub fn spawn(self: Arc<Self>) -> WatcherHandle {
+        let (stop, stopped) = mpsc::channel();
+        let thread = std::thread::spawn(move || self.run_until(&stopped));
+        WatcherHandle {
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// Each tide's station, lead time and the time it is due to be reported, for the
+    /// tides not yet reported.
+    fn due<'a>(
+        &'a self,
+        state: &'a State,
+    ) -> impl Iterator<
+        Item = (
+            &'a StationId,
+            &'a TidalEvent,
+            chrono::Duration,
+            DateTime<Utc>,
+        ),
+    > + 'a {
+        state.latest.iter().flat_map(move |(station, predictions)| {
+            predictions.tidal_event_list.iter().flat_map(move |event| {
+                self.lead_times
+                    .iter()
+                    .map(move |&lead| (station, event, lead, event.date_time - lead))
+                    .filter(|(.., due)| *due > state.checked_until)
+            })
+        })
+    }
+
+    fn emit(&self, event: &WatchEvent) {
+        for listener in self
+            .listeners
+            .lock()
+            .expect("Watcher lock poisoned.")
+            .iter()
+        {
+            listener(event);
+        }
+        let mut senders = self.senders.lock().expect("Watcher lock poisoned.");
+        senders.retain(|sender| sender.send(event.clone()).is_ok());
+    }
+}
+
+impl std::fmt::Debug for Watcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Watcher")
+            .field("stations", &self.stations)
+            .field("interval", &self.interval)
+            .field("lead_times", &self.lead_times)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A [`Watcher`] running on its own thread, which stops when the handle is dropped.
+#[derive(Debug)]
+pub struct WatcherHandle {
+    stop: Sender<()>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl WatcherHandle {
+    /// Stop the watcher, waiting for it to finish any fetch in progress.
+    pub fn stop(self) {}
+}
+
+impl Drop for WatcherHandle {
+    fn drop(&mut self) {
+        let _ = self.stop.send(());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Compare the tides, heights and lunar phases of the predictions, as the equality of
+/// tides only considers their times.
+fn same_predictions(a: &TidePredictions, b: &TidePredictions) -> bool {
+    let events = |p: &TidePredictions| -> Vec<_> {
+        p.tidal_event_list
+            .iter()
+            .map(|e| (e.date_time, e.event_type, e.height.0))
+            .collect()
+    };
+    let heights = |p: &TidePredictions| -> Vec<_> {
+        p.tidal_height_occurrence_list
+            .iter()
+            .map(|h| (h.date_time, h.height.0))
+            .collect()
+    };
+    let phases = |p: &TidePredictions| -> Vec<_> {
+        p.lunar_phase_list
+            .iter()
+            .map(|l| (l.date_time, l.lunar_phase_type))
+            .collect()
+    };
+    events(a) == events(b) && heights(a) == heights(b) && phases(a) == phases(b)
+}
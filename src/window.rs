@@ -0,0 +1,77 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{TidalEventType, TidePredictions};
+
+/// A span of time, such as the hours around low water or from sunrise to sunset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeWindow {
+    /// When the window opens.
+    pub start: DateTime<Utc>,
+    /// When the window closes.
+    pub end: DateTime<Utc>,
+}
+
+impl TimeWindow {
+    /// How long the window is open.
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    /// Whether `at` falls within the window, including its start and end.
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        self.start <= at && at <= self.end
+    }
+
+    /// The time within both windows, or `None` if they do not overlap.
+    pub fn intersection(&self, other: &TimeWindow) -> Option<TimeWindow> {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+        (start < end).then_some(TimeWindow { start, end })
+    }
+}
+
+impl TidePredictions {
+    /// The windows from `within` before to `within` after each tide of the type, in
+    /// order, with overlapping windows merged.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    ///
+    /// use chrono::Duration;
+    /// use rjw_uktides::TidalEventType;
+    ///
+    /// let tides = rjw_uktides::tides_from_reader(File::open("./reference/tides.json").unwrap())
+    ///     .expect("Failed to read file as tides data.");
+    /// let windows = tides.windows_around(TidalEventType::LowWater, Duration::minutes(90));
+    /// let lows: Vec<_> = tides
+    ///     .tidal_event_list
+    ///     .iter()
+    ///     .filter(|e| e.event_type == TidalEventType::LowWater)
+    ///     .collect();
+    /// assert_eq!(windows.len(), lows.len());
+    /// assert!(windows.iter().zip(lows).all(|(w, low)| w.contains(low.date_time)));
+    /// assert_eq!(windows[0].duration(), Duration::hours(3));
+    /// ```
+    pub fn windows_around(&self, event_type: TidalEventType, within: Duration) -> Vec<TimeWindow> {
+        let mut times: Vec<_> = self
+            .tidal_event_list
+            .iter()
+            .filter(|e| e.event_type == event_type)
+            .map(|e| e.date_time)
+            .collect();
+        times.sort();
+        let mut windows: Vec<TimeWindow> = Vec::new();
+        for at in times {
+            let window = TimeWindow {
+                start: at - within,
+                end: at + within,
+            };
+            match windows.last_mut() {
+                Some(last) if last.end >= window.start => last.end = last.end.max(window.end),
+                _ => windows.push(window),
+            }
+        }
+        windows
+    }
+}
@@ -0,0 +1,135 @@
+//! Property-based tests that serializing tide predictions produces JSON that parses back
+//! to the same predictions.
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use proptest::prelude::*;
+
+use rjw_uktides::{
+    LunarPhase, LunarPhaseType, Metres, TidalEvent, TidalEventType, TidalHeightOccurrence,
+    TidePredictions,
+};
+
+/// Datetimes to whole-second precision between 1900 and 2200.
+fn datetime() -> impl Strategy<Value = DateTime<Utc>> {
+    (-2_208_988_800i64..7_258_118_400).prop_map(|secs| Utc.timestamp_opt(secs, 0).unwrap())
+}
+
+fn date() -> impl Strategy<Value = NaiveDate> {
+    datetime().prop_map(|dt| dt.date_naive())
+}
+
+fn metres() -> impl Strategy<Value = Metres> {
+    // Heights from the Admiralty API are given to the nearest centimetre.
+    (-500i32..2000).prop_map(|cm| Metres(f64::from(cm) / 100.0))
+}
+
+fn tidal_event_type() -> impl Strategy<Value = TidalEventType> {
+    prop_oneof![
+        Just(TidalEventType::HighWater),
+        Just(TidalEventType::LowWater)
+    ]
+}
+
+fn lunar_phase_type() -> impl Strategy<Value = LunarPhaseType> {
+    prop_oneof![
+        Just(LunarPhaseType::NewMoon),
+        Just(LunarPhaseType::FirstQuarter),
+        Just(LunarPhaseType::FullMoon),
+        Just(LunarPhaseType::LastQuarter),
+    ]
+}
+
+prop_compose! {
+    fn tidal_event()(
+        date in date(),
+        date_time in datetime(),
+        event_type in tidal_event_type(),
+        height in metres(),
+        is_approximate_height in proptest::option::of(".*"),
+        is_approximate_time in proptest::option::of(".*"),
+    ) -> TidalEvent {
+        TidalEvent { date, date_time, event_type, height, is_approximate_height, is_approximate_time }
+    }
+}
+
+prop_compose! {
+    fn tidal_height()(date_time in datetime(), height in metres()) -> TidalHeightOccurrence {
+        TidalHeightOccurrence { date_time, height }
+    }
+}
+
+prop_compose! {
+    fn lunar_phase()(date_time in datetime(), lunar_phase_type in lunar_phase_type()) -> LunarPhase {
+        LunarPhase { date_time, lunar_phase_type }
+    }
+}
+
+prop_compose! {
+    fn tide_predictions()(
+        footer_note in ".*",
+        lunar_phase_list in proptest::collection::vec(lunar_phase(), 0..8),
+        tidal_event_list in proptest::collection::vec(tidal_event(), 0..32),
+        tidal_height_occurrence_list in proptest::collection::vec(tidal_height(), 0..64),
+    ) -> TidePredictions {
+        TidePredictions { footer_note: footer_note.into(), lunar_phase_list, tidal_event_list, tidal_height_occurrence_list }
+    }
+}
+
+fn assert_events_eq(left: &TidalEvent, right: &TidalEvent) {
+    assert_eq!(left.date, right.date);
+    assert_eq!(left.date_time, right.date_time);
+    assert_eq!(left.event_type, right.event_type);
+    assert_eq!(left.height.0, right.height.0);
+    assert_eq!(left.is_approximate_height, right.is_approximate_height);
+    assert_eq!(left.is_approximate_time, right.is_approximate_time);
+}
+
+fn assert_predictions_eq(left: &TidePredictions, right: &TidePredictions) {
+    assert_eq!(left.footer_note, right.footer_note);
+    assert_eq!(left.lunar_phase_list.len(), right.lunar_phase_list.len());
+    for (l, r) in left.lunar_phase_list.iter().zip(&right.lunar_phase_list) {
+        assert_eq!(l.date_time, r.date_time);
+        assert_eq!(l.lunar_phase_type, r.lunar_phase_type);
+    }
+    assert_eq!(left.tidal_event_list.len(), right.tidal_event_list.len());
+    for (l, r) in left.tidal_event_list.iter().zip(&right.tidal_event_list) {
+        assert_events_eq(l, r);
+    }
+    assert_eq!(
+        left.tidal_height_occurrence_list.len(),
+        right.tidal_height_occurrence_list.len()
+    );
+    for (l, r) in left
+        .tidal_height_occurrence_list
+        .iter()
+        .zip(&right.tidal_height_occurrence_list)
+    {
+        assert_eq!(l.date_time, r.date_time);
+        assert_eq!(l.height.0, r.height.0);
+    }
+}
+
+proptest! {
+    #[test]
+    fn tide_predictions_round_trip(tides in tide_predictions()) {
+        let json = serde_json::to_vec(&tides).unwrap();
+        let parsed = rjw_uktides::tides_from_slice(&json).unwrap();
+        assert_predictions_eq(&tides, &parsed);
+    }
+
+    #[test]
+    fn tide_predictions_round_trip_through_reader(tides in tide_predictions()) {
+        let json = serde_json::to_vec(&tides).unwrap();
+        let parsed = rjw_uktides::tides_from_reader(json.as_slice()).unwrap();
+        assert_predictions_eq(&tides, &parsed);
+    }
+}
+
+#[test]
+fn reference_predictions_round_trip() {
+    let data = std::fs::read("./reference/tides.json").unwrap();
+    let tides = rjw_uktides::tides_from_slice(&data).unwrap();
+    let json = serde_json::to_vec(&tides).unwrap();
+    let parsed = rjw_uktides::tides_from_slice(&json).unwrap();
+    assert_predictions_eq(&tides, &parsed);
+}